@@ -37,9 +37,42 @@ from stoolap._stoolap import (
     Database,
     Transaction,
     PreparedStatement,
+    Snapshot,
+    RowIterator,
+    ChunkIterator,
+    LimitedRows,
     Vector,
+    Json,
+    JsonPath,
+    Migrations,
+    WalReader,
+    TempTable,
+    Table,
+    CancellationToken,
+    TimeLimit,
+    Column,
     StoolapError,
+    ConflictError,
+    IntegrityError,
+    ResultTooLarge,
+    QueryCancelled,
+    QueryTimeout,
+    StoolapWarning,
+    VectorDimensionError,
+    cosine_distance,
+    dot_product,
+    l2_distance,
+    quote_ident,
+    quote_literal,
+    register_converter,
+    schema_diff,
+    set_nan_policy,
+    PARSE_COLNAMES,
+    PARSE_DECLTYPES,
+    set_warning_policy,
+    __version__,
 )
+from stoolap.dbapi import connect, apilevel, threadsafety, paramstyle
 
 import asyncio
 
@@ -57,39 +90,365 @@ class AsyncDatabase:
         self._db = db
 
     @classmethod
-    async def open(cls, path: str = ":memory:") -> "AsyncDatabase":
-        db = await asyncio.to_thread(Database.open, path)
+    async def open(
+        cls, path: str = ":memory:", *, paramstyle: str = "numeric_dollar", durability: str = None,
+        strict_types: bool = False, tz=None, naive_timestamps: bool = False,
+        reject_naive: bool = False, nanosecond_timestamps: bool = False,
+        midnight_as_date: bool = False, detect_types: int = 0, threads: int = None,
+    ) -> "AsyncDatabase":
+        db = await asyncio.to_thread(
+            Database.open, path, paramstyle=paramstyle, durability=durability,
+            strict_types=strict_types, tz=tz, naive_timestamps=naive_timestamps,
+            reject_naive=reject_naive, nanosecond_timestamps=nanosecond_timestamps,
+            midnight_as_date=midnight_as_date, detect_types=detect_types, threads=threads,
+        )
         return cls(db)
 
-    async def execute(self, sql: str, params=None) -> int:
-        return await asyncio.to_thread(self._db.execute, sql, params)
+    @classmethod
+    async def open_from_env(cls, prefix: str = "STOOLAP_") -> "AsyncDatabase":
+        return cls(await asyncio.to_thread(Database.open_from_env, prefix))
 
-    async def exec(self, sql: str) -> None:
-        return await asyncio.to_thread(self._db.exec, sql)
+    @property
+    def paramstyle(self) -> str:
+        return self._db.paramstyle
 
-    async def query(self, sql: str, params=None) -> list:
-        return await asyncio.to_thread(self._db.query, sql, params)
+    @property
+    def strict_types(self) -> bool:
+        return self._db.strict_types
 
-    async def query_one(self, sql: str, params=None):
-        return await asyncio.to_thread(self._db.query_one, sql, params)
+    @property
+    def tz(self):
+        return self._db.tz
+
+    @property
+    def detect_types(self) -> int:
+        return self._db.detect_types
 
-    async def query_raw(self, sql: str, params=None) -> dict:
-        return await asyncio.to_thread(self._db.query_raw, sql, params)
+    @paramstyle.setter
+    def paramstyle(self, style: str) -> None:
+        self._db.paramstyle = style
 
-    async def execute_batch(self, sql: str, params_list: list) -> int:
-        return await asyncio.to_thread(self._db.execute_batch, sql, params_list)
+    @property
+    def journal_mode(self) -> str:
+        return self._db.journal_mode
+
+    async def set_journal_mode(self, mode: str) -> None:
+        await asyncio.to_thread(self._db.set_journal_mode, mode)
+
+    @property
+    def threads(self) -> int:
+        return self._db.threads
+
+    def set_threads(self, threads: int = None) -> None:
+        self._db.set_threads(threads)
+
+    @property
+    def synchronous(self) -> str:
+        return self._db.synchronous
+
+    def set_var(self, name: str, value) -> None:
+        self._db.set_var(name, value)
+
+    def get_var(self, name: str):
+        return self._db.get_var(name)
+
+    def get_setting(self, name: str):
+        return self._db.get_setting(name)
+
+    async def set_setting(self, name: str, value) -> None:
+        await asyncio.to_thread(self._db.set_setting, name, value)
+
+    async def set_synchronous(self, mode: str) -> None:
+        await asyncio.to_thread(setattr, self._db, "synchronous", mode)
+
+    async def execute(self, sql: str, params=None, *, cancel=None) -> int:
+        return await asyncio.to_thread(self._db.execute, sql, params, cancel=cancel)
+
+    async def exec(
+        self, sql: str, params=None, *, params_list=None, transaction=False, cancel=None,
+    ) -> list:
+        return await asyncio.to_thread(
+            self._db.exec, sql, params, params_list=params_list, transaction=transaction,
+            cancel=cancel,
+        )
+
+    async def query(
+        self, sql: str, params=None, *, as_of=None, intern_strings=False,
+        max_rows=None, max_result_bytes=None, limit=None, cancel=None, hints=None,
+    ) -> "list | tuple[list, bool]":
+        return await asyncio.to_thread(
+            self._db.query, sql, params, as_of=as_of, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, limit=limit, cancel=cancel,
+            hints=hints,
+        )
+
+    async def query_one(self, sql: str, params=None, *, as_of=None, cancel=None, hints=None):
+        return await asyncio.to_thread(
+            self._db.query_one, sql, params, as_of=as_of, cancel=cancel, hints=hints,
+        )
+
+    async def query_raw(
+        self, sql: str, params=None, *, as_of=None, intern_strings=False,
+        max_rows=None, max_result_bytes=None, typed=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._db.query_raw, sql, params, as_of=as_of, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, typed=typed,
+        )
+
+    async def query_columnar(
+        self, sql: str, params=None, *, as_of=None, intern_strings=False,
+        max_rows=None, max_result_bytes=None, numpy=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._db.query_columnar, sql, params, as_of=as_of, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, numpy=numpy,
+        )
+
+    async def vector_search(
+        self, table: str, column: str, query_vector, k: int, metric: str = "cosine",
+        where=None, params=None, *, strategy: str = "auto", candidates=None,
+    ) -> list:
+        return await asyncio.to_thread(
+            self._db.vector_search, table, column, query_vector, k, metric, where, params,
+            strategy=strategy, candidates=candidates,
+        )
+
+    async def json_get(self, table: str, column: str, path, where=None, params=None) -> list:
+        return await asyncio.to_thread(self._db.json_get, table, column, path, where, params)
+
+    async def query_many(self, queries, max_parallel=None) -> list:
+        return await asyncio.to_thread(self._db.query_many, queries, max_parallel)
+
+    async def query_chunks(self, sql: str, params=None, chunk_size: int = 1000, *, as_of=None):
+        chunks = await asyncio.to_thread(self._db.query_chunks, sql, params, chunk_size, as_of=as_of)
+        try:
+            while (chunk := await asyncio.to_thread(next, chunks, None)) is not None:
+                yield chunk
+        finally:
+            chunks.close()
+
+    async def execute_batch(self, sql: str, params_list: list, *, parallel: int = None) -> int:
+        return await asyncio.to_thread(
+            self._db.execute_batch, sql, params_list, parallel=parallel,
+        )
+
+    async def insert_vectors(
+        self, table: str, id_column: str, vector_column: str, ids, matrix, *,
+        batch_size: int = 10000, normalize: bool = False,
+    ) -> int:
+        return await asyncio.to_thread(
+            self._db.insert_vectors, table, id_column, vector_column, ids, matrix,
+            batch_size=batch_size, normalize=normalize,
+        )
+
+    async def sync_from(self, other, tables: list = None, *, mode: str = "upsert") -> dict:
+        if isinstance(other, AsyncDatabase):
+            other = other._db
+        return await asyncio.to_thread(self._db.sync_from, other, tables, mode=mode)
+
+    def mogrify(self, sql: str, params=None) -> str:
+        return self._db.mogrify(sql, params)
+
+    async def explain(self, sql: str, params=None, *, format="text"):
+        return await asyncio.to_thread(self._db.explain, sql, params, format=format)
+
+    async def explain_analyze(self, sql: str, params=None, *, format="text"):
+        return await asyncio.to_thread(self._db.explain_analyze, sql, params, format=format)
 
     def prepare(self, sql: str) -> "AsyncPreparedStatement":
         stmt = self._db.prepare(sql)
         return AsyncPreparedStatement(stmt)
 
-    async def begin(self) -> "AsyncTransaction":
-        tx = await asyncio.to_thread(self._db.begin)
+    def table(self, table: str) -> "AsyncTable":
+        return AsyncTable(self._db.table(table))
+
+    async def delete_where(self, table: str, where, params=None, batch_size: int = None) -> int:
+        return await asyncio.to_thread(self._db.delete_where, table, where, params, batch_size)
+
+    async def create_table(self, table: str, columns, *, if_not_exists: bool = True) -> None:
+        await asyncio.to_thread(
+            self._db.create_table, table, columns, if_not_exists=if_not_exists,
+        )
+
+    async def truncate(self, table: str, restart_identity: bool = False) -> None:
+        await asyncio.to_thread(self._db.truncate, table, restart_identity)
+
+    def map(self, cls, table: str, pk=None, columns=None) -> None:
+        self._db.map(cls, table, pk, columns)
+
+    async def get(self, cls, pk):
+        return await asyncio.to_thread(self._db.get, cls, pk)
+
+    async def select(self, cls, where=None, params=None, *, order_by=None, limit=None) -> list:
+        return await asyncio.to_thread(
+            self._db.select, cls, where, params, order_by=order_by, limit=limit,
+        )
+
+    async def save(self, obj) -> None:
+        await asyncio.to_thread(self._db.save, obj)
+
+    async def begin(self, mode: str = "deferred") -> "AsyncTransaction":
+        tx = await asyncio.to_thread(self._db.begin, mode)
         return AsyncTransaction(tx)
 
+    async def transaction(self, func, retries: int = 3, backoff: float = 0.01):
+        return await asyncio.to_thread(self._db.transaction, func, retries, backoff)
+
+    @property
+    def in_transaction(self) -> bool:
+        return self._db.in_transaction
+
+    @property
+    def server_version(self) -> str:
+        return self._db.server_version
+
+    @property
+    def server_version_info(self) -> tuple:
+        return self._db.server_version_info
+
+    def get_isolation_level(self) -> str:
+        return self._db.get_isolation_level()
+
+    async def tables(self) -> list:
+        return await asyncio.to_thread(self._db.tables)
+
+    async def describe(self, table: str) -> list:
+        return await asyncio.to_thread(self._db.describe, table)
+
+    async def indexes(self, table: str) -> list:
+        return await asyncio.to_thread(self._db.indexes, table)
+
+    async def table_stats(self, table: str) -> dict:
+        return await asyncio.to_thread(self._db.table_stats, table)
+
+    async def create_vector_index(
+        self, table: str, column: str, metric: str = "cosine", index_type: str = "hnsw", *,
+        m=None, ef_construction=None, ef_search=None, name=None, if_not_exists: bool = False,
+    ) -> str:
+        return await asyncio.to_thread(
+            self._db.create_vector_index, table, column, metric, index_type,
+            m=m, ef_construction=ef_construction, ef_search=ef_search, name=name,
+            if_not_exists=if_not_exists,
+        )
+
+    async def vector_index_info(self, table: str, column: str):
+        return await asyncio.to_thread(self._db.vector_index_info, table, column)
+
+    async def vector_columns(self, table: str = None) -> list:
+        return await asyncio.to_thread(self._db.vector_columns, table)
+
+    async def diff_schema(self, other, *, ddl: bool = False) -> dict:
+        if isinstance(other, AsyncDatabase):
+            other = other._db
+        return await asyncio.to_thread(self._db.diff_schema, other, ddl=ddl)
+
+    async def suggest_indexes(self, sql_or_workload) -> list:
+        return await asyncio.to_thread(self._db.suggest_indexes, sql_or_workload)
+
+    async def snapshot(self) -> "AsyncSnapshot":
+        snap = await asyncio.to_thread(self._db.snapshot)
+        return AsyncSnapshot(snap)
+
+    async def checkpoint(self) -> None:
+        await asyncio.to_thread(self._db.checkpoint)
+
+    async def flush(self) -> None:
+        await asyncio.to_thread(self._db.flush)
+
+    async def set_checkpoint_interval(self, seconds: int = None, *, wal_bytes: int = None) -> None:
+        await asyncio.to_thread(self._db.set_checkpoint_interval, seconds, wal_bytes=wal_bytes)
+
+    async def checkpoint_status(self) -> dict:
+        return await asyncio.to_thread(self._db.checkpoint_status)
+
+    async def disk_usage(self) -> dict:
+        return await asyncio.to_thread(self._db.disk_usage)
+
+    async def set_auto_compaction(self, enabled: bool, threshold: int = None) -> None:
+        await asyncio.to_thread(self._db.set_auto_compaction, enabled, threshold)
+
+    async def compaction_status(self) -> dict:
+        return await asyncio.to_thread(self._db.compaction_status)
+
+    async def ping(self) -> float:
+        return await asyncio.to_thread(self._db.ping)
+
+    async def vacuum(self, table: str = None) -> int:
+        return await asyncio.to_thread(self._db.vacuum, table)
+
+    async def compact(self, table: str) -> int:
+        return await asyncio.to_thread(self._db.compact, table)
+
+    async def analyze(self, table: str = None) -> None:
+        await asyncio.to_thread(self._db.analyze, table)
+
+    async def optimize(self) -> None:
+        await asyncio.to_thread(self._db.optimize)
+
+    async def integrity_check(self) -> list:
+        return await asyncio.to_thread(self._db.integrity_check)
+
+    async def stats(self) -> dict:
+        return await asyncio.to_thread(self._db.stats)
+
+    def enable_result_cache(self, max_bytes: int) -> None:
+        self._db.enable_result_cache(max_bytes)
+
+    def disable_result_cache(self) -> None:
+        self._db.disable_result_cache()
+
+    def enable_wal_capture(self, max_records: int = 100_000) -> None:
+        self._db.enable_wal_capture(max_records)
+
+    def disable_wal_capture(self) -> None:
+        self._db.disable_wal_capture()
+
+    def wal_reader(self, from_lsn: int = 1) -> WalReader:
+        return self._db.wal_reader(from_lsn)
+
+    async def apply_wal(self, records):
+        return await asyncio.to_thread(self._db.apply_wal, records)
+
+    def set_slow_query_handler(self, threshold_ms: float, handler) -> None:
+        self._db.set_slow_query_handler(threshold_ms, handler)
+
+    def set_trace(self, target, *, include_params: bool = False, level: int = 10) -> None:
+        self._db.set_trace(target, include_params=include_params, level=level)
+
+    def add_listener(self, on_start=None, on_end=None) -> int:
+        return self._db.add_listener(on_start, on_end)
+
+    def remove_listener(self, listener_id: int) -> bool:
+        return self._db.remove_listener(listener_id)
+
+    def set_audit_hook(self, hook) -> None:
+        self._db.set_audit_hook(hook)
+
+    def watch(self, table: str, callback) -> int:
+        return self._db.watch(table, callback)
+
+    def unwatch(self, watch_id: int) -> bool:
+        return self._db.unwatch(watch_id)
+
+    def current_snapshot_id(self) -> int:
+        return self._db.current_snapshot_id()
+
+    def set_commit_hook(self, hook) -> None:
+        self._db.set_commit_hook(hook)
+
+    def set_rollback_hook(self, hook) -> None:
+        self._db.set_rollback_hook(hook)
+
+    async def reopen(self) -> None:
+        await asyncio.to_thread(self._db.reopen)
+
     async def close(self) -> None:
         await asyncio.to_thread(self._db.close)
 
+    def __reduce__(self):
+        return (AsyncDatabase, (self._db,))
+
     def __repr__(self) -> str:
         return "AsyncDatabase(open)"
 
@@ -107,17 +466,38 @@ class AsyncTransaction:
     def __init__(self, tx: Transaction):
         self._tx = tx
 
-    async def execute(self, sql: str, params=None) -> int:
-        return await asyncio.to_thread(self._tx.execute, sql, params)
-
-    async def query(self, sql: str, params=None) -> list:
-        return await asyncio.to_thread(self._tx.query, sql, params)
-
-    async def query_one(self, sql: str, params=None):
-        return await asyncio.to_thread(self._tx.query_one, sql, params)
-
-    async def query_raw(self, sql: str, params=None) -> dict:
-        return await asyncio.to_thread(self._tx.query_raw, sql, params)
+    async def execute(self, sql: str, params=None, *, cancel=None) -> int:
+        return await asyncio.to_thread(self._tx.execute, sql, params, cancel=cancel)
+
+    async def query(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, limit=None, cancel=None,
+    ) -> "list | tuple[list, bool]":
+        return await asyncio.to_thread(
+            self._tx.query, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, limit=limit, cancel=cancel,
+        )
+
+    async def query_one(self, sql: str, params=None, *, cancel=None):
+        return await asyncio.to_thread(self._tx.query_one, sql, params, cancel=cancel)
+
+    async def query_raw(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, typed=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._tx.query_raw, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, typed=typed,
+        )
+
+    async def query_columnar(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, numpy=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._tx.query_columnar, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, numpy=numpy,
+        )
 
     async def execute_batch(self, sql: str, params_list: list) -> int:
         return await asyncio.to_thread(self._tx.execute_batch, sql, params_list)
@@ -125,14 +505,47 @@ class AsyncTransaction:
     async def execute_prepared(self, stmt, params=None) -> int:
         return await asyncio.to_thread(self._tx.execute_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params)
 
-    async def query_prepared(self, stmt, params=None) -> list:
-        return await asyncio.to_thread(self._tx.query_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params)
+    async def query_prepared(
+        self, stmt, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, limit=None,
+    ) -> "list | tuple[list, bool]":
+        return await asyncio.to_thread(
+            self._tx.query_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, limit=limit,
+        )
 
     async def query_one_prepared(self, stmt, params=None):
         return await asyncio.to_thread(self._tx.query_one_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params)
 
-    async def query_raw_prepared(self, stmt, params=None) -> dict:
-        return await asyncio.to_thread(self._tx.query_raw_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params)
+    async def query_raw_prepared(
+        self, stmt, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, typed=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._tx.query_raw_prepared, stmt._stmt if isinstance(stmt, AsyncPreparedStatement) else stmt, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, typed=typed,
+        )
+
+    @property
+    def active(self) -> bool:
+        return self._tx.active
+
+    @property
+    def id(self) -> int:
+        return self._tx.id
+
+    @property
+    def isolation_level(self) -> str:
+        return self._tx.isolation_level
+
+    async def savepoint(self, name: str) -> None:
+        await asyncio.to_thread(self._tx.savepoint, name)
+
+    async def release_savepoint(self, name: str) -> None:
+        await asyncio.to_thread(self._tx.release_savepoint, name)
+
+    async def rollback_to_savepoint(self, name: str) -> None:
+        await asyncio.to_thread(self._tx.rollback_to_savepoint, name)
 
     async def commit(self) -> None:
         await asyncio.to_thread(self._tx.commit)
@@ -154,6 +567,66 @@ class AsyncTransaction:
         return repr(self._tx)
 
 
+class AsyncSnapshot:
+    """Async wrapper around Snapshot.
+
+    Can be used as an async context manager:
+        async with await db.snapshot() as snap:
+            await snap.query(...)
+    """
+
+    __slots__ = ("_snap",)
+
+    def __init__(self, snap: Snapshot):
+        self._snap = snap
+
+    async def query(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, limit=None,
+    ) -> "list | tuple[list, bool]":
+        return await asyncio.to_thread(
+            self._snap.query, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, limit=limit,
+        )
+
+    async def query_one(self, sql: str, params=None):
+        return await asyncio.to_thread(self._snap.query_one, sql, params)
+
+    async def query_raw(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, typed=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._snap.query_raw, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, typed=typed,
+        )
+
+    async def query_columnar(
+        self, sql: str, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, numpy=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._snap.query_columnar, sql, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, numpy=numpy,
+        )
+
+    async def query_iter(self, sql: str, params=None, *, limit=None) -> RowIterator:
+        return await asyncio.to_thread(self._snap.query_iter, sql, params, limit=limit)
+
+    async def close(self) -> None:
+        await asyncio.to_thread(self._snap.close)
+
+    async def __aenter__(self) -> "AsyncSnapshot":
+        return self
+
+    async def __aexit__(self, exc_type, exc_val, exc_tb) -> bool:
+        await self.close()
+        return False
+
+    def __repr__(self) -> str:
+        return repr(self._snap)
+
+
 class AsyncPreparedStatement:
     """Async wrapper around PreparedStatement."""
 
@@ -165,14 +638,35 @@ class AsyncPreparedStatement:
     async def execute(self, params=None) -> int:
         return await asyncio.to_thread(self._stmt.execute, params)
 
-    async def query(self, params=None) -> list:
-        return await asyncio.to_thread(self._stmt.query, params)
+    async def query(
+        self, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, limit=None,
+    ) -> "list | tuple[list, bool]":
+        return await asyncio.to_thread(
+            self._stmt.query, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, limit=limit,
+        )
 
     async def query_one(self, params=None):
         return await asyncio.to_thread(self._stmt.query_one, params)
 
-    async def query_raw(self, params=None) -> dict:
-        return await asyncio.to_thread(self._stmt.query_raw, params)
+    async def query_raw(
+        self, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, typed=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._stmt.query_raw, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, typed=typed,
+        )
+
+    async def query_columnar(
+        self, params=None, *, intern_strings=False,
+        max_rows=None, max_result_bytes=None, numpy=False,
+    ) -> dict:
+        return await asyncio.to_thread(
+            self._stmt.query_columnar, params, intern_strings=intern_strings,
+            max_rows=max_rows, max_result_bytes=max_result_bytes, numpy=numpy,
+        )
 
     async def execute_batch(self, params_list: list) -> int:
         return await asyncio.to_thread(self._stmt.execute_batch, params_list)
@@ -185,13 +679,83 @@ class AsyncPreparedStatement:
         return repr(self._stmt)
 
 
+class AsyncTable:
+    """Async wrapper around Table."""
+
+    __slots__ = ("_table",)
+
+    def __init__(self, table: Table):
+        self._table = table
+
+    async def insert(self, row: dict) -> int:
+        return await asyncio.to_thread(self._table.insert, row)
+
+    async def get(self, pk):
+        return await asyncio.to_thread(self._table.get, pk)
+
+    async def find(self, where=None, params=None, *, order_by=None, limit=None) -> list:
+        return await asyncio.to_thread(
+            self._table.find, where, params, order_by=order_by, limit=limit,
+        )
+
+    async def update(self, where, values: dict, params=None) -> int:
+        return await asyncio.to_thread(self._table.update, where, values, params)
+
+    async def delete(self, where, params=None) -> int:
+        return await asyncio.to_thread(self._table.delete, where, params)
+
+    @property
+    def name(self) -> str:
+        return self._table.name
+
+    def __repr__(self) -> str:
+        return repr(self._table)
+
+
 __all__ = [
     "Database",
     "Transaction",
     "PreparedStatement",
+    "Snapshot",
+    "RowIterator",
+    "ChunkIterator",
+    "LimitedRows",
     "Vector",
+    "Json",
+    "JsonPath",
+    "Migrations",
+    "WalReader",
+    "TempTable",
+    "Table",
     "AsyncDatabase",
     "AsyncTransaction",
+    "AsyncSnapshot",
     "AsyncPreparedStatement",
+    "AsyncTable",
+    "CancellationToken",
+    "TimeLimit",
+    "Column",
     "StoolapError",
+    "ConflictError",
+    "IntegrityError",
+    "ResultTooLarge",
+    "QueryCancelled",
+    "QueryTimeout",
+    "StoolapWarning",
+    "VectorDimensionError",
+    "cosine_distance",
+    "dot_product",
+    "l2_distance",
+    "quote_ident",
+    "quote_literal",
+    "register_converter",
+    "schema_diff",
+    "set_nan_policy",
+    "PARSE_COLNAMES",
+    "PARSE_DECLTYPES",
+    "set_warning_policy",
+    "connect",
+    "apilevel",
+    "threadsafety",
+    "paramstyle",
 ]
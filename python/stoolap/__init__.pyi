@@ -12,77 +12,688 @@
 # See the License for the specific language governing permissions and
 # limitations under the License.
 
-from typing import Any, Optional, Union, Dict, List, Sequence
+import datetime
+import logging
+import os
+from typing import Any, AsyncIterator, Callable, Optional, Type, TypeVar, Union, Dict, Iterable, Iterator, List, Sequence, Tuple, overload
+
+from stoolap import dbapi
 
 Params = Optional[Union[List[Any], tuple, Dict[str, Any]]]
+AsOf = Optional[Union[int, datetime.datetime, str]]
+T = TypeVar("T")
 ParamSet = Union[List[Any], tuple]
+# A list of row dicts, a LimitedRows when a limit is given
+QueryResult = List[Dict[str, Any]]
 
 __all__: list[str]
 
-class StoolapError(RuntimeError): ...
+class StoolapError(RuntimeError):
+    code: Optional[str]
+    sqlstate: Optional[str]
+    category: Optional[str]
+    table: Optional[str]
+    constraint_name: Optional[str]
+    line: Optional[int]
+    column: Optional[int]
+    offset: Optional[int]
+    token: Optional[str]
+
+class ConflictError(StoolapError): ...
+class IntegrityError(StoolapError): ...
+class ResultTooLarge(StoolapError): ...
+class QueryCancelled(StoolapError): ...
+class QueryTimeout(QueryCancelled): ...
+class StoolapWarning(UserWarning): ...
+
+class VectorDimensionError(StoolapError):
+    expected: Optional[int]
+    actual: Optional[int]
+    param_index: Optional[int]
+    param_name: Optional[str]
+
+class CancellationToken:
+    def __init__(self) -> None: ...
+    def cancel(self) -> None: ...
+    @property
+    def cancelled(self) -> bool: ...
+
+class TimeLimit:
+    def __enter__(self) -> TimeLimit: ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+    @property
+    def remaining(self) -> float: ...
+
+class Column:
+    def __init__(self, name: str, type: str, *, primary_key: bool = False, not_null: bool = False,
+        unique: bool = False, default: Any = None) -> None: ...
+    @property
+    def name(self) -> str: ...
+    @property
+    def type(self) -> str: ...
+    @property
+    def primary_key(self) -> bool: ...
+    @property
+    def not_null(self) -> bool: ...
+    @property
+    def unique(self) -> bool: ...
+    @property
+    def default(self) -> Any: ...
+
+__version__: str
+apilevel: str
+threadsafety: int
+paramstyle: str
+PARSE_DECLTYPES: int
+PARSE_COLNAMES: int
+
+def connect(
+    dsn: str = ":memory:", *, autocommit: bool = False, paramstyle: str = "numeric_dollar"
+) -> dbapi.Connection: ...
+def quote_ident(name: str) -> str: ...
+def quote_literal(value: Any) -> str: ...
+def set_warning_policy(policy: str) -> str: ...
+def register_converter(typename: str, converter: Callable[[Any], Any]) -> None: ...
+def set_nan_policy(
+    *, bind: Optional[str] = None, fetch: Optional[str] = None
+) -> Tuple[str, str]: ...
+def cosine_distance(a: Any, b: Any) -> float: ...
+def l2_distance(a: Any, b: Any) -> float: ...
+def dot_product(a: Any, b: Any) -> float: ...
+def schema_diff(expected: "Database", target: "Database", *, ddl: bool = False) -> Dict[str, Any]: ...
 
 class Database:
     @staticmethod
-    def open(path: str = ":memory:") -> "Database": ...
-    def execute(self, sql: str, params: Params = None) -> int: ...
-    def exec(self, sql: str) -> None: ...
-    def query(self, sql: str, params: Params = None) -> List[Dict[str, Any]]: ...
-    def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    def query_raw(self, sql: str, params: Params = None) -> Dict[str, Any]: ...
-    def execute_batch(self, sql: str, params_list: Sequence[ParamSet]) -> int: ...
+    def open(
+        path: str = ":memory:", *, paramstyle: str = "numeric_dollar", durability: Optional[str] = None,
+        strict_types: bool = False, tz: Union[str, datetime.tzinfo, None] = None,
+        naive_timestamps: bool = False, reject_naive: bool = False,
+        nanosecond_timestamps: bool = False, midnight_as_date: bool = False,
+        detect_types: int = 0, threads: Optional[int] = None,
+    ) -> "Database": ...
+    @staticmethod
+    def open_from_env(prefix: str = "STOOLAP_") -> "Database": ...
+    @property
+    def paramstyle(self) -> str: ...
+    @paramstyle.setter
+    def paramstyle(self, style: str) -> None: ...
+    @property
+    def strict_types(self) -> bool: ...
+    @strict_types.setter
+    def strict_types(self, strict: bool) -> None: ...
+    @property
+    def reject_naive(self) -> bool: ...
+    @reject_naive.setter
+    def reject_naive(self, reject: bool) -> None: ...
+    @property
+    def tz(self) -> Union[str, datetime.tzinfo, None]: ...
+    @property
+    def detect_types(self) -> int: ...
+    @property
+    def dsn(self) -> str: ...
+    def get_setting(self, name: str) -> Any: ...
+    def set_setting(self, name: str, value: Any) -> None: ...
+    def set_var(self, name: str, value: Any) -> None: ...
+    def get_var(self, name: str) -> Any: ...
+    @property
+    def journal_mode(self) -> str: ...
+    def set_journal_mode(self, mode: str) -> None: ...
+    @property
+    def threads(self) -> int: ...
+    def set_threads(self, threads: Optional[int] = None) -> None: ...
+    @property
+    def synchronous(self) -> str: ...
+    @synchronous.setter
+    def synchronous(self, mode: str) -> None: ...
+    def execute(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> int: ...
+    def exec(
+        self, sql: str, params: Params = None, *, params_list: Optional[Sequence[Params]] = None,
+        transaction: bool = False, cancel: Optional[CancellationToken] = None,
+    ) -> List[Union[int, List[Dict[str, Any]]]]: ...
+    def query(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None, cancel: Optional[CancellationToken] = None,
+        hints: Optional[Dict[str, Any]] = None) -> QueryResult: ...
+    def query_one(self, sql: str, params: Params = None, *, as_of: AsOf = None,
+        cancel: Optional[CancellationToken] = None,
+        hints: Optional[Dict[str, Any]] = None) -> Optional[Dict[str, Any]]: ...
+    def query_raw(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    def query_columnar(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
+    def query_chunks(
+        self, sql: str, params: Params = None, chunk_size: int = 1000, *, as_of: AsOf = None
+    ) -> "ChunkIterator": ...
+    def query_arrow_batches(
+        self, sql: str, params: Params = None, batch_rows: int = 65536, *, as_of: AsOf = None
+    ) -> Any: ...
+    def vector_search(
+        self, table: str, column: str, query_vector: Any, k: int, metric: str = "cosine",
+        where: Optional[str] = None, params: Params = None, *, strategy: str = "auto",
+        candidates: Optional[int] = None,
+    ) -> List[Dict[str, Any]]: ...
+    def json_get(
+        self, table: str, column: str, path: Union[str, "JsonPath"], where: Optional[str] = None,
+        params: Params = None,
+    ) -> List[Any]: ...
+    def query_many(
+        self, queries: Iterable[Union[str, Tuple[str, Params]]], max_parallel: Optional[int] = None
+    ) -> List[List[Dict[str, Any]]]: ...
+    def execute_batch(self, sql: str, params_list: Sequence[ParamSet], *,
+        parallel: Optional[int] = None) -> int: ...
+    def insert_vectors(
+        self, table: str, id_column: str, vector_column: str, ids: Iterable[Any], matrix: Any, *,
+        batch_size: int = 10000, normalize: bool = False,
+    ) -> int: ...
+    def sync_from(
+        self, other: "Database", tables: Optional[Sequence[str]] = None, *, mode: str = "upsert"
+    ) -> Dict[str, Dict[str, int]]: ...
+    def temp_table(self, data: Any, columns: Optional[Sequence[str]] = None) -> "TempTable": ...
+    def table(self, table: str) -> "Table": ...
+    def delete_where(self, table: str, where: Union[Dict[str, Any], str], params: Params = None,
+        batch_size: Optional[int] = None) -> int: ...
+    def create_table(self, table: str, columns: Union[Dict[str, str], Sequence[Column]], *,
+        if_not_exists: bool = True) -> None: ...
+    def truncate(self, table: str, restart_identity: bool = False) -> None: ...
+    def map(
+        self, cls: type, table: str, pk: Optional[Union[str, Sequence[str]]] = None,
+        columns: Optional[Sequence[str]] = None,
+    ) -> None: ...
+    def get(self, cls: Type[T], pk: Any) -> Optional[T]: ...
+    def select(
+        self, cls: Type[T], where: Optional[Union[str, Dict[str, Any]]] = None, params: Params = None, *,
+        order_by: Optional[str] = None, limit: Optional[int] = None,
+    ) -> List[T]: ...
+    def save(self, obj: Any) -> None: ...
+    def mogrify(self, sql: str, params: Params = None) -> str: ...
+    def explain(
+        self, sql: str, params: Params = None, *, format: str = "text"
+    ) -> Union[str, Dict[str, Any]]: ...
+    def explain_analyze(
+        self, sql: str, params: Params = None, *, format: str = "text"
+    ) -> Union[str, Dict[str, Any]]: ...
     def prepare(self, sql: str) -> "PreparedStatement": ...
-    def begin(self) -> "Transaction": ...
+    def begin(self, mode: str = "deferred") -> "Transaction": ...
+    def transaction(
+        self, func: Callable[["Transaction"], T], retries: int = 3, backoff: float = 0.01
+    ) -> T: ...
+    @property
+    def in_transaction(self) -> bool: ...
+    @property
+    def server_version(self) -> str: ...
+    @property
+    def server_version_info(self) -> Tuple[int, int, int]: ...
+    def get_isolation_level(self) -> str: ...
+    def tables(self) -> List[str]: ...
+    def describe(self, table: str) -> List[Dict[str, Any]]: ...
+    def indexes(self, table: str) -> List[Dict[str, Any]]: ...
+    def table_stats(self, table: str) -> Dict[str, Any]: ...
+    def create_vector_index(
+        self, table: str, column: str, metric: str = "cosine", index_type: str = "hnsw", *,
+        m: Optional[int] = None, ef_construction: Optional[int] = None,
+        ef_search: Optional[int] = None, name: Optional[str] = None, if_not_exists: bool = False,
+    ) -> str: ...
+    def vector_index_info(self, table: str, column: str) -> Optional[Dict[str, Any]]: ...
+    def vector_columns(self, table: Optional[str] = None) -> List[Dict[str, Any]]: ...
+    def diff_schema(self, other: "Database", *, ddl: bool = False) -> Dict[str, Any]: ...
+    def suggest_indexes(
+        self, sql_or_workload: Union[str, Iterable[Union[str, Tuple[str, Params]]]]
+    ) -> List[Dict[str, Any]]: ...
+    def time_limit(self, seconds: float) -> TimeLimit: ...
+    def snapshot(self) -> "Snapshot": ...
+    def ping(self) -> float: ...
+    def checkpoint(self) -> None: ...
+    def flush(self) -> None: ...
+    def set_checkpoint_interval(
+        self, seconds: Optional[int] = None, *, wal_bytes: Optional[int] = None
+    ) -> None: ...
+    def checkpoint_status(self) -> Dict[str, Any]: ...
+    def disk_usage(self) -> Dict[str, Any]: ...
+    def set_auto_compaction(self, enabled: bool, threshold: Optional[int] = None) -> None: ...
+    def compaction_status(self) -> Dict[str, Any]: ...
+    def vacuum(self, table: Optional[str] = None) -> int: ...
+    def compact(self, table: str) -> int: ...
+    def analyze(self, table: Optional[str] = None) -> None: ...
+    def optimize(self) -> None: ...
+    def integrity_check(self) -> List[Dict[str, str]]: ...
+    def stats(self) -> Dict[str, Union[int, float]]: ...
+    def enable_result_cache(self, max_bytes: int) -> None: ...
+    def disable_result_cache(self) -> None: ...
+    def enable_wal_capture(self, max_records: int = 100_000) -> None: ...
+    def disable_wal_capture(self) -> None: ...
+    def wal_reader(self, from_lsn: int = 1) -> "WalReader": ...
+    def apply_wal(self, records: Iterable[Union[bytes, str]]) -> Optional[int]: ...
+    def set_slow_query_handler(
+        self,
+        threshold_ms: float,
+        handler: Optional[Callable[[str, float, Optional[int]], Any]],
+    ) -> None: ...
+    def set_trace(
+        self,
+        target: Optional[Union[Callable[[str, float, Optional[str]], Any], logging.Logger]],
+        *,
+        include_params: bool = False,
+        level: int = logging.DEBUG,
+    ) -> None: ...
+    def add_listener(
+        self,
+        on_start: Optional[Callable[[Dict[str, Any]], Any]] = None,
+        on_end: Optional[Callable[[Dict[str, Any], Any], Any]] = None,
+    ) -> int: ...
+    def remove_listener(self, listener_id: int) -> bool: ...
+    def set_audit_hook(self, hook: Optional[Callable[[Dict[str, Any]], Any]]) -> None: ...
+    def watch(self, table: str, callback: Callable[[str, str, Any], Any]) -> int: ...
+    def unwatch(self, watch_id: int) -> bool: ...
+    def current_snapshot_id(self) -> int: ...
+    def set_commit_hook(self, hook: Optional[Callable[[], Any]]) -> None: ...
+    def set_rollback_hook(self, hook: Optional[Callable[[], Any]]) -> None: ...
+    def reopen(self) -> None: ...
     def close(self) -> None: ...
 
 class Transaction:
-    def execute(self, sql: str, params: Params = None) -> int: ...
-    def query(self, sql: str, params: Params = None) -> List[Dict[str, Any]]: ...
-    def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    def query_raw(self, sql: str, params: Params = None) -> Dict[str, Any]: ...
+    def execute(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> int: ...
+    def query(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None, cancel: Optional[CancellationToken] = None) -> QueryResult: ...
+    def query_one(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> Optional[Dict[str, Any]]: ...
+    def query_raw(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    def query_columnar(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
     def execute_batch(self, sql: str, params_list: Sequence[ParamSet]) -> int: ...
+    @property
+    def active(self) -> bool: ...
+    @property
+    def id(self) -> int: ...
+    @property
+    def isolation_level(self) -> str: ...
+    def savepoint(self, name: str) -> None: ...
+    def release_savepoint(self, name: str) -> None: ...
+    def rollback_to_savepoint(self, name: str) -> None: ...
     def commit(self) -> None: ...
     def rollback(self) -> None: ...
     def __enter__(self) -> "Transaction": ...
     def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
 
+class Snapshot:
+    def query(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None) -> QueryResult: ...
+    def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
+    def query_raw(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    def query_columnar(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
+    def query_iter(self, sql: str, params: Params = None, *, limit: Optional[int] = None) -> "RowIterator": ...
+    def close(self) -> None: ...
+    def __enter__(self) -> "Snapshot": ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+
+class LimitedRows(List[Dict[str, Any]]):
+    truncated: bool
+
+class RowIterator:
+    @property
+    def columns(self) -> List[str]: ...
+    @property
+    def truncated(self) -> bool: ...
+    def __iter__(self) -> "RowIterator": ...
+    def __next__(self) -> Dict[str, Any]: ...
+    def __len__(self) -> int: ...
+
+class ChunkIterator:
+    @property
+    def columns(self) -> List[str]: ...
+    def __iter__(self) -> "ChunkIterator": ...
+    def __next__(self) -> List[Dict[str, Any]]: ...
+    def close(self) -> None: ...
+
 class PreparedStatement:
     @property
     def sql(self) -> str: ...
     def execute(self, params: Params = None) -> int: ...
-    def query(self, params: Params = None) -> List[Dict[str, Any]]: ...
+    def query(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None) -> QueryResult: ...
     def query_one(self, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    def query_raw(self, params: Params = None) -> Dict[str, Any]: ...
+    def query_raw(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    def query_columnar(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
     def execute_batch(self, params_list: Sequence[ParamSet]) -> int: ...
 
+class Vector:
+    def __init__(
+        self, data: Any, dtype: Optional[str] = None, *, scale: float = 1.0, offset: float = 0.0,
+        normalize: bool = False,
+    ) -> None: ...
+    def __len__(self) -> int: ...
+    @overload
+    def __getitem__(self, index: int) -> float: ...
+    @overload
+    def __getitem__(self, index: slice) -> "Vector": ...
+    def __iter__(self) -> Iterator[float]: ...
+    def __contains__(self, value: object) -> bool: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __dlpack__(
+        self, *, stream: Any = None, max_version: Optional[Tuple[int, int]] = None,
+        dl_device: Optional[Tuple[int, int]] = None, copy: Optional[bool] = None,
+    ) -> Any: ...
+    def __dlpack_device__(self) -> Tuple[int, int]: ...
+    @property
+    def dtype(self) -> str: ...
+    @property
+    def scale(self) -> float: ...
+    @property
+    def offset(self) -> float: ...
+    @property
+    def normalize(self) -> bool: ...
+    def codes(self) -> Optional[List[int]]: ...
+    @staticmethod
+    def from_bytes(
+        data: bytes, dtype: str = "f32", *, scale: float = 1.0, offset: float = 0.0,
+        normalize: bool = False,
+    ) -> "Vector": ...
+    def to_bytes(self) -> bytes: ...
+    def to_list(self) -> List[float]: ...
+
+class Json:
+    def __init__(self, value: Any) -> None: ...
+    @property
+    def value(self) -> Any: ...
+
+class JsonPath:
+    def __init__(self, *segments: Union[str, int]) -> None: ...
+    def child(self, *segments: Union[str, int]) -> "JsonPath": ...
+    @property
+    def path(self) -> str: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+class Migrations:
+    def __init__(
+        self, db: Database, directory: Optional[Union[str, "os.PathLike[str]"]] = None, *,
+        table: str = "_migrations",
+    ) -> None: ...
+    def add(
+        self, version: int, up: Union[str, Callable[[Transaction], Any]],
+        down: Optional[Union[str, Callable[[Transaction], Any]]] = None, *, name: Optional[str] = None,
+    ) -> None: ...
+    def applied(self) -> List[Dict[str, Any]]: ...
+    def pending(self) -> List[Tuple[int, str]]: ...
+    @property
+    def current_version(self) -> Optional[int]: ...
+    def apply(self, target: Optional[int] = None) -> List[int]: ...
+    def rollback(self, to_version: int) -> List[int]: ...
+
+class TempTable:
+    def __enter__(self) -> str: ...
+    def __exit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+    @property
+    def name(self) -> str: ...
+
+class Table:
+    @property
+    def name(self) -> str: ...
+    def insert(self, row: Dict[str, Any]) -> int: ...
+    def get(self, pk: Any) -> Optional[Dict[str, Any]]: ...
+    def find(
+        self, where: Optional[Union[str, Dict[str, Any]]] = None, params: Params = None, *,
+        order_by: Optional[str] = None, limit: Optional[int] = None,
+    ) -> List[Dict[str, Any]]: ...
+    def update(self, where: Union[str, Dict[str, Any]], values: Dict[str, Any], params: Params = None) -> int: ...
+    def delete(self, where: Union[str, Dict[str, Any]], params: Params = None) -> int: ...
+
+class WalReader:
+    def __iter__(self) -> "WalReader": ...
+    def __next__(self) -> bytes: ...
+    @property
+    def lsn(self) -> int: ...
+
 class AsyncDatabase:
     @classmethod
-    async def open(cls, path: str = ":memory:") -> "AsyncDatabase": ...
-    async def execute(self, sql: str, params: Params = None) -> int: ...
-    async def exec(self, sql: str) -> None: ...
-    async def query(self, sql: str, params: Params = None) -> List[Dict[str, Any]]: ...
-    async def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    async def query_raw(self, sql: str, params: Params = None) -> Dict[str, Any]: ...
-    async def execute_batch(self, sql: str, params_list: Sequence[ParamSet]) -> int: ...
+    async def open(
+        cls, path: str = ":memory:", *, paramstyle: str = "numeric_dollar", durability: Optional[str] = None,
+        strict_types: bool = False, tz: Union[str, datetime.tzinfo, None] = None,
+        naive_timestamps: bool = False, reject_naive: bool = False,
+        nanosecond_timestamps: bool = False, midnight_as_date: bool = False,
+        detect_types: int = 0, threads: Optional[int] = None,
+    ) -> "AsyncDatabase": ...
+    @classmethod
+    async def open_from_env(cls, prefix: str = "STOOLAP_") -> "AsyncDatabase": ...
+    @property
+    def paramstyle(self) -> str: ...
+    @paramstyle.setter
+    def paramstyle(self, style: str) -> None: ...
+    @property
+    def strict_types(self) -> bool: ...
+    @property
+    def tz(self) -> Union[str, datetime.tzinfo, None]: ...
+    @property
+    def detect_types(self) -> int: ...
+    def get_setting(self, name: str) -> Any: ...
+    async def set_setting(self, name: str, value: Any) -> None: ...
+    def set_var(self, name: str, value: Any) -> None: ...
+    def get_var(self, name: str) -> Any: ...
+    @property
+    def journal_mode(self) -> str: ...
+    async def set_journal_mode(self, mode: str) -> None: ...
+    @property
+    def threads(self) -> int: ...
+    def set_threads(self, threads: Optional[int] = None) -> None: ...
+    @property
+    def synchronous(self) -> str: ...
+    async def set_synchronous(self, mode: str) -> None: ...
+    async def execute(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> int: ...
+    async def exec(
+        self, sql: str, params: Params = None, *, params_list: Optional[Sequence[Params]] = None,
+        transaction: bool = False, cancel: Optional[CancellationToken] = None,
+    ) -> List[Union[int, List[Dict[str, Any]]]]: ...
+    async def query(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None, cancel: Optional[CancellationToken] = None,
+        hints: Optional[Dict[str, Any]] = None) -> QueryResult: ...
+    async def query_one(self, sql: str, params: Params = None, *, as_of: AsOf = None,
+        cancel: Optional[CancellationToken] = None,
+        hints: Optional[Dict[str, Any]] = None) -> Optional[Dict[str, Any]]: ...
+    async def query_raw(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    async def query_columnar(self, sql: str, params: Params = None, *, as_of: AsOf = None, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
+    def query_chunks(
+        self, sql: str, params: Params = None, chunk_size: int = 1000, *, as_of: AsOf = None
+    ) -> AsyncIterator[List[Dict[str, Any]]]: ...
+    async def vector_search(
+        self, table: str, column: str, query_vector: Any, k: int, metric: str = "cosine",
+        where: Optional[str] = None, params: Params = None, *, strategy: str = "auto",
+        candidates: Optional[int] = None,
+    ) -> List[Dict[str, Any]]: ...
+    async def json_get(
+        self, table: str, column: str, path: Union[str, "JsonPath"], where: Optional[str] = None,
+        params: Params = None,
+    ) -> List[Any]: ...
+    async def query_many(
+        self, queries: Iterable[Union[str, Tuple[str, Params]]], max_parallel: Optional[int] = None
+    ) -> List[List[Dict[str, Any]]]: ...
+    async def execute_batch(self, sql: str, params_list: Sequence[ParamSet], *,
+        parallel: Optional[int] = None) -> int: ...
+    async def insert_vectors(
+        self, table: str, id_column: str, vector_column: str, ids: Iterable[Any], matrix: Any, *,
+        batch_size: int = 10000, normalize: bool = False,
+    ) -> int: ...
+    async def sync_from(
+        self, other: Union["Database", "AsyncDatabase"], tables: Optional[Sequence[str]] = None, *,
+        mode: str = "upsert",
+    ) -> Dict[str, Dict[str, int]]: ...
+    def mogrify(self, sql: str, params: Params = None) -> str: ...
+    async def explain(
+        self, sql: str, params: Params = None, *, format: str = "text"
+    ) -> Union[str, Dict[str, Any]]: ...
+    async def explain_analyze(
+        self, sql: str, params: Params = None, *, format: str = "text"
+    ) -> Union[str, Dict[str, Any]]: ...
     def prepare(self, sql: str) -> "AsyncPreparedStatement": ...
-    async def begin(self) -> "AsyncTransaction": ...
+    def table(self, table: str) -> "AsyncTable": ...
+    async def delete_where(self, table: str, where: Union[Dict[str, Any], str], params: Params = None,
+        batch_size: Optional[int] = None) -> int: ...
+    async def create_table(self, table: str, columns: Union[Dict[str, str], Sequence[Column]], *,
+        if_not_exists: bool = True) -> None: ...
+    async def truncate(self, table: str, restart_identity: bool = False) -> None: ...
+    def map(
+        self, cls: type, table: str, pk: Optional[Union[str, Sequence[str]]] = None,
+        columns: Optional[Sequence[str]] = None,
+    ) -> None: ...
+    async def get(self, cls: Type[T], pk: Any) -> Optional[T]: ...
+    async def select(
+        self, cls: Type[T], where: Optional[Union[str, Dict[str, Any]]] = None, params: Params = None, *,
+        order_by: Optional[str] = None, limit: Optional[int] = None,
+    ) -> List[T]: ...
+    async def save(self, obj: Any) -> None: ...
+    async def begin(self, mode: str = "deferred") -> "AsyncTransaction": ...
+    async def transaction(
+        self, func: Callable[["Transaction"], T], retries: int = 3, backoff: float = 0.01
+    ) -> T: ...
+    @property
+    def in_transaction(self) -> bool: ...
+    @property
+    def server_version(self) -> str: ...
+    @property
+    def server_version_info(self) -> Tuple[int, int, int]: ...
+    def get_isolation_level(self) -> str: ...
+    async def tables(self) -> List[str]: ...
+    async def describe(self, table: str) -> List[Dict[str, Any]]: ...
+    async def indexes(self, table: str) -> List[Dict[str, Any]]: ...
+    async def table_stats(self, table: str) -> Dict[str, Any]: ...
+    async def create_vector_index(
+        self, table: str, column: str, metric: str = "cosine", index_type: str = "hnsw", *,
+        m: Optional[int] = None, ef_construction: Optional[int] = None,
+        ef_search: Optional[int] = None, name: Optional[str] = None, if_not_exists: bool = False,
+    ) -> str: ...
+    async def vector_index_info(self, table: str, column: str) -> Optional[Dict[str, Any]]: ...
+    async def vector_columns(self, table: Optional[str] = None) -> List[Dict[str, Any]]: ...
+    async def diff_schema(
+        self, other: Union["Database", "AsyncDatabase"], *, ddl: bool = False
+    ) -> Dict[str, Any]: ...
+    async def suggest_indexes(
+        self, sql_or_workload: Union[str, Iterable[Union[str, Tuple[str, Params]]]]
+    ) -> List[Dict[str, Any]]: ...
+    async def snapshot(self) -> "AsyncSnapshot": ...
+    async def ping(self) -> float: ...
+    async def checkpoint(self) -> None: ...
+    async def flush(self) -> None: ...
+    async def set_checkpoint_interval(
+        self, seconds: Optional[int] = None, *, wal_bytes: Optional[int] = None
+    ) -> None: ...
+    async def checkpoint_status(self) -> Dict[str, Any]: ...
+    async def disk_usage(self) -> Dict[str, Any]: ...
+    async def set_auto_compaction(self, enabled: bool, threshold: Optional[int] = None) -> None: ...
+    async def compaction_status(self) -> Dict[str, Any]: ...
+    async def vacuum(self, table: Optional[str] = None) -> int: ...
+    async def compact(self, table: str) -> int: ...
+    async def analyze(self, table: Optional[str] = None) -> None: ...
+    async def optimize(self) -> None: ...
+    async def integrity_check(self) -> List[Dict[str, str]]: ...
+    async def stats(self) -> Dict[str, Union[int, float]]: ...
+    def enable_result_cache(self, max_bytes: int) -> None: ...
+    def disable_result_cache(self) -> None: ...
+    def enable_wal_capture(self, max_records: int = 100_000) -> None: ...
+    def disable_wal_capture(self) -> None: ...
+    def wal_reader(self, from_lsn: int = 1) -> "WalReader": ...
+    async def apply_wal(self, records: Iterable[Union[bytes, str]]) -> Optional[int]: ...
+    def set_slow_query_handler(
+        self,
+        threshold_ms: float,
+        handler: Optional[Callable[[str, float, Optional[int]], Any]],
+    ) -> None: ...
+    def set_trace(
+        self,
+        target: Optional[Union[Callable[[str, float, Optional[str]], Any], logging.Logger]],
+        *,
+        include_params: bool = False,
+        level: int = logging.DEBUG,
+    ) -> None: ...
+    def add_listener(
+        self,
+        on_start: Optional[Callable[[Dict[str, Any]], Any]] = None,
+        on_end: Optional[Callable[[Dict[str, Any], Any], Any]] = None,
+    ) -> int: ...
+    def remove_listener(self, listener_id: int) -> bool: ...
+    def set_audit_hook(self, hook: Optional[Callable[[Dict[str, Any]], Any]]) -> None: ...
+    def watch(self, table: str, callback: Callable[[str, str, Any], Any]) -> int: ...
+    def unwatch(self, watch_id: int) -> bool: ...
+    def current_snapshot_id(self) -> int: ...
+    def set_commit_hook(self, hook: Optional[Callable[[], Any]]) -> None: ...
+    def set_rollback_hook(self, hook: Optional[Callable[[], Any]]) -> None: ...
+    async def reopen(self) -> None: ...
     async def close(self) -> None: ...
 
 class AsyncTransaction:
-    async def execute(self, sql: str, params: Params = None) -> int: ...
-    async def query(self, sql: str, params: Params = None) -> List[Dict[str, Any]]: ...
-    async def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    async def query_raw(self, sql: str, params: Params = None) -> Dict[str, Any]: ...
+    async def execute(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> int: ...
+    async def query(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None, cancel: Optional[CancellationToken] = None) -> QueryResult: ...
+    async def query_one(self, sql: str, params: Params = None, *,
+        cancel: Optional[CancellationToken] = None) -> Optional[Dict[str, Any]]: ...
+    async def query_raw(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    async def query_columnar(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
     async def execute_batch(self, sql: str, params_list: Sequence[ParamSet]) -> int: ...
+    @property
+    def active(self) -> bool: ...
+    @property
+    def id(self) -> int: ...
+    @property
+    def isolation_level(self) -> str: ...
+    async def savepoint(self, name: str) -> None: ...
+    async def release_savepoint(self, name: str) -> None: ...
+    async def rollback_to_savepoint(self, name: str) -> None: ...
     async def commit(self) -> None: ...
     async def rollback(self) -> None: ...
     async def __aenter__(self) -> "AsyncTransaction": ...
     async def __aexit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
 
+class AsyncSnapshot:
+    async def query(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None) -> QueryResult: ...
+    async def query_one(self, sql: str, params: Params = None) -> Optional[Dict[str, Any]]: ...
+    async def query_raw(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    async def query_columnar(self, sql: str, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
+    async def query_iter(self, sql: str, params: Params = None, *, limit: Optional[int] = None) -> RowIterator: ...
+    async def close(self) -> None: ...
+    async def __aenter__(self) -> "AsyncSnapshot": ...
+    async def __aexit__(self, exc_type: Any, exc_val: Any, exc_tb: Any) -> bool: ...
+
+class AsyncTable:
+    @property
+    def name(self) -> str: ...
+    async def insert(self, row: Dict[str, Any]) -> int: ...
+    async def get(self, pk: Any) -> Optional[Dict[str, Any]]: ...
+    async def find(
+        self, where: Optional[Union[str, Dict[str, Any]]] = None, params: Params = None, *,
+        order_by: Optional[str] = None, limit: Optional[int] = None,
+    ) -> List[Dict[str, Any]]: ...
+    async def update(self, where: Union[str, Dict[str, Any]], values: Dict[str, Any], params: Params = None) -> int: ...
+    async def delete(self, where: Union[str, Dict[str, Any]], params: Params = None) -> int: ...
+
 class AsyncPreparedStatement:
     @property
     def sql(self) -> str: ...
     async def execute(self, params: Params = None) -> int: ...
-    async def query(self, params: Params = None) -> List[Dict[str, Any]]: ...
+    async def query(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None,
+        limit: Optional[int] = None) -> QueryResult: ...
     async def query_one(self, params: Params = None) -> Optional[Dict[str, Any]]: ...
-    async def query_raw(self, params: Params = None) -> Dict[str, Any]: ...
+    async def query_raw(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, typed: bool = False) -> Dict[str, Any]: ...
+    async def query_columnar(self, params: Params = None, *, intern_strings: bool = False,
+        max_rows: Optional[int] = None, max_result_bytes: Optional[int] = None, numpy: bool = False) -> Dict[str, Any]: ...
     async def execute_batch(self, params_list: Sequence[ParamSet]) -> int: ...
@@ -0,0 +1,45 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Arrow export of query results. Imported on first use; requires pyarrow."""
+
+try:
+    import pyarrow as pa
+except ImportError as e:
+    raise ImportError("query_arrow_batches requires pyarrow (pip install pyarrow)") from e
+
+
+def record_batch_reader(chunks):
+    """Wrap a columnar ChunkIterator in a pyarrow.RecordBatchReader.
+
+    The schema is inferred from the first batch and applied to the rest.
+    """
+    first = next(chunks, None)
+    if first is None:
+        chunks.close()
+        schema = pa.schema([(name, pa.null()) for name in chunks.columns])
+        return pa.RecordBatchReader.from_batches(schema, [])
+
+    head = pa.RecordBatch.from_pydict(first)
+    schema = head.schema
+
+    def batches():
+        try:
+            yield head
+            for data in chunks:
+                yield pa.RecordBatch.from_pydict(data, schema=schema)
+        finally:
+            chunks.close()
+
+    return pa.RecordBatchReader.from_batches(schema, batches())
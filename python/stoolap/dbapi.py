@@ -0,0 +1,445 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""DB-API 2.0 (PEP 249) interface, for tools such as SQLAlchemy dialects.
+
+    import stoolap.dbapi
+
+    conn = stoolap.dbapi.connect(":memory:")
+    cur = conn.cursor()
+    cur.execute("SELECT $1 AS answer", [42])
+    cur.description  # (("answer", None, None, None, None, None, None),)
+    cur.fetchone()   # (42,)
+    conn.commit()
+
+Statements on a connection run in a transaction that is begun implicitly
+and ended by commit() or rollback(), unless `autocommit` is set.
+"""
+
+import datetime
+import functools
+import re
+import time
+
+from stoolap import _stoolap
+from stoolap._stoolap import (
+    Database,
+    StoolapError,
+    quote_ident,
+    __version__,
+)
+
+apilevel = "2.0"
+# Threads may share the module and connections, but not cursors
+threadsafety = 2
+# $1, $2, ... (SQLAlchemy's name for PostgreSQL-style numeric placeholders)
+paramstyle = "numeric_dollar"
+
+
+class Warning(Exception):
+    pass
+
+
+Error = StoolapError
+
+
+class InterfaceError(Error):
+    """Misuse of the DB-API objects, such as a closed cursor."""
+
+
+class DatabaseError(Error):
+    pass
+
+
+class DataError(DatabaseError):
+    """Invalid values, such as a type mismatch or division by zero."""
+
+
+class OperationalError(DatabaseError):
+    """Write-write conflicts, timeouts and I/O errors; conflicts can be retried."""
+
+
+class IntegrityError(DatabaseError, _stoolap.IntegrityError):
+    """Constraint violations. Also a `stoolap.IntegrityError`."""
+
+
+class InternalError(DatabaseError):
+    pass
+
+
+class ProgrammingError(DatabaseError):
+    """Syntax errors, missing or duplicate tables and columns, and misuse."""
+
+
+class NotSupportedError(DatabaseError):
+    pass
+
+
+# The DB-API class for an engine error, by the first two characters of its
+# `sqlstate`
+_SQLSTATE_CLASSES = {
+    "22": DataError,
+    "23": IntegrityError,
+    "25": OperationalError,
+    "40": OperationalError,
+    "42": ProgrammingError,
+    "58": OperationalError,
+}
+
+
+@functools.lru_cache(maxsize=None)
+def _error_class(cls, native):
+    """`cls`, also deriving from the engine's exception class `native`."""
+    if native is StoolapError or issubclass(cls, native):
+        return cls
+    return type(cls.__name__, (cls, native), {"__module__": __name__})
+
+
+def _translate(exc):
+    """The DB-API exception for the engine error `exc`.
+
+    The result keeps the class of `exc` as a second base, so handlers for
+    `stoolap.ConflictError` or `stoolap.QueryTimeout` still catch it, and
+    carries the same details (`sqlstate`, `category`, `table`, ...).
+    """
+    if isinstance(exc, (DatabaseError, InterfaceError)):
+        return exc
+    if isinstance(exc, _stoolap.QueryCancelled):
+        cls = OperationalError
+    else:
+        cls = _SQLSTATE_CLASSES.get((getattr(exc, "sqlstate", None) or "")[:2], DatabaseError)
+    translated = _error_class(cls, type(exc))(*exc.args)
+    translated.__dict__.update(exc.__dict__)
+    return translated
+
+
+def _translating(method):
+    """Raise engine errors from `method` as their DB-API classes."""
+
+    @functools.wraps(method)
+    def wrapper(*args, **kwargs):
+        try:
+            return method(*args, **kwargs)
+        except StoolapError as exc:
+            translated = _translate(exc)
+            if translated is exc:
+                raise
+            raise translated from exc
+
+    return wrapper
+
+
+Date = datetime.date
+Time = datetime.time
+Timestamp = datetime.datetime
+Binary = bytes
+
+
+def DateFromTicks(ticks):
+    return Date.fromtimestamp(ticks)
+
+
+def TimeFromTicks(ticks):
+    return Time(*time.localtime(ticks)[3:6])
+
+
+def TimestampFromTicks(ticks):
+    return Timestamp.fromtimestamp(ticks)
+
+
+class _TypeCode(frozenset):
+    def __eq__(self, other):
+        return other in self
+
+    def __ne__(self, other):
+        return other not in self
+
+    __hash__ = frozenset.__hash__
+
+
+# Result columns carry no type information, so description type codes are
+# always None and these only match engine type names.
+STRING = _TypeCode({"TEXT", "JSON"})
+BINARY = _TypeCode()
+NUMBER = _TypeCode({"INTEGER", "FLOAT", "BOOLEAN"})
+DATETIME = _TypeCode({"TIMESTAMP"})
+ROWID = _TypeCode({"INTEGER"})
+
+# Statements that produce a result set. Anything else is run with execute()
+# and reports a row count.
+_QUERY_RE = re.compile(
+    r"^\s*(?:(?:--[^\n]*\n|/\*.*?\*/)\s*)*\(*\s*(SELECT|WITH|SHOW|EXPLAIN|VALUES|DESCRIBE|PRAGMA)\b",
+    re.IGNORECASE | re.DOTALL,
+)
+_RETURNING_RE = re.compile(r"\bRETURNING\b", re.IGNORECASE)
+# String literals, quoted identifiers and comments, blanked out before
+# looking for RETURNING
+_LITERAL_RE = re.compile(
+    r"'(?:[^']|'')*'?|\"(?:[^\"]|\"\")*\"?|--[^\n]*|/\*.*?(?:\*/|$)",
+    re.DOTALL,
+)
+# Savepoint statements, run through the Transaction's savepoint methods
+_SAVEPOINT_RE = re.compile(
+    r'^\s*(SAVEPOINT|RELEASE(?:\s+SAVEPOINT)?|ROLLBACK\s+TO(?:\s+SAVEPOINT)?)\s+'
+    r'("(?:[^"]|"")+"|\w+)\s*;?\s*$',
+    re.IGNORECASE,
+)
+
+
+def _returns_rows(sql):
+    if _QUERY_RE.match(sql):
+        return True
+    return bool(_RETURNING_RE.search(_LITERAL_RE.sub(" ", sql)))
+
+
+@_translating
+def connect(dsn=":memory:", *, autocommit=False, paramstyle="numeric_dollar"):
+    """Open `dsn` (any path accepted by Database.open) as a DB-API connection.
+
+    `paramstyle="pyformat"` accepts `%s` and `%(name)s` placeholders, for
+    code written against psycopg or MySQLdb.
+    """
+    return Connection(Database.open(dsn, paramstyle=paramstyle), autocommit=autocommit)
+
+
+class Connection:
+    """DB-API connection wrapping a Database."""
+
+    Warning = Warning
+    Error = Error
+    InterfaceError = InterfaceError
+    DatabaseError = DatabaseError
+    DataError = DataError
+    OperationalError = OperationalError
+    IntegrityError = IntegrityError
+    InternalError = InternalError
+    ProgrammingError = ProgrammingError
+    NotSupportedError = NotSupportedError
+
+    def __init__(self, db, *, autocommit=False):
+        self._db = db
+        self._tx = None
+        self._autocommit = autocommit
+        self._closed = False
+
+    @property
+    def database(self):
+        """The underlying Database."""
+        return self._db
+
+    @property
+    def autocommit(self):
+        return self._autocommit
+
+    @autocommit.setter
+    def autocommit(self, value):
+        # Switching autocommit on commits the pending transaction
+        if value and self._tx is not None:
+            self.commit()
+        self._autocommit = bool(value)
+
+    @property
+    def in_transaction(self):
+        return self._tx is not None
+
+    @property
+    def paramstyle(self):
+        return self._db.paramstyle
+
+    @property
+    def server_version(self):
+        return self._db.server_version
+
+    @property
+    def server_version_info(self):
+        return self._db.server_version_info
+
+    def get_isolation_level(self):
+        if self._tx is not None:
+            return self._tx.isolation_level
+        return self._db.get_isolation_level()
+
+    @_translating
+    def last_insert_id(self, table, pk_name="id"):
+        """Largest `pk_name` in `table` as seen by the current transaction.
+
+        The engine does not report generated keys, so this relies on
+        AUTO_INCREMENT keys only growing. It is exact unless another
+        connection inserts into `table` concurrently.
+        """
+        sql = f"SELECT MAX({quote_ident(pk_name)}) AS id FROM {quote_ident(table)}"
+        return self._target().query_one(sql)["id"]
+
+    def cursor(self):
+        self._check_open()
+        return Cursor(self)
+
+    @_translating
+    def commit(self):
+        self._check_open()
+        tx, self._tx = self._tx, None
+        if tx is not None:
+            tx.commit()
+
+    @_translating
+    def rollback(self):
+        self._check_open()
+        tx, self._tx = self._tx, None
+        if tx is not None:
+            tx.rollback()
+
+    @_translating
+    def close(self):
+        if self._closed:
+            return
+        tx, self._tx = self._tx, None
+        if tx is not None:
+            tx.rollback()
+        self._db.close()
+        self._closed = True
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_val, exc_tb):
+        # Like sqlite3: ends the transaction but leaves the connection open
+        if exc_type is None:
+            self.commit()
+        else:
+            self.rollback()
+        return False
+
+    def _check_open(self):
+        if self._closed:
+            raise InterfaceError("connection is closed")
+
+    def _target(self):
+        """Where the next statement runs: the database or the open transaction."""
+        self._check_open()
+        if self._autocommit:
+            return self._db
+        if self._tx is None:
+            self._tx = self._db.begin()
+        return self._tx
+
+
+def _run_savepoint(tx, command, name):
+    if name.startswith('"'):
+        name = name[1:-1].replace('""', '"')
+    command = command.split()[0].upper()
+    if command == "SAVEPOINT":
+        tx.savepoint(name)
+    elif command == "RELEASE":
+        tx.release_savepoint(name)
+    else:
+        tx.rollback_to_savepoint(name)
+
+
+class Cursor:
+    """DB-API cursor. Results are fetched in full by execute()."""
+
+    def __init__(self, connection):
+        self.connection = connection
+        self.arraysize = 1
+        self.description = None
+        self.rowcount = -1
+        self.lastrowid = None
+        self._rows = []
+        self._pos = 0
+        self._closed = False
+
+    @_translating
+    def execute(self, operation, parameters=None):
+        self._check_open()
+        target = self.connection._target()
+        savepoint = _SAVEPOINT_RE.match(operation)
+        if savepoint and not self.connection.autocommit:
+            _run_savepoint(target, *savepoint.groups())
+            self.rowcount = -1
+            self.description = None
+            self._rows = []
+        elif _returns_rows(operation):
+            raw = target.query_raw(operation, parameters)
+            self.description = tuple(
+                (name, None, None, None, None, None, None) for name in raw["columns"]
+            )
+            self._rows = [tuple(row) for row in raw["rows"]]
+            self.rowcount = len(self._rows)
+        else:
+            self.rowcount = target.execute(operation, parameters)
+            self.description = None
+            self._rows = []
+        self._pos = 0
+        return self
+
+    @_translating
+    def executemany(self, operation, seq_of_parameters):
+        self._check_open()
+        target = self.connection._target()
+        self.rowcount = target.execute_batch(operation, list(seq_of_parameters))
+        self.description = None
+        self._rows = []
+        self._pos = 0
+        return self
+
+    def fetchone(self):
+        self._check_result()
+        if self._pos >= len(self._rows):
+            return None
+        row = self._rows[self._pos]
+        self._pos += 1
+        return row
+
+    def fetchmany(self, size=None):
+        self._check_result()
+        size = self.arraysize if size is None else size
+        rows = self._rows[self._pos:self._pos + size]
+        self._pos += len(rows)
+        return rows
+
+    def fetchall(self):
+        self._check_result()
+        rows = self._rows[self._pos:]
+        self._pos = len(self._rows)
+        return rows
+
+    def __iter__(self):
+        return iter(self.fetchone, None)
+
+    def close(self):
+        self._closed = True
+        self._rows = []
+
+    def setinputsizes(self, sizes):
+        pass
+
+    def setoutputsize(self, size, column=None):
+        pass
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, exc_type, exc_val, exc_tb):
+        self.close()
+        return False
+
+    def _check_open(self):
+        if self._closed:
+            raise InterfaceError("cursor is closed")
+        self.connection._check_open()
+
+    def _check_result(self):
+        self._check_open()
+        if self.description is None:
+            raise ProgrammingError("no result set; the last statement did not return rows")
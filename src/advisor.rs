@@ -0,0 +1,304 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Index suggestions, from the full table scans in the plans of a workload.
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+use std::collections::HashMap;
+
+use crate::database::{field, Database};
+use crate::explain::PlanNode;
+use crate::sql;
+
+/// A full scan of `table` and the columns its filter compares.
+struct Scan {
+    table: String,
+    equality: Vec<String>,
+    range: Vec<String>,
+    /// The planner's cost for the scan, or its row estimate.
+    weight: f64,
+}
+
+/// A table's columns, and the columns of each of its indexes in order.
+struct Table {
+    name: String,
+    columns: Vec<String>,
+    indexes: Vec<Vec<String>>,
+}
+
+/// An index to suggest, with the scans it would serve.
+struct Candidate {
+    table: String,
+    columns: Vec<String>,
+    benefit: f64,
+    queries: usize,
+}
+
+/// Suggest indexes for `workload`: one SQL string, or an iterable of SQL
+/// strings and `(sql, params)` pairs.
+///
+/// Each query is run through `EXPLAIN`. A full scan whose filter compares
+/// columns that no index leads with yields a candidate on those columns,
+/// equality columns first and then one range column. Returns one dict per
+/// candidate with `table`, `columns`, the `ddl` that creates it,
+/// `estimated_benefit` (the summed planner cost of the scans it would
+/// replace, or their row estimates where the engine prints no cost) and
+/// `queries`, the number of workload queries it serves, most beneficial
+/// first.
+pub fn suggest_indexes<'py>(
+    db: &Bound<'py, Database>,
+    workload: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyList>> {
+    let py = db.py();
+    let mut tables: HashMap<String, Option<Table>> = HashMap::new();
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    for (sql, params) in queries(workload)? {
+        let text: String = db.call_method1("explain", (sql, params))?.extract()?;
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        let Some(plan) = PlanNode::parse(&lines) else {
+            continue;
+        };
+        let mut scans = Vec::new();
+        collect_scans(&plan, None, &mut scans);
+        let mut served = Vec::new();
+        for scan in scans {
+            let key = scan.table.to_lowercase();
+            if !tables.contains_key(&key) {
+                tables.insert(key.clone(), Table::load(db, &scan.table)?);
+            }
+            let Some(table) = &tables[&key] else {
+                continue;
+            };
+            let Some(columns) = table.index_columns(&scan) else {
+                continue;
+            };
+            let i = match candidates
+                .iter()
+                .position(|c| c.table == table.name && c.columns == columns)
+            {
+                Some(i) => i,
+                None => {
+                    candidates.push(Candidate {
+                        table: table.name.clone(),
+                        columns,
+                        benefit: 0.0,
+                        queries: 0,
+                    });
+                    candidates.len() - 1
+                }
+            };
+            candidates[i].benefit += scan.weight;
+            if !served.contains(&i) {
+                served.push(i);
+                candidates[i].queries += 1;
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| b.benefit.total_cmp(&a.benefit));
+    let result = PyList::empty(py);
+    for candidate in candidates {
+        let name = format!("idx_{}_{}", candidate.table, candidate.columns.join("_"));
+        let columns: Vec<String> = candidate.columns.iter().map(|c| sql::ident(c)).collect();
+        let dict = PyDict::new(py);
+        dict.set_item("table", &candidate.table)?;
+        dict.set_item("columns", &candidate.columns)?;
+        dict.set_item(
+            "ddl",
+            format!(
+                "CREATE INDEX {} ON {} ({})",
+                sql::ident(&name),
+                sql::ident(&candidate.table),
+                columns.join(", ")
+            ),
+        )?;
+        dict.set_item("estimated_benefit", candidate.benefit)?;
+        dict.set_item("queries", candidate.queries)?;
+        result.append(dict)?;
+    }
+    Ok(result)
+}
+
+/// The `(sql, params)` pairs of a workload.
+fn queries<'py>(
+    workload: &Bound<'py, PyAny>,
+) -> PyResult<Vec<(String, Option<Bound<'py, PyAny>>)>> {
+    if let Ok(sql) = workload.downcast::<PyString>() {
+        return Ok(vec![(sql.to_str()?.to_string(), None)]);
+    }
+    let mut queries = Vec::new();
+    for item in workload.try_iter()? {
+        let item = item?;
+        if let Ok(sql) = item.downcast::<PyString>() {
+            queries.push((sql.to_str()?.to_string(), None));
+        } else {
+            queries.push(item.extract().map_err(|_| {
+                PyTypeError::new_err(
+                    "suggest_indexes expects SQL strings or (sql, params) pairs",
+                )
+            })?);
+        }
+    }
+    Ok(queries)
+}
+
+/// Gather the full table scans of a plan, with the comparisons in their
+/// details and in those of a `Filter` node right above them.
+fn collect_scans(node: &PlanNode, parent: Option<&PlanNode>, scans: &mut Vec<Scan>) {
+    if let Some(table) = scanned_table(&node.name) {
+        let mut scan = Scan {
+            table,
+            equality: Vec::new(),
+            range: Vec::new(),
+            weight: node.figures.cost.or(node.figures.rows).unwrap_or(1.0),
+        };
+        let filter = parent.filter(|p| p.name.to_ascii_lowercase().starts_with("filter"));
+        for detail in node.details.iter().chain(filter.iter().flat_map(|p| &p.details)) {
+            comparisons(detail, &mut scan);
+        }
+        if !scan.equality.is_empty() || !scan.range.is_empty() {
+            scans.push(scan);
+        }
+    }
+    for child in &node.children {
+        collect_scans(child, Some(node), scans);
+    }
+}
+
+/// The table a node scans in full, as in `Seq Scan on orders`. Index and
+/// primary key lookups are not full scans.
+fn scanned_table(name: &str) -> Option<String> {
+    let on = name.to_ascii_lowercase().find(" on ")?;
+    let operator = name[..on].to_ascii_lowercase();
+    let lookup = ["index", "pk", "primary"].iter().any(|w| operator.contains(w));
+    if !operator.contains("scan") || lookup {
+        return None;
+    }
+    let table = name[on + 4..].split_whitespace().next()?;
+    Some(table.trim_matches(|c| c == '"' || c == '`').to_string())
+}
+
+/// Record the columns compared in `text`: `col = ...` and `col IN (...)`
+/// as equality, `col < ...`, `col BETWEEN ...` and `col LIKE ...` as range.
+fn comparisons(text: &str, scan: &mut Scan) {
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (name, after) = if c == '\'' {
+            // Skip string literals
+            let end = rest[1..].find('\'').map_or(rest.len(), |e| e + 2);
+            rest = &rest[end..];
+            continue;
+        } else if c == '"' {
+            match rest[1..].find('"') {
+                Some(e) => (&rest[1..e + 1], &rest[e + 2..]),
+                None => (&rest[1..], ""),
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        } else {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        rest = after;
+        let column = name.rsplit('.').next().unwrap_or(name).to_string();
+        let list = match comparison(after.trim_start()) {
+            Some(true) => &mut scan.equality,
+            Some(false) => &mut scan.range,
+            None => continue,
+        };
+        if !list.contains(&column) {
+            list.push(column);
+        }
+    }
+}
+
+/// Whether `text`, following a column, starts an equality comparison
+/// (true) or a range comparison (false).
+fn comparison(text: &str) -> Option<bool> {
+    let keyword = |word: &str| {
+        text.get(..word.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(word))
+            && !text[word.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+    };
+    if text.starts_with("<>") || text.starts_with("!=") {
+        None
+    } else if text.starts_with('=') || keyword("IN") {
+        Some(true)
+    } else if text.starts_with('<')
+        || text.starts_with('>')
+        || keyword("BETWEEN")
+        || keyword("LIKE")
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+impl Table {
+    /// Read the columns and indexes of `name`, or None when it is not a
+    /// table, such as a view or a CTE.
+    fn load(db: &Bound<'_, Database>, name: &str) -> PyResult<Option<Self>> {
+        let Ok(described) = db.call_method1("describe", (name,)) else {
+            return Ok(None);
+        };
+        let mut columns = Vec::new();
+        for row in described.try_iter()? {
+            let row = row?.downcast_into::<PyDict>()?;
+            if let Some(column) = field(&row, &["field", "column", "column_name", "name"])? {
+                columns.push(column);
+            }
+        }
+        let mut indexes: Vec<(String, Vec<String>)> = Vec::new();
+        for row in db.call_method1("indexes", (name,))?.try_iter()? {
+            let row = row?.downcast_into::<PyDict>()?;
+            let index = field(&row, &["index_name", "key_name", "name"])?.unwrap_or_default();
+            let Some(column) = field(&row, &["column_name", "column"])? else {
+                continue;
+            };
+            match indexes.iter_mut().find(|(n, _)| *n == index) {
+                Some((_, columns)) => columns.push(column),
+                None => indexes.push((index, vec![column])),
+            }
+        }
+        Ok(Some(Self {
+            name: name.to_string(),
+            columns,
+            indexes: indexes.into_iter().map(|(_, columns)| columns).collect(),
+        }))
+    }
+
+    /// The columns of an index serving `scan`: its equality columns, then
+    /// its first range column. None when no column compared is one of the
+    /// table's, or when an existing index already leads with the first.
+    fn index_columns(&self, scan: &Scan) -> Option<Vec<String>> {
+        let column = |name: &String| self.columns.iter().find(|c| c.eq_ignore_ascii_case(name));
+        let mut columns: Vec<String> = scan.equality.iter().filter_map(column).cloned().collect();
+        if let Some(range) = scan.range.iter().filter_map(column).find(|c| !columns.contains(*c)) {
+            columns.push(range.clone());
+        }
+        let first = columns.first()?;
+        let indexed = self
+            .indexes
+            .iter()
+            .any(|index| index.first().is_some_and(|c| c.eq_ignore_ascii_case(first)));
+        (!indexed).then_some(columns)
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::database::RowBuffer;
+use crate::hooks::Change;
+use crate::value::BindParams;
+
+/// A query and its parameters, as used to look up cached results.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    sql: String,
+    params: String,
+}
+
+impl CacheKey {
+    pub fn new(sql: &str, bind: &BindParams) -> Self {
+        let params = match bind {
+            BindParams::Positional(values) => format!("{:?}", &values[..]),
+            BindParams::Named(named) => format!("{named:?}"),
+        };
+        Self {
+            sql: sql.to_string(),
+            params,
+        }
+    }
+}
+
+struct Entry {
+    rows: Arc<RowBuffer>,
+    /// Every identifier in the query, lowercased. A superset of the tables
+    /// it reads, so a write may invalidate more entries than needed but
+    /// never fewer.
+    names: HashSet<String>,
+    bytes: usize,
+    last_used: u64,
+}
+
+struct CacheState {
+    max_bytes: usize,
+    bytes: usize,
+    entries: HashMap<CacheKey, Entry>,
+    clock: u64,
+}
+
+/// Opt-in cache of query results, keyed by SQL and parameters.
+///
+/// Writes made through the same `Database` (and its transactions and
+/// prepared statements) invalidate the entries that name the written table.
+/// Statements whose target cannot be recognised, such as DDL, clear the
+/// whole cache.
+#[derive(Default)]
+pub struct ResultCache {
+    enabled: AtomicBool,
+    /// Bumped by every invalidation, so that a query that was running while
+    /// a write committed does not store its possibly stale result.
+    generation: AtomicU64,
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+    state: Mutex<Option<CacheState>>,
+}
+
+impl ResultCache {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enable the cache with a budget of `max_bytes`, or disable it and drop
+    /// every entry with None.
+    pub fn configure(&self, max_bytes: Option<usize>) {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = max_bytes.map(|max_bytes| CacheState {
+            max_bytes,
+            bytes: 0,
+            entries: HashMap::new(),
+            clock: 0,
+        });
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.enabled.store(guard.is_some(), Ordering::Relaxed);
+    }
+
+    /// Drop every entry, keeping the budget.
+    pub fn clear(&self) {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(state) = guard.as_mut() {
+            state.entries.clear();
+            state.bytes = 0;
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Arc<RowBuffer>> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = guard.as_mut()?;
+        state.clock += 1;
+        let clock = state.clock;
+        let found = state.entries.get_mut(key).map(|entry| {
+            entry.last_used = clock;
+            entry.rows.clone()
+        });
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    /// Store a result read while the cache was at `generation`, evicting
+    /// the least recently used entries to stay within the budget.
+    pub fn insert(&self, key: CacheKey, rows: Arc<RowBuffer>, generation: u64) {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = guard.as_mut() else {
+            return;
+        };
+        let bytes = key.sql.len() + key.params.len() + rows.size();
+        if self.generation() != generation || bytes > state.max_bytes {
+            return;
+        }
+        while state.bytes + bytes > state.max_bytes {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            if let Some(entry) = state.entries.remove(&oldest) {
+                state.bytes -= entry.bytes;
+            }
+        }
+        state.clock += 1;
+        let entry = Entry {
+            names: identifiers(&key.sql),
+            rows,
+            bytes,
+            last_used: state.clock,
+        };
+        state.bytes += bytes;
+        if let Some(old) = state.entries.insert(key, entry) {
+            state.bytes -= old.bytes;
+        }
+    }
+
+    /// Drop the entries a committed write statement may have changed.
+    pub fn invalidate(&self, sql: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = guard.as_mut() else {
+            return;
+        };
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        match Change::from_sql(sql) {
+            Some(change) => {
+                let table = change.table().to_lowercase();
+                let mut freed = 0;
+                state.entries.retain(|_, entry| {
+                    let keep = !entry.names.contains(&table);
+                    if !keep {
+                        freed += entry.bytes;
+                    }
+                    keep
+                });
+                state.bytes -= freed;
+            }
+            None => {
+                state.entries.clear();
+                state.bytes = 0;
+            }
+        }
+    }
+}
+
+/// Lowercased identifiers (bare words and double-quoted names) in `sql`,
+/// skipping string literals and comments. Qualified names are also split
+/// into their parts.
+fn identifiers(sql: &str) -> HashSet<String> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut names = HashSet::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied().unwrap_or('\0');
+
+        if c == '\'' || c == '"' {
+            let mut word = String::new();
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == c {
+                    if chars.get(i + 1) == Some(&c) {
+                        word.push(c);
+                        i += 2;
+                        continue;
+                    }
+                    i += 1;
+                    break;
+                }
+                word.push(chars[i]);
+                i += 1;
+            }
+            if c == '"' {
+                names.insert(word.to_lowercase());
+            }
+            continue;
+        }
+
+        if c == '-' && next == '-' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == '*' {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect::<String>().to_lowercase();
+            for part in word.split('.') {
+                names.insert(part.to_string());
+            }
+            names.insert(word);
+            continue;
+        }
+
+        i += 1;
+    }
+
+    names
+}
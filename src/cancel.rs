@@ -0,0 +1,80 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cancellation tokens, passed as `cancel=` to the statements they stop.
+
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::QueryCancelled;
+
+/// A flag that stops the statements it is passed to, set from any thread.
+///
+/// ```python
+/// token = stoolap.CancellationToken()
+/// threading.Timer(5.0, token.cancel).start()
+/// rows = db.query("SELECT * FROM events", cancel=token)
+/// ```
+///
+/// A statement checks the token before it starts and, while its rows are
+/// read, between chunks of rows, and raises QueryCancelled once it is
+/// cancelled. The engine has no way to stop a statement it is running, so
+/// the token cannot interrupt one: a write that has started is carried out
+/// in full, and a query raises only once the engine hands over its rows.
+/// `Database.time_limit()` bounds a running query instead. A token stays
+/// cancelled: use a new one for the next operation.
+#[pyclass(frozen)]
+pub struct CancellationToken {
+    cancelled: AtomicBool,
+}
+
+#[pymethods]
+impl CancellationToken {
+    #[new]
+    fn new() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Cancel every statement observing the token, now and later.
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel()` was called.
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CancellationToken(cancelled={})", self.cancelled())
+    }
+}
+
+impl CancellationToken {
+    /// Fail with QueryCancelled once the token is cancelled.
+    pub fn check(&self) -> PyResult<()> {
+        if self.cancelled() {
+            return Err(QueryCancelled::new_err("query cancelled"));
+        }
+        Ok(())
+    }
+}
+
+/// Check an optional token, as passed to `cancel=`.
+pub fn check(token: Option<&Bound<'_, CancellationToken>>) -> PyResult<()> {
+    token.map_or(Ok(()), |token| token.get().check())
+}
@@ -0,0 +1,892 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use stoolap::api::Database as ApiDatabase;
+
+use crate::cache::ResultCache;
+use crate::database::dir_size;
+use crate::deadline;
+use crate::error::{self, to_py, StoolapError};
+use crate::hints;
+use crate::hooks::{Change, Event, Hooks, ParamInfo, Started};
+use crate::mapping::Mappings;
+use crate::sql;
+use crate::value::{BindOptions, BindParams, FetchOptions};
+use crate::wal;
+use crate::warning;
+
+/// Start time and listener state of a statement, when hooks are set.
+pub type QueryToken = (Option<Instant>, Option<Started>);
+
+/// Incremented in the child process after every `os.fork()`. Connections
+/// opened under an older value were inherited from the parent.
+static FORK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Journal modes by engine `sync_mode` level: WAL writes are never synced,
+/// synced in batches, or synced on every commit.
+pub const JOURNAL_MODES: [&str; 3] = ["none", "normal", "full"];
+
+/// The `sync_mode` level of a journal mode name.
+pub fn journal_level(mode: &str) -> PyResult<u8> {
+    JOURNAL_MODES
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(mode))
+        .map(|level| level as u8)
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "journal mode must be 'none', 'normal' or 'full', got '{mode}'"
+            ))
+        })
+}
+
+/// The value of option `name` in a DSN's query string.
+pub fn dsn_option<'a>(dsn: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = dsn.split_once('?')?;
+    query.split('&').find_map(|option| {
+        option
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
+/// What the value of a DSN option must be.
+#[derive(Clone, Copy)]
+enum OptionValue {
+    /// A whole number, such as a count, a size or a duration.
+    Number,
+    /// `on` or `off`.
+    Switch,
+    /// `true` or `false`.
+    Flag,
+    /// A journal mode name or level.
+    SyncMode,
+}
+
+/// Options a DSN may carry. The binding's own options are read by the
+/// binding and removed before the DSN reaches the engine.
+const ENGINE_OPTIONS: [(&str, OptionValue); 15] = [
+    ("sync_mode", OptionValue::SyncMode),
+    ("checkpoint_interval", OptionValue::Number),
+    ("compact_threshold", OptionValue::Number),
+    ("target_volume_rows", OptionValue::Number),
+    ("checkpoint_on_close", OptionValue::Switch),
+    ("keep_snapshots", OptionValue::Number),
+    ("compression", OptionValue::Switch),
+    ("wal_compression", OptionValue::Switch),
+    ("volume_compression", OptionValue::Switch),
+    ("compression_threshold", OptionValue::Number),
+    ("wal_buffer_size", OptionValue::Number),
+    ("wal_flush_trigger", OptionValue::Number),
+    ("wal_max_size", OptionValue::Number),
+    ("commit_batch_size", OptionValue::Number),
+    ("sync_interval_ms", OptionValue::Number),
+];
+const BINDING_OPTIONS: [(&str, OptionValue); 2] = [
+    ("read_only", OptionValue::Flag),
+    ("cache_mb", OptionValue::Number),
+];
+
+/// Whether `name` is an option a DSN may carry.
+pub fn is_dsn_option(name: &str) -> bool {
+    ENGINE_OPTIONS
+        .iter()
+        .chain(&BINDING_OPTIONS)
+        .any(|(known, _)| *known == name)
+}
+
+/// Check every option in a DSN's query string: each must be known and have
+/// a valid value, or ValueError says which is not.
+pub fn validate_dsn(dsn: &str) -> PyResult<()> {
+    let Some((_, query)) = dsn.split_once('?') else {
+        return Ok(());
+    };
+    for option in query.split('&').filter(|option| !option.is_empty()) {
+        let Some((name, value)) = option.split_once('=') else {
+            return Err(PyValueError::new_err(format!(
+                "DSN option '{option}' has no value"
+            )));
+        };
+        let Some((_, kind)) = ENGINE_OPTIONS
+            .iter()
+            .chain(&BINDING_OPTIONS)
+            .find(|(known, _)| *known == name)
+        else {
+            let known: Vec<&str> = ENGINE_OPTIONS
+                .iter()
+                .chain(&BINDING_OPTIONS)
+                .map(|(known, _)| *known)
+                .collect();
+            return Err(PyValueError::new_err(format!(
+                "unknown DSN option '{name}'; options are {}",
+                known.join(", ")
+            )));
+        };
+        let (valid, expected) = match kind {
+            OptionValue::Number => (value.parse::<u64>().is_ok(), "a whole number"),
+            OptionValue::Switch => (matches!(value, "on" | "off"), "'on' or 'off'"),
+            OptionValue::Flag => (flag(value).is_some(), "'true' or 'false'"),
+            OptionValue::SyncMode => (sync_level(value).is_some(), "'none', 'normal' or 'full'"),
+        };
+        if !valid {
+            return Err(PyValueError::new_err(format!(
+                "DSN option '{name}' must be {expected}, got '{value}'"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The DSN to open the engine with: `dsn` without the binding's options.
+pub fn engine_dsn(dsn: &str) -> Cow<'_, str> {
+    let Some((base, query)) = dsn.split_once('?') else {
+        return Cow::Borrowed(dsn);
+    };
+    let is_binding = |option: &&str| {
+        let name = option.split('=').next().unwrap_or(option);
+        BINDING_OPTIONS.iter().any(|(known, _)| *known == name)
+    };
+    if !query.split('&').any(|option| is_binding(&option)) {
+        return Cow::Borrowed(dsn);
+    }
+    let engine: Vec<&str> = query.split('&').filter(|option| !is_binding(option)).collect();
+    if engine.is_empty() {
+        Cow::Owned(base.to_string())
+    } else {
+        Cow::Owned(format!("{base}?{}", engine.join("&")))
+    }
+}
+
+/// The value of a `true`/`false` DSN option.
+pub fn flag(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// The `sync_mode` option of a DSN, by name or level.
+pub fn sync_mode_of(dsn: &str) -> Option<u8> {
+    sync_level(dsn_option(dsn, "sync_mode")?)
+}
+
+/// `dsn` with its `sync_mode` option set to journal mode `level`, replacing
+/// any given.
+pub fn with_sync_mode(dsn: &str, level: u8) -> String {
+    let (base, query) = dsn.split_once('?').unwrap_or((dsn, ""));
+    let mut options: Vec<&str> = query
+        .split('&')
+        .filter(|option| !option.is_empty() && !option.starts_with("sync_mode="))
+        .collect();
+    let mode = format!("sync_mode={}", JOURNAL_MODES[level as usize]);
+    options.push(&mode);
+    format!("{base}?{}", options.join("&"))
+}
+
+/// The `sync_mode` level of a DSN value, by name or level.
+fn sync_level(value: &str) -> Option<u8> {
+    match value.parse::<u8>() {
+        Ok(level) => (level < 3).then_some(level),
+        Err(_) => journal_level(value).ok(),
+    }
+}
+
+/// The engine's default `checkpoint_interval`, in seconds.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 60;
+
+/// The engine's default `compact_threshold`: sub-target volumes a table
+/// collects before a checkpoint merges them.
+pub const DEFAULT_COMPACT_THRESHOLD: u32 = 4;
+
+/// Registered with `os.register_at_fork(after_in_child=...)`.
+#[pyfunction]
+pub fn after_fork_in_child() {
+    FORK_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Thread pools started for `threads=` and the `parallel` hint, least
+/// recently used first: their size, the fork generation they were started
+/// in, and the pool.
+static POOLS: Mutex<Vec<(usize, u64, Arc<ThreadPool>)>> = Mutex::new(Vec::new());
+
+/// Pools kept for reuse once no handle or running statement holds them.
+const IDLE_POOLS: usize = 4;
+
+/// A pool of `threads` threads for the engine's parallel operators,
+/// started on first use and shared by every handle asking for that size.
+///
+/// Pools started before a `fork()` are dropped from the cache, their
+/// threads being gone in the child. Beyond `IDLE_POOLS` pools that nothing
+/// holds, the least recently used are shut down.
+pub fn thread_pool(threads: usize) -> PyResult<Arc<ThreadPool>> {
+    let generation = FORK_GENERATION.load(Ordering::Relaxed);
+    let mut pools = POOLS.lock().unwrap_or_else(|e| e.into_inner());
+    let (current, stale): (Vec<_>, Vec<_>) =
+        pools.drain(..).partition(|(_, g, _)| *g == generation);
+    // Shutting down a pool wakes its threads, which only exist in the
+    // parent, so the child lets go of the memory instead
+    stale.into_iter().for_each(std::mem::forget);
+    *pools = current;
+
+    let pool = match pools.iter().position(|(n, _, _)| *n == threads) {
+        Some(at) => pools.remove(at).2,
+        None => {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("stoolap-{i}"))
+                .build()
+                .map_err(|e| StoolapError::new_err(e.to_string()))?;
+            Arc::new(pool)
+        }
+    };
+    pools.push((threads, generation, pool.clone()));
+    let mut idle = pools.iter().filter(|(_, _, p)| Arc::strong_count(p) == 1).count();
+    pools.retain(|(_, _, p)| {
+        let evict = idle > IDLE_POOLS && Arc::strong_count(p) == 1;
+        idle -= evict as usize;
+        !evict
+    });
+    Ok(pool)
+}
+
+/// State shared by a `Database` and every transaction and prepared
+/// statement created from it.
+pub struct Connection {
+    /// The engine handle, swapped by `reopen()`. Callers work on a clone,
+    /// so a statement in flight keeps the engine it started on.
+    db: RwLock<Arc<ApiDatabase>>,
+    generation: u64,
+    pub dsn: String,
+    pub stats: Stats,
+    pub hooks: Hooks,
+    pub cache: ResultCache,
+    /// Snapshots taken with `Database.snapshot()` and not yet closed.
+    pub snapshots: AtomicI64,
+    /// Classes mapped to tables with `Database.map()`.
+    pub mappings: Mappings,
+    /// How bound Python values are converted.
+    pub binding: BindOptions,
+    /// How TIMESTAMP results are returned, set when the database is opened.
+    pub fetch: FetchOptions,
+    /// Whether SQL uses pyformat placeholders (`%s`, `%(name)s`).
+    pyformat: AtomicBool,
+    /// Engine sync mode, an index into `JOURNAL_MODES`.
+    sync_mode: AtomicU8,
+    /// Whether checkpoints merge small volumes, and after how many.
+    auto_compaction: AtomicBool,
+    compact_threshold: AtomicU32,
+    /// Seconds between the engine's checkpoint cycles.
+    checkpoint_interval: AtomicU64,
+    /// WAL size that triggers a checkpoint after a commit, 0 for none.
+    checkpoint_bytes: AtomicU64,
+    /// Checkpoints run through this handle, and when the last one ended.
+    checkpoints: AtomicU64,
+    last_checkpoint: Mutex<Option<Instant>>,
+    /// Deadlines set with `Database.time_limit()`, by thread.
+    deadlines: Mutex<HashMap<ThreadId, Instant>>,
+    /// Session variables by lower-cased name: the value as given and its
+    /// SQL literal, substituted for `current_setting('name')`.
+    vars: Mutex<HashMap<String, (PyObject, String)>>,
+    /// Whether statements that write are refused (`read_only=true`).
+    read_only: bool,
+    /// Threads for the engine's parallel operators, set with `threads=`;
+    /// None for the process-wide pool.
+    pool: RwLock<Option<Arc<ThreadPool>>>,
+}
+
+impl Connection {
+    pub fn new(db: ApiDatabase, dsn: String) -> Self {
+        let sync_mode = sync_mode_of(&dsn).unwrap_or(1);
+        let compact_threshold = dsn_option(&dsn, "compact_threshold")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_COMPACT_THRESHOLD);
+        let checkpoint_interval = dsn_option(&dsn, "checkpoint_interval")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL);
+        let read_only = dsn_option(&dsn, "read_only")
+            .and_then(flag)
+            .unwrap_or(false);
+        Self {
+            db: RwLock::new(Arc::new(db)),
+            generation: FORK_GENERATION.load(Ordering::Relaxed),
+            dsn,
+            stats: Stats::default(),
+            hooks: Hooks::default(),
+            cache: ResultCache::default(),
+            snapshots: AtomicI64::new(0),
+            mappings: Mappings::default(),
+            binding: BindOptions::default(),
+            fetch: FetchOptions::default(),
+            pyformat: AtomicBool::new(false),
+            sync_mode: AtomicU8::new(sync_mode),
+            auto_compaction: AtomicBool::new(true),
+            compact_threshold: AtomicU32::new(compact_threshold),
+            checkpoint_interval: AtomicU64::new(checkpoint_interval),
+            checkpoint_bytes: AtomicU64::new(0),
+            checkpoints: AtomicU64::new(0),
+            last_checkpoint: Mutex::new(None),
+            deadlines: Mutex::new(HashMap::new()),
+            vars: Mutex::new(HashMap::new()),
+            read_only,
+            pool: RwLock::new(None),
+        }
+    }
+
+    /// SQL as sent to the engine. With the default paramstyle, qmark
+    /// placeholders (`?`) become `$1`, `$2`, ...; with "pyformat", `%s` and
+    /// `%(name)s` placeholders are translated instead.
+    pub fn translate<'a>(&self, sql: &'a str) -> PyResult<Cow<'a, str>> {
+        let sql = if self.pyformat.load(Ordering::Relaxed) {
+            sql::translate_pyformat(sql).map_err(StoolapError::new_err)?
+        } else {
+            sql::translate_qmark(sql)
+        };
+        sql::substitute_settings(sql, |name| {
+            let vars = self.vars.lock().unwrap_or_else(|e| e.into_inner());
+            vars.get(&name.to_lowercase()).map(|(_, literal)| literal.clone())
+        })
+        .map_err(StoolapError::new_err)
+    }
+
+    /// The value of session variable `name`, as given to `set_var()`.
+    pub fn var(&self, py: Python<'_>, name: &str) -> Option<PyObject> {
+        let vars = self.vars.lock().unwrap_or_else(|e| e.into_inner());
+        vars.get(&name.to_lowercase()).map(|(value, _)| value.clone_ref(py))
+    }
+
+    /// Set session variable `name` to a value and its SQL literal, or
+    /// remove it with None.
+    pub fn set_var(&self, name: &str, value: Option<(PyObject, String)>) {
+        let mut vars = self.vars.lock().unwrap_or_else(|e| e.into_inner());
+        match value {
+            Some(value) => vars.insert(name.to_lowercase(), value),
+            None => vars.remove(&name.to_lowercase()),
+        };
+    }
+
+    pub fn paramstyle(&self) -> &'static str {
+        if self.pyformat.load(Ordering::Relaxed) {
+            "pyformat"
+        } else {
+            "numeric_dollar"
+        }
+    }
+
+    pub fn set_paramstyle(&self, style: &str) -> PyResult<()> {
+        let pyformat = match style {
+            "numeric_dollar" => false,
+            "pyformat" => true,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "paramstyle must be 'numeric_dollar' or 'pyformat', got '{style}'"
+                )))
+            }
+        };
+        self.pyformat.store(pyformat, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// How commits are made durable: "none", "normal" or "full".
+    pub fn journal_mode(&self) -> &'static str {
+        JOURNAL_MODES[self.sync_mode.load(Ordering::Relaxed) as usize]
+    }
+
+    pub fn set_journal_mode(&self, level: u8) {
+        self.sync_mode.store(level, Ordering::Relaxed);
+    }
+
+    /// Whether background compaction is on, and its threshold.
+    pub fn compaction(&self) -> (bool, u32) {
+        (
+            self.auto_compaction.load(Ordering::Relaxed),
+            self.compact_threshold.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn set_compaction(&self, enabled: bool, threshold: u32) {
+        self.auto_compaction.store(enabled, Ordering::Relaxed);
+        self.compact_threshold.store(threshold, Ordering::Relaxed);
+    }
+
+    /// Seconds between checkpoint cycles, and the WAL size in bytes that
+    /// triggers one after a commit (0 for none).
+    pub fn checkpoint_policy(&self) -> (u64, u64) {
+        (
+            self.checkpoint_interval.load(Ordering::Relaxed),
+            self.checkpoint_bytes.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn set_checkpoint_policy(&self, seconds: u64, bytes: u64) {
+        self.checkpoint_interval.store(seconds, Ordering::Relaxed);
+        self.checkpoint_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Checkpoints run through this handle, and how long ago the last one
+    /// ended.
+    pub fn checkpoints(&self) -> (u64, Option<Duration>) {
+        let last = self.last_checkpoint.lock().unwrap_or_else(|e| e.into_inner());
+        (
+            self.checkpoints.load(Ordering::Relaxed),
+            last.map(|at| at.elapsed()),
+        )
+    }
+
+    /// Fold the WAL into the main store. No-op for in-memory databases.
+    pub fn checkpoint(&self, py: Python<'_>) -> PyResult<()> {
+        if self.data_dir().is_none() {
+            return Ok(());
+        }
+        self.run_internal(py, "PRAGMA CHECKPOINT")?;
+        self.checkpoints.fetch_add(1, Ordering::Relaxed);
+        *self.last_checkpoint.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Checkpoint once the WAL has grown past the size set with
+    /// `Database.set_checkpoint_interval(wal_bytes=...)`. Called after every
+    /// commit; a failed checkpoint is reported through
+    /// `sys.unraisablehook`, since the commit itself succeeded.
+    pub fn committed(&self, py: Python<'_>) {
+        let bytes = self.checkpoint_bytes.load(Ordering::Relaxed);
+        if bytes == 0 {
+            return;
+        }
+        let Some(dir) = self.data_dir() else {
+            return;
+        };
+        if py.allow_threads(|| dir_size(&dir.join("wal"))) < bytes {
+            return;
+        }
+        if let Err(err) = self.checkpoint(py) {
+            err.write_unraisable(py, None);
+        }
+    }
+
+    /// The current thread's statement deadline.
+    pub fn deadline(&self) -> Option<Instant> {
+        let deadlines = self.deadlines.lock().unwrap_or_else(|e| e.into_inner());
+        deadlines.get(&thread::current().id()).copied()
+    }
+
+    /// Set or clear the current thread's statement deadline.
+    pub fn set_deadline(&self, deadline: Option<Instant>) {
+        let mut deadlines = self.deadlines.lock().unwrap_or_else(|e| e.into_inner());
+        let id = thread::current().id();
+        match deadline {
+            Some(deadline) => deadlines.insert(id, deadline),
+            None => deadlines.remove(&id),
+        };
+    }
+
+    /// Number of threads the engine's parallel operators run on.
+    pub fn threads(&self) -> usize {
+        let pool = self.pool.read().unwrap_or_else(|e| e.into_inner());
+        pool.as_ref().map_or_else(rayon::current_num_threads, |pool| pool.current_num_threads())
+    }
+
+    /// The `threads` given to `set_threads()`, None for the process-wide pool.
+    pub fn threads_option(&self) -> Option<usize> {
+        let pool = self.pool.read().unwrap_or_else(|e| e.into_inner());
+        pool.as_ref().map(|pool| pool.current_num_threads())
+    }
+
+    /// Run the engine's parallel operators on a pool of `threads` threads,
+    /// or on the process-wide pool (one thread per CPU) for None.
+    pub fn set_threads(&self, threads: Option<usize>) -> PyResult<()> {
+        let pool = match threads {
+            Some(0) => return Err(PyValueError::new_err("threads must be at least 1")),
+            Some(threads) => Some(thread_pool(threads)?),
+            None => None,
+        };
+        *self.pool.write().unwrap_or_else(|e| e.into_inner()) = pool;
+        Ok(())
+    }
+
+    /// Release the GIL and run engine work, on the pool chosen by the
+    /// running statement's `parallel` hint, or else on the handle's pool
+    /// when `threads` is set. The pool's threads do not survive `fork()`,
+    /// so a handle inherited by a child process uses the process-wide pool.
+    pub fn allow_threads<T, F>(&self, py: Python<'_>, f: F) -> T
+    where
+        F: FnOnce() -> T + Send,
+        T: Send,
+    {
+        let pool = if self.inherited() {
+            None
+        } else {
+            let pool = self.pool.read().unwrap_or_else(|e| e.into_inner());
+            hints::pool().or_else(|| pool.clone())
+        };
+        py.allow_threads(|| match pool {
+            Some(pool) => pool.install(f),
+            None => f(),
+        })
+    }
+
+    /// Refuse a statement that writes on a read-only handle.
+    fn check_writable(&self, sql: &str) -> PyResult<()> {
+        if self.read_only && !sql::is_read_only(sql) {
+            return Err(StoolapError::new_err(format!(
+                "database '{}' is read-only",
+                self.dsn
+            )));
+        }
+        Ok(())
+    }
+
+    /// Refuse to start a statement past the thread's deadline.
+    fn check_deadline(&self) -> PyResult<Option<Instant>> {
+        let until = self.deadline();
+        if let Some(until) = until {
+            deadline::expired(until)?;
+        }
+        Ok(until)
+    }
+
+    /// Directory of a file database, or None for an in-memory one.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        let path = self.dsn.strip_prefix("file://")?;
+        let path = path.split('?').next().unwrap_or(path);
+        Some(PathBuf::from(path))
+    }
+
+    /// The engine handle, unless it was inherited across `fork()`.
+    pub fn db(&self) -> PyResult<Arc<ApiDatabase>> {
+        self.check_fork()?;
+        Ok(self.db.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+
+    /// Close the engine and open it again from the DSN, with the current
+    /// journal mode. Refused while a transaction or snapshot is open, since
+    /// it would be lost with the old engine.
+    ///
+    /// Closing is best effort: the engine being replaced may be the one
+    /// that failed. If opening fails, the handle stays closed and `reopen`
+    /// can be retried.
+    pub fn reopen(&self, py: Python<'_>) -> PyResult<()> {
+        let active = self.stats.active_transactions.load(Ordering::Relaxed);
+        if active > 0 {
+            return Err(StoolapError::new_err(format!(
+                "cannot reopen the database with {active} open transaction(s)"
+            )));
+        }
+        let snapshots = self.snapshots.load(Ordering::Relaxed);
+        if snapshots > 0 {
+            return Err(StoolapError::new_err(format!(
+                "cannot reopen the database with {snapshots} open snapshot(s)"
+            )));
+        }
+        let old = self.db()?;
+        // The engine takes its sync mode only when opened, so the current
+        // journal mode goes into the DSN
+        let dsn = if self.dsn.starts_with("file://") {
+            with_sync_mode(&self.dsn, self.sync_mode.load(Ordering::Relaxed))
+        } else {
+            self.dsn.clone()
+        };
+        let db = py
+            .allow_threads(|| {
+                let _ = old.close();
+                ApiDatabase::open(&engine_dsn(&dsn))
+            })
+            .map_err(to_py)?;
+        *self.db.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(db);
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Whether this connection was opened in a parent of the current process.
+    pub fn inherited(&self) -> bool {
+        FORK_GENERATION.load(Ordering::Relaxed) != self.generation
+    }
+
+    /// Refuse to touch a file database inherited from a parent process: its
+    /// files, WAL and locks belong to the parent. In-memory databases are
+    /// private copies after a fork and stay usable.
+    pub fn check_fork(&self) -> PyResult<()> {
+        if self.inherited() && !self.dsn.starts_with("memory://") {
+            return Err(StoolapError::new_err(format!(
+                "database '{}' was opened before os.fork(); open it again in the child process",
+                self.dsn
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run a query and convert its rows, recording it in the stats and
+    /// reporting it to the hooks.
+    ///
+    /// `f` releases the GIL around engine work itself and returns the
+    /// converted result together with the number of rows it contained.
+    pub fn run_query<F>(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: ParamInfo,
+        f: F,
+    ) -> PyResult<PyObject>
+    where
+        F: FnOnce(Python<'_>) -> PyResult<(PyObject, usize)>,
+    {
+        self.check_fork()?;
+        self.check_writable(sql)?;
+        let until = self.check_deadline()?;
+        let token = self.start(py, sql, &params)?;
+        let result = {
+            // The engine runs the query under the time left, and the
+            // deadline is checked again between chunks of rows, see
+            // `ConvertOptions`
+            let _guard = deadline::enter(until);
+            f(py)
+        };
+        self.finish_query(py, sql, &params, token, result)
+    }
+
+    /// Audit a query and start timing it, for callers that run the query
+    /// themselves and then hand its result to `finish_query`.
+    pub fn start_query(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: &ParamInfo,
+    ) -> PyResult<QueryToken> {
+        self.check_fork()?;
+        self.check_writable(sql)?;
+        self.check_deadline()?;
+        self.start(py, sql, params)
+    }
+
+    /// Record a query's outcome in the stats and report it to the hooks.
+    pub fn finish_query(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: &ParamInfo,
+        (start, started): QueryToken,
+        result: PyResult<(PyObject, usize)>,
+    ) -> PyResult<PyObject> {
+        match &result {
+            Ok((_, rows)) => {
+                self.stats.queries.fetch_add(1, Ordering::Relaxed);
+                self.stats.rows_read.fetch_add(*rows as u64, Ordering::Relaxed);
+            }
+            Err(err) => {
+                error::annotate(py, err, &params.vectors);
+                error::locate(py, err, sql);
+            }
+        }
+        if let Some(start) = start {
+            let outcome = result.as_ref().map(|(_, rows)| *rows as u64);
+            self.report(py, sql, params, start, started, outcome);
+        }
+        result.map(|(object, _)| object)
+    }
+
+    /// Run `count` write statements, recording them in the stats and
+    /// reporting them to the hooks.
+    pub fn run_write<F>(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: ParamInfo,
+        count: u64,
+        f: F,
+    ) -> PyResult<i64>
+    where
+        F: FnOnce(Python<'_>) -> PyResult<i64>,
+    {
+        self.check_fork()?;
+        self.check_writable(sql)?;
+        let until = self.check_deadline()?;
+        if !params.fractional.is_empty() {
+            let db = self.db()?;
+            warning::coercions(py, &db, sql, &params.fractional)?;
+        }
+        let (start, started) = self.start(py, sql, &params)?;
+        let result = {
+            // The engine runs the write under the time left, see `deadline`
+            let _guard = deadline::enter(until);
+            f(py)
+        };
+        match &result {
+            Ok(affected) => {
+                self.stats.statements.fetch_add(count, Ordering::Relaxed);
+                self.stats
+                    .rows_written
+                    .fetch_add((*affected).max(0) as u64, Ordering::Relaxed);
+            }
+            Err(err) => {
+                error::annotate(py, err, &params.vectors);
+                error::locate(py, err, sql);
+            }
+        }
+        if let Some(start) = start {
+            let outcome = result.as_ref().map(|affected| (*affected).max(0) as u64);
+            self.report(py, sql, &params, start, started, outcome);
+        }
+        result
+    }
+
+    /// Run write statements outside an explicit transaction. Behaves like
+    /// `run_write`, then reports the implicit commit (or the rollback of a
+    /// failed statement) to the transaction hooks and invalidates cached
+    /// results.
+    pub fn run_autocommit<F>(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        mut params: ParamInfo,
+        count: u64,
+        f: F,
+    ) -> PyResult<i64>
+    where
+        F: FnOnce(Python<'_>) -> PyResult<i64>,
+    {
+        let values = params.values.take();
+        let logged = match values.as_ref().filter(|_| self.hooks.wal.is_enabled()) {
+            Some(values) => Some(wal::statements(sql, values)?),
+            None => None,
+        };
+        let result = self.run_write(py, sql, params, count, f);
+        if result.is_ok() {
+            self.cache.invalidate(sql);
+            if let Some(statements) = logged {
+                self.hooks.wal.append(statements);
+            }
+        }
+        self.hooks.transaction_finished(py, result.is_ok());
+        if result.is_ok() {
+            self.committed(py);
+        }
+        if matches!(result, Ok(affected) if affected > 0) && self.hooks.is_watching() {
+            if let Some(change) = self.change(sql, values.as_deref()) {
+                self.hooks.changed(py, &[change]);
+            }
+        }
+        result
+    }
+
+    /// Describe the change made by write `sql` for `watch()` callbacks, with
+    /// the primary key of the row when the statement names it. `values` are
+    /// the bound parameters (one set; batches report no key).
+    pub fn change(&self, sql: &str, values: Option<&[BindParams]>) -> Option<Change> {
+        let change = Change::from_sql(sql)?;
+        let bind = match values {
+            Some([bind]) => Some(bind),
+            Some(_) => return Some(change),
+            None => None,
+        };
+        let Ok(db) = self.db() else {
+            return Some(change);
+        };
+        let Ok(store) = db.engine().get_version_store(change.table()) else {
+            return Some(change);
+        };
+        let schema = store.schema();
+        let Some(pk) = schema.pk_column_index().and_then(|i| schema.get_column(i)) else {
+            return Some(change);
+        };
+        let pk = pk.name.clone();
+        Some(change.with_key(sql, bind, &pk, schema.column_names_owned()))
+    }
+
+    /// Run a statement issued by the binding itself (maintenance commands),
+    /// passing it through the audit hook first.
+    pub fn run_internal(&self, py: Python<'_>, sql: &str) -> PyResult<i64> {
+        let db = self.db()?;
+        self.hooks.audit(py, sql, &ParamInfo::default())?;
+        self.allow_threads(py, || db.execute(sql, ()).map_err(to_py))
+    }
+
+    /// Audit a statement, then start timing it and notify listeners, if any
+    /// hooks are set.
+    fn start(&self, py: Python<'_>, sql: &str, params: &ParamInfo) -> PyResult<QueryToken> {
+        if !self.hooks.is_active() {
+            return Ok((None, None));
+        }
+        self.hooks.audit(py, sql, params)?;
+        let started = self.hooks.started(py, sql, params);
+        Ok((Some(Instant::now()), started))
+    }
+
+    fn report(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: &ParamInfo,
+        start: Instant,
+        started: Option<Started>,
+        outcome: Result<u64, &PyErr>,
+    ) {
+        let event = Event {
+            sql,
+            duration: start.elapsed(),
+            rows: outcome.as_ref().ok().copied(),
+            params,
+            error: outcome.err().map(|e| e.value(py).clone().into_any().unbind()),
+        };
+        self.hooks.finished(py, &event, started);
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Closing an inherited handle would flush and unlock files the
+        // parent process still owns, so the child leaks it instead.
+        if self.inherited() {
+            std::mem::forget(self.db.read().unwrap_or_else(|e| e.into_inner()).clone());
+        }
+    }
+}
+
+/// Counters collected for `Database.stats()`.
+#[derive(Default)]
+pub struct Stats {
+    pub queries: AtomicU64,
+    pub statements: AtomicU64,
+    pub rows_read: AtomicU64,
+    pub rows_written: AtomicU64,
+    pub transactions: AtomicU64,
+    pub commits: AtomicU64,
+    pub rollbacks: AtomicU64,
+    pub active_transactions: AtomicI64,
+}
+
+impl Stats {
+    pub fn transaction_started(&self) {
+        self.transactions.fetch_add(1, Ordering::Relaxed);
+        self.active_transactions.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn transaction_finished(&self, committed: bool) {
+        if committed {
+            self.commits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.rollbacks.fetch_add(1, Ordering::Relaxed);
+        }
+        self.active_transactions.fetch_sub(1, Ordering::Relaxed);
+    }
+}
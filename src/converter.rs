@@ -0,0 +1,92 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Converters for result values, registered by type name and applied
+//! under `Database.open(detect_types=...)`, like sqlite3's.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::Mutex;
+
+use stoolap::core::Value;
+
+/// Convert each value with the converter of its engine type.
+pub const PARSE_DECLTYPES: u8 = 1;
+/// Convert the values of a column named `name [TYPE]` with the converter
+/// of `TYPE`, and name the column `name`.
+pub const PARSE_COLNAMES: u8 = 2;
+
+/// Registered converters by upper-cased type name.
+static CONVERTERS: Mutex<Vec<(String, PyObject)>> = Mutex::new(Vec::new());
+
+/// Register `converter`, a callable taking a value as returned without
+/// converters, for the type `typename` (matched case-insensitively). A
+/// later registration for the same type replaces the earlier one.
+///
+/// Converters apply to the whole process, on connections opened with
+/// `detect_types`.
+#[pyfunction]
+pub fn register_converter(typename: &str, converter: &Bound<'_, PyAny>) -> PyResult<()> {
+    if !converter.is_callable() {
+        return Err(PyTypeError::new_err("converter must be callable"));
+    }
+    if typename.trim().is_empty() {
+        return Err(PyValueError::new_err("typename must not be empty"));
+    }
+    let name = typename.trim().to_uppercase();
+    let mut converters = CONVERTERS.lock().unwrap_or_else(|e| e.into_inner());
+    converters.retain(|(n, _)| *n != name);
+    converters.push((name, converter.clone().unbind()));
+    Ok(())
+}
+
+/// The converter registered for `typename`.
+pub fn lookup(py: Python<'_>, typename: &str) -> Option<PyObject> {
+    let converters = CONVERTERS.lock().unwrap_or_else(|e| e.into_inner());
+    converters
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(typename))
+        .map(|(_, converter)| converter.clone_ref(py))
+}
+
+/// Whether a converter is registered for `typename`.
+pub fn is_registered(typename: &str) -> bool {
+    let converters = CONVERTERS.lock().unwrap_or_else(|e| e.into_inner());
+    converters
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case(typename))
+}
+
+/// The engine type of `value` as written in `CREATE TABLE`, or None for
+/// NULL, which is never converted.
+pub fn type_name(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null(_) => None,
+        Value::Integer(_) => Some("INTEGER"),
+        Value::Float(_) => Some("FLOAT"),
+        Value::Text(_) => Some("TEXT"),
+        Value::Boolean(_) => Some("BOOLEAN"),
+        Value::Timestamp(_) => Some("TIMESTAMP"),
+        Value::Extension(_) if value.as_json().is_some() => Some("JSON"),
+        Value::Extension(_) => Some("VECTOR"),
+    }
+}
+
+/// Split a column name of the form `name [TYPE]` into `name` and `TYPE`.
+pub fn split_column_name(column: &str) -> Option<(&str, &str)> {
+    let open = column.find('[')?;
+    let close = open + column[open..].find(']')?;
+    let typename = column[open + 1..close].trim();
+    (!typename.is_empty()).then(|| (column[..open].trim_end(), typename))
+}
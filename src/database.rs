@@ -12,23 +12,54 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use pyo3::buffer::{Element, PyBuffer};
+use pyo3::exceptions::{PyImportError, PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList, PyString};
-use std::sync::Arc;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBytes, PyDict, PyList, PyModule, PyString, PyType};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::{Duration, Instant};
 
-use stoolap::api::Database as ApiDatabase;
+use stoolap::api::{Database as ApiDatabase, ParamVec, Transaction as ApiTransaction};
+use stoolap::core::{IndexType, IsolationLevel, Value};
 
-use crate::error::to_py;
+use crate::advisor;
+use crate::cache::CacheKey;
+use crate::cancel::{self, CancellationToken};
+use crate::connection::{self, Connection};
+use crate::ddl;
+use crate::deadline::{self, TimeLimit};
+use crate::error::{to_py, ConflictError, ResultTooLarge, StoolapError};
+use crate::explain::{self, PlanNode};
+use crate::hints::Hints;
+use crate::hooks::{ParamInfo, TraceTarget};
+use crate::json;
+use crate::mapping::{self, Mapping};
+use crate::schema;
+use crate::settings;
+use crate::snapshot::Snapshot;
+use crate::sql;
 use crate::statement::PreparedStatement;
-use crate::transaction::Transaction;
-use crate::value::{parse_params, value_to_py, BindParams};
+use crate::table::{self, Table};
+use crate::temp_table::TempTable;
+use crate::transaction::{Session, Transaction};
+use crate::value::{
+    plain_value_to_py, py_to_value, value_to_py, BindOptions, BindParams, FetchOptions,
+    StringInterner,
+};
+use crate::vector;
+use crate::wal::{self, WalReader};
 
 /// A Stoolap database connection.
 ///
 /// Open with `Database.open(path)`. Use `:memory:` for in-memory databases.
-#[pyclass]
+#[pyclass(module = "stoolap._stoolap")]
 pub struct Database {
-    db: Arc<ApiDatabase>,
+    conn: Arc<Connection>,
 }
 
 #[pymethods]
@@ -39,112 +70,759 @@ impl Database {
     /// - `:memory:` or empty string for in-memory database
     /// - `memory://` for in-memory database
     /// - `./mydb` or `file:///path/to/db` for file-based database
+    ///
+    /// Options follow a `?`, as in `file:///data/db?sync_mode=full&cache_mb=64`:
+    /// the engine's (see the README) and the binding's own, `read_only=true`
+    /// to refuse every statement that writes and `cache_mb` to enable the
+    /// result cache with that many megabytes. An unknown option or an
+    /// invalid value raises ValueError.
+    ///
+    /// `paramstyle` selects the placeholders used in SQL; see the
+    /// `paramstyle` property. `durability` sets the journal mode of a
+    /// file database ("none", "normal" or "full"), like the `sync_mode`
+    /// DSN option; see `set_journal_mode()`. `threads` caps the threads the
+    /// engine's parallel operators use; see `set_threads()`.
+    ///
+    /// With `strict_types=True`, parameters are only converted when their
+    /// SQL type is unambiguous: a dict or list must be wrapped in `Json()`,
+    /// an array in `Vector()`, and datetimes must be timezone-aware.
+    ///
+    /// TIMESTAMP values are returned as aware datetimes in UTC, or in `tz`,
+    /// a zone name such as "Europe/Istanbul" or a `tzinfo`. With
+    /// `naive_timestamps=True` they are returned without tzinfo, as wall
+    /// time in UTC or `tz`. Naive datetime parameters are taken as UTC;
+    /// `reject_naive=True` refuses them with TypeError instead.
+    ///
+    /// With `nanosecond_timestamps=True`, TIMESTAMP values are returned as
+    /// integer nanoseconds since the Unix epoch, and as `datetime64[ns]`
+    /// arrays from `query_columnar(numpy=True)`, so they keep the precision
+    /// a datetime truncates to microseconds.
+    ///
+    /// With `midnight_as_date=True`, TIMESTAMP values at exactly midnight
+    /// UTC are returned as `datetime.date`. DATE columns are stored as such
+    /// timestamps and results carry no column types, so a TIMESTAMP column
+    /// holding midnight gets dates too.
+    ///
+    /// `detect_types` applies the converters added with
+    /// `register_converter()`, like sqlite3's: with `PARSE_DECLTYPES`, by the
+    /// engine type of each value ("INTEGER", "TEXT", "TIMESTAMP", "JSON",
+    /// ...), and with `PARSE_COLNAMES`, by a type in the column name, as in
+    /// `SELECT id AS "id [UUID]"`. A column type takes precedence.
+    #[staticmethod]
+    #[pyo3(signature = (
+        path, *, paramstyle="numeric_dollar", durability=None, strict_types=false,
+        tz=None, naive_timestamps=false, reject_naive=false, nanosecond_timestamps=false,
+        midnight_as_date=false, detect_types=0, threads=None
+    ))]
+    fn open(
+        path: &str,
+        paramstyle: &str,
+        durability: Option<&str>,
+        strict_types: bool,
+        tz: Option<&Bound<'_, PyAny>>,
+        naive_timestamps: bool,
+        reject_naive: bool,
+        nanosecond_timestamps: bool,
+        midnight_as_date: bool,
+        detect_types: u8,
+        threads: Option<usize>,
+    ) -> PyResult<Self> {
+        let fetch = FetchOptions::new(
+            tz,
+            naive_timestamps,
+            nanosecond_timestamps,
+            midnight_as_date,
+            detect_types,
+        )?;
+        let mut dsn = translate_path(path)?;
+        if let Some(mode) = durability {
+            let level = connection::journal_level(mode)?;
+            match connection::sync_mode_of(&dsn) {
+                Some(current) if current != level => {
+                    return Err(PyValueError::new_err(format!(
+                        "durability='{mode}' conflicts with the sync_mode of the DSN"
+                    )))
+                }
+                Some(_) => {}
+                None if dsn.starts_with("file://") => {
+                    let separator = if dsn.contains('?') { '&' } else { '?' };
+                    let mode = connection::JOURNAL_MODES[level as usize];
+                    dsn = format!("{dsn}{separator}sync_mode={mode}");
+                }
+                None => {}
+            }
+        }
+        let db = ApiDatabase::open(&connection::engine_dsn(&dsn)).map_err(to_py)?;
+        let cache_mb = connection::dsn_option(&dsn, "cache_mb")
+            .and_then(|mb| mb.parse::<usize>().ok());
+        let mut conn = Connection::new(db, dsn);
+        if let Some(mb) = cache_mb {
+            conn.cache.configure(Some(mb.saturating_mul(1024 * 1024)));
+        }
+        conn.set_paramstyle(paramstyle)?;
+        conn.binding.set_strict(strict_types);
+        conn.binding.set_reject_naive(reject_naive);
+        conn.fetch = fetch;
+        conn.set_threads(threads)?;
+        Ok(Self {
+            conn: Arc::new(conn),
+        })
+    }
+
+    /// Open a database configured by environment variables named
+    /// `prefix` + the upper-cased setting, for containers and other
+    /// deployments configured without code changes:
+    /// - `STOOLAP_DSN`: the path or DSN, required
+    /// - `STOOLAP_SYNC_MODE`, `STOOLAP_READ_ONLY`, ...: any DSN option,
+    ///   added to the DSN unless it already sets it
+    /// - `STOOLAP_PARAMSTYLE`, `STOOLAP_DURABILITY`, `STOOLAP_TZ`,
+    ///   `STOOLAP_DETECT_TYPES`, `STOOLAP_THREADS` and the boolean options
+    ///   of `open()`, such as `STOOLAP_STRICT_TYPES=true`
+    ///
+    /// Booleans are `true`/`false` or `1`/`0`. Any other variable starting
+    /// with `prefix` raises ValueError, so a misspelled name is not
+    /// silently ignored.
     #[staticmethod]
-    fn open(path: &str) -> PyResult<Self> {
-        let dsn = translate_path(path);
-        let db = ApiDatabase::open(&dsn).map_err(to_py)?;
-        Ok(Self { db: Arc::new(db) })
+    #[pyo3(signature = (prefix="STOOLAP_"))]
+    fn open_from_env(py: Python<'_>, prefix: &str) -> PyResult<Self> {
+        let mut vars: Vec<(String, String)> = std::env::vars()
+            .filter_map(|(key, value)| {
+                Some((key.strip_prefix(prefix)?.to_ascii_lowercase(), value))
+            })
+            .collect();
+        vars.sort();
+
+        let flag = |name: &str, value: &str| match value.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(PyValueError::new_err(format!(
+                "{prefix}{} must be 'true' or 'false', got '{value}'",
+                name.to_ascii_uppercase()
+            ))),
+        };
+        let mut dsn = None;
+        let mut options = Vec::new();
+        let mut paramstyle = "numeric_dollar".to_string();
+        let mut durability = None;
+        let mut tz = None;
+        let mut detect_types = 0;
+        let mut threads = None;
+        let mut strict_types = false;
+        let mut naive_timestamps = false;
+        let mut reject_naive = false;
+        let mut nanosecond_timestamps = false;
+        let mut midnight_as_date = false;
+        for (name, value) in &vars {
+            match name.as_str() {
+                "dsn" => dsn = Some(value.clone()),
+                "paramstyle" => paramstyle = value.clone(),
+                "durability" => durability = Some(value.clone()),
+                "tz" => tz = Some(PyString::new(py, value).into_any()),
+                "detect_types" => {
+                    detect_types = value.parse().map_err(|_| {
+                        PyValueError::new_err(format!(
+                            "{prefix}DETECT_TYPES must be a number, got '{value}'"
+                        ))
+                    })?
+                }
+                "threads" => {
+                    threads = Some(value.parse().map_err(|_| {
+                        PyValueError::new_err(format!(
+                            "{prefix}THREADS must be a number, got '{value}'"
+                        ))
+                    })?)
+                }
+                "strict_types" => strict_types = flag(name, value)?,
+                "naive_timestamps" => naive_timestamps = flag(name, value)?,
+                "reject_naive" => reject_naive = flag(name, value)?,
+                "nanosecond_timestamps" => nanosecond_timestamps = flag(name, value)?,
+                "midnight_as_date" => midnight_as_date = flag(name, value)?,
+                _ if connection::is_dsn_option(name) => options.push((name, value)),
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown setting {prefix}{} in the environment",
+                        name.to_ascii_uppercase()
+                    )))
+                }
+            }
+        }
+        let dsn = dsn.ok_or_else(|| PyValueError::new_err(format!("{prefix}DSN is not set")))?;
+        let mut dsn = translate_path(&dsn)?;
+        for (name, value) in options {
+            if connection::dsn_option(&dsn, name).is_none() {
+                let separator = if dsn.contains('?') { '&' } else { '?' };
+                dsn = format!("{dsn}{separator}{name}={value}");
+            }
+        }
+        Self::open(
+            &dsn,
+            &paramstyle,
+            durability.as_deref(),
+            strict_types,
+            tz.as_ref(),
+            naive_timestamps,
+            reject_naive,
+            nanosecond_timestamps,
+            midnight_as_date,
+            detect_types,
+            threads,
+        )
     }
 
     /// Execute a DDL/DML statement. Returns the number of rows affected.
     ///
     /// Parameters can be a list/tuple (positional: $1, $2, ...) or dict (named: :key).
-    #[pyo3(signature = (sql, params=None))]
-    fn execute(&self, py: Python<'_>, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<i64> {
-        let bind = parse_params(params)?;
-        py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.execute(sql, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.execute_named(sql, to_named_params(&named)).map_err(to_py)
-            }
+    /// A cancelled `cancel` token stops the statement before it starts.
+    #[pyo3(signature = (sql, params=None, *, cancel=None))]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+    ) -> PyResult<i64> {
+        cancel::check(cancel)?;
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_autocommit(py, sql, info, 1, |py| {
+            self.conn
+                .allow_threads(py, || deadline::execute(&db, sql, bind).map_err(to_py))
         })
     }
 
     /// Execute one or more SQL statements separated by semicolons.
-    #[pyo3(signature = (sql,))]
-    fn exec(&self, py: Python<'_>, sql: &str) -> PyResult<()> {
-        let sql = sql.to_string();
-        py.allow_threads(|| {
-            for stmt in split_sql_statements(&sql) {
-                let trimmed = stmt.trim();
-                if trimmed.is_empty() {
-                    continue;
+    ///
+    /// The script is split by the SQL parser, so semicolons inside string
+    /// literals, quoted identifiers, comments and dollar-quoted bodies are
+    /// safe. Returns one result per statement, in order: a list of dicts for
+    /// statements that produce rows (queries and writes with RETURNING) and
+    /// the number of rows affected for the others.
+    ///
+    /// `params` is bound to every statement that has placeholders, and
+    /// `params_list` gives one parameter set (or None) per statement. With
+    /// `transaction=True` the whole script runs in one transaction that is
+    /// rolled back if any statement fails. A `cancel` token is checked
+    /// before each statement; cancelling stops the script there, rolling
+    /// the transaction back if there is one.
+    #[pyo3(signature = (sql, params=None, *, params_list=None, transaction=false, cancel=None))]
+    fn exec(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        params_list: Option<&Bound<'_, PyAny>>,
+        transaction: bool,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+    ) -> PyResult<PyObject> {
+        let script = self.conn.translate(sql)?;
+        let statements = sql::split_statements(&script);
+        let shared = self.conn.binding.params(params)?;
+        let per_statement = match params_list.filter(|p| !p.is_none()) {
+            Some(_) if params.is_some_and(|p| !p.is_none()) => {
+                return Err(PyTypeError::new_err(
+                    "exec() takes params or params_list, not both",
+                ))
+            }
+            Some(list) => {
+                let sets = list
+                    .try_iter()?
+                    .map(|item| self.conn.binding.params(Some(&item?)))
+                    .collect::<PyResult<Vec<_>>>()?;
+                if sets.len() != statements.len() {
+                    return Err(PyValueError::new_err(format!(
+                        "params_list has {} parameter sets for {} statements",
+                        sets.len(),
+                        statements.len()
+                    )));
                 }
-                self.db.execute(trimmed, ()).map_err(to_py)?;
+                Some(sets)
             }
-            Ok(())
-        })
+            None => None,
+        };
+
+        let bind_for = |i: usize, stmt: &str| match &per_statement {
+            Some(sets) => sets[i].clone(),
+            None if sql::has_placeholders(stmt) => shared.clone(),
+            None => BindParams::Positional(ParamVec::new()),
+        };
+
+        let results = PyList::empty(py);
+        if transaction {
+            let tx = self.begin(py, "deferred")?;
+            for (i, stmt) in statements.iter().enumerate() {
+                let result = cancel::check(cancel)
+                    .and_then(|()| tx.exec_statement(py, stmt, bind_for(i, stmt)));
+                match result {
+                    Ok(result) => results.append(result)?,
+                    Err(e) => {
+                        let _ = tx.rollback(py);
+                        return Err(e);
+                    }
+                }
+            }
+            tx.commit(py)?;
+        } else {
+            for (i, stmt) in statements.iter().enumerate() {
+                cancel::check(cancel)?;
+                results.append(self.exec_statement(py, stmt, bind_for(i, stmt))?)?;
+            }
+        }
+        Ok(results.into_any().unbind())
     }
 
     /// Query rows as a list of dicts.
     ///
     /// Each row is a dict with column names as keys.
-    #[pyo3(signature = (sql, params=None))]
-    fn query(&self, py: Python<'_>, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let rows_result = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query(sql, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named(sql, to_named_params(&named)).map_err(to_py)
+    /// `as_of` (a transaction id, datetime or timestamp string) reads every
+    /// table as it was at that point. With `intern_strings`, repeated TEXT
+    /// values in the result share one Python string object. A result with
+    /// more than `max_rows` rows or `max_result_bytes` bytes of values
+    /// raises ResultTooLarge instead of being converted. With `limit`, at
+    /// most `limit` rows are read, into a `LimitedRows` list whose
+    /// `truncated` attribute tells whether there were more. A cancelled `cancel` token
+    /// stops the query before it starts or between chunks of rows, raising
+    /// QueryCancelled.
+    ///
+    /// `hints` tunes this query alone: `{"parallel": 4}` runs the engine's
+    /// parallel operators on 4 threads whatever `threads` is set to. An
+    /// unknown hint raises ValueError.
+    #[pyo3(signature = (sql, params=None, *, as_of=None, intern_strings=false, max_rows=None, max_result_bytes=None, limit=None, cancel=None, hints=None))]
+    fn query(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        as_of: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        limit: Option<usize>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+        hints: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<PyObject> {
+        cancel::check(cancel)?;
+        let hints = Hints::parse(hints)?;
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .limit(limit)
+                .cancel(cancel);
+        let sql = self.conn.translate(sql)?;
+        let sql = with_as_of(&sql, as_of)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            let _hints = hints.enter();
+            if self.conn.cache.is_enabled() {
+                return self.cached_rows(py, sql, bind)?.to_dicts(py, options);
             }
-        })?;
-        rows_to_dicts(py, rows_result)
+            let rows = self
+                .conn
+                .allow_threads(py, || deadline::query(&db, sql, bind).map_err(to_py))?;
+            rows_to_dicts(py, rows, options)
+        })
     }
 
     /// Query a single row as a dict. Returns None if no rows.
-    #[pyo3(signature = (sql, params=None))]
+    #[pyo3(signature = (sql, params=None, *, as_of=None, cancel=None, hints=None))]
     fn query_one(
         &self,
         py: Python<'_>,
         sql: &str,
         params: Option<&Bound<'_, PyAny>>,
+        as_of: Option<&Bound<'_, PyAny>>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+        hints: Option<&Bound<'_, PyDict>>,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let rows_result = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query(sql, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named(sql, to_named_params(&named)).map_err(to_py)
-            }
-        })?;
-        first_row_to_dict(py, rows_result)
+        cancel::check(cancel)?;
+        let hints = Hints::parse(hints)?;
+        let sql = self.conn.translate(sql)?;
+        let sql = with_as_of(&sql, as_of)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            let _hints = hints.enter();
+            let rows = self
+                .conn
+                .allow_threads(py, || deadline::query(&db, sql, bind).map_err(to_py))?;
+            first_row_to_dict(py, rows, &self.conn.fetch)
+        })
     }
 
     /// Query rows in raw columnar format.
     ///
     /// Returns a dict with 'columns' (list of str) and 'rows' (list of lists).
-    #[pyo3(signature = (sql, params=None))]
+    /// With `typed`, 'rows' is replaced by 'data', one buffer per column:
+    /// an `array.array` for integer ('q'), float ('d') and boolean ('B')
+    /// columns without NULLs, and a list otherwise.
+    /// `as_of`, `intern_strings` and the limits work as for `query()`.
+    #[pyo3(signature = (sql, params=None, *, as_of=None, intern_strings=false, max_rows=None, max_result_bytes=None, typed=false))]
     fn query_raw(
         &self,
         py: Python<'_>,
         sql: &str,
         params: Option<&Bound<'_, PyAny>>,
+        as_of: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        typed: bool,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let rows_result = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query(sql, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named(sql, to_named_params(&named)).map_err(to_py)
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .typed(typed);
+        let sql = self.conn.translate(sql)?;
+        let sql = with_as_of(&sql, as_of)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            if self.conn.cache.is_enabled() {
+                return self.cached_rows(py, sql, bind)?.to_raw(py, options);
             }
-        })?;
-        rows_to_raw(py, rows_result)
+            let rows = self
+                .conn
+                .allow_threads(py, || deadline::query(&db, sql, bind).map_err(to_py))?;
+            rows_to_raw(py, rows, options)
+        })
+    }
+
+    /// Query rows as a dict of column lists: {column: [values...]}.
+    ///
+    /// The lists are built a column at a time, which is cheaper than a
+    /// dict per row and is the layout plotting and data preparation code
+    /// expects. With `numpy`, each column is a NumPy array instead: numeric
+    /// columns are written straight into int64, float64 or bool arrays
+    /// (masked arrays when they hold NULLs) and the rest are object arrays.
+    /// `as_of`, `intern_strings` and the limits work as for `query()`.
+    #[pyo3(signature = (sql, params=None, *, as_of=None, intern_strings=false, max_rows=None, max_result_bytes=None, numpy=false))]
+    fn query_columnar(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        as_of: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        numpy: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .numpy(py, numpy)?;
+        let sql = self.conn.translate(sql)?;
+        let sql = with_as_of(&sql, as_of)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            if self.conn.cache.is_enabled() {
+                return self.cached_rows(py, sql, bind)?.to_columnar(py, options);
+            }
+            let rows = self
+                .conn
+                .allow_threads(py, || deadline::query(&db, sql, bind).map_err(to_py))?;
+            rows_to_columnar(py, rows, options)
+        })
+    }
+
+    /// Query rows in lists of up to `chunk_size` dicts.
+    ///
+    /// Returns an iterator. Each chunk is read from the engine with the GIL
+    /// released, then converted in one pass, so only one chunk is held in
+    /// memory at a time. `as_of` works as for `query()`.
+    #[pyo3(signature = (sql, params=None, chunk_size=1000, *, as_of=None))]
+    fn query_chunks(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        chunk_size: usize,
+        as_of: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        self.chunks(py, sql, params, chunk_size, as_of, false)
+    }
+
+    /// Query rows as a stream of Arrow record batches of up to
+    /// `batch_rows` rows each. Requires pyarrow.
+    ///
+    /// Returns a `pyarrow.RecordBatchReader`, which polars, duckdb and other
+    /// Arrow consumers accept through the C stream interface. Batches are
+    /// read from the engine as the reader is consumed. Column types are
+    /// inferred from the first batch.
+    #[pyo3(signature = (sql, params=None, batch_rows=65536, *, as_of=None))]
+    fn query_arrow_batches(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        batch_rows: usize,
+        as_of: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        // Fail before running the query if pyarrow is missing
+        let arrow = py.import("stoolap._arrow")?;
+        let chunks = self.chunks(py, sql, params, batch_rows, as_of, true)?;
+        Ok(arrow.call_method1("record_batch_reader", (chunks,))?.unbind())
+    }
+
+    /// Find the `k` rows of `table` nearest to `query_vector` in `column`.
+    ///
+    /// Runs `SELECT *, <distance> AS distance FROM table [WHERE ...]
+    /// ORDER BY distance LIMIT k` through the engine's plan cache, so
+    /// repeated searches skip parsing and planning. `metric` is "cosine",
+    /// "l2" or "ip" (negative inner product); it should match the metric of
+    /// the column's HNSW index for the index to be used. `where` filters the
+    /// candidates and takes `params`; the query vector is bound after them,
+    /// or as `:query_vector` with named parameters. Returns a list of dicts,
+    /// nearest first, each with a `distance` key.
+    ///
+    /// `strategy` decides how `where` combines with the ranking:
+    /// "pre_filter" ranks only the matching rows (exact, but scans them all),
+    /// "post_filter" takes the `candidates` nearest rows (default `10 * k`)
+    /// and filters those (fast, but may return fewer than `k` rows), and
+    /// "auto" post-filters and falls back to pre-filtering when that comes
+    /// up short.
+    #[pyo3(signature = (
+        table, column, query_vector, k, metric="cosine", r#where=None, params=None,
+        *, strategy="auto", candidates=None
+    ))]
+    fn vector_search(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        column: &str,
+        query_vector: &Bound<'_, PyAny>,
+        k: usize,
+        metric: &str,
+        r#where: Option<&str>,
+        params: Option<&Bound<'_, PyAny>>,
+        strategy: &str,
+        candidates: Option<usize>,
+    ) -> PyResult<PyObject> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k must be at least 1"));
+        }
+        if !matches!(strategy, "auto" | "pre_filter" | "post_filter") {
+            return Err(PyValueError::new_err(format!(
+                "unknown search strategy '{strategy}' (expected 'auto', 'pre_filter' or 'post_filter')"
+            )));
+        }
+        let candidates = candidates.unwrap_or(k.saturating_mul(10));
+        if candidates < k {
+            return Err(PyValueError::new_err("candidates must be at least k"));
+        }
+        let function = vector::distance_function(metric)?;
+        let vector = Value::vector(vector::vector_values(query_vector)?);
+        let mut bind = self.conn.binding.params(params)?;
+        let placeholder = bind_extra(&mut bind, "query_vector", vector)?;
+        let ranked = format!(
+            "SELECT *, {function}({}, {placeholder}) AS distance FROM {}",
+            sql::quote_ident(column)?,
+            sql::quote_ident(table)?,
+        );
+        let Some(condition) = r#where else {
+            let sql = format!("{ranked} ORDER BY distance LIMIT {k}");
+            return self.search(py, &sql, bind);
+        };
+        let condition = self.conn.translate(condition)?;
+        let pre_filter = format!("{ranked} WHERE {condition} ORDER BY distance LIMIT {k}");
+        if strategy == "pre_filter" {
+            return self.search(py, &pre_filter, bind);
+        }
+        // The condition now applies to the derived table, so columns
+        // qualified with the table name must name it instead
+        let outer =
+            sql::requalify(&condition, table, "candidates").map_err(PyValueError::new_err)?;
+        let post_filter = format!(
+            "SELECT * FROM ({ranked} ORDER BY distance LIMIT {candidates}) AS candidates \
+             WHERE {outer} ORDER BY distance LIMIT {k}"
+        );
+        let hits = self.search(py, &post_filter, bind.clone())?;
+        if strategy == "auto" && hits.bind(py).len()? < k {
+            return self.search(py, &pre_filter, bind);
+        }
+        Ok(hits)
+    }
+
+    /// Read the value at `path` from the JSON `column` of each row of
+    /// `table`.
+    ///
+    /// `path` is a `JsonPath` or a path string such as "$.user.name" (a bare
+    /// key like "name" means "$.name"). Runs `SELECT JSON_EXTRACT(column,
+    /// path) AS value FROM table [WHERE ...]` with the path as a string
+    /// literal: the engine does not bind parameters in the select list of a
+    /// table scan. Returns the list of values; rows without the path give
+    /// None.
+    #[pyo3(signature = (table, column, path, r#where=None, params=None))]
+    fn json_get(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        column: &str,
+        path: &Bound<'_, PyAny>,
+        r#where: Option<&str>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let path = sql::literal(&Value::text(&json::path_text(path)?));
+        let bind = self.conn.binding.params(params)?;
+        let filter = match r#where {
+            Some(condition) => format!(" WHERE {}", self.conn.translate(condition)?),
+            None => String::new(),
+        };
+        let sql = format!(
+            "SELECT JSON_EXTRACT({}, {path}) AS value FROM {}{filter}",
+            sql::quote_ident(column)?,
+            sql::quote_ident(table)?,
+        );
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                let rows = deadline::query(&db, &sql, bind);
+                rows.and_then(RowBuffer::drain).map_err(to_py)
+            })?;
+            let values = rows
+                .rows
+                .iter()
+                .map(|row| value_to_py(py, &row[0], &self.conn.fetch))
+                .collect::<PyResult<Vec<_>>>()?;
+            let count = rows.rows.len();
+            Ok((PyList::new(py, values)?.into_any().unbind(), count))
+        })
+    }
+
+    /// Run independent read queries concurrently. Returns one list of dicts
+    /// per query, in the order given.
+    ///
+    /// `queries` holds `(sql, params)` pairs or bare SQL strings. Up to
+    /// `max_parallel` queries (default: one per CPU) run at once on
+    /// background threads while the GIL is released.
+    #[pyo3(signature = (queries, max_parallel=None))]
+    fn query_many(
+        &self,
+        py: Python<'_>,
+        queries: &Bound<'_, PyAny>,
+        max_parallel: Option<usize>,
+    ) -> PyResult<PyObject> {
+        if max_parallel == Some(0) {
+            return Err(PyValueError::new_err("max_parallel must be at least 1"));
+        }
+
+        // Parse and audit every query on the Python thread (need GIL)
+        let mut pending = Vec::new();
+        let mut jobs = Vec::new();
+        for item in queries.try_iter()? {
+            let item = item?;
+            let (sql, params) = if let Ok(sql) = item.downcast::<PyString>() {
+                (sql.to_str()?.to_string(), None)
+            } else {
+                item.extract::<(String, Option<Bound<'_, PyAny>>)>().map_err(|_| {
+                    PyTypeError::new_err("query_many expects (sql, params) pairs or SQL strings")
+                })?
+            };
+            let sql = self.conn.translate(&sql)?.into_owned();
+            let bind = self.conn.binding.params(params.as_ref())?;
+            let info = self.conn.hooks.describe(&bind);
+            let token = self.conn.start_query(py, &sql, &info)?;
+            jobs.push((jobs.len(), sql.clone(), bind));
+            pending.push((sql, info, token));
+        }
+
+        // Run without GIL, each worker taking the next query off the queue
+        let db = self.conn.db()?;
+        let workers = max_parallel
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+            .min(jobs.len());
+        let mut buffers: Vec<Option<PyResult<RowBuffer>>> = Vec::new();
+        buffers.resize_with(jobs.len(), || None);
+        py.allow_threads(|| {
+            let queue = Mutex::new(jobs.into_iter());
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..workers)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut done = Vec::new();
+                            loop {
+                                let Some((i, sql, bind)) = queue.lock().unwrap().next() else {
+                                    break done;
+                                };
+                                let rows = match bind {
+                                    BindParams::Positional(p) => db.query(&sql, p),
+                                    BindParams::Named(named) => {
+                                        db.query_named(&sql, to_named_params(&named))
+                                    }
+                                };
+                                done.push((i, rows.and_then(RowBuffer::drain).map_err(to_py)));
+                            }
+                        })
+                    })
+                    .collect();
+                for handle in handles {
+                    let done = handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                    for (i, buffer) in done {
+                        buffers[i] = Some(buffer);
+                    }
+                }
+            })
+        });
+
+        // Build the results and report every query, even after a failure
+        let result = PyList::empty(py);
+        let mut error = None;
+        let options = ConvertOptions::plain(&self.conn.fetch);
+        for ((sql, info, token), buffer) in pending.into_iter().zip(buffers) {
+            let buffer = buffer.expect("every query runs on a worker");
+            let outcome = buffer.and_then(|buffer| buffer.to_dicts(py, options));
+            match self.conn.finish_query(py, &sql, &info, token, outcome) {
+                Ok(rows) => result.append(rows)?,
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        match error {
+            Some(err) => Err(err),
+            None => Ok(result.into_any().unbind()),
+        }
     }
 
     /// Execute the same SQL with multiple parameter sets.
     ///
     /// Automatically wraps in a transaction. Returns total rows affected.
-    #[pyo3(signature = (sql, params_list))]
+    ///
+    /// With `parallel=N`, the parameter sets are split into N contiguous
+    /// shares executed at once, each on its own thread and in its own
+    /// transaction, for ingest bound by the engine's single-threaded work.
+    /// The shares commit once every one has run, or all roll back if one
+    /// failed. The sets must be independent: shares writing the same rows
+    /// conflict.
+    #[pyo3(signature = (sql, params_list, *, parallel=None))]
     fn execute_batch(
         &self,
         py: Python<'_>,
         sql: &str,
         params_list: &Bound<'_, PyList>,
+        parallel: Option<usize>,
     ) -> PyResult<i64> {
-        let sql = sql.to_string();
+        if parallel == Some(0) {
+            return Err(PyValueError::new_err("parallel must be at least 1"));
+        }
+        let sql = self.conn.translate(sql)?.into_owned();
 
         // Parse all param sets on the Python thread (need GIL)
         let mut all_params = Vec::with_capacity(params_list.len());
         for item in params_list.iter() {
-            let bind = parse_params(Some(&item))?;
+            let bind = self.conn.binding.params(Some(&item))?;
             match bind {
                 BindParams::Positional(p) => all_params.push(p),
                 BindParams::Named(_) => {
@@ -155,213 +833,3481 @@ impl Database {
             }
         }
 
-        // Execute without GIL
-        py.allow_threads(|| {
-            use stoolap::parser::Parser;
-            let mut parser = Parser::new(&sql);
-            let program = parser.parse_program().map_err(|e| {
-                crate::error::StoolapError::new_err(e.to_string())
-            })?;
-            let stmt = program.statements.first().ok_or_else(|| {
-                crate::error::StoolapError::new_err("No SQL statement found")
-            })?;
-
-            let mut tx = self.db.begin().map_err(to_py)?;
-            let mut total = 0i64;
-            for params in all_params {
-                total += tx.execute_prepared(stmt, params).map_err(to_py)?;
+        match parallel {
+            Some(workers) if workers > 1 && all_params.len() > 1 => {
+                self.execute_params_parallel(py, &sql, all_params, workers)
             }
-            tx.commit().map_err(to_py)?;
-            Ok(total)
-        })
+            _ => self.execute_params(py, &sql, all_params),
+        }
     }
 
-    /// Create a prepared statement.
+    /// Insert one row per row of `matrix`, an (N, dims) float32 array,
+    /// storing the matching entry of `ids` in `id_column` and the row in
+    /// `vector_column`.
     ///
-    /// Parses SQL once and caches the execution plan.
-    fn prepare(&self, sql: &str) -> PyResult<PreparedStatement> {
-        PreparedStatement::new(Arc::clone(&self.db), sql)
-    }
-
-    /// Begin a transaction.
-    fn begin(&self, py: Python<'_>) -> PyResult<Transaction> {
-        let tx = py.allow_threads(|| self.db.begin().map_err(to_py))?;
-        Ok(Transaction::from_tx(tx))
-    }
+    /// The matrix is read through the buffer protocol in a single copy and
+    /// the rows are bound as vectors in Rust, so no `Vector` object is made
+    /// per row. Rows are inserted `batch_size` at a time, each batch in its
+    /// own transaction: a failure rolls back the batch in progress, while
+    /// earlier batches stay committed. Returns the number of rows inserted.
+    ///
+    /// With `normalize=True` each row is scaled to unit L2 norm first.
+    #[pyo3(signature = (
+        table, id_column, vector_column, ids, matrix, *, batch_size=10000, normalize=false
+    ))]
+    fn insert_vectors(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        id_column: &str,
+        vector_column: &str,
+        ids: &Bound<'_, PyAny>,
+        matrix: &Bound<'_, PyAny>,
+        batch_size: usize,
+        normalize: bool,
+    ) -> PyResult<i64> {
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch size must be at least 1"));
+        }
+        let (values, rows, dims) = vector::matrix_values(matrix)?;
+        let ids = ids
+            .try_iter()?
+            .map(|id| self.conn.binding.value(&id?))
+            .collect::<PyResult<Vec<_>>>()?;
+        if ids.len() != rows {
+            return Err(PyValueError::new_err(format!(
+                "got {} ids for {rows} matrix rows",
+                ids.len()
+            )));
+        }
+        let all_params: Vec<ParamVec> = ids
+            .into_iter()
+            .zip(values.chunks(dims))
+            .map(|(id, row)| {
+                let mut row = row.to_vec();
+                if normalize {
+                    vector::normalize(&mut row);
+                }
+                let mut params = ParamVec::new();
+                params.push(id);
+                params.push(Value::vector(row));
+                params
+            })
+            .collect();
+        drop(values);
 
-    /// Close the database connection.
-    fn close(&self, py: Python<'_>) -> PyResult<()> {
-        py.allow_threads(|| self.db.close().map_err(to_py))
-    }
+        let sql = format!(
+            "INSERT INTO {} ({}, {}) VALUES ($1, $2)",
+            sql::quote_ident(table)?,
+            sql::quote_ident(id_column)?,
+            sql::quote_ident(vector_column)?,
+        );
+        let db = self.conn.db()?;
+        let info = self.conn.hooks.describe_batch(&all_params);
+        self.conn.run_autocommit(py, &sql, info, rows as u64, |py| {
+            self.conn.allow_threads(py, || {
+                use stoolap::parser::Parser;
+                let mut parser = Parser::new(&sql);
+                let program = parser.parse_program().map_err(|e| {
+                    crate::error::parse_error(e.to_string())
+                })?;
+                let stmt = &program.statements[0];
 
-    fn __repr__(&self) -> String {
-        "Database(open)".to_string()
+                let mut total = 0i64;
+                let mut all_params = all_params.into_iter().peekable();
+                while all_params.peek().is_some() {
+                    let mut tx = db.begin().map_err(to_py)?;
+                    for params in all_params.by_ref().take(batch_size) {
+                        total += tx.execute_prepared(stmt, params).map_err(to_py)?;
+                    }
+                    tx.commit().map_err(to_py)?;
+                }
+                Ok(total)
+            })
+        })
     }
-}
 
-/// Translate user-friendly paths to Stoolap DSN format.
-fn translate_path(path: &str) -> String {
-    let trimmed = path.trim();
-    if trimmed.is_empty() || trimmed == ":memory:" {
-        "memory://".to_string()
-    } else if trimmed.starts_with("memory://") || trimmed.starts_with("file://") {
-        trimmed.to_string()
-    } else {
-        format!("file://{trimmed}")
+    /// Copy new and changed rows of `tables` (default: every table) from
+    /// `other` into this database, matching rows by primary key.
+    ///
+    /// `mode` is "upsert" (insert new rows, update changed ones), "insert"
+    /// (only insert rows whose key is new) or "mirror" (upsert, then delete
+    /// rows `other` no longer has). Each table must exist in both databases
+    /// with a primary key, and every column of `other` must exist here.
+    /// The whole sync runs in one transaction on this database. Both tables
+    /// are read in full, so each call compares every row.
+    ///
+    /// Returns `{table: {"inserted": n, "updated": n, "deleted": n}}`.
+    #[pyo3(signature = (other, tables=None, *, mode="upsert"))]
+    fn sync_from(
+        &self,
+        py: Python<'_>,
+        other: &Database,
+        tables: Option<Vec<String>>,
+        mode: &str,
+    ) -> PyResult<PyObject> {
+        let (update, delete) = match mode.to_ascii_lowercase().as_str() {
+            "upsert" => (true, false),
+            "insert" => (false, false),
+            "mirror" => (true, true),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "mode must be 'upsert', 'insert' or 'mirror', got '{mode}'"
+                )))
+            }
+        };
+        let tables = match tables {
+            Some(tables) => tables,
+            None => other.tables(py)?,
+        };
+        let tx = self.begin(py, "deferred")?;
+        let result = PyDict::new(py);
+        let synced = tables.iter().try_for_each(|table| {
+            let counts = self.sync_table(py, &tx, other, table, update, delete)?;
+            let entry = PyDict::new(py);
+            for (key, count) in ["inserted", "updated", "deleted"].iter().zip(counts) {
+                entry.set_item(key, count)?;
+            }
+            result.set_item(table, entry)
+        });
+        match synced.and_then(|_| tx.commit(py)) {
+            Ok(()) => Ok(result.into_any().unbind()),
+            Err(err) => {
+                if tx.is_active() {
+                    let _ = tx.rollback(py);
+                }
+                Err(err)
+            }
+        }
     }
-}
 
-/// Convert named params to stoolap NamedParams.
-pub fn to_named_params(named: &[(String, stoolap::core::Value)]) -> stoolap::api::NamedParams {
-    let mut np = stoolap::api::NamedParams::new();
-    for (key, val) in named {
-        np.insert(key, val.clone());
+    /// A context manager holding `data` in a temporary table, for joining
+    /// Python data against database tables.
+    ///
+    /// `data` is an iterable of dicts (columns from the first dict's keys
+    /// unless `columns` is given), an iterable of sequences with `columns`,
+    /// or a pandas DataFrame. Entering the `with` block creates a uniquely
+    /// named table with column types inferred from the values, loads the
+    /// rows and returns the name; leaving it drops the table. The table is
+    /// an ordinary table while it exists, visible to other connections.
+    #[pyo3(signature = (data, columns=None))]
+    fn temp_table(
+        slf: &Bound<'_, Self>,
+        data: &Bound<'_, PyAny>,
+        columns: Option<Vec<String>>,
+    ) -> PyResult<TempTable> {
+        TempTable::new(slf.clone().unbind(), data, columns)
     }
-    np
-}
-
-/// Convert Rows iterator to a list of Python dicts.
-pub fn rows_to_dicts(py: Python<'_>, rows: stoolap::api::Rows) -> PyResult<PyObject> {
-    let columns: Vec<String> = rows.columns().to_vec();
-    // Pre-allocate column names as PyString once, reuse for every row
-    let py_col_names: Vec<Bound<'_, PyString>> =
-        columns.iter().map(|c| PyString::new(py, c)).collect();
-    let result = PyList::empty(py);
 
-    for row_result in rows {
-        let row = row_result.map_err(to_py)?;
-        let dict = PyDict::new(py);
-        for (i, col) in py_col_names.iter().enumerate() {
-            let val = match row.get_value(i) {
-                Some(v) => value_to_py(py, v),
-                None => py.None(),
+    /// Delete the rows of `table` matching `where`: a dict of column values
+    /// or a SQL condition with its `params`, as for `table().delete()`.
+    /// Returns the number of rows deleted.
+    ///
+    /// With `batch_size`, at most that many rows are deleted per statement,
+    /// each committed on its own, so a large purge neither holds one huge
+    /// transaction nor writes it to the WAL at once, and readers see it
+    /// progress. Batches are picked by primary key and checked against
+    /// `where` again as they are deleted. A failure leaves the batches
+    /// before it deleted.
+    #[pyo3(signature = (table, r#where, params=None, batch_size=None))]
+    fn delete_where(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        r#where: &Bound<'_, PyAny>,
+        params: Option<&Bound<'_, PyAny>>,
+        batch_size: Option<usize>,
+    ) -> PyResult<i64> {
+        if batch_size == Some(0) {
+            return Err(PyValueError::new_err("batch size must be at least 1"));
+        }
+        let (condition, bind) = table::condition(self, Some(r#where), params)?;
+        let condition = table::required(condition, "delete_where")?;
+        let name = sql::quote_ident(table)?;
+        let Some(batch_size) = batch_size else {
+            let stmt = format!("DELETE FROM {name} WHERE {condition}");
+            return self.exec_statement(py, &stmt, bind)?.extract(py);
+        };
+        let key = self.primary_key(py, table)?;
+        let columns: Vec<String> = key
+            .iter()
+            .map(|c| sql::quote_ident(c))
+            .collect::<PyResult<_>>()?;
+        let select = format!(
+            "SELECT {} FROM {name} WHERE {condition} LIMIT {batch_size}",
+            columns.join(", ")
+        );
+        let mut total = 0;
+        loop {
+            let rows = self.exec_statement(py, &select, bind.clone())?;
+            let rows = rows.bind(py).downcast::<PyList>()?;
+            if rows.is_empty() {
+                break;
+            }
+            // Match the batch by key, binding the key values after `params`
+            let mut delete = bind.clone();
+            let mut matches = Vec::with_capacity(rows.len());
+            for (i, row) in rows.iter().enumerate() {
+                let row = row.downcast_into::<PyDict>()?;
+                let mut placeholders = Vec::with_capacity(key.len());
+                for (j, column) in key.iter().enumerate() {
+                    let value = row.get_item(column)?.ok_or_else(|| {
+                        StoolapError::new_err(format!("key column '{column}' missing from row"))
+                    })?;
+                    let value = self.conn.binding.value(&value)?;
+                    placeholders.push(bind_extra(&mut delete, &format!("key_{i}_{j}"), value)?);
+                }
+                matches.push(placeholders);
+            }
+            let matched = if let [column] = columns.as_slice() {
+                let values: Vec<&str> = matches.iter().map(|m| m[0].as_str()).collect();
+                format!("{column} IN ({})", values.join(", "))
+            } else {
+                let rows: Vec<String> = matches
+                    .iter()
+                    .map(|m| {
+                        let pairs: Vec<String> =
+                            columns.iter().zip(m).map(|(c, p)| format!("{c} = {p}")).collect();
+                        format!("({})", pairs.join(" AND "))
+                    })
+                    .collect();
+                rows.join(" OR ")
             };
-            dict.set_item(col, val)?;
+            let stmt = format!("DELETE FROM {name} WHERE ({condition}) AND ({matched})");
+            let deleted: i64 = self.exec_statement(py, &stmt, delete)?.extract(py)?;
+            total += deleted;
+            if deleted == 0 || rows.len() < batch_size {
+                break;
+            }
         }
-        result.append(dict)?;
+        Ok(total)
     }
 
-    Ok(result.into_any().unbind())
-}
-
-/// Convert Rows iterator to first row dict or None.
-pub fn first_row_to_dict(py: Python<'_>, mut rows: stoolap::api::Rows) -> PyResult<PyObject> {
-    let columns: Vec<String> = rows.columns().to_vec();
+    /// Create `table` from `columns`: a dict of column names to their
+    /// definitions, as in `{"id": "INTEGER PRIMARY KEY", "name": "TEXT NOT
+    /// NULL"}`, or a list of `Column`s. Table and column names are quoted
+    /// as identifiers, so any name works; definitions are used as written.
+    /// With `if_not_exists` (the default), an existing table is left as is.
+    #[pyo3(signature = (table, columns, *, if_not_exists=true))]
+    fn create_table(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        columns: &Bound<'_, PyAny>,
+        if_not_exists: bool,
+    ) -> PyResult<()> {
+        let stmt = ddl::create_table(&self.conn.binding, table, columns, if_not_exists)?;
+        self.exec_statement(py, &stmt, BindParams::Positional(ParamVec::new()))?;
+        Ok(())
+    }
 
-    if let Some(row_result) = rows.next() {
-        let row = row_result.map_err(to_py)?;
-        let dict = PyDict::new(py);
-        // Single row — pre-allocate column names as PyString
-        let py_col_names: Vec<Bound<'_, PyString>> =
-            columns.iter().map(|c| PyString::new(py, c)).collect();
-        for (i, col) in py_col_names.iter().enumerate() {
-            let val = match row.get_value(i) {
-                Some(v) => value_to_py(py, v),
-                None => py.None(),
-            };
-            dict.set_item(col, val)?;
+    /// Remove every row of `table` at once with the engine's `TRUNCATE
+    /// TABLE`, instead of deleting the rows one by one.
+    ///
+    /// The engine's truncate starts AUTO_INCREMENT ids over. Unless
+    /// `restart_identity=True`, the counter is set back to where it was, so
+    /// new rows do not reuse ids. The engine rebuilds the counter from the
+    /// rows when the database is opened, so ids of an emptied table start
+    /// over after a reopen either way.
+    #[pyo3(signature = (table, restart_identity=false))]
+    fn truncate(&self, py: Python<'_>, table: &str, restart_identity: bool) -> PyResult<()> {
+        let quoted = sql::quote_ident(table)?;
+        let db = self.conn.db()?;
+        let counter = if restart_identity {
+            None
+        } else {
+            let store = db.engine().get_version_store(table).ok();
+            store.map(|store| store.get_current_auto_increment_value())
+        };
+        let no_params = BindParams::Positional(ParamVec::new());
+        self.exec_statement(py, &format!("TRUNCATE TABLE {quoted}"), no_params)?;
+        if let Some(counter) = counter {
+            if let Ok(store) = db.engine().get_version_store(table) {
+                store.set_auto_increment_counter(counter);
+            }
         }
-        return Ok(dict.into_any().unbind());
+        Ok(())
     }
 
-    Ok(py.None())
-}
-
-/// Convert Rows to raw format: { columns: [...], rows: [[...], ...] }
-pub fn rows_to_raw(py: Python<'_>, rows: stoolap::api::Rows) -> PyResult<PyObject> {
-    let columns: Vec<String> = rows.columns().to_vec();
-    let py_columns = PyList::new(py, &columns)?;
-    let py_rows = PyList::empty(py);
+    /// A handle on `table` for simple CRUD with dicts: `insert()`, `get()`
+    /// by primary key, `find()`, `update()` and `delete()`.
+    fn table(slf: &Bound<'_, Self>, table: String) -> Table {
+        Table::new(slf.clone().unbind(), table)
+    }
 
-    for row_result in rows {
-        let row = row_result.map_err(to_py)?;
-        let py_row = PyList::empty(py);
-        for i in 0..columns.len() {
-            let val = match row.get_value(i) {
-                Some(v) => value_to_py(py, v),
-                None => py.None(),
-            };
-            py_row.append(val)?;
+    /// Map class `cls` to `table` for `get()`, `save()` and `select()`.
+    ///
+    /// `pk` names the primary key column, or a list of columns for a
+    /// composite key; it defaults to the table's primary key. `columns`
+    /// defaults to all columns of the table. Mapping a class again replaces
+    /// its mapping; subclasses use the mapping of their nearest mapped base.
+    #[pyo3(signature = (cls, table, pk=None, columns=None))]
+    fn map(
+        &self,
+        py: Python<'_>,
+        cls: &Bound<'_, PyType>,
+        table: String,
+        pk: Option<&Bound<'_, PyAny>>,
+        columns: Option<Vec<String>>,
+    ) -> PyResult<()> {
+        let key: Vec<String> = match pk {
+            None => self.primary_key(py, &table)?,
+            Some(pk) if pk.is_instance_of::<PyString>() => vec![pk.extract()?],
+            Some(pk) => pk.extract()?,
+        };
+        let columns = match columns {
+            Some(columns) => columns,
+            None => {
+                let mut names = Vec::new();
+                for column in self.describe(py, &table)?.bind(py).try_iter()? {
+                    let column = column?.downcast_into::<PyDict>()?;
+                    let name = field(&column, &["field", "column", "column_name", "name"])?;
+                    names.extend(name);
+                }
+                names
+            }
+        };
+        if key.is_empty() {
+            return Err(PyValueError::new_err("pk must name at least one column"));
         }
-        py_rows.append(py_row)?;
+        if let Some(missing) = key.iter().find(|k| !columns.contains(k)) {
+            return Err(PyValueError::new_err(format!(
+                "primary key column '{missing}' is not among the mapped columns"
+            )));
+        }
+        let mapping = Mapping::new(cls.clone().unbind(), table, key, columns);
+        self.conn.mappings.register(py, mapping);
+        Ok(())
     }
 
-    let result = PyDict::new(py);
-    result.set_item("columns", py_columns)?;
-    result.set_item("rows", py_rows)?;
-    Ok(result.into_any().unbind())
-}
-
-/// Split SQL statements by semicolons, handling quotes and comments.
-fn split_sql_statements(input: &str) -> Vec<String> {
-    let mut statements = Vec::new();
-    let mut current = String::new();
-    let mut in_single = false;
-    let mut in_double = false;
-    let mut in_line_comment = false;
-    let mut in_block_comment = false;
+    /// The instance of mapped class `cls` whose primary key is `pk` (a
+    /// tuple for a composite key), or None.
+    fn get(
+        &self,
+        py: Python<'_>,
+        cls: &Bound<'_, PyType>,
+        pk: &Bound<'_, PyAny>,
+    ) -> PyResult<PyObject> {
+        let mapping = self.conn.mappings.of_class(cls)?;
+        mapping::get(self, py, &mapping, pk)
+    }
 
-    let chars: Vec<char> = input.chars().collect();
-    let mut i = 0;
+    /// Instances of mapped class `cls` for the rows matching `where`: a
+    /// dict of column values or a SQL condition with `params`, as in
+    /// `table()`.
+    #[pyo3(signature = (cls, r#where=None, params=None, *, order_by=None, limit=None))]
+    fn select(
+        &self,
+        py: Python<'_>,
+        cls: &Bound<'_, PyType>,
+        r#where: Option<&Bound<'_, PyAny>>,
+        params: Option<&Bound<'_, PyAny>>,
+        order_by: Option<&str>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let mapping = self.conn.mappings.of_class(cls)?;
+        let (condition, bind) = table::condition(self, r#where, params)?;
+        mapping::select(self, py, &mapping, condition, bind, order_by, limit)
+    }
 
-    while i < chars.len() {
-        let c = chars[i];
+    /// Write an instance of a mapped class: update its row when the primary
+    /// key is set and exists, insert it otherwise. Key attributes that are
+    /// None are filled in from the inserted row, for generated keys.
+    fn save(&self, py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<()> {
+        let mapping = self.conn.mappings.of_class(&obj.get_type())?;
+        mapping::save(self, py, &mapping, obj)
+    }
 
-        if in_line_comment {
-            if c == '\n' {
-                in_line_comment = false;
-                current.push(c);
-            }
-            i += 1;
-            continue;
-        }
+    /// Return `sql` with its parameters rendered as SQL literals.
+    ///
+    /// For logging and debugging only: the statement is not parsed or run,
+    /// and executing the result bypasses parameter binding.
+    #[pyo3(signature = (sql, params=None))]
+    fn mogrify(&self, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<String> {
+        let bind = self.conn.binding.params(params)?;
+        sql::mogrify(&self.conn.translate(sql)?, &bind).map_err(PyValueError::new_err)
+    }
 
-        if !in_single && !in_double && !in_block_comment && c == '-' && i + 1 < chars.len() && chars[i + 1] == '-' {
-            let next = if i + 2 < chars.len() { chars[i + 2] } else { '\0' };
-            if next == '\0' || next == ' ' || next == '\t' || next == '\n' || next == '\r' {
-                in_line_comment = true;
-                i += 2;
-                continue;
-            }
-        }
+    /// The plan the engine chooses for `sql`, from `EXPLAIN`.
+    ///
+    /// `format` is "text" for the engine's output as one string, "dict" for
+    /// nested `{"node", "details", "children"}` dicts, or "tree" for an
+    /// indented tree drawn from those nodes, for notebooks and logs. Dict
+    /// nodes also hold the planner's `estimated_rows` and `estimated_cost`,
+    /// and None for `actual_rows` and `actual_time_ms`.
+    #[pyo3(signature = (sql, params=None, *, format="text"))]
+    fn explain(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        format: &str,
+    ) -> PyResult<PyObject> {
+        self.plan(py, "EXPLAIN", sql, params, format)
+    }
+
+    /// Run `sql` and return its plan with what each node took, from
+    /// `EXPLAIN ANALYZE`. The statement is executed, writes included.
+    ///
+    /// `format` is as for `explain()`. Dict nodes hold `actual_rows` and
+    /// `actual_time_ms` next to the estimates, where the engine prints them,
+    /// so misestimates can be spotted by comparing the two.
+    #[pyo3(signature = (sql, params=None, *, format="text"))]
+    fn explain_analyze(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        format: &str,
+    ) -> PyResult<PyObject> {
+        self.plan(py, "EXPLAIN ANALYZE", sql, params, format)
+    }
+
+    /// Create a prepared statement.
+    ///
+    /// Parses SQL once and caches the execution plan.
+    fn prepare(&self, sql: &str) -> PyResult<PreparedStatement> {
+        PreparedStatement::new(Arc::clone(&self.conn), sql)
+    }
+
+    /// Begin a transaction.
+    ///
+    /// `mode` controls when the transaction's view is fixed:
+    /// - "deferred" (default): each statement sees the latest committed data
+    /// - "snapshot": the snapshot is taken at BEGIN and kept until commit,
+    ///   so conflicting writes surface as `ConflictError`
+    /// - "immediate" and "exclusive": not supported; Stoolap uses MVCC and
+    ///   never takes a write lock at BEGIN
+    #[pyo3(signature = (mode="deferred"))]
+    fn begin(&self, py: Python<'_>, mode: &str) -> PyResult<Transaction> {
+        let snapshot = match mode.to_ascii_lowercase().as_str() {
+            "deferred" => false,
+            "snapshot" => true,
+            "immediate" | "exclusive" => {
+                return Err(crate::error::StoolapError::new_err(format!(
+                    "{mode} transactions are not supported: Stoolap uses MVCC and never takes a write lock at BEGIN; use mode='snapshot' to fix the snapshot at BEGIN"
+                )))
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "mode must be 'deferred' or 'snapshot', got '{mode}'"
+                )))
+            }
+        };
+        let db = self.conn.db()?;
+        let tx = py.allow_threads(|| Session::begin(&db, snapshot).map_err(to_py))?;
+        let level = if snapshot {
+            "SNAPSHOT"
+        } else {
+            DEFAULT_ISOLATION
+        };
+        Ok(Transaction::new(Arc::clone(&self.conn), tx, level))
+    }
+
+    /// Isolation level of transactions begun with the default mode, as an
+    /// SQL name ("READ COMMITTED").
+    fn get_isolation_level(&self) -> &'static str {
+        DEFAULT_ISOLATION
+    }
+
+    /// Run `func(tx)` inside a transaction and return its result.
+    ///
+    /// Commits when `func` returns and rolls back if it raises. A
+    /// `ConflictError` (from `func` or the commit) is retried up to
+    /// `retries` times, sleeping `backoff * 2**attempt` seconds before each
+    /// retry, so `func` must be safe to run again.
+    #[pyo3(signature = (func, retries=3, backoff=0.01))]
+    fn transaction(
+        &self,
+        py: Python<'_>,
+        func: &Bound<'_, PyAny>,
+        retries: u32,
+        backoff: f64,
+    ) -> PyResult<PyObject> {
+        if !func.is_callable() {
+            return Err(PyTypeError::new_err("func must be callable"));
+        }
+        if !backoff.is_finite() || backoff < 0.0 {
+            return Err(PyValueError::new_err("backoff must be a non-negative number"));
+        }
+        let mut attempt = 0;
+        loop {
+            let tx = Bound::new(py, self.begin(py, "deferred")?)?;
+            let result = func
+                .call1((tx.clone(),))
+                .and_then(|value| tx.call_method0("commit").map(|_| value.unbind()));
+            let err = match result {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            if tx.borrow().is_active() {
+                let _ = tx.call_method0("rollback");
+            }
+            if attempt >= retries || !err.is_instance_of::<ConflictError>(py) {
+                return Err(err);
+            }
+            let delay = Duration::try_from_secs_f64(backoff * 2f64.powi(attempt as i32))
+                .unwrap_or(Duration::MAX);
+            py.allow_threads(|| std::thread::sleep(delay));
+            attempt += 1;
+        }
+    }
+
+    /// Version of the Stoolap engine. This package is released in step with
+    /// the engine, so the two share a version number.
+    #[getter]
+    fn server_version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// `server_version` as a tuple of integers, e.g. `(0, 4, 0)`.
+    #[getter]
+    fn server_version_info(&self) -> (u32, u32, u32) {
+        let mut parts = env!("CARGO_PKG_VERSION")
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0));
+        let mut next = || parts.next().unwrap_or(0);
+        (next(), next(), next())
+    }
+
+    /// True while any transaction begun from this database is active.
+    #[getter]
+    fn in_transaction(&self) -> bool {
+        self.conn.stats.active_transactions.load(Ordering::Relaxed) > 0
+    }
+
+    /// Names of all tables, sorted.
+    fn tables(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        let db = self.conn.db()?;
+        let mut names = py.allow_threads(|| table_names(&db)).map_err(to_py)?;
+        names.sort();
+        Ok(names)
+    }
+
+    /// Columns of `table` as reported by the engine's `DESCRIBE`, one dict
+    /// per column.
+    fn describe(&self, py: Python<'_>, table: &str) -> PyResult<PyObject> {
+        self.introspect(py, &format!("DESCRIBE {}", sql::quote_ident(table)?))
+    }
+
+    /// Indexes of `table`, including those backing primary key and unique
+    /// constraints, as reported by `SHOW INDEXES`. One dict per index column.
+    fn indexes(&self, py: Python<'_>, table: &str) -> PyResult<PyObject> {
+        self.introspect(py, &format!("SHOW INDEXES FROM {}", sql::quote_ident(table)?))
+    }
+
+    /// Statistics for `table`, without scanning it.
+    ///
+    /// Returns a dict with `row_count` (answered from volume statistics),
+    /// `size_bytes` (the table's files on disk, None for in-memory databases
+    /// or when the engine keeps no files per table), `last_analyzed`, and
+    /// `columns`, a dict of `{"min", "max", "ndv", "null_count"}` per column.
+    /// Column statistics and `last_analyzed` are recorded by `analyze()` and
+    /// are None where the engine has none.
+    fn table_stats<'py>(&self, py: Python<'py>, table: &str) -> PyResult<PyObject> {
+        let described = self.describe(py, table)?;
+        let count = self.fetch(py, &format!("SELECT COUNT(*) FROM {}", sql::quote_ident(table)?))?;
+        let row_count = match count.rows.first().and_then(|row| row.first()) {
+            Some(Value::Integer(n)) => Some(*n),
+            _ => None,
+        };
+        let size_bytes = match self.data_dir() {
+            Some(dir) => py.allow_threads(|| table_size(&dir, table)),
+            None => None,
+        };
+
+        // Statistics are only there once the table has been analyzed
+        let name = sql::literal(&Value::text(table));
+        let recorded = |stats: &str| -> PyResult<Vec<Bound<'py, PyDict>>> {
+            let sql = format!("SELECT * FROM {stats} WHERE table_name = {name}");
+            let Ok(rows) = self.introspect(py, &sql) else {
+                return Ok(Vec::new());
+            };
+            rows.into_bound(py)
+                .try_iter()?
+                .map(|row| Ok(row?.downcast_into::<PyDict>()?))
+                .collect()
+        };
+        let table_rows = recorded(TABLE_STATS)?;
+        let column_rows = recorded(COLUMN_STATS)?;
+        let last_analyzed = match table_rows.first() {
+            Some(row) => field_value(
+                row,
+                &["last_analyzed", "analyzed_at", "last_updated", "updated_at"],
+            )?,
+            None => None,
+        };
+
+        let columns = PyDict::new(py);
+        for column in described.bind(py).try_iter()? {
+            let column = column?.downcast_into::<PyDict>()?;
+            let Some(name) = field(&column, &["field", "column", "column_name", "name"])? else {
+                continue;
+            };
+            let mut stats = None;
+            for row in &column_rows {
+                if field(row, &["column_name", "column"])?
+                    .is_some_and(|c| c.eq_ignore_ascii_case(&name))
+                {
+                    stats = Some(row);
+                    break;
+                }
+            }
+            let entry = PyDict::new(py);
+            for (key, keys) in [
+                ("min", &["min_value", "min"][..]),
+                ("max", &["max_value", "max"][..]),
+                ("ndv", &["distinct_count", "ndv", "n_distinct"][..]),
+                ("null_count", &["null_count"][..]),
+            ] {
+                let value = match stats {
+                    Some(row) => field_value(row, keys)?,
+                    None => None,
+                };
+                entry.set_item(key, value)?;
+            }
+            columns.set_item(name, entry)?;
+        }
+
+        let result = PyDict::new(py);
+        result.set_item("table", table)?;
+        result.set_item("row_count", row_count)?;
+        result.set_item("size_bytes", size_bytes)?;
+        result.set_item("last_analyzed", last_analyzed)?;
+        result.set_item("columns", columns)?;
+        Ok(result.into_any().unbind())
+    }
+
+    /// Create a vector index on `column` of `table` and return its name.
+    ///
+    /// `metric` is "cosine", "l2" or "ip" and should match the metric used
+    /// by `vector_search()`. `index_type` must be "hnsw", the only vector
+    /// index the engine has. `m` (links per node, 2-256), `ef_construction`
+    /// (build-time candidate list, at least `m`) and `ef_search` (query-time
+    /// candidate list) are left to the engine defaults when omitted. `name`
+    /// defaults to `idx_<table>_<column>`.
+    #[pyo3(signature = (
+        table, column, metric="cosine", index_type="hnsw", *, m=None, ef_construction=None,
+        ef_search=None, name=None, if_not_exists=false
+    ))]
+    fn create_vector_index(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        column: &str,
+        metric: &str,
+        index_type: &str,
+        m: Option<u32>,
+        ef_construction: Option<u32>,
+        ef_search: Option<u32>,
+        name: Option<&str>,
+        if_not_exists: bool,
+    ) -> PyResult<String> {
+        vector::distance_function(metric)?;
+        if !index_type.eq_ignore_ascii_case("hnsw") {
+            return Err(PyValueError::new_err(format!(
+                "index_type must be 'hnsw', got '{index_type}'"
+            )));
+        }
+        if m.is_some_and(|m| !(2..=256).contains(&m)) {
+            return Err(PyValueError::new_err("m must be between 2 and 256"));
+        }
+        if ef_construction.is_some_and(|ef| ef < m.unwrap_or(1).max(1)) {
+            return Err(PyValueError::new_err("ef_construction must be at least m"));
+        }
+        if ef_search == Some(0) {
+            return Err(PyValueError::new_err("ef_search must be at least 1"));
+        }
+
+        let name = name.map_or_else(|| format!("idx_{table}_{column}"), str::to_string);
+        let mut options = vec![format!("metric = '{}'", metric.to_ascii_lowercase())];
+        let settings = [("m", m), ("ef_construction", ef_construction), ("ef_search", ef_search)];
+        for (key, value) in settings {
+            if let Some(value) = value {
+                options.push(format!("{key} = {value}"));
+            }
+        }
+        let sql = format!(
+            "CREATE INDEX {}{} ON {}({}) USING HNSW WITH ({})",
+            if if_not_exists { "IF NOT EXISTS " } else { "" },
+            sql::quote_ident(&name)?,
+            sql::quote_ident(table)?,
+            sql::quote_ident(column)?,
+            options.join(", "),
+        );
+        self.execute(py, &sql, None, None)?;
+        Ok(name)
+    }
+
+    /// Settings of the vector index on `column` of `table`, or None when the
+    /// column has none.
+    ///
+    /// Returns a dict with `name`, `table`, `column`, `index_type`, and the
+    /// `metric`, `m`, `ef_construction` and `ef_search` the engine built the
+    /// index with, including the defaults it chose for omitted settings.
+    fn vector_index_info(&self, py: Python<'_>, table: &str, column: &str) -> PyResult<PyObject> {
+        for info in self.vector_indexes(py, table)? {
+            if info.get_item("column")?.is_some_and(|c| c.eq(column).unwrap_or(false)) {
+                return Ok(info.into_any().unbind());
+            }
+        }
+        Ok(py.None())
+    }
+
+    /// The VECTOR columns of `table`, or of every table when omitted.
+    ///
+    /// Returns one dict per column with `table`, `column`, `dimensions`,
+    /// `indexed`, and the `index` name and `metric` of its vector index
+    /// (None without one), for checking an embedding pipeline against the
+    /// schema at startup.
+    #[pyo3(signature = (table=None))]
+    fn vector_columns(&self, py: Python<'_>, table: Option<&str>) -> PyResult<PyObject> {
+        let tables = match table {
+            Some(name) => vec![name.to_string()],
+            None => self.tables(py)?,
+        };
+        let db = self.conn.db()?;
+        let result = PyList::empty(py);
+        for table in &tables {
+            let indexes = self.vector_indexes(py, table)?;
+            // DESCRIBE reports a bare "Vector" type, so the dimensions come
+            // from the table's schema
+            let schema = db
+                .engine()
+                .get_version_store(table)
+                .map_err(to_py)?
+                .schema();
+            let columns: Vec<(String, u16)> = schema
+                .columns
+                .iter()
+                .filter(|c| c.vector_dimensions > 0)
+                .map(|c| (c.name.clone(), c.vector_dimensions))
+                .collect();
+            for (name, dimensions) in columns {
+                let index = indexes.iter().find(|info| {
+                    info.get_item("column")
+                        .ok()
+                        .flatten()
+                        .is_some_and(|c| c.eq(&name).unwrap_or(false))
+                });
+                let info = PyDict::new(py);
+                info.set_item("table", table)?;
+                info.set_item("column", name)?;
+                info.set_item("dimensions", dimensions)?;
+                info.set_item("indexed", index.is_some())?;
+                match index {
+                    Some(index) => {
+                        info.set_item("index", index.get_item("name")?)?;
+                        info.set_item("metric", index.get_item("metric")?)?;
+                    }
+                    None => {
+                        info.set_item("index", py.None())?;
+                        info.set_item("metric", py.None())?;
+                    }
+                }
+                result.append(info)?;
+            }
+        }
+        Ok(result.into_any().unbind())
+    }
+
+    /// Compare the schema of `other` against this database; see
+    /// `stoolap.schema_diff()`.
+    #[pyo3(signature = (other, *, ddl=false))]
+    fn diff_schema<'py>(
+        slf: &Bound<'py, Self>,
+        other: &Bound<'py, Database>,
+        ddl: bool,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        schema::schema_diff(slf.py(), slf, other, ddl)
+    }
+
+    /// Suggest indexes for one query or a workload of SQL strings and
+    /// `(sql, params)` pairs, from the full table scans in their plans;
+    /// see `advisor::suggest_indexes`. Each suggestion is a dict with
+    /// `table`, `columns`, `ddl`, `estimated_benefit` and `queries`.
+    fn suggest_indexes<'py>(
+        slf: &Bound<'py, Self>,
+        sql_or_workload: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyList>> {
+        advisor::suggest_indexes(slf, sql_or_workload)
+    }
+
+    /// Give every statement this thread runs in a `with` block a deadline:
+    ///
+    /// ```python
+    /// with db.time_limit(2.0):
+    ///     rows = db.query("SELECT * FROM events")
+    /// ```
+    ///
+    /// Statements through the database, its transactions and its prepared
+    /// statements are covered. One that would start past the deadline, or
+    /// whose rows are still being converted when it passes, raises
+    /// QueryTimeout (a QueryCancelled). The time left is the engine's
+    /// timeout for the statement, which the engine checks while it scans a
+    /// table and for each row of a correlated subquery; work it does in one
+    /// step, such as a join, a sort or a write, finishes first. Other
+    /// threads are not affected.
+    fn time_limit(&self, seconds: f64) -> PyResult<TimeLimit> {
+        TimeLimit::new(self.conn.clone(), seconds)
+    }
+
+    /// Take a read-only snapshot pinned to the current committed state.
+    fn snapshot(&self, py: Python<'_>) -> PyResult<Snapshot> {
+        let db = self.conn.db()?;
+        let tx = py.allow_threads(|| {
+            db.begin_with_isolation(IsolationLevel::SnapshotIsolation)
+                .map_err(to_py)
+        })?;
+        Ok(Snapshot::new(Arc::clone(&self.conn), tx))
+    }
+
+    /// Force a checkpoint, persisting the WAL and pending writes to disk.
+    ///
+    /// Returns once the data is durable. No-op for in-memory databases.
+    fn checkpoint(&self, py: Python<'_>) -> PyResult<()> {
+        self.conn.checkpoint(py)
+    }
+
+    /// Set how often the WAL is folded into the main store: every `seconds`
+    /// by the engine (the `checkpoint_interval` DSN option), and with
+    /// `wal_bytes`, also after any commit that leaves the WAL at least that
+    /// large. `wal_bytes=0` turns the size trigger off; omitted values keep
+    /// their setting. Ignored by in-memory databases, which have no WAL.
+    #[pyo3(signature = (seconds=None, *, wal_bytes=None))]
+    fn set_checkpoint_interval(
+        &self,
+        py: Python<'_>,
+        seconds: Option<u64>,
+        wal_bytes: Option<u64>,
+    ) -> PyResult<()> {
+        if seconds == Some(0) {
+            return Err(PyValueError::new_err("seconds must be at least 1"));
+        }
+        let (current_seconds, current_bytes) = self.conn.checkpoint_policy();
+        if let Some(seconds) = seconds.filter(|_| !self.is_memory()) {
+            self.conn
+                .run_internal(py, &format!("PRAGMA checkpoint_interval = {seconds}"))?;
+        }
+        self.conn.set_checkpoint_policy(
+            seconds.unwrap_or(current_seconds),
+            wal_bytes.unwrap_or(current_bytes),
+        );
+        Ok(())
+    }
+
+    /// Checkpoint settings and progress.
+    ///
+    /// Returns a dict with `interval_seconds`, `wal_bytes_threshold` (None
+    /// when off), `wal_bytes` (the WAL's current size), `checkpoints` (run
+    /// through this handle: `checkpoint()`, `vacuum()` and the size
+    /// trigger, not the engine's own cycles) and
+    /// `seconds_since_checkpoint` (None before the first).
+    fn checkpoint_status(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (interval, bytes) = self.conn.checkpoint_policy();
+        let (checkpoints, since) = self.conn.checkpoints();
+        let wal_bytes = match self.data_dir() {
+            Some(dir) => py.allow_threads(|| dir_size(&dir.join("wal"))),
+            None => 0,
+        };
+        let result = PyDict::new(py);
+        result.set_item("interval_seconds", interval)?;
+        result.set_item("wal_bytes_threshold", (bytes > 0).then_some(bytes))?;
+        result.set_item("wal_bytes", wal_bytes)?;
+        result.set_item("checkpoints", checkpoints)?;
+        result.set_item("seconds_since_checkpoint", since.map(|d| d.as_secs_f64()))?;
+        Ok(result.into_any().unbind())
+    }
+
+    /// Flush pending writes to disk. Same as `checkpoint()`.
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        self.checkpoint(py)
+    }
+
+    /// Disk space taken by the database, to alert before a device fills up.
+    ///
+    /// Returns a dict with `total_bytes` (every file of the database),
+    /// `wal_bytes`, `tables` (bytes per table, None for a table with no files
+    /// of its own yet) and `free_bytes`, the space left on the filesystem.
+    /// The engine keeps tables in volumes rather than pages, so there is no
+    /// count of free pages: space held by deleted rows is what `vacuum()`
+    /// reclaims. In-memory databases report 0 bytes and no `free_bytes`.
+    fn disk_usage(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let names = self.tables(py)?;
+        let result = PyDict::new(py);
+        let tables = PyDict::new(py);
+        let Some(dir) = self.data_dir() else {
+            for name in names {
+                tables.set_item(name, 0)?;
+            }
+            result.set_item("total_bytes", 0)?;
+            result.set_item("wal_bytes", 0)?;
+            result.set_item("free_bytes", py.None())?;
+            result.set_item("tables", tables)?;
+            return Ok(result.into_any().unbind());
+        };
+        let (total, wal, sizes) = py.allow_threads(|| {
+            let sizes: Vec<Option<u64>> = names.iter().map(|name| table_size(&dir, name)).collect();
+            (dir_size(&dir), dir_size(&dir.join("wal")), sizes)
+        });
+        let free: u64 = py
+            .import("shutil")?
+            .call_method1("disk_usage", (dir.to_string_lossy().as_ref(),))?
+            .getattr("free")?
+            .extract()?;
+        for (name, size) in names.iter().zip(sizes) {
+            tables.set_item(name, size)?;
+        }
+        result.set_item("total_bytes", total)?;
+        result.set_item("wal_bytes", wal)?;
+        result.set_item("free_bytes", free)?;
+        result.set_item("tables", tables)?;
+        Ok(result.into_any().unbind())
+    }
+
+    /// Turn the merging of small volumes at checkpoints on or off, and set
+    /// `threshold`, how many sub-target volumes a table collects before
+    /// they are merged (the `compact_threshold` DSN option).
+    ///
+    /// Turned off, checkpoints still seal rows and truncate the WAL but
+    /// leave volumes unmerged, so a latency-sensitive application can run
+    /// `compact()` or `vacuum()` off-peak instead. The engine has no switch
+    /// of its own, so this raises its threshold out of reach.
+    #[pyo3(signature = (enabled, threshold=None))]
+    fn set_auto_compaction(
+        &self,
+        py: Python<'_>,
+        enabled: bool,
+        threshold: Option<u32>,
+    ) -> PyResult<()> {
+        if threshold == Some(0) {
+            return Err(PyValueError::new_err("threshold must be at least 1"));
+        }
+        let threshold = threshold.unwrap_or(self.conn.compaction().1);
+        if !self.is_memory() {
+            let effective = if enabled { threshold } else { COMPACTION_OFF };
+            self.conn
+                .run_internal(py, &format!("PRAGMA compact_threshold = {effective}"))?;
+        }
+        self.conn.set_compaction(enabled, threshold);
+        Ok(())
+    }
+
+    /// Background compaction settings and what is waiting for it.
+    ///
+    /// Returns a dict with `enabled`, `threshold`, `volumes` (files per
+    /// table on disk) and `pending`, the tables with at least `threshold`
+    /// of them, which the next compaction would merge. In-memory databases
+    /// have no volumes.
+    fn compaction_status(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let (enabled, threshold) = self.conn.compaction();
+        let names = self.tables(py)?;
+        let counts: Vec<usize> = match self.data_dir() {
+            Some(dir) => {
+                py.allow_threads(|| names.iter().map(|name| table_files(&dir, name)).collect())
+            }
+            None => vec![0; names.len()],
+        };
+        let volumes = PyDict::new(py);
+        let mut pending = Vec::new();
+        for (name, count) in names.iter().zip(counts) {
+            volumes.set_item(name, count)?;
+            if count >= threshold as usize {
+                pending.push(name);
+            }
+        }
+        let result = PyDict::new(py);
+        result.set_item("enabled", enabled)?;
+        result.set_item("threshold", threshold)?;
+        result.set_item("volumes", volumes)?;
+        result.set_item("pending", pending)?;
+        Ok(result.into_any().unbind())
+    }
+
+    /// Reclaim space from deleted rows and old row versions.
+    ///
+    /// Vacuums every table, or only `table` when given. Returns the number of
+    /// bytes reclaimed on disk (always 0 for in-memory databases).
+    #[pyo3(signature = (table=None))]
+    fn vacuum(&self, py: Python<'_>, table: Option<&str>) -> PyResult<u64> {
+        let sql = match table {
+            Some(name) => format!("VACUUM {}", sql::quote_ident(name)?),
+            None => "VACUUM".to_string(),
+        };
+        let Some(dir) = self.data_dir() else {
+            self.conn.run_internal(py, &sql)?;
+            return Ok(0);
+        };
+        let before = py.allow_threads(|| dir_size(&dir));
+        self.conn.run_internal(py, &sql)?;
+        // Rewrite the cold volumes so the reclaimed space shows up on disk
+        self.conn.checkpoint(py)?;
+        let after = py.allow_threads(|| dir_size(&dir));
+        Ok(before.saturating_sub(after))
+    }
+
+    /// Rewrite the storage of a single table. Same as `vacuum(table)`.
+    fn compact(&self, py: Python<'_>, table: &str) -> PyResult<u64> {
+        self.vacuum(py, Some(table))
+    }
+
+    /// Refresh planner statistics for `table`, or for every table when omitted.
+    #[pyo3(signature = (table=None))]
+    fn analyze(&self, py: Python<'_>, table: Option<&str>) -> PyResult<()> {
+        let db = self.conn.db()?;
+        let tables = match table {
+            Some(name) => {
+                // The engine only logs a warning for a missing table
+                if !py.allow_threads(|| db.table_exists(name)).map_err(to_py)? {
+                    return Err(to_py(stoolap::Error::TableNotFound(name.to_string())));
+                }
+                vec![name.to_string()]
+            }
+            None => py.allow_threads(|| table_names(&db).map_err(to_py))?,
+        };
+        for name in &tables {
+            self.conn
+                .run_internal(py, &format!("ANALYZE {}", sql::quote_ident(name)?))?;
+        }
+        Ok(())
+    }
+
+    /// Run the recommended maintenance after large data changes.
+    ///
+    /// Refreshes statistics for every table, reclaims space from deleted rows
+    /// and, for file databases, checkpoints the result to disk.
+    fn optimize(&self, py: Python<'_>) -> PyResult<()> {
+        self.analyze(py, None)?;
+        self.vacuum(py, None)?;
+        Ok(())
+    }
+
+    /// Verify that every table and index can be read back consistently.
+    ///
+    /// Scans each table in full and cross-checks the scanned row count against
+    /// `COUNT(*)` (which is answered from volume statistics), then looks the
+    /// indexed values of every row up through each of the table's indexes so
+    /// that rows missing from an index are reported. Returns a list of
+    /// `{"table": ..., "problem": ...}` dicts, empty when the database is healthy.
+    fn integrity_check(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let db = self.conn.db()?;
+        let problems = py.allow_threads(|| -> PyResult<Vec<(String, String)>> {
+            let mut problems = Vec::new();
+            for table in table_names(&db).map_err(to_py)? {
+                if let Err(problem) = check_table(&db, &table) {
+                    problems.push((table, problem));
+                }
+            }
+            Ok(problems)
+        })?;
+
+        let result = PyList::empty(py);
+        for (table, problem) in problems {
+            let dict = PyDict::new(py);
+            dict.set_item("table", table)?;
+            dict.set_item("problem", problem)?;
+            result.append(dict)?;
+        }
+        Ok(result.into_any().unbind())
+    }
+
+    /// Engine counters for monitoring.
+    ///
+    /// Returns a dict with `queries`, `statements` (writes), `rows_read`,
+    /// `rows_written`, `transactions`, `commits`, `rollbacks`,
+    /// `active_transactions`, `wal_bytes` (0 for in-memory databases),
+    /// `cache_hits` and `cache_misses` for the result cache, and
+    /// `query_cache_hits`, `query_cache_misses` and `query_cache_hit_ratio`
+    /// (0.0 before the first lookup) for the engine's query cache.
+    /// Counters cover everything run through this handle since it was opened,
+    /// except the query cache ones, which the engine keeps for the database.
+    fn stats(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let stats = &self.conn.stats;
+        let wal_bytes = self
+            .data_dir()
+            .map(|dir| dir_size(&dir.join("wal")))
+            .unwrap_or(0);
+
+        let dict = PyDict::new(py);
+        dict.set_item("queries", stats.queries.load(Ordering::Relaxed))?;
+        dict.set_item("statements", stats.statements.load(Ordering::Relaxed))?;
+        dict.set_item("rows_read", stats.rows_read.load(Ordering::Relaxed))?;
+        dict.set_item("rows_written", stats.rows_written.load(Ordering::Relaxed))?;
+        dict.set_item("transactions", stats.transactions.load(Ordering::Relaxed))?;
+        dict.set_item("commits", stats.commits.load(Ordering::Relaxed))?;
+        dict.set_item("rollbacks", stats.rollbacks.load(Ordering::Relaxed))?;
+        dict.set_item(
+            "active_transactions",
+            stats.active_transactions.load(Ordering::Relaxed),
+        )?;
+        dict.set_item("wal_bytes", wal_bytes)?;
+        dict.set_item("cache_hits", self.conn.cache.hits.load(Ordering::Relaxed))?;
+        dict.set_item("cache_misses", self.conn.cache.misses.load(Ordering::Relaxed))?;
+        let db = self.conn.db()?;
+        let cache = py.allow_threads(|| db.semantic_cache_stats()).map_err(to_py)?;
+        let lookups = cache.hits + cache.misses;
+        dict.set_item("query_cache_hits", cache.hits)?;
+        dict.set_item("query_cache_misses", cache.misses)?;
+        dict.set_item(
+            "query_cache_hit_ratio",
+            if lookups == 0 { 0.0 } else { cache.hits as f64 / lookups as f64 },
+        )?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Cache the results of `query()` and `query_raw()`, keyed by SQL and
+    /// parameters, using up to `max_bytes` of memory.
+    ///
+    /// Writes made through this database, its transactions and prepared
+    /// statements drop the cached results of queries that name the written
+    /// table; DDL and other statements the binding cannot attribute to a
+    /// table clear the whole cache. Writes from other processes are not
+    /// seen, so only enable it when this handle is the only writer.
+    fn enable_result_cache(&self, max_bytes: usize) {
+        self.conn.cache.configure(Some(max_bytes));
+    }
+
+    /// Turn the result cache off and release its memory.
+    fn disable_result_cache(&self) {
+        self.conn.cache.configure(None);
+    }
+
+    /// Start capturing committed writes for `wal_reader()`, keeping the
+    /// last `max_records` records in memory.
+    ///
+    /// Each committed transaction or autocommit statement becomes one
+    /// record of its write statements, with bound values rendered as SQL
+    /// literals, numbered from LSN 1. The engine's own WAL is not exposed,
+    /// so the log has these limits:
+    /// - only writes made through this handle are captured, not those of
+    ///   other handles or processes on the same database;
+    /// - records are kept in memory and lost when the handle is closed or
+    ///   the process restarts;
+    /// - statements are shipped rather than row images, so ones whose
+    ///   result depends on when or where they run, such as `NOW()` or an
+    ///   auto-increment key left to the engine, may differ on a replica.
+    ///
+    /// A write whose values cannot be rendered as literals raises before it
+    /// runs.
+    #[pyo3(signature = (max_records=100_000))]
+    fn enable_wal_capture(&self, max_records: usize) -> PyResult<()> {
+        if max_records == 0 {
+            return Err(PyValueError::new_err("max_records must be at least 1"));
+        }
+        self.conn.hooks.wal.configure(Some(max_records));
+        Ok(())
+    }
+
+    /// Stop capturing writes and drop the captured records.
+    fn disable_wal_capture(&self) {
+        self.conn.hooks.wal.configure(None);
+    }
+
+    /// Iterate over the captured WAL records from LSN `from_lsn` on, each
+    /// as bytes for `apply_wal()` on a replica.
+    ///
+    /// Raises StoolapError if capture is off or the records from `from_lsn`
+    /// were already dropped to respect `max_records`.
+    #[pyo3(signature = (from_lsn=1))]
+    fn wal_reader(&self, from_lsn: u64) -> PyResult<WalReader> {
+        self.conn.hooks.wal.read(from_lsn)?;
+        Ok(WalReader::new(Arc::clone(&self.conn), from_lsn))
+    }
+
+    /// Replay WAL records from `wal_reader()` in order, each in its own
+    /// transaction. Returns the LSN of the last record applied, or None
+    /// for no records. A failing record is rolled back and raises, leaving
+    /// the records before it applied.
+    fn apply_wal(&self, py: Python<'_>, records: &Bound<'_, PyAny>) -> PyResult<Option<u64>> {
+        let mut last = None;
+        for record in records.try_iter()? {
+            let (lsn, statements) = wal::decode(&record?)?;
+            let tx = self.begin(py, "deferred")?;
+            let applied = statements
+                .iter()
+                .try_for_each(|stmt| {
+                    let bind = BindParams::Positional(ParamVec::new());
+                    tx.exec_statement(py, stmt, bind).map(drop)
+                })
+                .and_then(|_| tx.commit(py));
+            if let Err(err) = applied {
+                if tx.is_active() {
+                    let _ = tx.rollback(py);
+                }
+                return Err(err);
+            }
+            last = Some(lsn);
+        }
+        Ok(last)
+    }
+
+    /// Call `handler(sql, duration_ms, rows)` whenever a statement takes at
+    /// least `threshold_ms` milliseconds.
+    ///
+    /// `rows` is the number of rows returned or affected, or None if the
+    /// statement failed. Covers statements run through this database and its
+    /// transactions and prepared statements. Pass `None` to remove the handler.
+    #[pyo3(signature = (threshold_ms, handler))]
+    fn set_slow_query_handler(
+        &self,
+        threshold_ms: f64,
+        handler: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        if !threshold_ms.is_finite() || threshold_ms < 0.0 {
+            return Err(PyValueError::new_err(
+                "threshold_ms must be a non-negative number",
+            ));
+        }
+        let callback = optional_callable(handler, "handler")?;
+        let threshold = Duration::from_secs_f64(threshold_ms / 1000.0);
+        self.conn.hooks.set_slow_query(threshold, callback);
+        Ok(())
+    }
+
+    /// Trace every executed statement to a callable or a `logging.Logger`.
+    ///
+    /// A callable is invoked as `target(sql, duration_ms, params)`; a logger
+    /// receives one record per statement at `level` (DEBUG by default).
+    /// `params` is a short summary of the bound parameters when
+    /// `include_params=True`, otherwise None. Pass `None` to stop tracing;
+    /// while tracing is off statements are not timed at all.
+    #[pyo3(signature = (target, *, include_params=false, level=10))]
+    fn set_trace(
+        &self,
+        py: Python<'_>,
+        target: Option<&Bound<'_, PyAny>>,
+        include_params: bool,
+        level: i32,
+    ) -> PyResult<()> {
+        let target = match target {
+            Some(t) if !t.is_none() => {
+                let logger_type = py.import("logging")?.getattr("Logger")?;
+                if t.is_instance(&logger_type)? {
+                    Some(TraceTarget::Logger {
+                        logger: t.clone().unbind(),
+                        level,
+                    })
+                } else if t.is_callable() {
+                    Some(TraceTarget::Callable(t.clone().unbind()))
+                } else {
+                    return Err(PyTypeError::new_err(
+                        "trace target must be a callable or a logging.Logger",
+                    ));
+                }
+            }
+            _ => None,
+        };
+        self.conn.hooks.set_trace(target, include_params);
+        Ok(())
+    }
+
+    /// Register listeners called around every statement, e.g. to create
+    /// OpenTelemetry spans.
+    ///
+    /// `on_start(event)` runs before the statement with `event` holding `sql`,
+    /// `param_count` and `batch_size` (None unless a batch). Its return value
+    /// is passed as `context` to `on_end(event, context)`, which runs afterwards
+    /// with `duration_ms`, `rows` and `error` (the exception, or None) added.
+    /// Either callback may be None. Returns an id for `remove_listener()`.
+    #[pyo3(signature = (on_start=None, on_end=None))]
+    fn add_listener(
+        &self,
+        on_start: Option<&Bound<'_, PyAny>>,
+        on_end: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<u64> {
+        let on_start = optional_callable(on_start, "on_start")?;
+        let on_end = optional_callable(on_end, "on_end")?;
+        if on_start.is_none() && on_end.is_none() {
+            return Err(PyValueError::new_err(
+                "at least one of on_start or on_end is required",
+            ));
+        }
+        Ok(self.conn.hooks.add_listener(on_start, on_end))
+    }
+
+    /// Remove a listener registered with `add_listener()`.
+    ///
+    /// Returns False if no listener has that id.
+    fn remove_listener(&self, listener_id: u64) -> bool {
+        self.conn.hooks.remove_listener(listener_id)
+    }
+
+    /// Call `callback(operation, table, key)` after committed writes to `table`.
+    ///
+    /// `operation` is "insert", "update" or "delete". Writes inside a
+    /// transaction are reported once it commits and dropped on rollback.
+    /// Changes are tracked per statement: `key` is the primary key of the
+    /// written row when the statement names a single one (a one-row INSERT,
+    /// or `WHERE id = value`), and None otherwise. Returns an id for
+    /// `unwatch()`.
+    fn watch(&self, table: &str, callback: &Bound<'_, PyAny>) -> PyResult<u64> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("callback must be callable"));
+        }
+        Ok(self.conn.hooks.watch(table, callback.clone().unbind()))
+    }
+
+    /// Remove a watch registered with `watch()`.
+    ///
+    /// Returns False if no watch has that id.
+    fn unwatch(&self, watch_id: u64) -> bool {
+        self.conn.hooks.unwatch(watch_id)
+    }
+
+    /// Install an audit hook called as `hook(event)` before every statement.
+    ///
+    /// `event` holds `sql`, `param_count` and `batch_size`. The hook sees every
+    /// statement run through this database, its transactions and prepared
+    /// statements (each statement of `exec()`, each batch) as well as the
+    /// maintenance commands issued by `checkpoint()`, `vacuum()` and
+    /// `analyze()`. If the hook raises, the statement is not executed and the
+    /// exception propagates to the caller. Pass `None` to remove it.
+    #[pyo3(signature = (hook))]
+    fn set_audit_hook(&self, hook: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let callback = optional_callable(hook, "hook")?;
+        self.conn.hooks.set_audit(callback);
+        Ok(())
+    }
+
+    /// Install a hook called as `hook()` after every commit: explicit
+    /// transactions as well as autocommit writes (`execute()`, `exec()`,
+    /// `execute_batch()`). Exceptions are reported as unraisable.
+    /// Pass `None` to remove it.
+    #[pyo3(signature = (hook))]
+    fn set_commit_hook(&self, hook: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let callback = optional_callable(hook, "hook")?;
+        self.conn.hooks.set_commit(callback);
+        Ok(())
+    }
+
+    /// Install a hook called as `hook()` after every rollback: explicit or
+    /// on context-manager exceptions, abandoned transactions, and failed
+    /// autocommit writes. Pass `None` to remove it.
+    #[pyo3(signature = (hook))]
+    fn set_rollback_hook(&self, hook: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let callback = optional_callable(hook, "hook")?;
+        self.conn.hooks.set_rollback(callback);
+        Ok(())
+    }
+
+    /// Id of the most recent transaction. Pass it as `as_of=` to later
+    /// queries to see the database as it is now.
+    fn current_snapshot_id(&self, py: Python<'_>) -> PyResult<i64> {
+        let db = self.conn.db()?;
+        py.allow_threads(|| {
+            let mut tx = db.begin().map_err(to_py)?;
+            let id = tx.id();
+            tx.rollback().map_err(to_py)?;
+            Ok(id)
+        })
+    }
+
+    /// Check that the handle is usable, for health endpoints and pool
+    /// validation: the directory of a file database must still be readable
+    /// and the engine must answer a trivial query. Returns how long the
+    /// check took in milliseconds, or raises StoolapError. Pings are not
+    /// counted in `stats()`.
+    fn ping(&self, py: Python<'_>) -> PyResult<f64> {
+        let start = Instant::now();
+        // A closed engine still evaluates queries without tables
+        if !self.conn.db()?.engine().is_open() {
+            return Err(crate::error::StoolapError::new_err("database is closed"));
+        }
+        if let Some(dir) = self.data_dir() {
+            py.allow_threads(|| std::fs::read_dir(&dir)).map_err(|e| {
+                crate::error::StoolapError::new_err(format!(
+                    "database directory '{}' is not accessible: {e}",
+                    dir.display()
+                ))
+            })?;
+        }
+        self.fetch(py, "SELECT 1")?;
+        Ok(start.elapsed().as_secs_f64() * 1000.0)
+    }
+
+    /// Close the database connection.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        let db = self.conn.db()?;
+        py.allow_threads(|| db.close().map_err(to_py))
+    }
+
+    /// Close the engine and open it again with the original DSN, to
+    /// recover from file-level errors without recreating the objects that
+    /// depend on this handle.
+    ///
+    /// Settings held by the binding carry over: hooks, listeners, mapped
+    /// classes, the paramstyle and type options, and the journal mode,
+    /// compaction and checkpoint settings, which are applied to the new
+    /// engine. Prepared statements stay usable. The result cache is
+    /// emptied. Raises StoolapError while a transaction or snapshot is
+    /// open, and for in-memory databases, whose data would be lost.
+    fn reopen(&self, py: Python<'_>) -> PyResult<()> {
+        if self.is_memory() {
+            return Err(crate::error::StoolapError::new_err(
+                "cannot reopen an in-memory database: its data lives only in the engine",
+            ));
+        }
+        self.conn.reopen(py)?;
+        let (enabled, threshold) = self.conn.compaction();
+        let threshold = if enabled { threshold } else { COMPACTION_OFF };
+        self.conn
+            .run_internal(py, &format!("PRAGMA compact_threshold = {threshold}"))?;
+        let (seconds, _) = self.conn.checkpoint_policy();
+        self.conn
+            .run_internal(py, &format!("PRAGMA checkpoint_interval = {seconds}"))?;
+        Ok(())
+    }
+
+    /// Pickle as a connection descriptor: unpickling calls
+    /// `Database.open(dsn, ...)`, e.g. in a `multiprocessing` worker. The
+    /// options of `open()` that differ from their defaults are carried
+    /// over with their current values, and so is the journal mode.
+    fn __reduce__<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<(Bound<'py, PyAny>, (String,))> {
+        if self.is_memory() {
+            return Err(PyTypeError::new_err(
+                "cannot pickle an in-memory Database: its data lives only in this process",
+            ));
+        }
+        let fetch = &self.conn.fetch;
+        let kwargs = PyDict::new(py);
+        let paramstyle = self.conn.paramstyle();
+        if paramstyle != "numeric_dollar" {
+            kwargs.set_item("paramstyle", paramstyle)?;
+        }
+        let flags = [
+            ("strict_types", self.conn.binding.is_strict()),
+            ("reject_naive", self.conn.binding.rejects_naive()),
+            ("naive_timestamps", fetch.is_naive()),
+            ("nanosecond_timestamps", fetch.in_nanoseconds()),
+            ("midnight_as_date", fetch.midnight_as_date()),
+        ];
+        for (name, value) in flags {
+            if value {
+                kwargs.set_item(name, true)?;
+            }
+        }
+        let tz = fetch.tz(py)?;
+        if !tz.is_none(py) {
+            kwargs.set_item("tz", tz)?;
+        }
+        if fetch.detect_types() != 0 {
+            kwargs.set_item("detect_types", fetch.detect_types())?;
+        }
+        if let Some(threads) = self.conn.threads_option() {
+            kwargs.set_item("threads", threads)?;
+        }
+        let level = connection::journal_level(self.conn.journal_mode())?;
+        let dsn = if connection::sync_mode_of(&self.conn.dsn).unwrap_or(1) == level {
+            self.conn.dsn.clone()
+        } else {
+            connection::with_sync_mode(&self.conn.dsn, level)
+        };
+
+        let mut open = py.get_type::<Database>().getattr("open")?;
+        if !kwargs.is_empty() {
+            let partial = py.import("functools")?.getattr("partial")?;
+            open = partial.call((open,), Some(&kwargs))?;
+        }
+        Ok((open, (dsn,)))
+    }
+
+    /// Placeholder style of the SQL passed to this database and the
+    /// transactions, snapshots and prepared statements created from it:
+    /// - "numeric_dollar" (default): `$1` and `:name`, plus `?` numbered in order
+    /// - "pyformat": `%s` numbered in order and `%(name)s`, with `%%` for a
+    ///   literal `%`, as in psycopg and MySQLdb
+    ///
+    /// Prepared statements keep the style they were prepared with.
+    #[getter]
+    fn paramstyle(&self) -> &'static str {
+        self.conn.paramstyle()
+    }
+
+    /// Whether parameters need explicit wrapper types; see `open()`.
+    #[getter]
+    fn strict_types(&self) -> bool {
+        self.conn.binding.is_strict()
+    }
+
+    #[setter]
+    fn set_strict_types(&self, strict: bool) {
+        self.conn.binding.set_strict(strict);
+    }
+
+    /// Whether naive datetimes are rejected as parameters; see `open()`.
+    #[getter]
+    fn reject_naive(&self) -> bool {
+        self.conn.binding.rejects_naive()
+    }
+
+    #[setter]
+    fn set_reject_naive(&self, reject: bool) {
+        self.conn.binding.set_reject_naive(reject);
+    }
+
+    /// Set a session variable, which SQL run through this handle reads with
+    /// `current_setting('name')`, e.g. for row-level filtering:
+    ///
+    /// ```python
+    /// db.set_var("tenant_id", 42)
+    /// db.query("SELECT * FROM orders WHERE tenant_id = current_setting('tenant_id')")
+    /// ```
+    ///
+    /// The call is replaced by the value as a literal of its own type, so
+    /// 42 compares as an INTEGER. Names are case-insensitive, and None
+    /// removes the variable. `current_setting()` of an unknown variable
+    /// raises StoolapError, and `current_setting('name', true)` is NULL
+    /// instead. Prepared statements keep the values they were prepared with.
+    fn set_var(&self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        if name.is_empty() {
+            return Err(PyValueError::new_err("variable name must not be empty"));
+        }
+        if value.is_none() {
+            self.conn.set_var(name, None);
+            return Ok(());
+        }
+        let literal = sql::literal(&self.conn.binding.value(value)?);
+        self.conn.set_var(name, Some((value.clone().unbind(), literal)));
+        Ok(())
+    }
+
+    /// The value of a session variable, or None when it is not set.
+    fn get_var(&self, py: Python<'_>, name: &str) -> Option<PyObject> {
+        self.conn.var(py, name)
+    }
+
+    /// The value of a named setting. See `set_setting()` for the names.
+    fn get_setting<'py>(slf: &Bound<'py, Self>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        settings::get(slf, name)
+    }
+
+    /// Change a named setting of this handle, validated like the method or
+    /// property behind it:
+    /// - "journal_mode", "synchronous": commit durability
+    /// - "checkpoint_interval": seconds between checkpoints
+    /// - "checkpoint_wal_bytes": WAL size that triggers a checkpoint after a
+    ///   commit, None for none
+    /// - "auto_compaction", "compact_threshold": background compaction
+    /// - "paramstyle", "strict_types", "reject_naive": parameter binding
+    ///
+    /// An unknown name raises ValueError listing the known ones.
+    fn set_setting(
+        slf: &Bound<'_, Self>,
+        name: &str,
+        value: &Bound<'_, PyAny>,
+    ) -> PyResult<()> {
+        settings::set(slf, name, value)
+    }
+
+    /// The timezone TIMESTAMP values are returned in, as given to `open()`,
+    /// or None for UTC.
+    #[getter]
+    fn tz(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.conn.fetch.tz(py)
+    }
+
+    /// The `detect_types` flags given to `open()`.
+    #[getter]
+    fn detect_types(&self) -> u8 {
+        self.conn.fetch.detect_types()
+    }
+
+    #[setter]
+    fn set_paramstyle(&self, style: &str) -> PyResult<()> {
+        self.conn.set_paramstyle(style)
+    }
+
+    /// How commits are made durable, from the `durability` option of
+    /// `open()` or the last `set_journal_mode()`:
+    /// - "none": the WAL is written but never synced, fastest
+    /// - "normal" (default): the WAL is synced in batches, so a crash may
+    ///   lose the last moments of commits but never corrupts the database
+    /// - "full": the WAL is synced on every commit
+    #[getter]
+    fn journal_mode(&self) -> &'static str {
+        self.conn.journal_mode()
+    }
+
+    /// Change the journal mode of the open database; see `journal_mode`.
+    ///
+    /// The engine only takes a sync mode when opened, so a file database
+    /// is reopened with the new mode, as by `reopen()`, and this raises
+    /// StoolapError while a transaction or snapshot is open.
+    fn set_journal_mode(&self, py: Python<'_>, mode: &str) -> PyResult<()> {
+        self.set_sync_level(py, connection::journal_level(mode)?)
+    }
+
+    /// Number of threads the engine's parallel operators (scans, joins,
+    /// sorts and aggregations over large tables) run on.
+    #[getter]
+    fn threads(&self) -> usize {
+        self.conn.threads()
+    }
+
+    /// Run the engine's parallel operators for this handle on `threads`
+    /// threads, or on the process-wide pool of one thread per CPU for
+    /// None (the default). Batch jobs can leave it unset to use every
+    /// core; an interactive app can cap it, down to 1 for serial plans.
+    #[pyo3(signature = (threads=None))]
+    fn set_threads(&self, threads: Option<usize>) -> PyResult<()> {
+        self.conn.set_threads(threads)
+    }
+
+    /// Fsync policy under SQLite's names: "off", "normal" (default) or
+    /// "full", the same setting as `journal_mode` ("off" is "none").
+    ///
+    /// Bulk loaders can set it to "off" for the load and back afterwards.
+    /// Raising the policy flushes first, so the rows written while it was
+    /// lower are on disk once the assignment returns; call `flush()` to do
+    /// so without changing the policy. Like `set_journal_mode()`, a change
+    /// reopens a file database, since the engine does not take a new sync
+    /// mode while open.
+    #[getter]
+    fn synchronous(&self) -> &'static str {
+        match self.conn.journal_mode() {
+            "none" => "off",
+            mode => mode,
+        }
+    }
+
+    #[setter]
+    fn set_synchronous(&self, py: Python<'_>, mode: &str) -> PyResult<()> {
+        let level = match mode.to_ascii_lowercase().as_str() {
+            "off" => 0,
+            "normal" => 1,
+            "full" => 2,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "synchronous must be 'off', 'normal' or 'full', got '{mode}'"
+                )))
+            }
+        };
+        let current = connection::journal_level(self.conn.journal_mode())?;
+        if level > current {
+            self.checkpoint(py)?;
+        }
+        self.set_sync_level(py, level)
+    }
+
+    /// The DSN this database was opened with.
+    #[getter]
+    fn dsn(&self) -> &str {
+        &self.conn.dsn
+    }
+
+    fn __repr__(&self) -> String {
+        "Database(open)".to_string()
+    }
+}
+
+impl Database {
+    /// Execute `sql` once per parameter set in one transaction, without
+    /// the GIL. Returns total rows affected.
+    pub(crate) fn execute_params(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        all_params: Vec<ParamVec>,
+    ) -> PyResult<i64> {
+        let db = self.conn.db()?;
+        let info = self.conn.hooks.describe_batch(&all_params);
+        let count = all_params.len() as u64;
+        self.conn.run_autocommit(py, sql, info, count, |py| {
+            self.conn.allow_threads(py, || {
+                use stoolap::parser::Parser;
+                let mut parser = Parser::new(sql);
+                let program = parser.parse_program().map_err(|e| {
+                    crate::error::parse_error(e.to_string())
+                })?;
+                let stmt = program.statements.first().ok_or_else(|| {
+                    crate::error::StoolapError::new_err("No SQL statement found")
+                })?;
+
+                let mut tx = db.begin().map_err(to_py)?;
+                let mut total = 0i64;
+                for params in all_params {
+                    total += tx.execute_prepared(stmt, params).map_err(to_py)?;
+                }
+                tx.commit().map_err(to_py)?;
+                Ok(total)
+            })
+        })
+    }
+
+    /// Execute `sql` once per parameter set, the sets split into at most
+    /// `workers` contiguous shares that each run on a thread in their own
+    /// transaction. The transactions commit after every share has run, or
+    /// all roll back if one failed; see `execute_batch(parallel=...)`.
+    fn execute_params_parallel(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        all_params: Vec<ParamVec>,
+        workers: usize,
+    ) -> PyResult<i64> {
+        let db = self.conn.db()?;
+        let info = self.conn.hooks.describe_batch(&all_params);
+        let count = all_params.len() as u64;
+        let share = all_params.len().div_ceil(workers);
+        let mut params = all_params.into_iter();
+        let mut shares = Vec::with_capacity(workers);
+        while params.len() > 0 {
+            shares.push(params.by_ref().take(share).collect::<Vec<_>>());
+        }
+        self.conn.run_autocommit(py, sql, info, count, |py| {
+            self.conn.allow_threads(py, || {
+                use stoolap::parser::Parser;
+                let failed = AtomicBool::new(false);
+                let executed = Barrier::new(shares.len());
+                // Execute a share, stopping early once any share has failed
+                let run = |params: Vec<ParamVec>| -> PyResult<(ApiTransaction, i64)> {
+                    let mut parser = Parser::new(sql);
+                    let program = parser
+                        .parse_program()
+                        .map_err(|e| crate::error::parse_error(e.to_string()))?;
+                    let stmt = program.statements.first().ok_or_else(|| {
+                        crate::error::StoolapError::new_err("No SQL statement found")
+                    })?;
+                    let mut tx = db.begin().map_err(to_py)?;
+                    let mut total = 0i64;
+                    for params in params {
+                        if failed.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        match tx.execute_prepared(stmt, params) {
+                            Ok(affected) => total += affected,
+                            Err(err) => {
+                                let _ = tx.rollback();
+                                return Err(to_py(err));
+                            }
+                        }
+                    }
+                    Ok((tx, total))
+                };
+                std::thread::scope(|scope| {
+                    let handles: Vec<_> = shares
+                        .into_iter()
+                        .map(|params| {
+                            let (run, failed, executed) = (&run, &failed, &executed);
+                            scope.spawn(move || -> PyResult<i64> {
+                                let result = run(params);
+                                if result.is_err() {
+                                    failed.store(true, Ordering::Relaxed);
+                                }
+                                // Commit only once every share has run
+                                executed.wait();
+                                let (mut tx, total) = result?;
+                                if failed.load(Ordering::Relaxed) {
+                                    tx.rollback().map_err(to_py)?;
+                                    return Ok(0);
+                                }
+                                tx.commit().map_err(to_py)?;
+                                Ok(total)
+                            })
+                        })
+                        .collect();
+                    let mut total = 0i64;
+                    let mut error = None;
+                    for handle in handles {
+                        let outcome = handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                        match outcome {
+                            Ok(affected) => total += affected,
+                            Err(err) => {
+                                error.get_or_insert(err);
+                            }
+                        }
+                    }
+                    error.map_or(Ok(total), Err)
+                })
+            })
+        })
+    }
+
+    /// Run a query and return a `ChunkIterator` over its rows.
+    fn chunks(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        chunk_size: usize,
+        as_of: Option<&Bound<'_, PyAny>>,
+        columnar: bool,
+    ) -> PyResult<PyObject> {
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("chunk size must be at least 1"));
+        }
+        let sql = self.conn.translate(sql)?;
+        let sql = with_as_of(&sql, as_of)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self
+                .conn
+                .allow_threads(py, || deadline::query(&db, sql, bind).map_err(to_py))?;
+            let chunks = ChunkIterator::new(self.conn.clone(), rows, chunk_size, columnar);
+            Ok((Py::new(py, chunks)?.into_any(), 0))
+        })
+    }
+
+    /// Run a query and buffer its rows, answering from the result cache
+    /// when it holds them.
+    fn cached_rows(&self, py: Python<'_>, sql: &str, bind: BindParams) -> PyResult<Arc<RowBuffer>> {
+        let db = self.conn.db()?;
+        let cache = &self.conn.cache;
+        let key = CacheKey::new(sql, &bind);
+        if let Some(rows) = cache.get(&key) {
+            return Ok(rows);
+        }
+        let generation = cache.generation();
+        let rows = self.conn.allow_threads(py, || {
+            let rows = deadline::query(&db, sql, bind);
+            rows.and_then(RowBuffer::drain).map_err(to_py)
+        })?;
+        let rows = Arc::new(rows);
+        cache.insert(key, rows.clone(), generation);
+        Ok(rows)
+    }
+
+    /// Run a `vector_search()` statement through the plan cache and return
+    /// its rows as dicts.
+    fn search(&self, py: Python<'_>, sql: &str, bind: BindParams) -> PyResult<PyObject> {
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self
+                .conn
+                .allow_threads(py, || {
+                    let plan = db.cached_plan(sql)?;
+                    deadline::query_plan(&db, &plan, sql, bind)
+                })
+                .map_err(to_py)?;
+            rows_to_dicts(py, rows, ConvertOptions::plain(&self.conn.fetch))
+        })
+    }
+
+    /// The vector indexes of `table` as `vector_index_info()` dicts.
+    fn vector_indexes<'py>(
+        &self,
+        py: Python<'py>,
+        table: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let db = self.conn.db()?;
+        let store = db.engine().get_version_store(table).map_err(to_py)?;
+        let mut found = Vec::new();
+        for index in self.indexes(py, table)?.bind(py).try_iter()? {
+            let index = index?.downcast_into::<PyDict>()?;
+            let is_vector = field(&index, &["index_type"])?
+                .is_some_and(|t| t.eq_ignore_ascii_case("hnsw"));
+            if !is_vector {
+                continue;
+            }
+            let name = field(&index, &["index_name"])?;
+            // SHOW INDEXES does not report the HNSW settings
+            let settings = name
+                .as_deref()
+                .and_then(|name| store.get_index(name))
+                .and_then(|index| vector::hnsw_settings(index.as_ref()));
+            let info = PyDict::new(py);
+            info.set_item("name", name)?;
+            info.set_item("table", table)?;
+            info.set_item("column", field(&index, &["column_name"])?)?;
+            info.set_item("index_type", "hnsw")?;
+            info.set_item("metric", settings.as_ref().map(|s| s.metric))?;
+            info.set_item("m", settings.as_ref().map(|s| s.m))?;
+            info.set_item("ef_construction", settings.as_ref().map(|s| s.ef_construction))?;
+            info.set_item("ef_search", settings.as_ref().and_then(|s| s.ef_search))?;
+            found.push(info);
+        }
+        Ok(found)
+    }
+
+    /// Run one statement of an `exec()` script, returning its rows or the
+    /// number of rows it affected.
+    pub(crate) fn exec_statement(
+        &self,
+        py: Python<'_>,
+        stmt: &str,
+        bind: BindParams,
+    ) -> PyResult<PyObject> {
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        if sql::returns_rows(stmt) {
+            return self.conn.run_query(py, stmt, info, |py| {
+                let rows = self
+                    .conn
+                    .allow_threads(py, || deadline::query(&db, stmt, bind).map_err(to_py))?;
+                rows_to_dicts(py, rows, ConvertOptions::plain(&self.conn.fetch))
+            });
+        }
+        let affected = self.conn.run_autocommit(py, stmt, info, 1, |py| {
+            self.conn
+                .allow_threads(py, || deadline::execute(&db, stmt, bind).map_err(to_py))
+        })?;
+        Ok(affected.into_pyobject(py)?.into_any().unbind())
+    }
+
+    /// Sync one table for `sync_from()`, returning the rows inserted,
+    /// updated and deleted.
+    fn sync_table(
+        &self,
+        py: Python<'_>,
+        tx: &Transaction,
+        other: &Database,
+        table: &str,
+        update: bool,
+        delete: bool,
+    ) -> PyResult<[i64; 3]> {
+        let key = other.primary_key(py, table)?;
+        let select = format!("SELECT * FROM {}", sql::quote_ident(table)?);
+        let source = other.fetch(py, &select)?;
+        let target = self.fetch(py, &select)?;
+
+        // Position in the target row of each source column
+        let mut positions = Vec::with_capacity(source.columns.len());
+        for column in &source.columns {
+            let position = target
+                .columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(column))
+                .ok_or_else(|| {
+                    crate::error::StoolapError::new_err(format!(
+                        "column '{column}' of table '{table}' does not exist in the target"
+                    ))
+                })?;
+            positions.push(position);
+        }
+        let key_columns = key
+            .iter()
+            .map(|name| {
+                source
+                    .columns
+                    .iter()
+                    .position(|c| c.eq_ignore_ascii_case(name))
+                    .ok_or_else(|| {
+                        crate::error::StoolapError::new_err(format!(
+                            "primary key column '{name}' is not among the columns of '{table}'"
+                        ))
+                    })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        // Rows are matched and compared by the SQL literals of their values
+        let source_key = |row: &[Value]| -> Vec<String> {
+            key_columns.iter().map(|&i| sql::literal(&row[i])).collect()
+        };
+        let target_key = |row: &[Value]| -> Vec<String> {
+            key_columns
+                .iter()
+                .map(|&i| sql::literal(&row[positions[i]]))
+                .collect()
+        };
+
+        let mut existing: HashMap<Vec<String>, &Vec<Value>> = target
+            .rows
+            .iter()
+            .map(|row| (target_key(row), row))
+            .collect();
+        let mut inserts = Vec::new();
+        let mut updates = Vec::new();
+        for row in &source.rows {
+            match existing.remove(&source_key(row)) {
+                None => inserts.push(params_of(row.iter())),
+                Some(current) => {
+                    let changed = positions
+                        .iter()
+                        .enumerate()
+                        .any(|(i, &p)| sql::literal(&row[i]) != sql::literal(&current[p]));
+                    if update && changed {
+                        let values = (0..row.len()).filter(|i| !key_columns.contains(i));
+                        let order = values.chain(key_columns.iter().copied());
+                        updates.push(params_of(order.map(|i| &row[i])));
+                    }
+                }
+            }
+        }
+        let deletes: Vec<ParamVec> = if delete {
+            existing
+                .into_values()
+                .map(|row| params_of(key_columns.iter().map(|&i| &row[positions[i]])))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let quoted = sql::quote_ident(table)?;
+        let names: Vec<String> = source.columns
+            .iter()
+            .map(|c| sql::quote_ident(c))
+            .collect::<PyResult<_>>()?;
+        let key_names: Vec<&String> = key_columns.iter().map(|&i| &names[i]).collect();
+        let mut counts = [0; 3];
+        if !inserts.is_empty() {
+            let placeholders: Vec<String> = (1..=names.len()).map(|i| format!("${i}")).collect();
+            let insert = format!(
+                "INSERT INTO {quoted} ({}) VALUES ({})",
+                names.join(", "),
+                placeholders.join(", ")
+            );
+            counts[0] = tx.execute_params(py, &insert, inserts)?;
+        }
+        let values: Vec<&String> = (0..names.len())
+            .filter(|i| !key_columns.contains(i))
+            .map(|i| &names[i])
+            .collect();
+        if !updates.is_empty() && !values.is_empty() {
+            let set: Vec<String> = values
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{name} = ${}", i + 1))
+                .collect();
+            let condition: Vec<String> = key_names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{name} = ${}", values.len() + i + 1))
+                .collect();
+            let update = format!(
+                "UPDATE {quoted} SET {} WHERE {}",
+                set.join(", "),
+                condition.join(" AND ")
+            );
+            counts[1] = tx.execute_params(py, &update, updates)?;
+        }
+        if !deletes.is_empty() {
+            let condition: Vec<String> = key_names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("{name} = ${}", i + 1))
+                .collect();
+            let delete = format!("DELETE FROM {quoted} WHERE {}", condition.join(" AND "));
+            counts[2] = tx.execute_params(py, &delete, deletes)?;
+        }
+        Ok(counts)
+    }
+
+    /// Names of the primary key columns of `table`, from `DESCRIBE`.
+    pub(crate) fn primary_key(&self, py: Python<'_>, table: &str) -> PyResult<Vec<String>> {
+        let mut key = Vec::new();
+        for column in self.describe(py, table)?.bind(py).try_iter()? {
+            let column = column?.downcast_into::<PyDict>()?;
+            let is_key = field(&column, &["key"])?
+                .is_some_and(|k| k.to_ascii_uppercase().starts_with("PRI"))
+                || field(&column, &["primary_key", "pk"])?
+                    .is_some_and(|k| matches!(k.to_ascii_lowercase().as_str(), "true" | "1"));
+            if is_key {
+                if let Some(name) = field(&column, &["field", "column", "column_name", "name"])? {
+                    key.push(name);
+                }
+            }
+        }
+        if key.is_empty() {
+            return Err(StoolapError::new_err(format!(
+                "table '{table}' has no primary key"
+            )));
+        }
+        Ok(key)
+    }
+
+    /// The engine database behind the connection.
+    pub(crate) fn engine(&self) -> PyResult<Arc<ApiDatabase>> {
+        self.conn.db()
+    }
+
+    /// How the connection converts bound Python values.
+    pub(crate) fn binding(&self) -> &BindOptions {
+        &self.conn.binding
+    }
+
+    /// `sql` in numeric dollar placeholders, whatever the paramstyle.
+    pub(crate) fn translate(&self, sql: &str) -> PyResult<String> {
+        Ok(self.conn.translate(sql)?.into_owned())
+    }
+
+    /// Run `sql` under `command` (`EXPLAIN` or `EXPLAIN ANALYZE`) and
+    /// return the plan in `format`.
+    fn plan(
+        &self,
+        py: Python<'_>,
+        command: &str,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        format: &str,
+    ) -> PyResult<PyObject> {
+        let format = explain::Format::parse(format)?;
+        let sql = format!("{command} {}", self.conn.translate(sql)?);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                deadline::query(&db, &sql, bind).and_then(RowBuffer::drain)
+            });
+            let lines = plan_lines(&rows.map_err(to_py)?);
+            let plan = || {
+                PlanNode::parse(&lines).ok_or_else(|| {
+                    crate::error::StoolapError::new_err(format!("{command} returned no plan"))
+                })
+            };
+            let result = match format {
+                explain::Format::Text => lines.join("\n").into_pyobject(py)?.into_any(),
+                explain::Format::Dict => plan()?.to_dict(py)?.into_any(),
+                explain::Format::Tree => plan()?.to_tree().into_pyobject(py)?.into_any(),
+            };
+            Ok((result.unbind(), lines.len()))
+        })
+    }
+
+    /// Read every row of a query issued by the binding itself, passing it
+    /// through the audit hook first.
+    fn fetch(&self, py: Python<'_>, sql: &str) -> PyResult<RowBuffer> {
+        let db = self.conn.db()?;
+        self.conn.hooks.audit(py, sql, &ParamInfo::default())?;
+        self.conn.allow_threads(py, || db.query(sql, ()).and_then(RowBuffer::drain))
+            .map_err(to_py)
+    }
+
+    /// Run a schema query and return its rows as dicts. Like maintenance
+    /// commands, it passes the audit hook but is not counted in `stats()`.
+    fn introspect(&self, py: Python<'_>, sql: &str) -> PyResult<PyObject> {
+        let rows = self.fetch(py, sql)?;
+        Ok(rows.to_dicts(py, ConvertOptions::plain(&self.conn.fetch))?.0)
+    }
+
+    /// Switch the engine to `sync_mode` `level`. The engine only takes it
+    /// when opened, so a file database is reopened with the new mode; an
+    /// in-memory one has no WAL, so only the reported mode changes.
+    fn set_sync_level(&self, py: Python<'_>, level: u8) -> PyResult<()> {
+        let current = connection::journal_level(self.conn.journal_mode())?;
+        self.conn.set_journal_mode(level);
+        if self.is_memory() || level == current {
+            return Ok(());
+        }
+        self.reopen(py).inspect_err(|_| self.conn.set_journal_mode(current))
+    }
+
+    fn is_memory(&self) -> bool {
+        self.conn.dsn.starts_with("memory://")
+    }
+
+    /// Directory holding the database files, or None for in-memory databases.
+    fn data_dir(&self) -> Option<PathBuf> {
+        self.conn.data_dir()
+    }
+}
+
+/// Add `value` to `bind` for a helper's own placeholder: appended to
+/// positional parameters, or under `name` with named ones. Returns the
+/// placeholder to use in the statement.
+pub fn bind_extra(bind: &mut BindParams, name: &str, value: Value) -> PyResult<String> {
+    match bind {
+        BindParams::Positional(p) => {
+            p.push(value);
+            Ok(format!("${}", p.len()))
+        }
+        BindParams::Named(named) => {
+            if named.iter().any(|(key, _)| key == name) {
+                return Err(PyValueError::new_err(format!(
+                    "params must not contain '{name}', which the helper binds itself"
+                )));
+            }
+            named.push((name.to_string(), value));
+            Ok(format!(":{name}"))
+        }
+    }
+}
+
+/// Positional parameters holding `values`.
+fn params_of<'a>(values: impl Iterator<Item = &'a Value>) -> ParamVec {
+    let mut params = ParamVec::new();
+    for value in values {
+        params.push(value.clone());
+    }
+    params
+}
+
+/// The value of the first of `keys` present in a schema row, compared
+/// case-insensitively, as a string. None values count as missing.
+pub fn field(row: &Bound<'_, PyDict>, keys: &[&str]) -> PyResult<Option<String>> {
+    match field_value(row, keys)? {
+        Some(value) => Ok(Some(value.str()?.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Like `field`, but the value as it is.
+fn field_value<'py>(
+    row: &Bound<'py, PyDict>,
+    keys: &[&str],
+) -> PyResult<Option<Bound<'py, PyAny>>> {
+    for (key, value) in row.iter() {
+        let key = key.str()?.to_string().to_ascii_lowercase();
+        if keys.contains(&key.as_str()) && !value.is_none() {
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+/// Names of all tables in the database.
+fn table_names(db: &ApiDatabase) -> stoolap::Result<Vec<String>> {
+    let rows = db.query("SHOW TABLES", ())?;
+    let mut names = Vec::new();
+    for row in rows {
+        if let Some(Value::Text(name)) = row?.get_value(0) {
+            names.push(name.as_str().to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// The lines of an `EXPLAIN` result, one or more per row.
+fn plan_lines(rows: &RowBuffer) -> Vec<String> {
+    rows.rows
+        .iter()
+        .filter_map(|row| match row.first() {
+            Some(Value::Text(line)) => Some(line.as_str()),
+            _ => None,
+        })
+        .flat_map(|line| line.lines().map(str::to_string))
+        .collect()
+}
+
+/// Scan `table` in full and compare against its reported row count.
+fn check_table(db: &ApiDatabase, table: &str) -> Result<(), String> {
+    let quoted = sql::ident(table);
+    let counted = match db
+        .query(&format!("SELECT COUNT(*) FROM {quoted}"), ())
+        .and_then(|mut rows| rows.next().transpose())
+    {
+        Ok(Some(row)) => match row.get_value(0) {
+            Some(Value::Integer(n)) => *n,
+            _ => return Err("COUNT(*) returned no value".to_string()),
+        },
+        Ok(None) => return Err("COUNT(*) returned no rows".to_string()),
+        Err(e) => return Err(format!("count failed: {e}")),
+    };
+
+    let rows = db
+        .query(&format!("SELECT * FROM {quoted}"), ())
+        .map_err(|e| format!("scan failed: {e}"))?;
+    let mut scanned = 0i64;
+    for row in rows {
+        row.map_err(|e| format!("scan failed at row {scanned}: {e}"))?;
+        scanned += 1;
+    }
+
+    if scanned != counted {
+        return Err(format!(
+            "row count mismatch: COUNT(*) reports {counted}, scan found {scanned}"
+        ));
+    }
+    check_indexes(db, table)
+}
+
+/// Look the indexed values of every row up through each index of `table`,
+/// so an entry missing from an index is found even though scans never read
+/// the index. Keys are counted with equality (or, for single-column indexes,
+/// batched `IN`) lookups on the index columns, which the planner answers from
+/// the index, and compared with the rows the scan found. Rows with a NULL in
+/// the key are skipped, and vector indexes, which are searched by distance,
+/// are not checked.
+fn check_indexes(db: &ApiDatabase, table: &str) -> Result<(), String> {
+    let store = db
+        .engine()
+        .get_version_store(table)
+        .map_err(|e| format!("indexes unavailable: {e}"))?;
+    let quoted = sql::ident(table);
+    for name in store.list_indexes() {
+        let Some(index) = store.get_index(&name) else {
+            continue;
+        };
+        if index.index_type() == IndexType::Hnsw {
+            continue;
+        }
+        let columns: Vec<String> = index.column_names().iter().map(|c| sql::ident(c)).collect();
+        let rows = db
+            .query(&format!("SELECT {} FROM {quoted}", columns.join(", ")), ())
+            .map_err(|e| format!("scan of index {name} columns failed: {e}"))?;
+        let mut keys: HashMap<Vec<Value>, i64> = HashMap::new();
+        for row in rows {
+            let row = row.map_err(|e| format!("scan of index {name} columns failed: {e}"))?;
+            let key: Vec<Value> = (0..columns.len())
+                .map(|i| {
+                    row.get_value(i)
+                        .cloned()
+                        .unwrap_or_else(Value::null_unknown)
+                })
+                .collect();
+            if !key.iter().any(Value::is_null) {
+                *keys.entry(key).or_default() += 1;
+            }
+        }
+
+        let keys: Vec<(Vec<Value>, i64)> = keys.into_iter().collect();
+        let count = |condition: String| -> Result<i64, String> {
+            match db
+                .query(
+                    &format!("SELECT COUNT(*) FROM {quoted} WHERE {condition}"),
+                    (),
+                )
+                .and_then(|mut rows| rows.next().transpose())
+            {
+                Ok(Some(row)) => match row.get_value(0) {
+                    Some(Value::Integer(n)) => Ok(*n),
+                    _ => Ok(0),
+                },
+                Ok(None) => Ok(0),
+                Err(e) => Err(format!("lookup through index {name} failed: {e}")),
+            }
+        };
+        let batch = if columns.len() == 1 { 256 } else { 1 };
+        for chunk in keys.chunks(batch) {
+            let expected: i64 = chunk.iter().map(|(_, rows)| rows).sum();
+            let literals: Vec<String> =
+                chunk.iter().map(|(key, _)| sql::literal(&key[0])).collect();
+            if columns.len() == 1
+                && count(format!("{} IN ({})", columns[0], literals.join(", ")))? == expected
+            {
+                continue;
+            }
+            // Find the key the index is missing rows for
+            for (key, rows) in chunk {
+                let condition: Vec<String> = columns
+                    .iter()
+                    .zip(key)
+                    .map(|(column, value)| format!("{column} = {}", sql::literal(value)))
+                    .collect();
+                let found = count(condition.join(" AND "))?;
+                if found != *rows {
+                    let key: Vec<String> = key.iter().map(|v| v.to_string()).collect();
+                    return Err(format!(
+                        "index {name} finds {found} rows for ({}), the scan found {rows}",
+                        key.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The directories named `table` below `path`, where the engine keeps a
+/// table's volumes.
+fn table_dirs(path: &Path, table: &str) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return Vec::new();
+    };
+    let mut dirs = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        if !entry.metadata().is_ok_and(|meta| meta.is_dir()) {
+            continue;
+        }
+        if entry.file_name().to_string_lossy().eq_ignore_ascii_case(table) {
+            dirs.push(entry.path());
+        } else {
+            dirs.extend(table_dirs(&entry.path(), table));
+        }
+    }
+    dirs
+}
+
+/// Total size in bytes of the files of `table` below `path`, or None when
+/// it has none.
+fn table_size(path: &Path, table: &str) -> Option<u64> {
+    let dirs = table_dirs(path, table);
+    (!dirs.is_empty()).then(|| dirs.iter().map(|dir| dir_size(dir)).sum())
+}
 
-        if in_block_comment {
-            if c == '*' && i + 1 < chars.len() && chars[i + 1] == '/' {
-                in_block_comment = false;
-                i += 2;
-                continue;
-            }
+/// Number of files of `table` below `path`.
+fn table_files(path: &Path, table: &str) -> usize {
+    table_dirs(path, table)
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.metadata().is_ok_and(|meta| meta.is_file()))
+                .count()
+        })
+        .sum()
+}
+
+/// Total size in bytes of all files below `path`.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Rewrite `sql` to read the snapshot selected by `as_of`, if given.
+fn with_as_of<'a>(sql: &'a str, as_of: Option<&Bound<'_, PyAny>>) -> PyResult<Cow<'a, str>> {
+    let Some(as_of) = as_of.filter(|a| !a.is_none()) else {
+        return Ok(Cow::Borrowed(sql));
+    };
+    let clause = match py_to_value(as_of)? {
+        Value::Integer(id) => format!("AS OF TRANSACTION {id}"),
+        Value::Timestamp(ts) => {
+            format!("AS OF TIMESTAMP '{}'", ts.format("%Y-%m-%d %H:%M:%S%.f"))
+        }
+        Value::Text(text) => {
+            format!("AS OF TIMESTAMP '{}'", text.as_str().replace('\'', "''"))
+        }
+        _ => {
+            return Err(PyTypeError::new_err(
+                "as_of must be a transaction id (int), datetime or timestamp string",
+            ))
+        }
+    };
+    Ok(Cow::Owned(apply_as_of(sql, &clause)))
+}
+
+/// Make every table named in the FROM list or a JOIN of each SELECT in
+/// `sql`, including subqueries, read the snapshot selected by `clause` (see
+/// `snapshot_source`). Subqueries and table functions in the FROM list are
+/// left as they are, apart from the tables they read. String literals and
+/// comments are copied unchanged.
+fn apply_as_of(sql: &str, clause: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len() + clause.len() * 2);
+    // Per parenthesis level: (inside a SELECT, inside its FROM list)
+    let mut levels = vec![(false, false)];
+    let mut expect_table = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied().unwrap_or('\0');
+
+        if c == '\'' || c == '"' {
+            let start = i;
             i += 1;
+            while i < chars.len() {
+                if chars[i] == c {
+                    if chars.get(i + 1) == Some(&c) {
+                        i += 2;
+                        continue;
+                    }
+                    i += 1;
+                    break;
+                }
+                i += 1;
+            }
+            let token: String = chars[start..i].iter().collect();
+            if c == '"' && expect_table {
+                out.push_str(&snapshot_source(&token, &chars[i..], clause));
+                expect_table = false;
+            } else {
+                out.push_str(&token);
+            }
             continue;
         }
 
-        if !in_single && !in_double && c == '/' && i + 1 < chars.len() && chars[i + 1] == '*' {
-            in_block_comment = true;
+        if (c == '-' && next == '-') || (c == '/' && next == '*') {
+            let start = i;
             i += 2;
+            if c == '-' {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            } else {
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+            }
+            out.extend(&chars[start..i]);
+            continue;
+        }
+
+        if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let level = levels.last_mut().expect("top level is never popped");
+            let upper = word.to_ascii_uppercase();
+            // A table function such as generate_series(...) has no snapshot
+            // to read.
+            let call = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+            if expect_table && !call && !matches!(upper.as_str(), "SELECT" | "LATERAL") {
+                out.push_str(&snapshot_source(&word, &chars[i..], clause));
+                expect_table = false;
+                continue;
+            }
+            out.push_str(&word);
+            match upper.as_str() {
+                "SELECT" => *level = (true, false),
+                "FROM" if level.0 => {
+                    level.1 = true;
+                    expect_table = true;
+                }
+                "JOIN" if level.0 => expect_table = true,
+                "WHERE" | "GROUP" | "HAVING" | "ORDER" | "LIMIT" | "OFFSET" | "UNION"
+                | "INTERSECT" | "EXCEPT" | "ON" | "USING" => {
+                    level.1 = false;
+                    expect_table = false;
+                }
+                _ => expect_table = false,
+            }
             continue;
         }
 
-        if !in_block_comment && !in_line_comment {
-            if c == '\'' && (i == 0 || chars[i - 1] != '\\') {
-                in_single = !in_single;
-            } else if c == '"' && (i == 0 || chars[i - 1] != '\\') {
-                in_double = !in_double;
+        match c {
+            '(' => {
+                levels.push((false, false));
+                expect_table = false;
+            }
+            ')' if levels.len() > 1 => {
+                levels.pop();
             }
+            ',' if levels.last().is_some_and(|level| level.1) => expect_table = true,
+            _ => {}
         }
+        out.push(c);
+        i += 1;
+    }
 
-        if c == ';' && !in_single && !in_double && !in_block_comment && !in_line_comment {
-            statements.push(current.clone());
-            current.clear();
+    out
+}
+
+/// Words that end a FROM item rather than give it an alias.
+const FROM_ITEM_END: &[&str] = &[
+    "WHERE", "GROUP", "HAVING", "ORDER", "LIMIT", "OFFSET", "UNION", "INTERSECT", "EXCEPT", "ON",
+    "USING", "JOIN", "INNER", "LEFT", "RIGHT", "FULL", "CROSS", "NATURAL", "OUTER", "WINDOW",
+];
+
+/// Read `table` at `clause` through a derived table,
+/// `(SELECT * FROM table AS OF ... WHERE TRUE) alias`, keeping the alias
+/// written in `rest` or else naming it after the table so qualified column
+/// names still resolve. The engine's temporal scan of a plain table source
+/// ignores the select list and the primary key lookups of the query around
+/// it, neither of which reach into a derived table; the WHERE keeps joins
+/// from reading through the derived table to the current rows.
+fn snapshot_source(table: &str, rest: &[char], clause: &str) -> String {
+    let next: String = rest
+        .iter()
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '"')
+        .collect();
+    let aliased = !next.is_empty() && !FROM_ITEM_END.iter().any(|w| w.eq_ignore_ascii_case(&next));
+    let derived = format!("(SELECT * FROM {table} {clause} WHERE TRUE)");
+    if aliased {
+        derived
+    } else {
+        let name = if table.starts_with('"') {
+            table
         } else {
-            current.push(c);
+            table.rsplit('.').next().unwrap_or(table)
+        };
+        format!("{derived} AS {name}")
+    }
+}
+
+/// Accept a callable or None, rejecting anything else.
+fn optional_callable(obj: Option<&Bound<'_, PyAny>>, name: &str) -> PyResult<Option<PyObject>> {
+    match obj {
+        Some(f) if !f.is_none() => {
+            if !f.is_callable() {
+                return Err(PyTypeError::new_err(format!("{name} must be callable")));
+            }
+            Ok(Some(f.clone().unbind()))
         }
+        _ => Ok(None),
+    }
+}
 
-        i += 1;
+/// Translate user-friendly paths to Stoolap DSN format, checking the
+/// options in its query string.
+fn translate_path(path: &str) -> PyResult<String> {
+    let trimmed = path.trim();
+    let dsn = if trimmed.is_empty() || trimmed == ":memory:" {
+        "memory://".to_string()
+    } else if trimmed.starts_with("memory://") || trimmed.starts_with("file://") {
+        trimmed.to_string()
+    } else {
+        format!("file://{trimmed}")
+    };
+    connection::validate_dsn(&dsn)?;
+    Ok(dsn)
+}
+
+/// Convert named params to stoolap NamedParams.
+pub fn to_named_params(named: &[(String, stoolap::core::Value)]) -> stoolap::api::NamedParams {
+    let mut np = stoolap::api::NamedParams::new();
+    for (key, val) in named {
+        np.insert(key, val.clone());
+    }
+    np
+}
+
+/// `compact_threshold` while background compaction is off: more volumes
+/// than a table ever collects.
+const COMPACTION_OFF: u32 = i32::MAX as u32;
+
+/// Isolation level of transactions begun in "deferred" mode.
+const DEFAULT_ISOLATION: &str = "READ COMMITTED";
+
+/// System tables where `ANALYZE` records table and column statistics.
+const TABLE_STATS: &str = "_sys_table_stats";
+const COLUMN_STATS: &str = "_sys_column_stats";
+
+/// Number of rows read from the engine per GIL release when converting a
+/// result set. Bounds the intermediate buffer on large results.
+const CHUNK_ROWS: usize = 4096;
+
+/// How a result set is converted: the connection's TIMESTAMP conversion,
+/// string interning, the limits that abort a query whose result is too
+/// large, the `limit` that cuts it short instead, the token that cancels
+/// it, and whether `query_raw` returns typed column buffers or
+/// `query_columnar` NumPy arrays.
+#[derive(Clone, Copy)]
+pub struct ConvertOptions<'a> {
+    pub fetch: &'a FetchOptions,
+    pub intern_strings: bool,
+    pub max_rows: Option<usize>,
+    pub max_bytes: Option<usize>,
+    pub limit: Option<usize>,
+    pub cancel: Option<&'a CancellationToken>,
+    pub typed: bool,
+    pub numpy: bool,
+}
+
+impl<'a> ConvertOptions<'a> {
+    pub fn new(
+        fetch: &'a FetchOptions,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_bytes: Option<usize>,
+    ) -> Self {
+        Self {
+            fetch,
+            intern_strings,
+            max_rows,
+            max_bytes,
+            limit: None,
+            cancel: None,
+            typed: false,
+            numpy: false,
+        }
+    }
+
+    /// No interning and no limits.
+    pub fn plain(fetch: &'a FetchOptions) -> Self {
+        Self::new(fetch, false, None, None)
+    }
+
+    /// Read at most `limit` rows, and return them as a `LimitedRows` list.
+    /// See `limited`.
+    pub fn limit(self, limit: Option<usize>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// Stop converting with QueryCancelled once `cancel` is cancelled. The
+    /// token is checked with the limits, once per chunk.
+    pub fn cancel(self, cancel: Option<&'a Bound<'_, CancellationToken>>) -> Self {
+        Self {
+            cancel: cancel.map(Bound::get),
+            ..self
+        }
+    }
+
+    /// Convert raw results column by column, into typed buffers where the
+    /// column allows it. See `typed_columns`.
+    pub fn typed(self, typed: bool) -> Self {
+        Self { typed, ..self }
+    }
+
+    /// Convert columnar results to NumPy arrays. See `numpy_columns`.
+    /// NumPy is imported here, so that a missing install fails before the
+    /// query runs.
+    pub fn numpy(self, py: Python<'_>, numpy: bool) -> PyResult<Self> {
+        if numpy {
+            import_numpy(py)?;
+        }
+        Ok(Self { numpy, ..self })
+    }
+
+    /// Rows to read in the next chunk, given `read` so far. Stops one past
+    /// `max_rows`, which is enough to tell that the limit was exceeded.
+    fn chunk_rows(&self, read: usize) -> usize {
+        let stop = match (self.max_rows, self.limit) {
+            (Some(max), Some(limit)) => Some(max.min(limit)),
+            (max, limit) => max.or(limit),
+        };
+        match stop {
+            Some(max) => CHUNK_ROWS.min((max + 1).saturating_sub(read)).max(1),
+            None => CHUNK_ROWS,
+        }
+    }
+
+    /// `rows` as returned from a query: a `LimitedRows` when a `limit` was
+    /// given, `rows` otherwise.
+    fn limited(
+        &self,
+        py: Python<'_>,
+        rows: Bound<'_, PyAny>,
+        truncated: bool,
+    ) -> PyResult<PyObject> {
+        if self.limit.is_none() {
+            return Ok(rows.unbind());
+        }
+        let limited = limited_rows_type(py)?.call1((rows,))?;
+        limited.setattr("truncated", truncated)?;
+        Ok(limited.unbind())
+    }
+
+    /// Fail with ResultTooLarge once `rows` or `bytes` exceed the limits,
+    /// with QueryCancelled once the token is cancelled, or with
+    /// QueryTimeout once the statement's deadline has passed.
+    fn check(&self, rows: usize, bytes: usize) -> PyResult<()> {
+        if let Some(token) = self.cancel {
+            token.check()?;
+        }
+        deadline::check()?;
+        if let Some(max) = self.max_rows.filter(|max| rows > *max) {
+            return Err(ResultTooLarge::new_err(format!(
+                "query returned more than max_rows={max} rows"
+            )));
+        }
+        if let Some(max) = self.max_bytes.filter(|max| bytes > *max) {
+            return Err(ResultTooLarge::new_err(format!(
+                "query result exceeds max_result_bytes={max} bytes"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Approximate memory held by a row's values, in bytes.
+fn row_size(row: &[Value]) -> usize {
+    row.iter()
+        .map(|val| match val {
+            Value::Text(s) => std::mem::size_of::<Value>() + s.as_str().len(),
+            _ => std::mem::size_of::<Value>(),
+        })
+        .sum()
+}
+
+/// Read up to `limit` rows into `out` as Rust values, without needing the
+/// GIL. Returns false once the result set is exhausted.
+fn read_rows(
+    rows: &mut stoolap::api::Rows,
+    width: usize,
+    limit: usize,
+    out: &mut Vec<Vec<Value>>,
+) -> stoolap::Result<bool> {
+    for _ in 0..limit {
+        let Some(row_result) = rows.next() else {
+            return Ok(false);
+        };
+        let row = row_result?;
+        out.push(
+            (0..width)
+                .map(|i| row.get_value(i).cloned().unwrap_or_else(Value::null_unknown))
+                .collect(),
+        );
+    }
+    Ok(true)
+}
+
+/// Read the next chunk of rows with the GIL released.
+fn read_chunk(
+    py: Python<'_>,
+    rows: &mut stoolap::api::Rows,
+    width: usize,
+    limit: usize,
+    out: &mut Vec<Vec<Value>>,
+) -> PyResult<bool> {
+    py.allow_threads(|| read_rows(rows, width, limit, out).map_err(to_py))
+}
+
+/// Column names as Python strings, created once and reused for every row.
+fn column_names<'py>(py: Python<'py>, columns: &[String]) -> Vec<Bound<'py, PyString>> {
+    columns.iter().map(|c| PyString::new(py, c)).collect()
+}
+
+fn row_to_dict<'py>(
+    py: Python<'py>,
+    names: &[Bound<'py, PyString>],
+    row: &[Value],
+    interner: &mut StringInterner,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (i, (name, val)) in names.iter().zip(row).enumerate() {
+        dict.set_item(name, interner.value_to_py(py, i, val)?)?;
+    }
+    Ok(dict)
+}
+
+/// The `LimitedRows` type, created on first use: PyO3 classes cannot
+/// extend `list`.
+static LIMITED_ROWS: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// The type of the rows of a query run with `limit`: a list of dicts whose
+/// `truncated` attribute tells whether the query had more rows than were
+/// read.
+pub fn limited_rows_type(py: Python<'_>) -> PyResult<&Bound<'_, PyType>> {
+    let cls = LIMITED_ROWS.get_or_try_init(py, || {
+        let namespace = PyDict::new(py);
+        namespace.set_item("__module__", "stoolap")?;
+        namespace.set_item(
+            "__doc__",
+            "Rows of a query run with limit=: a list of dicts whose truncated \
+             attribute tells whether the query had more rows.",
+        )?;
+        namespace.set_item("__slots__", ("truncated",))?;
+        let bases = (py.get_type::<PyList>(),);
+        py.get_type::<PyType>()
+            .call1(("LimitedRows", bases, namespace))?
+            .downcast_into::<PyType>()
+            .map(Bound::unbind)
+            .map_err(PyErr::from)
+    })?;
+    Ok(cls.bind(py))
+}
+
+/// Iterator over query results in lists of rows, or in dicts of column
+/// lists when `columnar`. The engine keeps its cursor open until the last
+/// chunk is read or `close()` is called.
+///
+/// Rows count towards `stats()["rows_read"]` as their chunk is read; hooks
+/// see the query itself with a row count of 0.
+#[pyclass]
+pub struct ChunkIterator {
+    conn: Arc<Connection>,
+    columns: Vec<String>,
+    rows: Mutex<Option<stoolap::api::Rows>>,
+    chunk_size: usize,
+    columnar: bool,
+}
+
+impl ChunkIterator {
+    fn new(
+        conn: Arc<Connection>,
+        rows: stoolap::api::Rows,
+        chunk_size: usize,
+        columnar: bool,
+    ) -> Self {
+        Self {
+            conn,
+            columns: rows.columns().to_vec(),
+            rows: Mutex::new(Some(rows)),
+            chunk_size,
+            columnar,
+        }
+    }
+}
+
+#[pymethods]
+impl ChunkIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        self.conn.check_fork()?;
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        py.allow_threads(|| {
+            let mut guard = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(rows) = guard.as_mut() else {
+                return Ok(());
+            };
+            let more = read_rows(rows, self.columns.len(), self.chunk_size, &mut chunk)
+                .map_err(to_py)?;
+            if !more {
+                // Release the engine's cursor as soon as it is exhausted
+                *guard = None;
+            }
+            Ok::<_, PyErr>(())
+        })?;
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        self.conn
+            .stats
+            .rows_read
+            .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+
+        let mut interner = StringInterner::new(false, &self.conn.fetch);
+        let columns = interner.columns(py, &self.columns);
+        if self.columnar {
+            let lists = empty_columns(py, &columns);
+            append_columns(py, &lists, &chunk, &mut interner)?;
+            return Ok(Some(columns_to_dict(py, &columns, lists)?));
+        }
+        let names = column_names(py, &columns);
+        let result = PyList::empty(py);
+        for row in &chunk {
+            result.append(row_to_dict(py, &names, row, &mut interner)?)?;
+        }
+        Ok(Some(result.into_any().unbind()))
+    }
+
+    /// Column names of the result.
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.columns.clone()
+    }
+
+    /// Release the engine's cursor. Further iteration stops.
+    fn close(&self, py: Python<'_>) {
+        py.allow_threads(|| {
+            let mut guard = self.rows.lock().unwrap_or_else(|e| e.into_inner());
+            drop(guard.take());
+        });
+    }
+}
+
+impl Drop for ChunkIterator {
+    fn drop(&mut self) {
+        // A cursor inherited across fork() belongs to the parent's engine
+        if self.conn.inherited() {
+            if let Ok(guard) = self.rows.get_mut() {
+                std::mem::forget(guard.take());
+            }
+        }
+    }
+}
+
+/// Rows read from the engine into Rust values, so that they can be
+/// collected without the GIL and converted later.
+pub struct RowBuffer {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl RowBuffer {
+    /// Read every row of a result set.
+    pub fn drain(mut rows: stoolap::api::Rows) -> stoolap::Result<Self> {
+        let columns: Vec<String> = rows.columns().to_vec();
+        let mut buffered = Vec::new();
+        read_rows(&mut rows, columns.len(), usize::MAX, &mut buffered)?;
+        Ok(Self {
+            columns,
+            rows: buffered,
+        })
+    }
+
+    /// Approximate memory held by the buffered values, in bytes.
+    pub fn size(&self) -> usize {
+        let names: usize = self.columns.iter().map(String::len).sum();
+        names + self.rows.iter().map(|row| row_size(row)).sum::<usize>()
+    }
+
+    /// Convert to a list of Python dicts, like `rows_to_dicts`.
+    pub fn to_dicts(&self, py: Python<'_>, options: ConvertOptions) -> PyResult<(PyObject, usize)> {
+        let count = options.limit.map_or(self.rows.len(), |limit| limit.min(self.rows.len()));
+        let rows = &self.rows[..count];
+        options.check(count, rows.iter().map(|row| row_size(row)).sum())?;
+        let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+        let names = column_names(py, &interner.columns(py, &self.columns));
+        let result = PyList::empty(py);
+        for row in rows {
+            result.append(row_to_dict(py, &names, row, &mut interner)?)?;
+        }
+        let truncated = count < self.rows.len();
+        Ok((options.limited(py, result.into_any(), truncated)?, count))
+    }
+
+    /// Convert to a dict of column lists, like `rows_to_columnar`.
+    pub fn to_columnar(&self, py: Python<'_>, options: ConvertOptions) -> PyResult<(PyObject, usize)> {
+        options.check(self.rows.len(), self.size())?;
+        if options.numpy {
+            return Ok((numpy_columns(py, &self.columns, &self.rows, options.fetch)?, self.rows.len()));
+        }
+        let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+        let columns = interner.columns(py, &self.columns);
+        let lists = empty_columns(py, &columns);
+        append_columns(py, &lists, &self.rows, &mut interner)?;
+        Ok((columns_to_dict(py, &columns, lists)?, self.rows.len()))
+    }
+
+    /// Convert to the raw format, like `rows_to_raw`.
+    pub fn to_raw(&self, py: Python<'_>, options: ConvertOptions) -> PyResult<(PyObject, usize)> {
+        options.check(self.rows.len(), self.size())?;
+        if options.typed {
+            let result = typed_columns(py, &self.columns, &self.rows, options)?;
+            return Ok((result, self.rows.len()));
+        }
+        let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+        let columns = interner.columns(py, &self.columns);
+        let py_rows = PyList::empty(py);
+        for row in &self.rows {
+            let py_row = row
+                .iter()
+                .enumerate()
+                .map(|(i, val)| interner.value_to_py(py, i, val))
+                .collect::<PyResult<Vec<_>>>()?;
+            py_rows.append(PyList::new(py, py_row)?)?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("columns", PyList::new(py, columns.iter())?)?;
+        result.set_item("rows", py_rows)?;
+        Ok((result.into_any().unbind(), self.rows.len()))
+    }
+}
+
+/// Convert Rows iterator to a list of Python dicts.
+///
+/// Rows are read from the engine in chunks with the GIL released; each chunk
+/// is then converted in one pass with the GIL held. Conversion stops with
+/// ResultTooLarge as soon as a chunk takes the result past the limits in
+/// `options`. Returns the list together with the number of rows converted.
+pub fn rows_to_dicts(
+    py: Python<'_>,
+    mut rows: stoolap::api::Rows,
+    options: ConvertOptions,
+) -> PyResult<(PyObject, usize)> {
+    let columns: Vec<String> = rows.columns().to_vec();
+    let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+    let names = column_names(py, &interner.columns(py, &columns));
+    let result = PyList::empty(py);
+    let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+    let mut bytes = 0;
+    let mut truncated = false;
+
+    loop {
+        let limit = options.chunk_rows(result.len());
+        let more = read_chunk(py, &mut rows, columns.len(), limit, &mut chunk)?;
+        // One row past `options.limit` is read only to tell that there are more.
+        if let Some(max) = options.limit.filter(|max| result.len() + chunk.len() > *max) {
+            chunk.truncate(max - result.len());
+            truncated = true;
+        }
+        bytes += chunk.iter().map(|row| row_size(row)).sum::<usize>();
+        options.check(result.len() + chunk.len(), bytes)?;
+        for row in chunk.drain(..) {
+            result.append(row_to_dict(py, &names, &row, &mut interner)?)?;
+        }
+        if !more || truncated {
+            break;
+        }
+    }
+
+    let count = result.len();
+    Ok((options.limited(py, result.into_any(), truncated)?, count))
+}
+
+/// Convert Rows to a dict of column lists: { column: [values...], ... }
+///
+/// Reads in chunks like `rows_to_dicts`, appending each chunk to the lists
+/// one column at a time. As with dicts per row, a repeated column name
+/// keeps the last column of that name. With `options.numpy` the columns are
+/// NumPy arrays, converted once every row has been read.
+pub fn rows_to_columnar(
+    py: Python<'_>,
+    mut rows: stoolap::api::Rows,
+    options: ConvertOptions,
+) -> PyResult<(PyObject, usize)> {
+    let columns: Vec<String> = rows.columns().to_vec();
+    if options.numpy {
+        let buffered = read_all(py, &mut rows, columns.len(), options)?;
+        return Ok((numpy_columns(py, &columns, &buffered, options.fetch)?, buffered.len()));
+    }
+    let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+    let names = interner.columns(py, &columns).into_owned();
+    let lists = empty_columns(py, &names);
+    let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+    let mut count = 0;
+    let mut bytes = 0;
+
+    loop {
+        let limit = options.chunk_rows(count);
+        let more = read_chunk(py, &mut rows, columns.len(), limit, &mut chunk)?;
+        bytes += chunk.iter().map(|row| row_size(row)).sum::<usize>();
+        options.check(count + chunk.len(), bytes)?;
+        append_columns(py, &lists, &chunk, &mut interner)?;
+        count += chunk.len();
+        chunk.clear();
+        if !more {
+            break;
+        }
+    }
+
+    Ok((columns_to_dict(py, &names, lists)?, count))
+}
+
+fn empty_columns<'py>(py: Python<'py>, columns: &[String]) -> Vec<Bound<'py, PyList>> {
+    columns.iter().map(|_| PyList::empty(py)).collect()
+}
+
+/// Append `rows` to the column lists, filling one column before the next.
+fn append_columns(
+    py: Python<'_>,
+    lists: &[Bound<'_, PyList>],
+    rows: &[Vec<Value>],
+    interner: &mut StringInterner,
+) -> PyResult<()> {
+    for (i, list) in lists.iter().enumerate() {
+        for row in rows {
+            list.append(interner.value_to_py(py, i, &row[i])?)?;
+        }
+    }
+    Ok(())
+}
+
+fn columns_to_dict(
+    py: Python<'_>,
+    columns: &[String],
+    lists: Vec<Bound<'_, PyList>>,
+) -> PyResult<PyObject> {
+    let result = PyDict::new(py);
+    for (name, list) in columns.iter().zip(lists) {
+        result.set_item(name, list)?;
+    }
+    Ok(result.into_any().unbind())
+}
+
+/// Convert Rows iterator to first row dict or None.
+pub fn first_row_to_dict(
+    py: Python<'_>,
+    mut rows: stoolap::api::Rows,
+    fetch: &FetchOptions,
+) -> PyResult<(PyObject, usize)> {
+    let columns: Vec<String> = rows.columns().to_vec();
+    let mut first = Vec::with_capacity(1);
+    read_chunk(py, &mut rows, columns.len(), 1, &mut first)?;
+
+    match first.pop() {
+        Some(row) => {
+            let mut interner = StringInterner::new(false, fetch);
+            let names = column_names(py, &interner.columns(py, &columns));
+            let dict = row_to_dict(py, &names, &row, &mut interner)?;
+            Ok((dict.into_any().unbind(), 1))
+        }
+        None => Ok((py.None(), 0)),
+    }
+}
+
+/// Convert Rows to raw format: { columns: [...], rows: [[...], ...] }, or
+/// { columns: [...], data: [column, ...] } with `options.typed`.
+///
+/// Reads and converts in chunks, like `rows_to_dicts`. Typed results are
+/// converted once every row has been read.
+pub fn rows_to_raw(
+    py: Python<'_>,
+    mut rows: stoolap::api::Rows,
+    options: ConvertOptions,
+) -> PyResult<(PyObject, usize)> {
+    let columns: Vec<String> = rows.columns().to_vec();
+    if options.typed {
+        let buffered = read_all(py, &mut rows, columns.len(), options)?;
+        let result = typed_columns(py, &columns, &buffered, options)?;
+        return Ok((result, buffered.len()));
+    }
+    let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+    let py_columns = PyList::new(py, interner.columns(py, &columns).iter())?;
+    let py_rows = PyList::empty(py);
+    let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+    let mut bytes = 0;
+
+    loop {
+        let limit = options.chunk_rows(py_rows.len());
+        let more = read_chunk(py, &mut rows, columns.len(), limit, &mut chunk)?;
+        bytes += chunk.iter().map(|row| row_size(row)).sum::<usize>();
+        options.check(py_rows.len() + chunk.len(), bytes)?;
+        for row in chunk.drain(..) {
+            let py_row = row
+                .iter()
+                .enumerate()
+                .map(|(i, val)| interner.value_to_py(py, i, val))
+                .collect::<PyResult<Vec<_>>>()?;
+            let py_row = PyList::new(py, py_row)?;
+            py_rows.append(py_row)?;
+        }
+        if !more {
+            break;
+        }
+    }
+
+    let count = py_rows.len();
+    let result = PyDict::new(py);
+    result.set_item("columns", py_columns)?;
+    result.set_item("rows", py_rows)?;
+    Ok((result.into_any().unbind(), count))
+}
+
+/// Read every row of `rows` in chunks, checking the limits in `options`
+/// after each one, for conversions that need the whole result at once.
+fn read_all(
+    py: Python<'_>,
+    rows: &mut stoolap::api::Rows,
+    width: usize,
+    options: ConvertOptions,
+) -> PyResult<Vec<Vec<Value>>> {
+    let mut buffered = Vec::new();
+    let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+    let mut bytes = 0;
+    loop {
+        let limit = options.chunk_rows(buffered.len());
+        let more = read_chunk(py, rows, width, limit, &mut chunk)?;
+        bytes += chunk.iter().map(|row| row_size(row)).sum::<usize>();
+        options.check(buffered.len() + chunk.len(), bytes)?;
+        buffered.append(&mut chunk);
+        if !more {
+            return Ok(buffered);
+        }
+    }
+}
+
+/// Element type of a typed column buffer.
+#[derive(Clone, Copy, PartialEq)]
+enum ColumnKind {
+    Integer,
+    Float,
+    Boolean,
+    /// Nanoseconds since the Unix epoch, when timestamps are returned so.
+    Timestamp,
+}
+
+impl ColumnKind {
+    /// The engine type of the column's values.
+    fn type_name(self) -> &'static str {
+        match self {
+            ColumnKind::Integer => "INTEGER",
+            ColumnKind::Float => "FLOAT",
+            ColumnKind::Boolean => "BOOLEAN",
+            ColumnKind::Timestamp => "TIMESTAMP",
+        }
+    }
+}
+
+/// The buffer type for column `i`, when every non-NULL value in it is an
+/// integer, float or boolean of the same type, or a timestamp that
+/// `fetch` returns in nanoseconds.
+fn column_kind(rows: &[Vec<Value>], i: usize, fetch: &FetchOptions) -> Option<ColumnKind> {
+    let mut kind = None;
+    for row in rows {
+        let value_kind = match &row[i] {
+            Value::Integer(_) => ColumnKind::Integer,
+            Value::Float(_) => ColumnKind::Float,
+            Value::Boolean(_) => ColumnKind::Boolean,
+            Value::Timestamp(ts) if fetch.in_nanoseconds() => {
+                // Outside 1677-2262 they do not fit in an int64
+                ts.timestamp_nanos_opt()?;
+                ColumnKind::Timestamp
+            }
+            Value::Null(_) => continue,
+            _ => return None,
+        };
+        if kind.is_some_and(|kind| kind != value_kind) {
+            return None;
+        }
+        kind = Some(value_kind);
+    }
+    kind
+}
+
+/// Typed raw format: { columns: [...], data: [column, ...] }.
+///
+/// Integer, float and boolean columns without NULLs become `array.array`
+/// buffers of type 'q', 'd' and 'B' (0 or 1), filled from native-endian
+/// bytes without creating a Python object per value. Timestamps returned in
+/// nanoseconds are 'q' buffers too. Other columns, columns with a
+/// registered converter, and every column of an empty result, are lists.
+fn typed_columns(
+    py: Python<'_>,
+    columns: &[String],
+    rows: &[Vec<Value>],
+    options: ConvertOptions,
+) -> PyResult<PyObject> {
+    let array = py.import("array")?.getattr("array")?;
+    let mut interner = StringInterner::new(options.intern_strings, options.fetch);
+    let columns = interner.columns(py, columns);
+    let data = PyList::empty(py);
+    for i in 0..columns.len() {
+        let kind = column_kind(rows, i, options.fetch)
+            .filter(|kind| !has_nulls(rows, i) && !interner.converts(i, kind.type_name()));
+        let Some(kind) = kind else {
+            let values = rows
+                .iter()
+                .map(|row| interner.value_to_py(py, i, &row[i]))
+                .collect::<PyResult<Vec<_>>>()?;
+            data.append(PyList::new(py, values)?)?;
+            continue;
+        };
+        let (code, bytes): (&str, Vec<u8>) = match kind {
+            ColumnKind::Integer => (
+                "q",
+                rows.iter()
+                    .flat_map(|row| match &row[i] {
+                        Value::Integer(v) => v.to_ne_bytes(),
+                        _ => [0; 8],
+                    })
+                    .collect(),
+            ),
+            ColumnKind::Float => (
+                "d",
+                rows.iter()
+                    .flat_map(|row| match &row[i] {
+                        Value::Float(v) => v.to_ne_bytes(),
+                        _ => [0; 8],
+                    })
+                    .collect(),
+            ),
+            ColumnKind::Boolean => (
+                "B",
+                rows.iter()
+                    .map(|row| matches!(&row[i], Value::Boolean(true)) as u8)
+                    .collect(),
+            ),
+            ColumnKind::Timestamp => (
+                "q",
+                rows.iter()
+                    .flat_map(|row| timestamp_nanos(&row[i]).to_ne_bytes())
+                    .collect(),
+            ),
+        };
+        let buffer = array.call1((code,))?;
+        buffer.call_method1("frombytes", (PyBytes::new(py, &bytes),))?;
+        data.append(buffer)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("columns", PyList::new(py, columns.iter())?)?;
+    result.set_item("data", data)?;
+    Ok(result.into_any().unbind())
+}
+
+/// Nanoseconds since the Unix epoch of a timestamp in a `ColumnKind::Timestamp`
+/// column, or 0 for NULL.
+fn timestamp_nanos(value: &Value) -> i64 {
+    match value {
+        Value::Timestamp(ts) => ts.timestamp_nanos_opt().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+fn has_nulls(rows: &[Vec<Value>], i: usize) -> bool {
+    rows.iter().any(|row| matches!(row[i], Value::Null(_)))
+}
+
+fn import_numpy(py: Python<'_>) -> PyResult<Bound<'_, PyModule>> {
+    py.import("numpy")
+        .map_err(|_| PyImportError::new_err("numpy=True requires numpy (pip install numpy)"))
+}
+
+/// Columns as NumPy arrays: { column: ndarray, ... }.
+///
+/// Integer, float and boolean columns become int64, float64 and bool
+/// arrays, copied in through the buffer protocol without creating a Python
+/// object per value, and so do timestamps, as datetime64[ns], when the
+/// connection returns them in nanoseconds. If such a column has NULLs it is a
+/// `numpy.ma.MaskedArray` whose mask marks them. Other columns are object
+/// arrays. Registered converters do not apply.
+fn numpy_columns(
+    py: Python<'_>,
+    columns: &[String],
+    rows: &[Vec<Value>],
+    fetch: &FetchOptions,
+) -> PyResult<PyObject> {
+    let numpy = import_numpy(py)?;
+    let result = PyDict::new(py);
+    for (i, name) in columns.iter().enumerate() {
+        result.set_item(name, numpy_column(py, &numpy, rows, i, fetch)?)?;
     }
+    Ok(result.into_any().unbind())
+}
 
-    if !current.is_empty() {
-        statements.push(current);
+fn numpy_column<'py>(
+    py: Python<'py>,
+    numpy: &Bound<'py, PyModule>,
+    rows: &[Vec<Value>],
+    i: usize,
+    fetch: &FetchOptions,
+) -> PyResult<Bound<'py, PyAny>> {
+    let Some(kind) = column_kind(rows, i, fetch) else {
+        let array = numpy.call_method1("empty", (rows.len(), "object"))?;
+        for (j, row) in rows.iter().enumerate() {
+            array.set_item(j, plain_value_to_py(py, &row[i], fetch)?)?;
+        }
+        return Ok(array);
+    };
+    let array = match kind {
+        ColumnKind::Integer => filled_array(
+            py,
+            numpy,
+            "int64",
+            rows.iter()
+                .map(|row| match &row[i] {
+                    Value::Integer(v) => *v,
+                    _ => 0,
+                })
+                .collect::<Vec<i64>>(),
+        )?,
+        ColumnKind::Float => filled_array(
+            py,
+            numpy,
+            "float64",
+            rows.iter()
+                .map(|row| match &row[i] {
+                    Value::Float(v) => *v,
+                    _ => 0.0,
+                })
+                .collect::<Vec<f64>>(),
+        )?,
+        ColumnKind::Boolean => bool_array(
+            py,
+            numpy,
+            rows.iter()
+                .map(|row| matches!(&row[i], Value::Boolean(true)))
+                .collect(),
+        )?,
+        // The buffer protocol has no datetime64 format, so the nanoseconds
+        // are written as int64 and viewed as datetime64[ns]
+        ColumnKind::Timestamp => filled_array(
+            py,
+            numpy,
+            "int64",
+            rows.iter().map(|row| timestamp_nanos(&row[i])).collect::<Vec<i64>>(),
+        )?
+        .call_method1("view", ("datetime64[ns]",))?,
+    };
+    if !has_nulls(rows, i) {
+        return Ok(array);
     }
+    let mask = rows.iter().map(|row| matches!(row[i], Value::Null(_))).collect();
+    let mask = bool_array(py, numpy, mask)?;
+    numpy.getattr("ma")?.call_method1("MaskedArray", (array, mask))
+}
+
+/// A new NumPy array of `dtype` holding `values`, written into the array's
+/// memory through the buffer protocol.
+fn filled_array<'py, T: Element>(
+    py: Python<'py>,
+    numpy: &Bound<'py, PyModule>,
+    dtype: &str,
+    values: Vec<T>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let array = numpy.call_method1("empty", (values.len(), dtype))?;
+    let buffer = PyBuffer::<T>::get(&array)?;
+    buffer.copy_from_slice(py, &values)?;
+    buffer.release(py);
+    Ok(array)
+}
 
-    statements
+/// A NumPy bool array holding `values`. `bool` has no buffer element type,
+/// so the values are written as uint8 0/1 and the array is viewed as bool.
+fn bool_array<'py>(
+    py: Python<'py>,
+    numpy: &Bound<'py, PyModule>,
+    values: Vec<bool>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let bytes = values.into_iter().map(u8::from).collect::<Vec<u8>>();
+    filled_array(py, numpy, "uint8", bytes)?.call_method1("view", (numpy.getattr("bool_")?,))
 }
@@ -0,0 +1,210 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Table definitions given in Python, for `Database.create_table()`.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+
+use crate::sql;
+use crate::value::BindOptions;
+
+/// One column of a table created with `Database.create_table()`.
+///
+///     db.create_table("users", [
+///         Column("id", "INTEGER", primary_key=True),
+///         Column("email", "TEXT", not_null=True, unique=True),
+///         Column("active", "BOOLEAN", default=True),
+///     ])
+///
+/// `type` is used as written; `default` is a Python value, rendered as a
+/// SQL literal.
+#[pyclass(name = "Column", frozen)]
+pub struct Column {
+    name: String,
+    r#type: String,
+    primary_key: bool,
+    not_null: bool,
+    unique: bool,
+    default: Option<PyObject>,
+}
+
+#[pymethods]
+impl Column {
+    #[new]
+    #[pyo3(signature = (name, r#type, *, primary_key=false, not_null=false, unique=false, default=None))]
+    fn new(
+        name: String,
+        r#type: String,
+        primary_key: bool,
+        not_null: bool,
+        unique: bool,
+        default: Option<PyObject>,
+    ) -> PyResult<Self> {
+        if name.is_empty() {
+            return Err(PyValueError::new_err("column name must not be empty"));
+        }
+        if r#type.trim().is_empty() {
+            return Err(PyValueError::new_err(format!("column '{name}' needs a type")));
+        }
+        Ok(Self {
+            name,
+            r#type,
+            primary_key,
+            not_null,
+            unique,
+            default,
+        })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[getter]
+    fn r#type(&self) -> &str {
+        &self.r#type
+    }
+
+    #[getter]
+    fn primary_key(&self) -> bool {
+        self.primary_key
+    }
+
+    #[getter]
+    fn not_null(&self) -> bool {
+        self.not_null
+    }
+
+    #[getter]
+    fn unique(&self) -> bool {
+        self.unique
+    }
+
+    #[getter]
+    fn default(&self, py: Python<'_>) -> Option<PyObject> {
+        self.default.as_ref().map(|d| d.clone_ref(py))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let mut repr = format!("Column({:?}, {:?}", self.name, self.r#type);
+        for (flag, set) in [
+            ("primary_key", self.primary_key),
+            ("not_null", self.not_null),
+            ("unique", self.unique),
+        ] {
+            if set {
+                repr.push_str(&format!(", {flag}=True"));
+            }
+        }
+        if let Some(default) = &self.default {
+            repr.push_str(&format!(", default={}", default.bind(py).repr()?));
+        }
+        repr.push(')');
+        Ok(repr)
+    }
+}
+
+impl Column {
+    /// The column's definition in `CREATE TABLE`, declaring it the primary
+    /// key when `inline_key` is set and it is one, and NOT NULL when it is
+    /// part of a composite key.
+    fn definition(
+        &self,
+        py: Python<'_>,
+        binding: &BindOptions,
+        inline_key: bool,
+    ) -> PyResult<String> {
+        let mut definition = format!("{} {}", sql::quote_ident(&self.name)?, self.r#type.trim());
+        if self.primary_key && inline_key {
+            definition.push_str(" PRIMARY KEY");
+        }
+        if self.not_null || (self.primary_key && !inline_key) {
+            definition.push_str(" NOT NULL");
+        }
+        if self.unique {
+            definition.push_str(" UNIQUE");
+        }
+        if let Some(default) = &self.default {
+            let value = binding.value(default.bind(py))?;
+            definition.push_str(&format!(" DEFAULT {}", sql::literal(&value)));
+        }
+        Ok(definition)
+    }
+}
+
+/// The `CREATE TABLE` statement for `table` with `columns`: a dict of
+/// column names to definitions such as "TEXT NOT NULL", or a list of
+/// `Column`s. Identifiers are quoted; definitions and types are used as
+/// written.
+pub fn create_table(
+    binding: &BindOptions,
+    table: &str,
+    columns: &Bound<'_, PyAny>,
+    if_not_exists: bool,
+) -> PyResult<String> {
+    let mut definitions = Vec::new();
+    if let Ok(columns) = columns.downcast::<PyDict>() {
+        for (name, definition) in columns.iter() {
+            let name: String = name.extract()?;
+            let definition = definition.downcast_into::<PyString>().map_err(|_| {
+                PyTypeError::new_err(format!(
+                    "the definition of column '{name}' must be a str such as \"TEXT NOT NULL\""
+                ))
+            })?;
+            let definition = definition.to_str()?.trim();
+            if definition.is_empty() {
+                return Err(PyValueError::new_err(format!("column '{name}' needs a type")));
+            }
+            definitions.push(format!("{} {definition}", sql::quote_ident(&name)?));
+        }
+    } else {
+        let mut list = Vec::new();
+        for column in columns.try_iter()? {
+            let column = column?.downcast_into::<Column>().map_err(|_| {
+                PyTypeError::new_err("columns must be a dict of definitions or a list of Column")
+            })?;
+            list.push(column);
+        }
+        let key: Vec<String> = list
+            .iter()
+            .map(|c| c.get())
+            .filter(|c| c.primary_key)
+            .map(|c| sql::quote_ident(&c.name))
+            .collect::<PyResult<_>>()?;
+        // A single key column is declared inline. The engine ignores a
+        // table-level PRIMARY KEY, so a composite key is enforced with a
+        // unique index over its columns, which are NOT NULL
+        for column in &list {
+            let definition = column.get().definition(columns.py(), binding, key.len() == 1)?;
+            definitions.push(definition);
+        }
+        if key.len() > 1 {
+            definitions.push(format!("UNIQUE ({})", key.join(", ")));
+        }
+    }
+    if definitions.is_empty() {
+        return Err(PyValueError::new_err(format!(
+            "create_table('{table}') needs at least one column"
+        )));
+    }
+    Ok(format!(
+        "CREATE TABLE {}{} ({})",
+        if if_not_exists { "IF NOT EXISTS " } else { "" },
+        sql::quote_ident(table)?,
+        definitions.join(", ")
+    ))
+}
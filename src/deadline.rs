@@ -0,0 +1,196 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Statement deadlines set with `Database.time_limit()`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use stoolap::api::{Database as ApiDatabase, Rows};
+use stoolap::CachedPlanRef;
+
+use crate::connection::Connection;
+use crate::database::to_named_params;
+use crate::error::QueryTimeout;
+use crate::sql;
+use crate::value::BindParams;
+
+thread_local! {
+    /// Deadline of the statement this thread is running, while it runs.
+    static CURRENT: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Fail with QueryTimeout once the statement this thread is running is
+/// past its deadline.
+pub fn check() -> PyResult<()> {
+    match CURRENT.get() {
+        Some(deadline) => expired(deadline),
+        None => Ok(()),
+    }
+}
+
+/// Fail with QueryTimeout if `deadline` has passed.
+pub fn expired(deadline: Instant) -> PyResult<()> {
+    if Instant::now() >= deadline {
+        return Err(QueryTimeout::new_err("statement exceeded its time limit"));
+    }
+    Ok(())
+}
+
+/// Milliseconds left before the deadline of the statement this thread is
+/// running, as the engine's timeout, in which 0 means none.
+fn timeout_ms() -> Option<u64> {
+    let left = CURRENT.get()?.saturating_duration_since(Instant::now());
+    Some(u64::try_from(left.as_millis()).unwrap_or(u64::MAX).max(1))
+}
+
+/// Run query `sql` with `bind` on `db`. Under a deadline the engine gets
+/// the time left as its timeout, so it cancels the query once the deadline
+/// passes instead of running it to the end.
+pub fn query(db: &ApiDatabase, sql: &str, bind: BindParams) -> stoolap::Result<Rows> {
+    match (timeout_ms(), bind) {
+        (Some(timeout), BindParams::Positional(p)) => db.query_with_timeout(sql, p, timeout),
+        (Some(timeout), BindParams::Named(named)) => match sql::positional(sql, &named) {
+            Ok((sql, p)) => db.query_with_timeout(&sql, p, timeout),
+            Err(_) => db.query_named(sql, to_named_params(&named)),
+        },
+        (None, BindParams::Positional(p)) => db.query(sql, p),
+        (None, BindParams::Named(named)) => db.query_named(sql, to_named_params(&named)),
+    }
+}
+
+/// Run write `sql` with `bind` on `db`, under the engine's timeout like
+/// `query()`.
+pub fn execute(db: &ApiDatabase, sql: &str, bind: BindParams) -> stoolap::Result<i64> {
+    match (timeout_ms(), bind) {
+        (Some(timeout), BindParams::Positional(p)) => db.execute_with_timeout(sql, p, timeout),
+        (Some(timeout), BindParams::Named(named)) => match sql::positional(sql, &named) {
+            Ok((sql, p)) => db.execute_with_timeout(&sql, p, timeout),
+            Err(_) => db.execute_named(sql, to_named_params(&named)),
+        },
+        (None, BindParams::Positional(p)) => db.execute(sql, p),
+        (None, BindParams::Named(named)) => db.execute_named(sql, to_named_params(&named)),
+    }
+}
+
+/// Run the query of `plan`, prepared from `sql`. The engine has no timeout
+/// for prepared plans, so under a deadline `sql` runs through `query()`.
+pub fn query_plan(
+    db: &ApiDatabase,
+    plan: &CachedPlanRef,
+    sql: &str,
+    bind: BindParams,
+) -> stoolap::Result<Rows> {
+    if timeout_ms().is_some() {
+        return query(db, sql, bind);
+    }
+    match bind {
+        BindParams::Positional(p) => db.query_plan(plan, p),
+        BindParams::Named(named) => db.query_named_plan(plan, to_named_params(&named)),
+    }
+}
+
+/// Run the write of `plan`, prepared from `sql`, like `query_plan()`.
+pub fn execute_plan(
+    db: &ApiDatabase,
+    plan: &CachedPlanRef,
+    sql: &str,
+    bind: BindParams,
+) -> stoolap::Result<i64> {
+    if timeout_ms().is_some() {
+        return execute(db, sql, bind);
+    }
+    match bind {
+        BindParams::Positional(p) => db.execute_plan(plan, p),
+        BindParams::Named(named) => db.execute_named_plan(plan, to_named_params(&named)),
+    }
+}
+
+/// Make `deadline` the one `check()` sees until the guard is dropped.
+pub fn enter(deadline: Option<Instant>) -> Guard {
+    Guard(CURRENT.replace(deadline))
+}
+
+/// Restores the deadline `check()` saw before `enter()`.
+pub struct Guard(Option<Instant>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
+
+/// Context manager returned by `Database.time_limit()`.
+///
+/// While the block runs, every statement the thread runs through the
+/// database, its transactions and its prepared statements has to finish
+/// by the deadline. Nested blocks keep the earlier deadline.
+#[pyclass]
+pub struct TimeLimit {
+    conn: Arc<Connection>,
+    duration: Duration,
+    /// The thread's deadline before `__enter__`, restored by `__exit__`.
+    previous: Mutex<Option<Option<Instant>>>,
+}
+
+impl TimeLimit {
+    pub fn new(conn: Arc<Connection>, seconds: f64) -> PyResult<Self> {
+        let duration = Duration::try_from_secs_f64(seconds)
+            .ok()
+            .filter(|d| !d.is_zero())
+            .ok_or_else(|| PyValueError::new_err("seconds must be a positive number"))?;
+        Ok(Self {
+            conn,
+            duration,
+            previous: Mutex::new(None),
+        })
+    }
+}
+
+#[pymethods]
+impl TimeLimit {
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        let deadline = Instant::now() + slf.duration;
+        let previous = slf.conn.deadline();
+        let deadline = previous.map_or(deadline, |previous| previous.min(deadline));
+        slf.conn.set_deadline(Some(deadline));
+        *slf.previous.lock().unwrap_or_else(|e| e.into_inner()) = Some(previous);
+        slf
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_val=None, _exc_tb=None))]
+    fn __exit__(
+        &self,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> bool {
+        let previous = self.previous.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(previous) = previous {
+            self.conn.set_deadline(previous);
+        }
+        false
+    }
+
+    /// Seconds left before the deadline, 0.0 once it has passed.
+    #[getter]
+    fn remaining(&self) -> f64 {
+        match self.conn.deadline() {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()).as_secs_f64(),
+            None => self.duration.as_secs_f64(),
+        }
+    }
+}
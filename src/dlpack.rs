@@ -0,0 +1,231 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DLPack exchange of vectors with PyTorch, JAX and other array libraries.
+//!
+//! Only the unversioned `dltensor` capsule is produced and read, which
+//! every DLPack consumer accepts.
+
+use std::ffi::{c_void, CStr};
+
+use pyo3::exceptions::{PyBufferError, PyTypeError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+
+use crate::vector::{PyVector, VectorData};
+
+const DLTENSOR: &CStr = c"dltensor";
+
+/// `kDLCPU`, the only device vectors live on.
+pub const CPU: i32 = 1;
+
+const FLOAT: u8 = 2;
+
+#[repr(C)]
+struct DLDevice {
+    device_type: i32,
+    device_id: i32,
+}
+
+#[repr(C)]
+struct DLDataType {
+    code: u8,
+    bits: u8,
+    lanes: u16,
+}
+
+#[repr(C)]
+struct DLTensor {
+    data: *mut c_void,
+    device: DLDevice,
+    ndim: i32,
+    dtype: DLDataType,
+    shape: *mut i64,
+    strides: *mut i64,
+    byte_offset: u64,
+}
+
+#[repr(C)]
+struct DLManagedTensor {
+    dl_tensor: DLTensor,
+    manager_ctx: *mut c_void,
+    deleter: Option<unsafe extern "C" fn(*mut DLManagedTensor)>,
+}
+
+/// An exported tensor together with its shape and what keeps its elements
+/// alive, freed by the tensor's deleter.
+struct Export {
+    tensor: DLManagedTensor,
+    shape: [i64; 1],
+    /// The exported vector, whose elements are never modified.
+    _vector: Option<Py<PyVector>>,
+    /// Dequantized values of an i8 vector.
+    _values: Vec<f32>,
+}
+
+/// Export `vector` as a `dltensor` capsule. f32 and f16 vectors share their
+/// elements with the capsule; i8 vectors are exported dequantized.
+pub fn export(py: Python<'_>, vector: &Bound<'_, PyVector>) -> PyResult<PyObject> {
+    let (data, bits, dims, values) = {
+        let v = vector.borrow();
+        match &v.data {
+            VectorData::F32(values) => (values.as_ptr() as *mut c_void, 32, values.len(), vec![]),
+            VectorData::F16(values) => (values.as_ptr() as *mut c_void, 16, values.len(), vec![]),
+            VectorData::I8 { .. } => {
+                let values = v.data.to_f32();
+                (values.as_ptr() as *mut c_void, 32, values.len(), values)
+            }
+        }
+    };
+    let shared = values.is_empty();
+    let mut export = Box::new(Export {
+        tensor: DLManagedTensor {
+            dl_tensor: DLTensor {
+                data,
+                device: DLDevice {
+                    device_type: CPU,
+                    device_id: 0,
+                },
+                ndim: 1,
+                dtype: DLDataType {
+                    code: FLOAT,
+                    bits,
+                    lanes: 1,
+                },
+                shape: std::ptr::null_mut(),
+                strides: std::ptr::null_mut(),
+                byte_offset: 0,
+            },
+            manager_ctx: std::ptr::null_mut(),
+            deleter: Some(delete_export),
+        },
+        shape: [dims as i64],
+        _vector: shared.then(|| vector.clone().unbind()),
+        // Moving the Vec leaves its buffer, and so `data`, in place
+        _values: values,
+    });
+    export.tensor.dl_tensor.shape = export.shape.as_mut_ptr();
+    let export = Box::into_raw(export);
+    // SAFETY: `export` is a live allocation owned by the capsule from here
+    // on; the capsule destructor or the consumer frees it through the
+    // deleter exactly once.
+    unsafe {
+        (*export).tensor.manager_ctx = export as *mut c_void;
+        let tensor = std::ptr::addr_of_mut!((*export).tensor);
+        let capsule = ffi::PyCapsule_New(
+            tensor as *mut c_void,
+            DLTENSOR.as_ptr(),
+            Some(drop_capsule),
+        );
+        if capsule.is_null() {
+            delete_export(tensor);
+            return Err(PyErr::fetch(py));
+        }
+        Ok(PyObject::from_owned_ptr(py, capsule))
+    }
+}
+
+/// Deleter of exported tensors. Consumers may call it from any thread.
+unsafe extern "C" fn delete_export(tensor: *mut DLManagedTensor) {
+    let export = (*tensor).manager_ctx as *mut Export;
+    // Releasing the vector needs the GIL
+    Python::with_gil(|_| drop(Box::from_raw(export)));
+}
+
+/// Capsule destructor: frees the tensor unless a consumer took it, which
+/// renames the capsule to `used_dltensor`.
+unsafe extern "C" fn drop_capsule(capsule: *mut ffi::PyObject) {
+    if ffi::PyCapsule_IsValid(capsule, DLTENSOR.as_ptr()) == 1 {
+        let tensor = ffi::PyCapsule_GetPointer(capsule, DLTENSOR.as_ptr()) as *mut DLManagedTensor;
+        if let Some(deleter) = (*tensor).deleter {
+            deleter(tensor);
+        }
+    }
+}
+
+/// Whether `obj` is a `dltensor` capsule or an object exporting one.
+pub fn is_dlpack(obj: &Bound<'_, PyAny>) -> bool {
+    match obj.downcast::<PyCapsule>() {
+        Ok(capsule) => capsule.name().is_ok_and(|name| name == Some(DLTENSOR)),
+        Err(_) => obj.hasattr("__dlpack__").unwrap_or(false),
+    }
+}
+
+/// Copy the elements of a 1-D float DLPack tensor on the CPU, given as a
+/// capsule or an object with `__dlpack__`. float16 tensors keep their
+/// precision; float32 and float64 tensors give f32 vectors.
+pub fn import(obj: &Bound<'_, PyAny>) -> PyResult<VectorData> {
+    let capsule = match obj.downcast::<PyCapsule>() {
+        Ok(capsule) => capsule.clone(),
+        Err(_) => obj.call_method0("__dlpack__")?.downcast_into::<PyCapsule>()?,
+    };
+    if capsule.name()? != Some(DLTENSOR) {
+        return Err(PyValueError::new_err(
+            "expected an unconsumed 'dltensor' capsule",
+        ));
+    }
+    // The capsule is left unconsumed, so its producer still frees the
+    // tensor once the capsule is released.
+    let tensor = capsule.pointer() as *const DLManagedTensor;
+    // SAFETY: a valid `dltensor` capsule points at a DLManagedTensor that
+    // lives at least as long as the capsule, which is held here.
+    let tensor = unsafe { &(*tensor).dl_tensor };
+    if tensor.device.device_type != CPU {
+        return Err(PyBufferError::new_err(
+            "DLPack tensors must be on the CPU to be used as vectors",
+        ));
+    }
+    if tensor.ndim != 1 {
+        return Err(PyValueError::new_err(format!(
+            "a vector must be a 1-D tensor, got {} dimensions",
+            tensor.ndim
+        )));
+    }
+    let dtype = &tensor.dtype;
+    if dtype.code != FLOAT || dtype.lanes != 1 || !matches!(dtype.bits, 16 | 32 | 64) {
+        return Err(PyTypeError::new_err(
+            "a vector tensor must hold float16, float32 or float64 values",
+        ));
+    }
+    // SAFETY: shape and strides hold `ndim` (= 1) entries; null strides
+    // mean a contiguous tensor. Each element read lies inside the tensor.
+    unsafe {
+        let len = *tensor.shape as usize;
+        let stride = if tensor.strides.is_null() {
+            1
+        } else {
+            *tensor.strides as isize
+        };
+        let base = (tensor.data as *const u8).add(tensor.byte_offset as usize);
+        let at = |i: usize, size: usize| base.offset(i as isize * stride * size as isize);
+        Ok(match dtype.bits {
+            16 => VectorData::F16(
+                (0..len)
+                    .map(|i| (at(i, 2) as *const u16).read_unaligned())
+                    .collect(),
+            ),
+            32 => VectorData::F32(
+                (0..len)
+                    .map(|i| (at(i, 4) as *const f32).read_unaligned())
+                    .collect(),
+            ),
+            _ => VectorData::F32(
+                (0..len)
+                    .map(|i| (at(i, 8) as *const f64).read_unaligned() as f32)
+                    .collect(),
+            ),
+        })
+    }
+}
@@ -15,10 +15,420 @@
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 
+use crate::hooks::VectorParam;
+
 // Custom Python exception for Stoolap errors.
 pyo3::create_exception!(stoolap, StoolapError, PyRuntimeError);
 
+// Raised for write-write conflicts and serialization failures between
+// concurrent transactions. Retrying the transaction may succeed.
+pyo3::create_exception!(stoolap, ConflictError, StoolapError);
+
+// Raised when a statement violates a constraint: a duplicate primary key
+// or unique value, a NULL in a NOT NULL column, or a failed CHECK.
+pyo3::create_exception!(stoolap, IntegrityError, StoolapError);
+
+// Raised when a query result exceeds its `max_rows` or `max_result_bytes`
+// limit. Nothing is returned for the query.
+pyo3::create_exception!(stoolap, ResultTooLarge, StoolapError);
+
+// Raised when a statement observes a cancelled `CancellationToken`.
+pyo3::create_exception!(stoolap, QueryCancelled, StoolapError);
+
+// Raised when a statement runs past the deadline of `Database.time_limit()`.
+pyo3::create_exception!(stoolap, QueryTimeout, QueryCancelled);
+
+// Raised when a bound vector's dimension does not match the VECTOR column
+// it is stored in or compared with. Carries `expected`, `actual` and the
+// offending parameter's `param_index` (and `param_name`), None when unknown.
+pyo3::create_exception!(stoolap, VectorDimensionError, StoolapError);
+
+/// Attributes every `StoolapError` carries, None unless set for the error.
+pub const DETAIL_ATTRIBUTES: [&str; 9] = [
+    "code",
+    "sqlstate",
+    "category",
+    "table",
+    "constraint_name",
+    "line",
+    "column",
+    "offset",
+    "token",
+];
+
 /// Convert a stoolap::Error into a PyErr.
+///
+/// Besides the message, the exception carries `code`, the engine's name for
+/// the error (such as "TableNotFound"), its `category` (see `category()`)
+/// and `sqlstate`, a SQLSTATE-style class. The `table` and, for constraint
+/// violations, `constraint_name` are set when the message names them.
+/// Parse errors also carry the `line` and `column` (from 1), byte `offset`
+/// (from 0) and offending `token` where the engine reports them.
 pub fn to_py(err: stoolap::Error) -> PyErr {
-    StoolapError::new_err(err.to_string())
+    // The engine cancels a statement only when it outlives the timeout
+    // `deadline` gave it
+    if matches!(err, stoolap::Error::QueryCancelled) {
+        return QueryTimeout::new_err("statement exceeded its time limit");
+    }
+    let code = variant_name(&format!("{err:?}"));
+    new_err(code, err.to_string())
+}
+
+/// A `StoolapError` (or subclass) for a parse error of the binding's own
+/// parser calls, with the same details as `to_py()` gives.
+pub fn parse_error(message: String) -> PyErr {
+    new_err(Some("ParseError".to_string()), message)
+}
+
+fn new_err(code: Option<String>, message: String) -> PyErr {
+    let lower = message.to_ascii_lowercase();
+    let category = category(&message);
+    let state = sqlstate(category, &lower);
+    let table = name_after(&message, &["table or view", "table"]).or_else(|| match category {
+        "missing_table" => name_after(&message, &["not found", "does not exist"]),
+        _ => None,
+    });
+    let constraint_name = match category {
+        "constraint" => name_after(&message, &["constraint", "unique index", "index"]),
+        _ => None,
+    };
+    let position = match category {
+        "syntax" => Position::parse(&message),
+        _ => None,
+    };
+    let err = if dimensions(&message).is_some() {
+        VectorDimensionError::new_err(message)
+    } else if is_conflict(&message) {
+        ConflictError::new_err(message)
+    } else if is_constraint_violation(&message) {
+        IntegrityError::new_err(message)
+    } else {
+        StoolapError::new_err(message)
+    };
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        let _ = value.setattr("code", code);
+        let _ = value.setattr("sqlstate", state);
+        let _ = value.setattr("category", category);
+        let _ = value.setattr("table", table);
+        let _ = value.setattr("constraint_name", constraint_name);
+        if let Some(position) = position {
+            let _ = value.setattr("line", position.line);
+            let _ = value.setattr("column", position.column);
+            let _ = value.setattr("offset", position.offset);
+            let _ = value.setattr("token", position.token);
+        }
+    });
+    err
+}
+
+/// The variant name leading the `Debug` output of an engine error.
+fn variant_name(debug: &str) -> Option<String> {
+    let name: String = debug
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    name.starts_with(|c: char| c.is_ascii_uppercase()).then_some(name)
+}
+
+/// The category of an engine error message: "constraint", "conflict",
+/// "type_mismatch", "missing_table", "missing_column", "already_exists",
+/// "syntax", "io", "transaction" or "other".
+fn category(message: &str) -> &'static str {
+    let lower = message.to_ascii_lowercase();
+    let has = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+    let missing = has(&["not found", "does not exist", "unknown", "no such"]);
+    if dimensions(message).is_some() {
+        "type_mismatch"
+    } else if is_conflict(message) {
+        "conflict"
+    } else if is_constraint_violation(message) {
+        "constraint"
+    } else if has(&["parse error", "parsing", "syntax", "unexpected"]) {
+        "syntax"
+    } else if has(&["table"]) && missing {
+        "missing_table"
+    } else if has(&["column"]) && missing {
+        "missing_column"
+    } else if has(&["already exists"]) {
+        "already_exists"
+    } else if has(&[
+        "cannot convert",
+        "invalid type",
+        "type mismatch",
+        "incompatible type",
+        "out of range",
+    ]) {
+        "type_mismatch"
+    } else if has(&[
+        "i/o",
+        "io error",
+        "permission denied",
+        "no such file",
+        "disk full",
+        "no space left",
+        "read-only file system",
+    ]) {
+        "io"
+    } else if has(&["transaction"]) && has(&["not active", "closed", "already"]) {
+        "transaction"
+    } else {
+        "other"
+    }
+}
+
+/// The SQLSTATE-style class of an error of `category`, whose message in
+/// lowercase is `lower`.
+fn sqlstate(category: &str, lower: &str) -> &'static str {
+    let has = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+    match category {
+        "constraint" if has(&["not null", "cannot be null"]) => "23502",
+        "constraint" if has(&["foreign key"]) => "23503",
+        "constraint" if has(&["check"]) => "23514",
+        "constraint" if has(&["unique", "duplicate"]) => "23505",
+        "constraint" => "23000",
+        "conflict" => "40001",
+        "type_mismatch" => "22000",
+        "missing_table" => "42P01",
+        "missing_column" => "42703",
+        "already_exists" => "42P07",
+        "syntax" => "42601",
+        "io" => "58030",
+        "transaction" => "25000",
+        _ if has(&["division by zero", "divide by zero"]) => "22012",
+        _ => "XX000",
+    }
+}
+
+/// The name following the first of `keys` in `message`: quoted text, or a
+/// bare identifier that is not a word of the message's own phrasing.
+fn name_after(message: &str, keys: &[&str]) -> Option<String> {
+    const WORDS: [&str; 12] = [
+        "not", "does", "is", "was", "has", "with", "already", "exists", "found", "violation",
+        "violated", "failed",
+    ];
+    let lower = message.to_ascii_lowercase();
+    keys.iter().find_map(|key| {
+        lower.match_indices(key).find_map(|(at, _)| {
+            let rest = message[at + key.len()..].trim_start_matches([' ', ':', '=']);
+            let mut chars = rest.chars();
+            match chars.next()? {
+                quote @ ('\'' | '"' | '`') => {
+                    let name: String = chars.take_while(|c| *c != quote).collect();
+                    (!name.is_empty()).then_some(name)
+                }
+                _ => {
+                    let name: String = rest
+                        .chars()
+                        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+                        .collect();
+                    let word = name.to_ascii_lowercase();
+                    (!name.is_empty() && !WORDS.contains(&word.as_str())).then_some(name)
+                }
+            }
+        })
+    })
+}
+
+/// Where in the statement a parse error occurred, as reported in its
+/// message: "line 2, column 5", "position 17" or "at offset 17", and a
+/// quoted or bare token after "token" or "near".
+struct Position {
+    line: Option<usize>,
+    column: Option<usize>,
+    offset: Option<usize>,
+    token: Option<String>,
+}
+
+impl Position {
+    fn parse(message: &str) -> Option<Self> {
+        let lower = message.to_ascii_lowercase();
+        let number = |keys: &[&str]| keys.iter().find_map(|key| number_after(&lower, key));
+        let position = Position {
+            line: number(&["line"]),
+            column: number(&["column", "col"]),
+            offset: number(&["offset", "position", "pos"]),
+            token: ["token", "near"]
+                .iter()
+                .find_map(|key| token_after(message, &lower, key)),
+        };
+        let found = position.line.is_some() || position.offset.is_some();
+        found.then_some(position)
+    }
+}
+
+/// The number following the word `key` in `lower`, skipping ':' and spaces.
+fn number_after(lower: &str, key: &str) -> Option<usize> {
+    lower.match_indices(key).find_map(|(at, _)| {
+        let before = lower[..at].chars().next_back();
+        if before.is_some_and(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let rest = lower[at + key.len()..].trim_start_matches([' ', ':', '=']);
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    })
+}
+
+/// The token following the word `key`: the quoted text, or the next word.
+fn token_after(message: &str, lower: &str, key: &str) -> Option<String> {
+    let at = lower.find(key)? + key.len();
+    let rest = message[at..].trim_start_matches([' ', ':', '=']);
+    let mut chars = rest.chars();
+    match chars.next()? {
+        quote @ ('\'' | '"' | '`') => {
+            let token: String = chars.take_while(|c| *c != quote).collect();
+            Some(token)
+        }
+        _ => {
+            let token: String = rest
+                .chars()
+                .take_while(|c| !c.is_whitespace() && *c != ',')
+                .collect();
+            (!token.is_empty()).then_some(token)
+        }
+    }
+}
+
+/// Complete the position of a parse error raised for `sql`: the byte
+/// offset from the line and column, or the line and column from the
+/// offset. Other errors are untouched.
+pub fn locate(py: Python<'_>, err: &PyErr, sql: &str) {
+    if !err.is_instance_of::<StoolapError>(py) {
+        return;
+    }
+    let value = err.value(py);
+    let get = |attr: &str| {
+        value
+            .getattr(attr)
+            .ok()
+            .and_then(|v| v.extract::<Option<usize>>().ok())
+            .flatten()
+    };
+    match (get("line"), get("column"), get("offset")) {
+        (Some(line), column, None) => {
+            if let Some(offset) = offset_of(sql, line, column.unwrap_or(1)) {
+                let _ = value.setattr("offset", offset);
+            }
+        }
+        (None, None, Some(offset)) if offset <= sql.len() && sql.is_char_boundary(offset) => {
+            let before = &sql[..offset];
+            let line = before.matches('\n').count() + 1;
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            let _ = value.setattr("line", line);
+            let _ = value.setattr("column", before[line_start..].chars().count() + 1);
+        }
+        _ => {}
+    }
+}
+
+/// Byte offset of `line` and `column` (both from 1, the column counted in
+/// characters) in `sql`.
+fn offset_of(sql: &str, line: usize, column: usize) -> Option<usize> {
+    let mut start = 0;
+    for (number, text) in sql.split('\n').enumerate() {
+        if number + 1 == line {
+            let within = text
+                .char_indices()
+                .nth(column.saturating_sub(1))
+                .map_or(text.len(), |(i, _)| i);
+            return Some(start + within);
+        }
+        start += text.len() + 1;
+    }
+    None
+}
+
+/// Whether an engine error reports a conflict with another transaction.
+fn is_conflict(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "write conflict",
+        "write-write conflict",
+        "transaction conflict",
+        "serialization failure",
+        "could not serialize",
+        "deadlock",
+        "lock timeout",
+        "lock wait timeout",
+        // A second transaction writing a row another has changed
+        "has uncommitted changes from transaction",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Whether an engine error reports a violated constraint.
+fn is_constraint_violation(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "constraint",
+        "violat",
+        "duplicate key",
+        "duplicate primary key",
+        "duplicate value",
+        "duplicate entry",
+        "cannot be null",
+        "non-nullable column",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// The expected and actual sizes reported by a vector dimension mismatch.
+fn dimensions(message: &str) -> Option<(usize, usize)> {
+    let lower = message.to_ascii_lowercase();
+    if !lower.contains("dimension") || !(lower.contains("mismatch") || lower.contains("expected")) {
+        return None;
+    }
+    let mut numbers = message
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|digits| digits.parse().ok());
+    let (first, second) = (numbers.next()?, numbers.next()?);
+    // "got 2, expected 3" names the actual size first
+    let actual_first = match (lower.find("got"), lower.find("expected")) {
+        (Some(got), Some(expected)) => got < expected,
+        _ => false,
+    };
+    Some(if actual_first { (second, first) } else { (first, second) })
+}
+
+/// A `VectorDimensionError` for a vector of `actual` dimensions passed as
+/// parameter `param_index` where `expected` were needed.
+pub fn dimension_error(py: Python<'_>, expected: usize, actual: usize, param_index: usize) -> PyErr {
+    let err = VectorDimensionError::new_err(format!(
+        "vector dimension mismatch: expected {expected}, got {actual}"
+    ));
+    let value = err.value(py);
+    let _ = value.setattr("expected", expected);
+    let _ = value.setattr("actual", actual);
+    let _ = value.setattr("param_index", param_index);
+    err
+}
+
+/// Fill in the sizes and the offending parameter of a `VectorDimensionError`
+/// raised for a statement bound with `vectors`. Other errors are untouched.
+pub fn annotate(py: Python<'_>, err: &PyErr, vectors: &[VectorParam]) {
+    if !err.is_instance_of::<VectorDimensionError>(py) {
+        return;
+    }
+    let value = err.value(py);
+    let Some((expected, actual)) = value
+        .str()
+        .ok()
+        .and_then(|message| dimensions(&message.to_string_lossy()))
+    else {
+        return;
+    };
+    // The vector of the reported size, or the only one bound
+    let param = vectors.iter().find(|v| v.dims == actual).or(match vectors {
+        [only] => Some(only),
+        _ => None,
+    });
+    let _ = value.setattr("expected", expected);
+    let _ = value.setattr("actual", actual);
+    if let Some(param) = param {
+        let _ = value.setattr("param_index", param.index);
+        let _ = value.setattr("param_name", param.name.as_deref());
+    }
 }
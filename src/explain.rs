@@ -0,0 +1,238 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Query plans, parsed from the engine's `EXPLAIN` output.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+/// How `Database.explain` returns the plan.
+#[derive(Clone, Copy)]
+pub enum Format {
+    /// The engine's `EXPLAIN` lines, joined by newlines.
+    Text,
+    /// Nested dicts, one per plan node.
+    Dict,
+    /// An indented tree drawn with box-drawing characters.
+    Tree,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> PyResult<Self> {
+        match format {
+            "text" => Ok(Self::Text),
+            "dict" => Ok(Self::Dict),
+            "tree" => Ok(Self::Tree),
+            _ => Err(PyValueError::new_err(format!(
+                "format must be 'text', 'dict' or 'tree', got '{format}'"
+            ))),
+        }
+    }
+}
+
+/// One node of a plan: an operator such as `Seq Scan on t`, the figures
+/// printed after it, the detail lines printed under it (`Filter: ...`),
+/// and its inputs.
+pub struct PlanNode {
+    pub name: String,
+    /// The node's line as printed, figures included.
+    pub line: String,
+    pub figures: Figures,
+    pub details: Vec<String>,
+    pub children: Vec<PlanNode>,
+}
+
+/// The planner's estimates for a node, as in `(cost=12.50 rows=100)`, and
+/// under `EXPLAIN ANALYZE` what running it took, as in
+/// `(actual time=0.05..1.20 rows=98 loops=1)`.
+#[derive(Default)]
+pub struct Figures {
+    pub cost: Option<f64>,
+    pub rows: Option<f64>,
+    pub actual_rows: Option<u64>,
+    pub actual_time_ms: Option<f64>,
+}
+
+impl Figures {
+    /// Split the parenthesized figures off the end of a node's line.
+    fn split(line: &str) -> (&str, Self) {
+        let mut name = line.trim_end();
+        let mut figures = Self::default();
+        while let Some(inner) = name.strip_suffix(')') {
+            let Some(open) = inner.rfind('(') else {
+                break;
+            };
+            if !figures.read(&inner[open + 1..]) {
+                break;
+            }
+            name = inner[..open].trim_end();
+        }
+        (name, figures)
+    }
+
+    /// Read `key=value` figures from one parenthesized group. Returns false
+    /// when the group has none, such as a join condition.
+    fn read(&mut self, group: &str) -> bool {
+        let actual = group.trim_start().starts_with("actual");
+        let mut found = false;
+        for (key, value) in group.split_whitespace().filter_map(|token| token.split_once('=')) {
+            let value = value.trim_end_matches(',');
+            match (actual, key) {
+                (false, "cost") => self.cost = number(value),
+                (false, "rows" | "est_rows") => self.rows = number(value),
+                (true, "rows") => self.actual_rows = number(value).map(|rows| rows as u64),
+                (true, "time") => self.actual_time_ms = milliseconds(value),
+                _ => continue,
+            }
+            found = true;
+        }
+        found
+    }
+}
+
+/// The number in a figure, or the end of a `start..end` range.
+fn number(value: &str) -> Option<f64> {
+    value.rsplit("..").next()?.parse().ok()
+}
+
+/// A time figure in milliseconds. Times without a unit are milliseconds.
+fn milliseconds(value: &str) -> Option<f64> {
+    let value = value.rsplit("..").next()?;
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let scale = match &value[split..] {
+        "" | "ms" => 1.0,
+        "s" => 1000.0,
+        "us" | "µs" => 0.001,
+        "ns" => 0.000_001,
+        _ => return None,
+    };
+    value[..split].parse::<f64>().ok().map(|n| n * scale)
+}
+
+/// A node while the plan is parsed, with children by index.
+struct Pending {
+    indent: usize,
+    node: PlanNode,
+    children: Vec<usize>,
+}
+
+impl PlanNode {
+    fn new(line: &str) -> Self {
+        let (name, figures) = Figures::split(line);
+        Self {
+            name: name.to_string(),
+            line: line.to_string(),
+            figures,
+            details: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Parse `EXPLAIN` lines. The first line is the root; a line starting
+    /// with `->` is a child of the closest less indented node above it, and
+    /// any other line is a detail of that node. Returns None for no lines.
+    pub fn parse(lines: &[String]) -> Option<Self> {
+        let mut nodes: Vec<Pending> = Vec::new();
+        // Indices of the nodes enclosing the current line, outermost first
+        let mut open: Vec<usize> = Vec::new();
+
+        for line in lines {
+            let text = line.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            if nodes.is_empty() {
+                let name = text.strip_prefix("->").unwrap_or(text).trim();
+                nodes.push(Pending {
+                    indent,
+                    node: Self::new(name),
+                    children: Vec::new(),
+                });
+                open.push(0);
+                continue;
+            }
+            while open.len() > 1 && nodes[open[open.len() - 1]].indent >= indent {
+                open.pop();
+            }
+            let parent = open[open.len() - 1];
+            match text.strip_prefix("->") {
+                Some(name) => {
+                    let index = nodes.len();
+                    nodes.push(Pending {
+                        indent,
+                        node: Self::new(name.trim()),
+                        children: Vec::new(),
+                    });
+                    nodes[parent].children.push(index);
+                    open.push(index);
+                }
+                None => nodes[parent].node.details.push(text.to_string()),
+            }
+        }
+
+        fn build(nodes: &mut [Pending], index: usize) -> PlanNode {
+            let mut node = std::mem::replace(&mut nodes[index].node, PlanNode::new(""));
+            let children = std::mem::take(&mut nodes[index].children);
+            node.children = children.into_iter().map(|child| build(nodes, child)).collect();
+            node
+        }
+        (!nodes.is_empty()).then(|| build(&mut nodes, 0))
+    }
+
+    /// The node as a dict with `node`, the figures (None where the engine
+    /// printed none), `details` and `children`.
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("node", &self.name)?;
+        dict.set_item("estimated_rows", self.figures.rows)?;
+        dict.set_item("estimated_cost", self.figures.cost)?;
+        dict.set_item("actual_rows", self.figures.actual_rows)?;
+        dict.set_item("actual_time_ms", self.figures.actual_time_ms)?;
+        dict.set_item("details", &self.details)?;
+        let children = PyList::empty(py);
+        for child in &self.children {
+            children.append(child.to_dict(py)?)?;
+        }
+        dict.set_item("children", children)?;
+        Ok(dict)
+    }
+
+    /// The plan as an indented tree, one node or detail per line.
+    pub fn to_tree(&self) -> String {
+        let mut lines = Vec::new();
+        self.draw("", "", &mut lines);
+        lines.join("\n")
+    }
+
+    /// Draw the node after `first` and everything below it after `rest`.
+    fn draw(&self, first: &str, rest: &str, lines: &mut Vec<String>) {
+        lines.push(format!("{first}{}", self.line));
+        let bar = if self.children.is_empty() { "    " } else { "│   " };
+        for detail in &self.details {
+            lines.push(format!("{rest}{bar}{detail}"));
+        }
+        for (i, child) in self.children.iter().enumerate() {
+            let (branch, indent) = if i + 1 == self.children.len() {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            child.draw(&format!("{rest}{branch}"), &format!("{rest}{indent}"), lines);
+        }
+    }
+}
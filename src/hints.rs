@@ -0,0 +1,89 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-statement hints, passed as `hints=` to `Database.query()`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::ThreadPool;
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::connection;
+
+/// Every hint, by name.
+const HINTS: [&str; 1] = ["parallel"];
+
+thread_local! {
+    /// Pool chosen by the `parallel` hint of the statement this thread is
+    /// running, while it runs.
+    static POOL: RefCell<Option<Arc<ThreadPool>>> = const { RefCell::new(None) };
+}
+
+/// The hints given to one statement.
+#[derive(Default)]
+pub struct Hints {
+    /// Threads for the statement's parallel operators (`parallel`).
+    pool: Option<Arc<ThreadPool>>,
+}
+
+impl Hints {
+    /// Check a `hints=` dict. An unknown hint raises ValueError, rather
+    /// than leaving the statement untuned without notice.
+    pub fn parse(hints: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let mut parsed = Self::default();
+        for (name, value) in hints.into_iter().flatten() {
+            let name: String = name.extract()?;
+            match name.as_str() {
+                "parallel" => {
+                    let threads = value.extract::<usize>().ok().filter(|n| *n > 0);
+                    let threads = threads.ok_or_else(|| {
+                        PyValueError::new_err("hint 'parallel' must be at least 1 thread")
+                    })?;
+                    // More threads than CPUs only adds contention
+                    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+                    parsed.pool = Some(connection::thread_pool(threads.min(cpus))?);
+                }
+                _ => {
+                    return Err(PyValueError::new_err(format!(
+                        "unknown hint '{name}'; hints are {}",
+                        HINTS.join(", ")
+                    )))
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Apply the hints to the engine work this thread does until the guard
+    /// is dropped.
+    pub fn enter(&self) -> Guard {
+        Guard(POOL.replace(self.pool.clone()))
+    }
+}
+
+/// The pool chosen by the running statement's `parallel` hint.
+pub fn pool() -> Option<Arc<ThreadPool>> {
+    POOL.with_borrow(Clone::clone)
+}
+
+/// Restores the hints in effect before `Hints::enter()`.
+pub struct Guard(Option<Arc<ThreadPool>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        POOL.set(self.0.take());
+    }
+}
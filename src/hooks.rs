@@ -0,0 +1,772 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use stoolap::api::ParamVec;
+use stoolap::core::Value;
+
+use crate::value::BindParams;
+use crate::wal::WalLog;
+
+/// Longest text value shown in a parameter summary before it is cut off.
+const SUMMARY_TEXT_LIMIT: usize = 32;
+
+/// What hooks are told about the parameters bound to a statement.
+#[derive(Default)]
+pub struct ParamInfo {
+    /// Number of values bound (per parameter set for batches).
+    pub count: usize,
+    /// Number of parameter sets, for batches only.
+    pub batch: Option<usize>,
+    /// Summary of the values, only built while tracing asks for it.
+    pub summary: Option<String>,
+    /// Vectors among the values (of the first set, for batches).
+    pub vectors: Vec<VectorParam>,
+    /// The values themselves, only kept while WAL capture or a watch is on.
+    pub values: Option<Vec<BindParams>>,
+    /// Floats that are not whole numbers in the INTEGER range (the first
+    /// of each parameter, for batches), checked against the columns they
+    /// are written to; see `warning::coercions`.
+    pub fractional: Vec<FloatParam>,
+}
+
+/// A vector bound to a statement, kept so that a dimension mismatch
+/// reported by the engine can be traced back to its parameter.
+pub struct VectorParam {
+    /// Position in the parameter list.
+    pub index: usize,
+    /// Name, for named parameters.
+    pub name: Option<String>,
+    pub dims: usize,
+}
+
+/// A bound float that an INTEGER column would not store exactly.
+pub struct FloatParam {
+    /// Position in the parameter list.
+    pub index: usize,
+    /// Name, for named parameters.
+    pub name: Option<String>,
+    pub value: f64,
+}
+
+/// A statement that finished running, as reported to hooks.
+pub struct Event<'a> {
+    pub sql: &'a str,
+    pub duration: Duration,
+    /// Rows returned or affected; None if the statement failed.
+    pub rows: Option<u64>,
+    pub params: &'a ParamInfo,
+    /// The exception raised by the statement, if it failed.
+    pub error: Option<PyObject>,
+}
+
+/// Listener state carried from the start of a statement to its end.
+pub struct Started {
+    event: Py<PyDict>,
+    pending: Vec<(PyObject, PyObject)>,
+}
+
+struct Listener {
+    id: u64,
+    on_start: Option<PyObject>,
+    on_end: Option<PyObject>,
+}
+
+struct Watcher {
+    id: u64,
+    table: String,
+    callback: PyObject,
+}
+
+/// A committed write to a table, reported to `watch()` callbacks.
+pub struct Change {
+    operation: &'static str,
+    table: String,
+    key: Option<i64>,
+}
+
+impl Change {
+    /// Recognise the operation and target table of an INSERT, UPDATE,
+    /// DELETE or TRUNCATE statement.
+    pub fn from_sql(sql: &str) -> Option<Self> {
+        let (word, _, rest) = next_word(sql)?;
+        let (operation, rest) = match word.to_ascii_uppercase().as_str() {
+            "INSERT" => ("insert", expect_word(rest, "INTO")?),
+            "UPDATE" => ("update", rest),
+            "DELETE" => ("delete", expect_word(rest, "FROM")?),
+            "TRUNCATE" => match next_word(rest)? {
+                (word, false, after) if word.eq_ignore_ascii_case("TABLE") => ("delete", after),
+                _ => ("delete", rest),
+            },
+            _ => return None,
+        };
+        let (table, quoted, _) = next_word(rest)?;
+        let table = if quoted {
+            table
+        } else {
+            table.to_ascii_lowercase()
+        };
+        Some(Self {
+            operation,
+            table,
+            key: None,
+        })
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Attach the primary key of the row written by `sql`, when the
+    /// statement names exactly one: the `pk` value of a single-row INSERT
+    /// (found by position in `columns` when no column list is given), or an
+    /// UPDATE or DELETE whose WHERE clause includes `pk = value`. The value
+    /// may be a literal or a bound parameter.
+    pub fn with_key(
+        mut self,
+        sql: &str,
+        bind: Option<&BindParams>,
+        pk: &str,
+        columns: &[String],
+    ) -> Self {
+        use stoolap::parser::ast::{Expression, Statement};
+
+        let Ok(mut statements) = stoolap::parser::parse_sql(sql) else {
+            return self;
+        };
+        if statements.len() != 1 {
+            return self;
+        }
+        let expr = match statements.remove(0) {
+            Statement::Insert(insert) if insert.select.is_none() && insert.values.len() == 1 => {
+                let position = if insert.columns.is_empty() {
+                    columns.iter().position(|c| c.eq_ignore_ascii_case(pk))
+                } else {
+                    insert
+                        .columns
+                        .iter()
+                        .position(|c| c.value.eq_ignore_ascii_case(pk))
+                };
+                position.and_then(|i| insert.values[0].get(i).cloned())
+            }
+            Statement::Update(update) => update.where_clause.and_then(|w| key_condition(&w, pk)),
+            Statement::Delete(delete) => delete.where_clause.and_then(|w| key_condition(&w, pk)),
+            _ => None,
+        };
+        let value = match expr {
+            Some(Expression::IntegerLiteral(lit)) => Some(Value::Integer(lit.value)),
+            Some(Expression::Parameter(param)) => match bind {
+                Some(BindParams::Positional(values)) => param
+                    .index
+                    .checked_sub(1)
+                    .and_then(|i| values.get(i))
+                    .cloned(),
+                Some(BindParams::Named(named)) => {
+                    let name = param.name.trim_start_matches([':', '@', '$']);
+                    named
+                        .iter()
+                        .find(|(n, _)| n == name)
+                        .map(|(_, v)| v.clone())
+                }
+                None => None,
+            },
+            _ => None,
+        };
+        // Primary keys are integers; a value of another type would have been
+        // coerced by the engine, so leave it unreported.
+        if let Some(Value::Integer(key)) = value {
+            self.key = Some(key);
+        }
+        self
+    }
+}
+
+/// The value compared with column `pk` in `expr`, looking through ANDs.
+fn key_condition(
+    expr: &stoolap::parser::ast::Expression,
+    pk: &str,
+) -> Option<stoolap::parser::ast::Expression> {
+    use stoolap::parser::ast::{Expression, InfixOperator};
+
+    let Expression::Infix(infix) = expr else {
+        return None;
+    };
+    let is_pk = |e: &Expression| match e {
+        Expression::Identifier(id) => id.value.eq_ignore_ascii_case(pk),
+        Expression::QualifiedIdentifier(q) => q.name.value.eq_ignore_ascii_case(pk),
+        _ => false,
+    };
+    match infix.op_type {
+        InfixOperator::And => {
+            key_condition(&infix.left, pk).or_else(|| key_condition(&infix.right, pk))
+        }
+        InfixOperator::Equal if is_pk(&infix.left) => Some((*infix.right).clone()),
+        InfixOperator::Equal if is_pk(&infix.right) => Some((*infix.left).clone()),
+        _ => None,
+    }
+}
+
+struct SlowQueryHandler {
+    threshold: Duration,
+    callback: PyObject,
+}
+
+/// Where traced statements are sent.
+pub enum TraceTarget {
+    Callable(PyObject),
+    Logger { logger: PyObject, level: i32 },
+}
+
+struct Trace {
+    target: TraceTarget,
+    include_params: bool,
+}
+
+/// Python callbacks fired around statement execution.
+///
+/// `active` is checked before any timing is done, so statements pay nothing
+/// for hooks while none are registered.
+#[derive(Default)]
+pub struct Hooks {
+    active: AtomicBool,
+    wants_params: AtomicBool,
+    slow_query: Mutex<Option<SlowQueryHandler>>,
+    trace: Mutex<Option<Trace>>,
+    listeners: Mutex<Vec<Listener>>,
+    next_listener: AtomicU64,
+    audit: Mutex<Option<PyObject>>,
+    commit: Mutex<Option<PyObject>>,
+    rollback: Mutex<Option<PyObject>>,
+    watching: AtomicBool,
+    watchers: Mutex<Vec<Watcher>>,
+    pub wal: WalLog,
+}
+
+impl Hooks {
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Describe `bind` for the hooks. The value summary is only built while
+    /// tracing asks for it, and the values are only kept for WAL capture.
+    pub fn describe(&self, bind: &BindParams) -> ParamInfo {
+        let (count, vectors, fractional) = match bind {
+            BindParams::Positional(values) => (
+                values.len(),
+                vector_params(values.iter().map(|v| (None, v))),
+                float_params(values.iter().map(|v| (None, v))),
+            ),
+            BindParams::Named(named) => (
+                named.len(),
+                vector_params(named.iter().map(|(name, v)| (Some(name.as_str()), v))),
+                float_params(named.iter().map(|(name, v)| (Some(name.as_str()), v))),
+            ),
+        };
+        ParamInfo {
+            count,
+            batch: None,
+            summary: self.param_summary(bind),
+            vectors,
+            values: (self.wal.is_enabled() || self.is_watching()).then(|| vec![bind.clone()]),
+            fractional,
+        }
+    }
+
+    /// Describe a batch of parameter sets for the hooks.
+    pub fn describe_batch(&self, sets: &[ParamVec]) -> ParamInfo {
+        let summary = self
+            .wants_params
+            .load(Ordering::Relaxed)
+            .then(|| format!("<{} parameter sets>", sets.len()));
+        let vectors = sets.first().map_or_else(Vec::new, |set| {
+            vector_params(set.iter().map(|v| (None, v)))
+        });
+        let mut fractional: Vec<FloatParam> = Vec::new();
+        for set in sets {
+            for param in float_params(set.iter().map(|v| (None, v))) {
+                if !fractional.iter().any(|p| p.index == param.index) {
+                    fractional.push(param);
+                }
+            }
+        }
+        ParamInfo {
+            count: sets.first().map_or(0, |set| set.len()),
+            batch: Some(sets.len()),
+            summary,
+            vectors,
+            values: self.wal.is_enabled().then(|| {
+                sets.iter()
+                    .map(|set| BindParams::Positional(set.clone()))
+                    .collect()
+            }),
+            fractional,
+        }
+    }
+
+    fn param_summary(&self, bind: &BindParams) -> Option<String> {
+        if !self.wants_params.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(match bind {
+            BindParams::Positional(values) => summarize(values),
+            BindParams::Named(named) => {
+                let mut out = String::from("{");
+                for (i, (key, value)) in named.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    let _ = write!(out, "{key}: ");
+                    summarize_value(&mut out, value);
+                }
+                out.push('}');
+                out
+            }
+        })
+    }
+
+    pub fn set_audit(&self, callback: Option<PyObject>) {
+        let mut guard = self.audit.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = callback;
+        drop(guard);
+        self.refresh();
+    }
+
+    /// Pass a statement to the audit hook before it runs.
+    ///
+    /// Unlike the other hooks, an exception raised by the audit hook is
+    /// propagated and the statement is not executed.
+    pub fn audit(&self, py: Python<'_>, sql: &str, params: &ParamInfo) -> PyResult<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let callback = {
+            let guard = self.audit.lock().unwrap_or_else(|e| e.into_inner());
+            match guard.as_ref() {
+                Some(f) => f.clone_ref(py),
+                None => return Ok(()),
+            }
+        };
+        let event = PyDict::new(py);
+        event.set_item("sql", sql)?;
+        event.set_item("param_count", params.count)?;
+        event.set_item("batch_size", params.batch)?;
+        callback.call1(py, (event,))?;
+        Ok(())
+    }
+
+    pub fn set_commit(&self, callback: Option<PyObject>) {
+        let mut guard = self.commit.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = callback;
+        drop(guard);
+        self.refresh();
+    }
+
+    pub fn set_rollback(&self, callback: Option<PyObject>) {
+        let mut guard = self.rollback.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = callback;
+        drop(guard);
+        self.refresh();
+    }
+
+    /// Report the outcome of a transaction (explicit or autocommit) to the
+    /// commit or rollback hook.
+    pub fn transaction_finished(&self, py: Python<'_>, committed: bool) {
+        if !self.is_active() {
+            return;
+        }
+        let slot = if committed { &self.commit } else { &self.rollback };
+        let callback = {
+            let guard = slot.lock().unwrap_or_else(|e| e.into_inner());
+            guard.as_ref().map(|f| f.clone_ref(py))
+        };
+        if let Some(f) = callback {
+            if let Err(err) = f.call0(py) {
+                err.write_unraisable(py, Some(f.bind(py)));
+            }
+        }
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching.load(Ordering::Relaxed)
+    }
+
+    /// Register a callback for committed changes to `table`.
+    /// Returns an id for `unwatch`.
+    pub fn watch(&self, table: &str, callback: PyObject) -> u64 {
+        let id = self.next_listener.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut guard = self.watchers.lock().unwrap_or_else(|e| e.into_inner());
+        guard.push(Watcher {
+            id,
+            table: table.to_string(),
+            callback,
+        });
+        self.watching.store(true, Ordering::Relaxed);
+        id
+    }
+
+    /// Remove a watch. Returns false if no watch has that id.
+    pub fn unwatch(&self, id: u64) -> bool {
+        let mut guard = self.watchers.lock().unwrap_or_else(|e| e.into_inner());
+        let before = guard.len();
+        guard.retain(|w| w.id != id);
+        self.watching.store(!guard.is_empty(), Ordering::Relaxed);
+        guard.len() != before
+    }
+
+    /// Call `callback(operation, table, key)` for every watch on a changed
+    /// table. `key` is the primary key of the written row when the statement
+    /// names it (see `Change::with_key`), and None otherwise.
+    pub fn changed(&self, py: Python<'_>, changes: &[Change]) {
+        for change in changes {
+            let callbacks: Vec<PyObject> = {
+                let guard = self.watchers.lock().unwrap_or_else(|e| e.into_inner());
+                guard
+                    .iter()
+                    .filter(|w| w.table.eq_ignore_ascii_case(&change.table))
+                    .map(|w| w.callback.clone_ref(py))
+                    .collect()
+            };
+            for callback in callbacks {
+                let args = (change.operation, change.table.as_str(), change.key);
+                if let Err(err) = callback.call1(py, args) {
+                    err.write_unraisable(py, Some(callback.bind(py)));
+                }
+            }
+        }
+    }
+
+    /// Register listeners called before and after every statement.
+    /// Returns an id for `remove_listener`.
+    pub fn add_listener(&self, on_start: Option<PyObject>, on_end: Option<PyObject>) -> u64 {
+        let id = self.next_listener.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut guard = self.listeners.lock().unwrap_or_else(|e| e.into_inner());
+        guard.push(Listener {
+            id,
+            on_start,
+            on_end,
+        });
+        drop(guard);
+        self.refresh();
+        id
+    }
+
+    /// Remove a listener. Returns false if no listener has that id.
+    pub fn remove_listener(&self, id: u64) -> bool {
+        let mut guard = self.listeners.lock().unwrap_or_else(|e| e.into_inner());
+        let before = guard.len();
+        guard.retain(|l| l.id != id);
+        let removed = guard.len() != before;
+        drop(guard);
+        self.refresh();
+        removed
+    }
+
+    /// Notify listeners that a statement is about to run.
+    ///
+    /// Each `on_start(event)` return value is handed back to the matching
+    /// `on_end(event, context)`, so a tracer can carry its span across.
+    pub fn started(&self, py: Python<'_>, sql: &str, params: &ParamInfo) -> Option<Started> {
+        let listeners: Vec<(Option<PyObject>, Option<PyObject>)> = {
+            let guard = self.listeners.lock().unwrap_or_else(|e| e.into_inner());
+            if guard.is_empty() {
+                return None;
+            }
+            guard
+                .iter()
+                .map(|l| {
+                    (
+                        l.on_start.as_ref().map(|f| f.clone_ref(py)),
+                        l.on_end.as_ref().map(|f| f.clone_ref(py)),
+                    )
+                })
+                .collect()
+        };
+
+        let event = PyDict::new(py);
+        let filled = event
+            .set_item("sql", sql)
+            .and_then(|_| event.set_item("param_count", params.count))
+            .and_then(|_| event.set_item("batch_size", params.batch));
+        if let Err(err) = filled {
+            err.write_unraisable(py, None);
+            return None;
+        }
+
+        let mut pending = Vec::new();
+        for (on_start, on_end) in listeners {
+            let context = match &on_start {
+                Some(f) => match f.call1(py, (&event,)) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        err.write_unraisable(py, Some(f.bind(py)));
+                        py.None()
+                    }
+                },
+                None => py.None(),
+            };
+            if let Some(on_end) = on_end {
+                pending.push((on_end, context));
+            }
+        }
+        Some(Started {
+            event: event.unbind(),
+            pending,
+        })
+    }
+
+    pub fn set_trace(&self, target: Option<TraceTarget>, include_params: bool) {
+        let mut guard = self.trace.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = target.map(|target| Trace {
+            target,
+            include_params,
+        });
+        drop(guard);
+        self.refresh();
+    }
+
+    pub fn set_slow_query(&self, threshold: Duration, callback: Option<PyObject>) {
+        let mut guard = self.slow_query.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = callback.map(|callback| SlowQueryHandler {
+            threshold,
+            callback,
+        });
+        drop(guard);
+        self.refresh();
+    }
+
+    /// Report a finished statement to the registered hooks.
+    ///
+    /// Exceptions raised by callbacks are reported as unraisable rather than
+    /// failing the statement that triggered them.
+    pub fn finished(&self, py: Python<'_>, event: &Event<'_>, started: Option<Started>) {
+        if let Some(started) = started {
+            let dict = started.event.bind(py);
+            let duration_ms = event.duration.as_secs_f64() * 1000.0;
+            let filled = dict
+                .set_item("duration_ms", duration_ms)
+                .and_then(|_| dict.set_item("rows", event.rows))
+                .and_then(|_| dict.set_item("error", event.error.as_ref()));
+            if let Err(err) = filled {
+                err.write_unraisable(py, None);
+            }
+            for (on_end, context) in started.pending {
+                if let Err(err) = on_end.call1(py, (dict, context)) {
+                    err.write_unraisable(py, Some(on_end.bind(py)));
+                }
+            }
+        }
+
+        let slow = {
+            let guard = self.slow_query.lock().unwrap_or_else(|e| e.into_inner());
+            guard
+                .as_ref()
+                .filter(|h| event.duration >= h.threshold)
+                .map(|h| h.callback.clone_ref(py))
+        };
+        let duration_ms = event.duration.as_secs_f64() * 1000.0;
+        if let Some(callback) = slow {
+            if let Err(err) = callback.call1(py, (event.sql, duration_ms, event.rows)) {
+                err.write_unraisable(py, Some(callback.bind(py)));
+            }
+        }
+
+        let trace = {
+            let guard = self.trace.lock().unwrap_or_else(|e| e.into_inner());
+            guard.as_ref().map(|t| match &t.target {
+                TraceTarget::Callable(f) => TraceTarget::Callable(f.clone_ref(py)),
+                TraceTarget::Logger { logger, level } => TraceTarget::Logger {
+                    logger: logger.clone_ref(py),
+                    level: *level,
+                },
+            })
+        };
+        let result = match &trace {
+            Some(TraceTarget::Callable(f)) => f
+                .call1(py, (event.sql, duration_ms, event.params.summary.as_deref()))
+                .map(|_| ()),
+            Some(TraceTarget::Logger { logger, level }) => {
+                let result = match event.params.summary.as_deref() {
+                    Some(params) => logger.call_method1(
+                        py,
+                        "log",
+                        (*level, "%s (%.3f ms) params=%s", event.sql, duration_ms, params),
+                    ),
+                    None => logger.call_method1(
+                        py,
+                        "log",
+                        (*level, "%s (%.3f ms)", event.sql, duration_ms),
+                    ),
+                };
+                result.map(|_| ())
+            }
+            None => Ok(()),
+        };
+        if let (Err(err), Some(target)) = (result, &trace) {
+            let obj = match target {
+                TraceTarget::Callable(f) => f,
+                TraceTarget::Logger { logger, .. } => logger,
+            };
+            err.write_unraisable(py, Some(obj.bind(py)));
+        }
+    }
+
+    fn refresh(&self) {
+        let slow = self
+            .slow_query
+            .lock()
+            .map(|g| g.is_some())
+            .unwrap_or(false);
+        let (trace, params) = self
+            .trace
+            .lock()
+            .map(|g| match g.as_ref() {
+                Some(t) => (true, t.include_params),
+                None => (false, false),
+            })
+            .unwrap_or((false, false));
+        let listeners = self
+            .listeners
+            .lock()
+            .map(|g| !g.is_empty())
+            .unwrap_or(false);
+        let audit = self.audit.lock().map(|g| g.is_some()).unwrap_or(false);
+        let outcome = self.commit.lock().map(|g| g.is_some()).unwrap_or(false)
+            || self.rollback.lock().map(|g| g.is_some()).unwrap_or(false);
+        self.wants_params.store(params, Ordering::Relaxed);
+        self.active.store(
+            slow || trace || listeners || audit || outcome,
+            Ordering::Relaxed,
+        );
+    }
+}
+
+/// Split the next word or double-quoted identifier off `sql`, skipping
+/// whitespace and comments. Returns the word, whether it was quoted, and the
+/// remaining text.
+fn next_word(sql: &str) -> Option<(String, bool, &str)> {
+    let mut sql = sql;
+    loop {
+        sql = sql.trim_start();
+        if let Some(rest) = sql.strip_prefix("--") {
+            sql = rest.find('\n').map_or("", |i| &rest[i..]);
+        } else if let Some(rest) = sql.strip_prefix("/*") {
+            sql = rest.find("*/").map_or("", |i| &rest[i + 2..]);
+        } else {
+            break;
+        }
+    }
+    if let Some(rest) = sql.strip_prefix('"') {
+        let mut word = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c != '"' {
+                word.push(c);
+            } else if rest[i + 1..].starts_with('"') {
+                word.push('"');
+                chars.next();
+            } else {
+                return Some((word, true, &rest[i + 1..]));
+            }
+        }
+        return None;
+    }
+    let end = sql
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(sql.len());
+    if end == 0 {
+        return None;
+    }
+    Some((sql[..end].to_string(), false, &sql[end..]))
+}
+
+/// Consume the unquoted keyword `expected`, returning the remaining text.
+fn expect_word<'a>(sql: &'a str, expected: &str) -> Option<&'a str> {
+    match next_word(sql)? {
+        (word, false, rest) if word.eq_ignore_ascii_case(expected) => Some(rest),
+        _ => None,
+    }
+}
+
+/// The vectors among `values`, given with their names when named.
+fn vector_params<'a>(values: impl Iterator<Item = (Option<&'a str>, &'a Value)>) -> Vec<VectorParam> {
+    values
+        .enumerate()
+        .filter(|(_, (_, value))| matches!(value, Value::Extension(_)))
+        .filter_map(|(index, (name, value))| {
+            let dims = value.as_vector_f32()?.len();
+            Some(VectorParam {
+                index,
+                name: name.map(str::to_string),
+                dims,
+            })
+        })
+        .collect()
+}
+
+fn float_params<'a>(values: impl Iterator<Item = (Option<&'a str>, &'a Value)>) -> Vec<FloatParam> {
+    values
+        .enumerate()
+        .filter_map(|(index, (name, value))| match value {
+            Value::Float(f)
+                if !(f.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(f)) =>
+            {
+                Some(FloatParam {
+                    index,
+                    name: name.map(str::to_string),
+                    value: *f,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render positional parameters as a short, log-friendly list.
+fn summarize(values: &[Value]) -> String {
+    let mut out = String::from("[");
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        summarize_value(&mut out, value);
+    }
+    out.push(']');
+    out
+}
+
+fn summarize_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null(_) => out.push_str("NULL"),
+        Value::Text(s) => {
+            let s = s.as_str();
+            match s.char_indices().nth(SUMMARY_TEXT_LIMIT) {
+                Some((cut, _)) => {
+                    let _ = write!(out, "'{}...' ({} chars)", &s[..cut], s.chars().count());
+                }
+                None => {
+                    let _ = write!(out, "'{s}'");
+                }
+            }
+        }
+        other => {
+            let _ = write!(out, "{other}");
+        }
+    }
+}
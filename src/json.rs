@@ -0,0 +1,175 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyInt, PyString, PyTuple};
+
+/// A JSON path for the engine's JSON functions, such as `$.user.tags[0]`.
+///
+/// Built from a path string starting with `$`, or from keys and array
+/// indexes, which are quoted as needed:
+///
+///     JsonPath("$.user.name")
+///     JsonPath("user", "tags", 0)        # $.user.tags[0]
+///     JsonPath("first name")             # $."first name"
+///
+/// Bound as a parameter, a JsonPath becomes its path text:
+///
+///     db.query("SELECT JSON_EXTRACT(data, $1) AS name FROM users",
+///              [JsonPath("user", "name")])
+#[pyclass(name = "JsonPath", frozen)]
+#[derive(Clone)]
+pub struct JsonPath {
+    pub path: String,
+}
+
+#[pymethods]
+impl JsonPath {
+    #[new]
+    #[pyo3(signature = (*segments))]
+    fn new(segments: &Bound<'_, PyTuple>) -> PyResult<Self> {
+        if segments.len() == 1 {
+            if let Ok(text) = segments.get_item(0)?.downcast::<PyString>() {
+                let text = text.to_str()?;
+                if text.starts_with('$') {
+                    return Ok(JsonPath {
+                        path: text.to_string(),
+                    });
+                }
+            }
+        }
+        let mut path = String::from("$");
+        for segment in segments.iter() {
+            push_segment(&mut path, &segment)?;
+        }
+        Ok(JsonPath { path })
+    }
+
+    /// A path reaching further into the document.
+    #[pyo3(signature = (*segments))]
+    fn child(&self, segments: &Bound<'_, PyTuple>) -> PyResult<Self> {
+        let mut path = self.path.clone();
+        for segment in segments.iter() {
+            push_segment(&mut path, &segment)?;
+        }
+        Ok(JsonPath { path })
+    }
+
+    /// The path text.
+    #[getter]
+    fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn __str__(&self) -> &str {
+        &self.path
+    }
+
+    fn __repr__(&self) -> String {
+        format!("JsonPath({:?})", self.path)
+    }
+
+    fn __eq__(&self, other: &Bound<'_, PyAny>) -> bool {
+        other
+            .downcast::<JsonPath>()
+            .is_ok_and(|other| other.get().path == self.path)
+    }
+
+    fn __hash__(&self) -> u64 {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        self.path.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A value bound as a JSON document.
+///
+/// Dicts and lists are stored as JSON anyway; `Json` makes it explicit, as
+/// `strict_types` requires, and stores other values such as strings and
+/// numbers as JSON documents too. The value is serialized with
+/// `json.dumps` when bound:
+///
+///     db.execute("INSERT INTO events (data) VALUES ($1)",
+///                [Json({"kind": "click"})])
+#[pyclass(name = "Json", frozen)]
+pub struct Json {
+    pub value: PyObject,
+}
+
+#[pymethods]
+impl Json {
+    #[new]
+    fn new(value: PyObject) -> Self {
+        Json { value }
+    }
+
+    /// The wrapped value.
+    #[getter]
+    fn value(&self, py: Python<'_>) -> PyObject {
+        self.value.clone_ref(py)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Json({})", self.value.bind(py).repr()?))
+    }
+}
+
+/// Append a key (`.key`, or `."key"` when it is not a plain identifier)
+/// or an array index (`[n]`) to `path`.
+fn push_segment(path: &mut String, segment: &Bound<'_, PyAny>) -> PyResult<()> {
+    if let Ok(index) = segment.downcast::<PyInt>() {
+        let index: usize = index
+            .extract()
+            .map_err(|_| PyValueError::new_err("JSON array indexes must not be negative"))?;
+        path.push_str(&format!("[{index}]"));
+        return Ok(());
+    }
+    let Ok(key) = segment.downcast::<PyString>() else {
+        return Err(PyTypeError::new_err(format!(
+            "JSON path segments must be str keys or int indexes, got {}",
+            segment.get_type().name()?
+        )));
+    };
+    let key = key.to_str()?;
+    let plain = key.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if plain {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push_str(".\"");
+        path.push_str(&key.replace('\\', "\\\\").replace('"', "\\\""));
+        path.push('"');
+    }
+    Ok(())
+}
+
+/// The path text of a `JsonPath` or path string.
+pub fn path_text(path: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(path) = path.downcast::<JsonPath>() {
+        return Ok(path.get().path.clone());
+    }
+    let Ok(text) = path.downcast::<PyString>() else {
+        return Err(PyTypeError::new_err("path must be a JsonPath or a str"));
+    };
+    if text.to_str()?.starts_with('$') {
+        return Ok(text.to_str()?.to_string());
+    }
+    // A bare key, as in JsonPath("name")
+    let mut text = String::from("$");
+    push_segment(&mut text, path)?;
+    Ok(text)
+}
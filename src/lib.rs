@@ -12,21 +12,102 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+// Python methods take their keyword options as arguments, one per option.
+#![allow(clippy::too_many_arguments)]
+
+mod advisor;
+mod cache;
+mod cancel;
+mod connection;
+mod converter;
 mod database;
+mod ddl;
+mod deadline;
+mod dlpack;
 mod error;
+mod explain;
+mod hints;
+mod hooks;
+mod json;
+mod mapping;
+mod migrations;
+mod schema;
+mod settings;
+mod snapshot;
+mod sql;
 mod statement;
+mod table;
+mod temp_table;
 mod transaction;
 mod value;
+mod vector;
+mod wal;
+mod warning;
 
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
 /// Native Stoolap database bindings for Python.
-#[pymodule]
+///
+/// The module holds no state that relies on the GIL, so free-threaded
+/// builds (3.13t) keep the GIL disabled when it is imported.
+#[pymodule(gil_used = false)]
 fn _stoolap(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     m.add_class::<database::Database>()?;
+    m.add_class::<database::ChunkIterator>()?;
     m.add_class::<transaction::Transaction>()?;
     m.add_class::<statement::PreparedStatement>()?;
-    m.add_class::<value::PyVector>()?;
-    m.add("StoolapError", m.py().get_type::<error::StoolapError>())?;
+    m.add_class::<snapshot::Snapshot>()?;
+    m.add_class::<snapshot::RowIterator>()?;
+    m.add_class::<vector::PyVector>()?;
+    m.add_class::<json::JsonPath>()?;
+    m.add_class::<json::Json>()?;
+    m.add_class::<migrations::Migrations>()?;
+    m.add_class::<wal::WalReader>()?;
+    m.add_class::<temp_table::TempTable>()?;
+    m.add_class::<table::Table>()?;
+    m.add_class::<cancel::CancellationToken>()?;
+    m.add_class::<deadline::TimeLimit>()?;
+    m.add_class::<ddl::Column>()?;
+    m.add_function(wrap_pyfunction!(sql::quote_ident, m)?)?;
+    m.add_function(wrap_pyfunction!(sql::quote_literal, m)?)?;
+    m.add_function(wrap_pyfunction!(schema::schema_diff, m)?)?;
+    m.add_function(wrap_pyfunction!(warning::set_warning_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(value::set_nan_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(converter::register_converter, m)?)?;
+    m.add("PARSE_DECLTYPES", converter::PARSE_DECLTYPES)?;
+    m.add("PARSE_COLNAMES", converter::PARSE_COLNAMES)?;
+    m.add_function(wrap_pyfunction!(vector::cosine_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(vector::l2_distance, m)?)?;
+    m.add_function(wrap_pyfunction!(vector::dot_product, m)?)?;
+    let stoolap_error = m.py().get_type::<error::StoolapError>();
+    for attr in error::DETAIL_ATTRIBUTES {
+        stoolap_error.setattr(attr, m.py().None())?;
+    }
+    m.add("StoolapError", stoolap_error)?;
+    m.add("ConflictError", m.py().get_type::<error::ConflictError>())?;
+    m.add("IntegrityError", m.py().get_type::<error::IntegrityError>())?;
+    m.add("ResultTooLarge", m.py().get_type::<error::ResultTooLarge>())?;
+    m.add("QueryCancelled", m.py().get_type::<error::QueryCancelled>())?;
+    m.add("QueryTimeout", m.py().get_type::<error::QueryTimeout>())?;
+    m.add("StoolapWarning", m.py().get_type::<warning::StoolapWarning>())?;
+    let dimension_error = m.py().get_type::<error::VectorDimensionError>();
+    for attr in ["expected", "actual", "param_index", "param_name"] {
+        dimension_error.setattr(attr, m.py().None())?;
+    }
+    m.add("VectorDimensionError", dimension_error)?;
+    m.add("LimitedRows", database::limited_rows_type(m.py())?)?;
+
+    // Detect handles inherited by forked children (not available on Windows)
+    let os = m.py().import("os")?;
+    if os.hasattr("register_at_fork")? {
+        let kwargs = PyDict::new(m.py());
+        kwargs.set_item(
+            "after_in_child",
+            wrap_pyfunction!(connection::after_fork_in_child, m)?,
+        )?;
+        os.call_method("register_at_fork", (), Some(&kwargs))?;
+    }
     Ok(())
 }
@@ -0,0 +1,251 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mapping of Python classes to tables, for `Database.map()`.
+//!
+//! Objects are built without calling `__init__`: the instance is made with
+//! `cls.__new__(cls)` and each mapped column is set with
+//! `object.__setattr__`, so dataclasses (frozen ones included), slotted and
+//! plain classes all work.
+
+use pyo3::exceptions::{PyAttributeError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyType};
+use std::sync::{Arc, Mutex};
+
+use stoolap::api::ParamVec;
+
+use crate::database::Database;
+use crate::sql;
+use crate::table::key_condition;
+use crate::value::BindParams;
+
+/// A class mapped to a table.
+pub struct Mapping {
+    cls: Py<PyType>,
+    pub table: String,
+    /// Primary key columns, which are also in `columns`.
+    pub key: Vec<String>,
+    pub columns: Vec<String>,
+}
+
+impl Mapping {
+    pub fn new(cls: Py<PyType>, table: String, key: Vec<String>, columns: Vec<String>) -> Self {
+        Self {
+            cls,
+            table,
+            key,
+            columns,
+        }
+    }
+
+    /// An instance of the class holding the mapped columns of `row`.
+    fn instance<'py>(&self, row: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyAny>> {
+        let py = row.py();
+        let cls = self.cls.bind(py);
+        let obj = cls.call_method1("__new__", (cls,))?;
+        let setattr = py.import("builtins")?.getattr("object")?.getattr("__setattr__")?;
+        for column in &self.columns {
+            if let Some(value) = row.get_item(column)? {
+                setattr.call1((&obj, column, value))?;
+            }
+        }
+        Ok(obj)
+    }
+
+    /// Instances for a list of row dicts.
+    fn instances<'py>(&self, rows: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyList>> {
+        let objects = PyList::empty(rows.py());
+        for row in rows.try_iter()? {
+            objects.append(self.instance(&row?.downcast_into::<PyDict>()?)?)?;
+        }
+        Ok(objects)
+    }
+}
+
+/// The mappings registered on a connection.
+#[derive(Default)]
+pub struct Mappings {
+    list: Mutex<Vec<Arc<Mapping>>>,
+}
+
+impl Mappings {
+    /// Register `mapping`, replacing any earlier mapping of its class.
+    pub fn register(&self, py: Python<'_>, mapping: Mapping) {
+        let mut list = self.list.lock().unwrap_or_else(|e| e.into_inner());
+        list.retain(|m| !m.cls.bind(py).is(mapping.cls.bind(py)));
+        list.push(Arc::new(mapping));
+    }
+
+    /// The mapping of `cls`, or of the nearest mapped base class.
+    pub fn of_class(&self, cls: &Bound<'_, PyType>) -> PyResult<Arc<Mapping>> {
+        let py = cls.py();
+        let list = self.list.lock().unwrap_or_else(|e| e.into_inner());
+        for base in cls.mro().iter() {
+            if let Some(mapping) = list.iter().find(|m| m.cls.bind(py).is(&base)) {
+                return Ok(mapping.clone());
+            }
+        }
+        Err(PyTypeError::new_err(format!(
+            "{} is not mapped to a table; register it with Database.map() first",
+            cls.name()?
+        )))
+    }
+}
+
+/// The object whose primary key is `pk`, or None.
+pub fn get(
+    db: &Database,
+    py: Python<'_>,
+    mapping: &Mapping,
+    pk: &Bound<'_, PyAny>,
+) -> PyResult<PyObject> {
+    let (condition, params) = key_condition(db, &mapping.table, &mapping.key, pk)?;
+    let stmt = format!(
+        "SELECT {} FROM {} WHERE {condition} LIMIT 1",
+        column_list(&mapping.columns)?,
+        sql::quote_ident(&mapping.table)?
+    );
+    let rows = db.exec_statement(py, &stmt, BindParams::Positional(params))?;
+    let objects = mapping.instances(rows.bind(py))?;
+    if objects.is_empty() {
+        return Ok(py.None());
+    }
+    Ok(objects.get_item(0)?.unbind())
+}
+
+/// The objects for the rows matching a `where` condition from
+/// `table::condition()`.
+pub fn select(
+    db: &Database,
+    py: Python<'_>,
+    mapping: &Mapping,
+    condition: Option<String>,
+    bind: BindParams,
+    order_by: Option<&str>,
+    limit: Option<usize>,
+) -> PyResult<PyObject> {
+    let mut stmt = format!(
+        "SELECT {} FROM {}",
+        column_list(&mapping.columns)?,
+        sql::quote_ident(&mapping.table)?
+    );
+    if let Some(condition) = condition {
+        stmt.push_str(&format!(" WHERE {condition}"));
+    }
+    if let Some(order_by) = order_by {
+        stmt.push_str(&format!(" ORDER BY {order_by}"));
+    }
+    if let Some(limit) = limit {
+        stmt.push_str(&format!(" LIMIT {limit}"));
+    }
+    let rows = db.exec_statement(py, &stmt, bind)?;
+    Ok(mapping.instances(rows.bind(py))?.into_any().unbind())
+}
+
+/// Write `obj` to its table: update the row with its primary key, or
+/// insert it when there is none. Key attributes that are None are left to
+/// the table's defaults and set on `obj` from the inserted row.
+pub fn save(
+    db: &Database,
+    py: Python<'_>,
+    mapping: &Mapping,
+    obj: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let mut values = Vec::with_capacity(mapping.columns.len());
+    for column in &mapping.columns {
+        match obj.getattr(column.as_str()) {
+            Ok(value) => values.push((column, value)),
+            Err(err) if err.is_instance_of::<PyAttributeError>(py) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    let is_key = |column: &String| mapping.key.contains(column);
+    let has_key = mapping.key.iter().all(|key| {
+        values
+            .iter()
+            .any(|(column, value)| *column == key && !value.is_none())
+    });
+
+    if has_key {
+        let key: Vec<_> = values.iter().filter(|(c, _)| is_key(c)).collect();
+        let rest: Vec<_> = values.iter().filter(|(c, _)| !is_key(c)).collect();
+        let mut params = ParamVec::new();
+        let mut assignments = Vec::with_capacity(rest.len());
+        for (column, value) in &rest {
+            params.push(db.binding().value(value)?);
+            assignments.push(format!("{} = ${}", sql::quote_ident(column)?, params.len()));
+        }
+        let mut condition = Vec::with_capacity(key.len());
+        for (column, value) in &key {
+            params.push(db.binding().value(value)?);
+            condition.push(format!("{} = ${}", sql::quote_ident(column)?, params.len()));
+        }
+        let condition = condition.join(" AND ");
+        let table = sql::quote_ident(&mapping.table)?;
+        let found = if assignments.is_empty() {
+            let stmt = format!("SELECT 1 FROM {table} WHERE {condition} LIMIT 1");
+            let rows = db.exec_statement(py, &stmt, BindParams::Positional(params))?;
+            rows.bind(py).len()? > 0
+        } else {
+            let stmt = format!(
+                "UPDATE {table} SET {} WHERE {condition}",
+                assignments.join(", ")
+            );
+            let affected = db.exec_statement(py, &stmt, BindParams::Positional(params))?;
+            affected.extract::<i64>(py)? > 0
+        };
+        if found {
+            return Ok(());
+        }
+    }
+
+    let values: Vec<_> = values
+        .into_iter()
+        .filter(|(column, value)| !(is_key(column) && value.is_none()))
+        .collect();
+    let mut params = ParamVec::new();
+    let mut columns = Vec::with_capacity(values.len());
+    for (column, value) in &values {
+        params.push(db.binding().value(value)?);
+        columns.push(sql::quote_ident(column)?);
+    }
+    let placeholders: Vec<String> = (1..=params.len()).map(|i| format!("${i}")).collect();
+    let stmt = format!(
+        "INSERT INTO {} ({}) VALUES ({}) RETURNING {}",
+        sql::quote_ident(&mapping.table)?,
+        columns.join(", "),
+        placeholders.join(", "),
+        column_list(&mapping.key)?
+    );
+    let rows = db.exec_statement(py, &stmt, BindParams::Positional(params))?;
+    let setattr = py.import("builtins")?.getattr("object")?.getattr("__setattr__")?;
+    if let Some(row) = rows.bind(py).try_iter()?.next() {
+        let row = row?.downcast_into::<PyDict>()?;
+        for key in &mapping.key {
+            if let Some(value) = row.get_item(key)? {
+                setattr.call1((obj, key, value))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn column_list(columns: &[String]) -> PyResult<String> {
+    Ok(columns
+        .iter()
+        .map(|c| sql::quote_ident(c))
+        .collect::<PyResult<Vec<_>>>()?
+        .join(", "))
+}
@@ -0,0 +1,313 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use stoolap::api::ParamVec;
+use stoolap::core::Value;
+
+use crate::database::Database;
+use crate::error::StoolapError;
+use crate::sql;
+use crate::transaction::Transaction;
+use crate::value::BindParams;
+
+/// One direction of a migration.
+enum Step {
+    /// A script of one or more SQL statements.
+    Sql(String),
+    /// A callable taking the migration's `Transaction`.
+    Call(PyObject),
+}
+
+struct Migration {
+    name: String,
+    up: Step,
+    down: Option<Step>,
+}
+
+/// Versioned schema migrations, tracked in a table of the database.
+///
+/// Migrations come from `directory`, which holds `<version>_<name>.sql`
+/// files with an optional `<version>_<name>.down.sql` to undo each, and
+/// from `add()`, which also takes Python callables. Versions are integers
+/// applied in ascending order. Each migration runs in its own transaction
+/// together with the row recording it in `table`, so a failed migration
+/// leaves neither its changes nor its record behind.
+///
+/// Usage:
+///     migrations = Migrations(db, "migrations")
+///     migrations.apply()          # run everything pending
+///     migrations.rollback(3)      # undo the migrations after version 3
+#[pyclass]
+pub struct Migrations {
+    db: Py<Database>,
+    table: String,
+    migrations: BTreeMap<i64, Migration>,
+}
+
+#[pymethods]
+impl Migrations {
+    #[new]
+    #[pyo3(signature = (db, directory=None, *, table="_migrations"))]
+    fn new(db: Py<Database>, directory: Option<PathBuf>, table: &str) -> PyResult<Self> {
+        let mut migrations = Migrations {
+            db,
+            table: sql::quote_ident(table)?,
+            migrations: BTreeMap::new(),
+        };
+        if let Some(directory) = directory {
+            migrations.load(&directory)?;
+        }
+        Ok(migrations)
+    }
+
+    /// Register migration `version`. `up` and `down` are SQL scripts or
+    /// callables taking the migration's `Transaction`. Without `down` the
+    /// migration cannot be rolled back.
+    #[pyo3(signature = (version, up, down=None, *, name=None))]
+    fn add(
+        &mut self,
+        version: i64,
+        up: &Bound<'_, PyAny>,
+        down: Option<&Bound<'_, PyAny>>,
+        name: Option<String>,
+    ) -> PyResult<()> {
+        if self.migrations.contains_key(&version) {
+            return Err(PyValueError::new_err(format!(
+                "migration {version} is already defined"
+            )));
+        }
+        let migration = Migration {
+            name: name.unwrap_or_else(|| format!("migration_{version}")),
+            up: step(up)?,
+            down: down.map(step).transpose()?,
+        };
+        self.migrations.insert(version, migration);
+        Ok(())
+    }
+
+    /// Applied migrations, oldest first, as dicts with `version`, `name`
+    /// and `applied_at`.
+    fn applied(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.ensure_table(py)?;
+        let sql = format!(
+            "SELECT version, name, applied_at FROM {} ORDER BY version",
+            self.table
+        );
+        Ok(self.db.bind(py).call_method1("query", (sql,))?.unbind())
+    }
+
+    /// `(version, name)` of the migrations not applied yet, in order.
+    fn pending(&self, py: Python<'_>) -> PyResult<Vec<(i64, String)>> {
+        let applied = self.applied_versions(py)?;
+        Ok(self
+            .migrations
+            .iter()
+            .filter(|(version, _)| !applied.contains(version))
+            .map(|(version, m)| (*version, m.name.clone()))
+            .collect())
+    }
+
+    /// Highest applied version, or None before the first migration.
+    #[getter]
+    fn current_version(&self, py: Python<'_>) -> PyResult<Option<i64>> {
+        Ok(self.applied_versions(py)?.last().copied())
+    }
+
+    /// Apply pending migrations in order, up to and including `target`
+    /// when given. Stops at the first failure, which is re-raised after its
+    /// transaction is rolled back. Returns the versions applied.
+    #[pyo3(signature = (target=None))]
+    fn apply(&self, py: Python<'_>, target: Option<i64>) -> PyResult<Vec<i64>> {
+        let applied = self.applied_versions(py)?;
+        let mut done = Vec::new();
+        for (version, migration) in &self.migrations {
+            if applied.contains(version) || target.is_some_and(|target| *version > target) {
+                continue;
+            }
+            let record = format!(
+                "INSERT INTO {} (version, name, applied_at) VALUES ($1, $2, $3)",
+                self.table
+            );
+            let mut params = ParamVec::new();
+            params.push(Value::Integer(*version));
+            params.push(Value::text(&migration.name));
+            params.push(Value::Timestamp(chrono::Utc::now()));
+            self.run(py, &migration.up, &record, BindParams::Positional(params))?;
+            done.push(*version);
+        }
+        Ok(done)
+    }
+
+    /// Undo applied migrations newer than `to_version`, newest first, with
+    /// their `down` steps; `rollback(0)` undoes everything. Returns the
+    /// versions rolled back.
+    fn rollback(&self, py: Python<'_>, to_version: i64) -> PyResult<Vec<i64>> {
+        let applied = self.applied_versions(py)?;
+        let mut undone = Vec::new();
+        for version in applied.iter().rev().filter(|v| **v > to_version) {
+            let down = match self.migrations.get(version) {
+                Some(Migration { down: Some(down), .. }) => down,
+                Some(_) => {
+                    return Err(StoolapError::new_err(format!(
+                        "migration {version} has no down step and cannot be rolled back"
+                    )))
+                }
+                None => {
+                    return Err(StoolapError::new_err(format!(
+                        "migration {version} is applied but not defined"
+                    )))
+                }
+            };
+            let record = format!("DELETE FROM {} WHERE version = $1", self.table);
+            let mut params = ParamVec::new();
+            params.push(Value::Integer(*version));
+            self.run(py, down, &record, BindParams::Positional(params))?;
+            undone.push(*version);
+        }
+        Ok(undone)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Migrations({} defined)", self.migrations.len())
+    }
+}
+
+impl Migrations {
+    /// Read the migrations of `directory`.
+    fn load(&mut self, directory: &Path) -> PyResult<()> {
+        let mut ups = BTreeMap::new();
+        let mut downs = BTreeMap::new();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let Some(file) = path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            let Some(stem) = file.strip_suffix(".sql") else {
+                continue;
+            };
+            let (stem, target) = match stem.strip_suffix(".down") {
+                Some(stem) => (stem, &mut downs),
+                None => (stem, &mut ups),
+            };
+            let Some((version, name)) = parse_file_name(stem) else {
+                continue;
+            };
+            let script = std::fs::read_to_string(&path)?;
+            if target.insert(version, (name, script)).is_some() {
+                return Err(PyValueError::new_err(format!(
+                    "migration {version} is defined by more than one file in {}",
+                    directory.display()
+                )));
+            }
+        }
+        if let Some(version) = downs.keys().find(|v| !ups.contains_key(v)) {
+            return Err(PyValueError::new_err(format!(
+                "migration {version} has a down script but no up script"
+            )));
+        }
+        for (version, (name, up)) in ups {
+            let down = downs.remove(&version).map(|(_, down)| Step::Sql(down));
+            let migration = Migration {
+                name,
+                up: Step::Sql(up),
+                down,
+            };
+            self.migrations.insert(version, migration);
+        }
+        Ok(())
+    }
+
+    fn ensure_table(&self, py: Python<'_>) -> PyResult<()> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (version INTEGER PRIMARY KEY, name TEXT NOT NULL, applied_at TIMESTAMP NOT NULL)",
+            self.table
+        );
+        self.db.bind(py).call_method1("execute", (sql,))?;
+        Ok(())
+    }
+
+    /// Versions recorded as applied, in ascending order.
+    fn applied_versions(&self, py: Python<'_>) -> PyResult<Vec<i64>> {
+        self.ensure_table(py)?;
+        let sql = format!("SELECT version FROM {} ORDER BY version", self.table);
+        let rows = self.db.bind(py).call_method1("query", (sql,))?;
+        rows.try_iter()?
+            .map(|row| row?.get_item("version")?.extract())
+            .collect()
+    }
+
+    /// Run `step` and the bookkeeping statement `record` in one
+    /// transaction, rolling it back if either fails.
+    fn run(&self, py: Python<'_>, step: &Step, record: &str, bind: BindParams) -> PyResult<()> {
+        let tx = self.db.bind(py).call_method1("begin", ("deferred",))?;
+        let tx = tx.downcast_into::<Transaction>()?;
+        let result = run_step(py, &tx, step)
+            .and_then(|_| tx.borrow().exec_statement(py, record, bind))
+            .and_then(|_| tx.borrow().commit(py));
+        if let Err(err) = result {
+            if tx.borrow().is_active() {
+                let _ = tx.borrow().rollback(py);
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+fn run_step(py: Python<'_>, tx: &Bound<'_, Transaction>, step: &Step) -> PyResult<()> {
+    match step {
+        Step::Sql(script) => {
+            let statements = sql::split_statements(script);
+            for stmt in &statements {
+                let bind = BindParams::Positional(ParamVec::new());
+                tx.borrow().exec_statement(py, stmt, bind)?;
+            }
+        }
+        Step::Call(func) => {
+            func.call1(py, (tx,))?;
+        }
+    }
+    Ok(())
+}
+
+fn step(obj: &Bound<'_, PyAny>) -> PyResult<Step> {
+    if let Ok(script) = obj.downcast::<PyString>() {
+        return Ok(Step::Sql(script.to_str()?.to_string()));
+    }
+    if obj.is_callable() {
+        return Ok(Step::Call(obj.clone().unbind()));
+    }
+    Err(PyTypeError::new_err(
+        "a migration step must be an SQL string or a callable",
+    ))
+}
+
+/// Split a migration file stem such as `0003_add_email` into its version
+/// and name.
+fn parse_file_name(stem: &str) -> Option<(i64, String)> {
+    let (version, name) = stem.split_once('_').unwrap_or((stem, ""));
+    let version = version.parse().ok()?;
+    let name = if name.is_empty() {
+        format!("migration_{version}")
+    } else {
+        name.to_string()
+    };
+    Some((version, name))
+}
@@ -0,0 +1,417 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of the schemas of two databases.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use std::collections::BTreeMap;
+use stoolap::core::IndexType;
+
+use crate::database::{field, Database};
+use crate::error::{to_py, StoolapError};
+use crate::sql;
+use crate::vector;
+
+/// One table's columns and indexes, keyed by name in schema order.
+struct Table<'py> {
+    columns: Vec<(String, Bound<'py, PyDict>)>,
+    indexes: BTreeMap<String, Vec<Bound<'py, PyDict>>>,
+}
+
+/// Compare the schema of `target` against `expected`.
+///
+/// Returns a dict with `missing_tables` (in `expected` only),
+/// `extra_tables` (in `target` only), `columns` and `indexes`, and `equal`
+/// when there are no differences. Column and index entries are dicts with
+/// `table`, the `column` or `index` name, `change` ("missing", "extra" or
+/// "changed"), and the `expected` and `actual` schema rows (None where
+/// absent). With `ddl=True` the dict also holds `ddl`, the statements that
+/// bring `target` in line with `expected`, including drops of extra
+/// tables, columns and indexes.
+///
+/// Usage:
+///     diff = stoolap.schema_diff(reference_db, deployed_db)
+///     assert diff["equal"], diff
+#[pyfunction]
+#[pyo3(signature = (expected, target, *, ddl=false))]
+pub fn schema_diff<'py>(
+    py: Python<'py>,
+    expected: &Bound<'py, Database>,
+    target: &Bound<'py, Database>,
+    ddl: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let want = load(expected)?;
+    let have = load(target)?;
+    let missing_tables: Vec<&String> = want.keys().filter(|t| !have.contains_key(*t)).collect();
+    let extra_tables: Vec<&String> = have.keys().filter(|t| !want.contains_key(*t)).collect();
+    let columns = Changes::new(py, "column");
+    let indexes = Changes::new(py, "index");
+    let mut statements = Vec::new();
+
+    for table in creation_order(expected, &missing_tables)? {
+        statements.push(create_table(expected, table)?);
+        for (name, rows) in &want[table].indexes {
+            if !is_primary(name, rows)? && !is_declared(table, name, rows)? {
+                statements.push(create_index(expected, table, name)?);
+            }
+        }
+    }
+    for table in &extra_tables {
+        statements.push(format!("DROP TABLE {}", sql::ident(table)));
+    }
+
+    for (table, want) in want.iter().filter(|(t, _)| have.contains_key(*t)) {
+        let have = &have[table];
+        let quoted = sql::ident(table);
+        for (name, row) in &want.columns {
+            let actual = have.columns.iter().find(|(n, _)| n == name).map(|(_, r)| r);
+            match actual {
+                None => {
+                    columns.push(table, name, "missing", Some(row.as_any()), None)?;
+                    statements.push(format!(
+                        "ALTER TABLE {quoted} ADD COLUMN {} {}",
+                        sql::ident(name),
+                        column_type(row)?
+                    ));
+                }
+                Some(actual) if !actual.eq(row)? => {
+                    columns.push(
+                        table,
+                        name,
+                        "changed",
+                        Some(row.as_any()),
+                        Some(actual.as_any()),
+                    )?;
+                    if column_type(actual)? != column_type(row)? {
+                        statements.push(format!(
+                            "ALTER TABLE {quoted} MODIFY COLUMN {} {}",
+                            sql::ident(name),
+                            column_type(row)?
+                        ));
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, row) in &have.columns {
+            if !want.columns.iter().any(|(n, _)| n == name) {
+                columns.push(table, name, "extra", None, Some(row.as_any()))?;
+                statements.push(format!(
+                    "ALTER TABLE {quoted} DROP COLUMN {}",
+                    sql::ident(name)
+                ));
+            }
+        }
+
+        for (name, rows) in &want.indexes {
+            let expected_rows = PyList::new(py, rows)?;
+            match have.indexes.get(name) {
+                None => {
+                    indexes.push(table, name, "missing", Some(expected_rows.as_any()), None)?;
+                    statements.push(create_index(expected, table, name)?);
+                }
+                Some(actual) => {
+                    let actual_rows = PyList::new(py, actual)?;
+                    if !actual_rows.eq(&expected_rows)? {
+                        indexes.push(
+                            table,
+                            name,
+                            "changed",
+                            Some(expected_rows.as_any()),
+                            Some(actual_rows.as_any()),
+                        )?;
+                        if !is_primary(name, rows)? {
+                            statements.push(drop_index(table, name));
+                            statements.push(create_index(expected, table, name)?);
+                        }
+                    }
+                }
+            }
+        }
+        for (name, rows) in &have.indexes {
+            if !want.indexes.contains_key(name) {
+                let actual_rows = PyList::new(py, rows)?;
+                indexes.push(table, name, "extra", None, Some(actual_rows.as_any()))?;
+                if !is_primary(name, rows)? {
+                    statements.push(drop_index(table, name));
+                }
+            }
+        }
+    }
+
+    let diff = PyDict::new(py);
+    let equal = missing_tables.is_empty()
+        && extra_tables.is_empty()
+        && columns.list.is_empty()
+        && indexes.list.is_empty();
+    diff.set_item("equal", equal)?;
+    diff.set_item("missing_tables", missing_tables)?;
+    diff.set_item("extra_tables", extra_tables)?;
+    diff.set_item("columns", columns.list)?;
+    diff.set_item("indexes", indexes.list)?;
+    if ddl {
+        diff.set_item("ddl", statements)?;
+    }
+    Ok(diff)
+}
+
+/// The tables of `db` with their `describe()` and `indexes()` rows.
+fn load<'py>(db: &Bound<'py, Database>) -> PyResult<BTreeMap<String, Table<'py>>> {
+    let mut tables = BTreeMap::new();
+    let names: Vec<String> = db.call_method0("tables")?.extract()?;
+    for name in names {
+        let mut columns = Vec::new();
+        for row in db.call_method1("describe", (&name,))?.try_iter()? {
+            let row = row?.downcast_into::<PyDict>()?;
+            let column =
+                field(&row, &["field", "column", "column_name", "name"])?.unwrap_or_default();
+            columns.push((column, row));
+        }
+        let indexes = load_indexes(db, &name)?;
+        tables.insert(name, Table { columns, indexes });
+    }
+    Ok(tables)
+}
+
+/// The `indexes()` rows of `table`, by index name.
+fn load_indexes<'py>(
+    db: &Bound<'py, Database>,
+    table: &str,
+) -> PyResult<BTreeMap<String, Vec<Bound<'py, PyDict>>>> {
+    let mut indexes: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for row in db.call_method1("indexes", (table,))?.try_iter()? {
+        let row = row?.downcast_into::<PyDict>()?;
+        let index = field(&row, &["index_name", "key_name", "name"])?.unwrap_or_default();
+        indexes.entry(index).or_default().push(row);
+    }
+    Ok(indexes)
+}
+
+/// Column or index differences, as dicts.
+struct Changes<'py> {
+    list: Bound<'py, PyList>,
+    kind: &'static str,
+}
+
+impl<'py> Changes<'py> {
+    fn new(py: Python<'py>, kind: &'static str) -> Self {
+        Changes {
+            list: PyList::empty(py),
+            kind,
+        }
+    }
+
+    fn push(
+        &self,
+        table: &str,
+        name: &str,
+        change: &str,
+        expected: Option<&Bound<'py, PyAny>>,
+        actual: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<()> {
+        let entry = PyDict::new(self.list.py());
+        entry.set_item("table", table)?;
+        entry.set_item(self.kind, name)?;
+        entry.set_item("change", change)?;
+        entry.set_item("expected", expected)?;
+        entry.set_item("actual", actual)?;
+        self.list.append(entry)
+    }
+}
+
+fn column_type(row: &Bound<'_, PyDict>) -> PyResult<String> {
+    Ok(field(row, &["type", "data_type"])?.unwrap_or_default())
+}
+
+/// `CREATE TABLE` for `table` as it is in `db`, from its engine schema.
+///
+/// Unlike `SHOW CREATE TABLE`, this keeps the dimensions of VECTOR columns,
+/// and declares `UNIQUE` only on columns whose unique index has the name
+/// the engine gives the index of a `UNIQUE` column, so that other unique
+/// indexes can be created under their own names.
+fn create_table(db: &Bound<'_, Database>, table: &str) -> PyResult<String> {
+    let engine = db.borrow().engine()?;
+    let store = engine.engine().get_version_store(table).map_err(to_py)?;
+    let schema = store.schema();
+    let mut definitions = Vec::new();
+    for column in &schema.columns {
+        let mut def = sql::ident(&column.name);
+        if column.vector_dimensions > 0 {
+            def.push_str(&format!(" VECTOR({})", column.vector_dimensions));
+        } else {
+            def.push_str(&format!(" {:?}", column.data_type).to_ascii_uppercase());
+        }
+        if column.primary_key {
+            def.push_str(" PRIMARY KEY");
+            if column.auto_increment {
+                def.push_str(" AUTO_INCREMENT");
+            }
+        } else {
+            let unique = format!("unique_{table}_{}", column.name);
+            if store.list_indexes().iter().any(|name| {
+                name.eq_ignore_ascii_case(&unique)
+                    && store.get_index(name).is_some_and(|index| index.is_unique())
+            }) {
+                def.push_str(" UNIQUE");
+            }
+            if !column.nullable {
+                def.push_str(" NOT NULL");
+            }
+        }
+        if let Some(default) = &column.default_expr {
+            def.push_str(&format!(" DEFAULT {default}"));
+        }
+        if let Some(check) = &column.check_expr {
+            def.push_str(&format!(" CHECK ({check})"));
+        }
+        definitions.push(def);
+    }
+    for fk in &schema.foreign_keys {
+        definitions.push(format!(
+            "FOREIGN KEY ({}) REFERENCES {}({}) ON DELETE {} ON UPDATE {}",
+            sql::ident(&fk.column_name),
+            sql::ident(&fk.referenced_table),
+            sql::ident(&fk.referenced_column),
+            fk.on_delete,
+            fk.on_update
+        ));
+    }
+    Ok(format!(
+        "CREATE TABLE {} ({})",
+        sql::ident(table),
+        definitions.join(", ")
+    ))
+}
+
+/// `tables` ordered so that each comes after the tables its foreign keys
+/// reference, where it can.
+fn creation_order<'a>(
+    db: &Bound<'_, Database>,
+    tables: &[&'a String],
+) -> PyResult<Vec<&'a String>> {
+    let engine = db.borrow().engine()?;
+    let mut pending = Vec::new();
+    for table in tables {
+        let store = engine.engine().get_version_store(table).map_err(to_py)?;
+        let references: Vec<String> = store
+            .schema()
+            .foreign_keys
+            .iter()
+            .map(|fk| fk.referenced_table.to_lowercase())
+            .filter(|parent| *parent != table.to_lowercase())
+            .collect();
+        pending.push((*table, references));
+    }
+    let mut order: Vec<&String> = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending
+            .iter()
+            .position(|(_, references)| {
+                references.iter().all(|parent| {
+                    order.iter().any(|t| t.to_lowercase() == *parent)
+                        || !pending.iter().any(|(t, _)| t.to_lowercase() == *parent)
+                })
+            })
+            // A cycle: keep the remaining tables in name order
+            .unwrap_or(0);
+        order.push(pending.remove(ready).0);
+    }
+    Ok(order)
+}
+
+/// Whether an index is one the engine creates with the table, for a
+/// `UNIQUE` column or a foreign key, which `create_table` declares.
+fn is_declared(table: &str, name: &str, rows: &[Bound<'_, PyDict>]) -> PyResult<bool> {
+    let [row] = rows else {
+        return Ok(false);
+    };
+    let Some(column) = field(row, &["column_name"])? else {
+        return Ok(false);
+    };
+    let unique = field(row, &["unique", "is_unique"])?.is_some_and(|flag| truthy(&flag));
+    let declared = if unique {
+        format!("unique_{table}_{column}")
+    } else {
+        format!("fk_{table}_{column}")
+    };
+    Ok(name.eq_ignore_ascii_case(&declared))
+}
+
+/// Whether an index backs the primary key, which its table creates.
+fn is_primary(name: &str, rows: &[Bound<'_, PyDict>]) -> PyResult<bool> {
+    let name = name.to_ascii_lowercase();
+    if name == "primary" || name.starts_with("pk_") {
+        return Ok(true);
+    }
+    for row in rows {
+        let index_type = field(row, &["index_type"])?.unwrap_or_default();
+        if index_type.to_ascii_uppercase().contains("PRIMARY") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// `CREATE INDEX` for index `name` of `table` as it is in `db`.
+///
+/// `SHOW INDEXES` lists the columns of a multi-column index as one
+/// `(a, b)` string and leaves out HNSW settings, so the columns, the type
+/// and the settings come from the engine's index.
+fn create_index(db: &Bound<'_, Database>, table: &str, name: &str) -> PyResult<String> {
+    let engine = db.borrow().engine()?;
+    let store = engine.engine().get_version_store(table).map_err(to_py)?;
+    let index = store.get_index(name).ok_or_else(|| {
+        StoolapError::new_err(format!("index '{name}' not found on table '{table}'"))
+    })?;
+    let columns: Vec<String> = index.column_names().iter().map(|c| sql::ident(c)).collect();
+    let mut stmt = format!(
+        "CREATE {}INDEX {} ON {} ({})",
+        if index.is_unique() { "UNIQUE " } else { "" },
+        sql::ident(name),
+        sql::ident(table),
+        columns.join(", ")
+    );
+    match index.index_type() {
+        IndexType::BTree => stmt.push_str(" USING BTREE"),
+        IndexType::Hash => stmt.push_str(" USING HASH"),
+        IndexType::Bitmap => stmt.push_str(" USING BITMAP"),
+        IndexType::Hnsw => {
+            stmt.push_str(" USING HNSW");
+            if let Some(settings) = vector::hnsw_settings(index.as_ref()) {
+                stmt.push_str(&format!(
+                    " WITH (metric = '{}', m = {}, ef_construction = {}",
+                    settings.metric, settings.m, settings.ef_construction
+                ));
+                if let Some(ef_search) = settings.ef_search {
+                    stmt.push_str(&format!(", ef_search = {ef_search}"));
+                }
+                stmt.push(')');
+            }
+        }
+        _ => {}
+    }
+    Ok(stmt)
+}
+
+fn drop_index(table: &str, name: &str) -> String {
+    format!("DROP INDEX {} ON {}", sql::ident(name), sql::ident(table))
+}
+
+fn truthy(flag: &str) -> bool {
+    matches!(
+        flag.to_ascii_lowercase().as_str(),
+        "true" | "1" | "yes" | "t"
+    )
+}
@@ -0,0 +1,95 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Named settings for `Database.get_setting()` and `Database.set_setting()`,
+//! each backed by the method or property that controls it.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::database::Database;
+
+/// Every setting, by name.
+pub const SETTINGS: [&str; 10] = [
+    "journal_mode",
+    "synchronous",
+    "checkpoint_interval",
+    "checkpoint_wal_bytes",
+    "auto_compaction",
+    "compact_threshold",
+    "paramstyle",
+    "strict_types",
+    "reject_naive",
+    "threads",
+];
+
+/// The current value of setting `name`.
+pub fn get<'py>(db: &Bound<'py, Database>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+    match name {
+        "journal_mode" | "synchronous" | "paramstyle" | "strict_types" | "reject_naive"
+        | "threads" => db.getattr(name),
+        "checkpoint_interval" => db
+            .call_method0("checkpoint_status")?
+            .get_item("interval_seconds"),
+        "checkpoint_wal_bytes" => db
+            .call_method0("checkpoint_status")?
+            .get_item("wal_bytes_threshold"),
+        "auto_compaction" => db.call_method0("compaction_status")?.get_item("enabled"),
+        "compact_threshold" => db.call_method0("compaction_status")?.get_item("threshold"),
+        _ => Err(unknown(name)),
+    }
+}
+
+/// Change setting `name` to `value`.
+pub fn set(db: &Bound<'_, Database>, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    match name {
+        "journal_mode" => {
+            db.call_method1("set_journal_mode", (value,))?;
+        }
+        "synchronous" | "paramstyle" | "strict_types" | "reject_naive" => {
+            db.setattr(name, value)?;
+        }
+        "checkpoint_interval" => {
+            db.call_method1("set_checkpoint_interval", (value,))?;
+        }
+        "checkpoint_wal_bytes" => {
+            let kwargs = PyDict::new(db.py());
+            let bytes = if value.is_none() { 0 } else { value.extract::<u64>()? };
+            kwargs.set_item("wal_bytes", bytes)?;
+            db.call_method("set_checkpoint_interval", (), Some(&kwargs))?;
+        }
+        "auto_compaction" => {
+            db.call_method1("set_auto_compaction", (value,))?;
+        }
+        "threads" => {
+            db.call_method1("set_threads", (value,))?;
+        }
+        "compact_threshold" => {
+            let enabled = get(db, "auto_compaction")?;
+            let kwargs = PyDict::new(db.py());
+            kwargs.set_item("threshold", value)?;
+            db.call_method("set_auto_compaction", (enabled,), Some(&kwargs))?;
+        }
+        _ => return Err(unknown(name)),
+    }
+    Ok(())
+}
+
+fn unknown(name: &str) -> PyErr {
+    PyValueError::new_err(format!(
+        "unknown setting '{name}'; settings are {}",
+        SETTINGS.join(", ")
+    ))
+}
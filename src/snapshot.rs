@@ -0,0 +1,323 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+use stoolap::api::{Rows, Transaction as ApiTransaction};
+use stoolap::core::Value;
+
+use crate::connection::Connection;
+use crate::database::{
+    first_row_to_dict, rows_to_columnar, rows_to_dicts, rows_to_raw, to_named_params,
+    ConvertOptions,
+};
+use crate::error::{to_py, StoolapError};
+use crate::value::{value_to_py, BindParams};
+
+/// A read-only view of the database pinned to the moment it was taken.
+///
+/// Every query sees the same consistent state, regardless of writes made
+/// since. Anything written through the snapshot is discarded on close.
+/// ```python
+/// with db.snapshot() as snap:
+///     total = snap.query_one("SELECT SUM(amount) AS s FROM orders")["s"]
+///     rows = snap.query("SELECT * FROM orders")
+/// ```
+#[pyclass]
+pub struct Snapshot {
+    conn: Arc<Connection>,
+    tx: Mutex<Option<ApiTransaction>>,
+}
+
+impl Snapshot {
+    pub fn new(conn: Arc<Connection>, tx: ApiTransaction) -> Self {
+        conn.snapshots.fetch_add(1, Ordering::Relaxed);
+        Self {
+            conn,
+            tx: Mutex::new(Some(tx)),
+        }
+    }
+
+    fn run(&self, sql: &str, bind: BindParams) -> PyResult<Rows> {
+        let mut guard = self
+            .tx
+            .lock()
+            .map_err(|_| StoolapError::new_err("Snapshot lock poisoned"))?;
+        let tx = guard
+            .as_mut()
+            .ok_or_else(|| StoolapError::new_err("Snapshot is closed"))?;
+        match bind {
+            BindParams::Positional(p) => tx.query(sql, p).map_err(to_py),
+            BindParams::Named(named) => tx.query_named(sql, to_named_params(&named)).map_err(to_py),
+        }
+    }
+}
+
+#[pymethods]
+impl Snapshot {
+    /// Query rows as a list of dicts.
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, limit=None))]
+    fn query(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .limit(limit);
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || self.run(sql, bind))?;
+            rows_to_dicts(py, rows, options)
+        })
+    }
+
+    /// Query a single row as a dict. Returns None if no rows.
+    #[pyo3(signature = (sql, params=None))]
+    fn query_one(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || self.run(sql, bind))?;
+            first_row_to_dict(py, rows, &self.conn.fetch)
+        })
+    }
+
+    /// Query rows in raw columnar format.
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, typed=false))]
+    fn query_raw(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        typed: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .typed(typed);
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || self.run(sql, bind))?;
+            rows_to_raw(py, rows, options)
+        })
+    }
+
+    /// Query rows as a dict of column lists: {column: [values...]}.
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, numpy=false))]
+    fn query_columnar(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        numpy: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .numpy(py, numpy)?;
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        self.conn.run_query(py, sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || self.run(sql, bind))?;
+            rows_to_columnar(py, rows, options)
+        })
+    }
+
+    /// Query rows, returning an iterator that builds each row dict on demand.
+    /// With `limit`, at most `limit` rows are read, and the iterator's
+    /// `truncated` tells whether there were more.
+    #[pyo3(signature = (sql, params=None, *, limit=None))]
+    fn query_iter(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let sql = self.conn.translate(sql)?;
+        let sql = sql.as_ref();
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        self.conn.run_query(py, sql, info, |py| {
+            let iter = self.conn.allow_threads(py, || {
+                RowIterator::collect(self.conn.clone(), self.run(sql, bind)?, limit)
+            })?;
+            let count = iter.rows.len();
+            Ok((Py::new(py, iter)?.into_any(), count))
+        })
+    }
+
+    /// Release the snapshot. Further queries raise StoolapError.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        self.conn.check_fork()?;
+        let tx = self
+            .tx
+            .lock()
+            .map_err(|_| StoolapError::new_err("Snapshot lock poisoned"))?
+            .take();
+        if let Some(mut tx) = tx {
+            self.conn.snapshots.fetch_sub(1, Ordering::Relaxed);
+            py.allow_threads(|| tx.rollback().map_err(to_py))?;
+        }
+        Ok(())
+    }
+
+    /// Context manager: enter.
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Context manager: exit. Closes the snapshot.
+    #[pyo3(signature = (_exc_type=None, _exc_val=None, _exc_tb=None))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_val: Option<&Bound<'_, PyAny>>,
+        _exc_tb: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.close(py)?;
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        let open = self.tx.lock().map(|g| g.is_some()).unwrap_or(false);
+        if open {
+            "Snapshot(open)".to_string()
+        } else {
+            "Snapshot(closed)".to_string()
+        }
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let Ok(guard) = self.tx.get_mut() else {
+            return;
+        };
+        if guard.is_some() {
+            self.conn.snapshots.fetch_sub(1, Ordering::Relaxed);
+        }
+        // A snapshot inherited across fork() belongs to the parent's engine
+        if self.conn.inherited() {
+            std::mem::forget(guard.take());
+        }
+    }
+}
+
+/// Iterator over query results. Rows are read from the engine up front,
+/// without the GIL; the dict for each row is built when it is reached.
+#[pyclass]
+pub struct RowIterator {
+    conn: Arc<Connection>,
+    columns: Vec<String>,
+    rows: std::vec::IntoIter<Vec<Value>>,
+    names: Option<Vec<Py<PyString>>>,
+    truncated: bool,
+}
+
+impl RowIterator {
+    /// Read the rows, at most `limit` of them.
+    pub fn collect(conn: Arc<Connection>, rows: Rows, limit: Option<usize>) -> PyResult<Self> {
+        let columns: Vec<String> = rows.columns().to_vec();
+        let mut values = Vec::new();
+        let mut truncated = false;
+        for row_result in rows {
+            let row = row_result.map_err(to_py)?;
+            if limit.is_some_and(|limit| values.len() == limit) {
+                truncated = true;
+                break;
+            }
+            values.push(
+                (0..columns.len())
+                    .map(|i| row.get_value(i).cloned().unwrap_or_else(Value::null_unknown))
+                    .collect(),
+            );
+        }
+        Ok(Self {
+            conn,
+            columns,
+            rows: values.into_iter(),
+            names: None,
+            truncated,
+        })
+    }
+}
+
+#[pymethods]
+impl RowIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let Some(row) = self.rows.next() else {
+            return Ok(None);
+        };
+        // Column names are converted once, on the first row
+        let columns = &self.columns;
+        let names = self
+            .names
+            .get_or_insert_with(|| columns.iter().map(|c| PyString::new(py, c).unbind()).collect());
+        let dict = PyDict::new(py);
+        for (name, value) in names.iter().zip(&row) {
+            dict.set_item(name.bind(py), value_to_py(py, value, &self.conn.fetch)?)?;
+        }
+        Ok(Some(dict.into_any().unbind()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Column names of the result.
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.columns.clone()
+    }
+
+    /// Whether rows were left unread because of the query's `limit`.
+    #[getter]
+    fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
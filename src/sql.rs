@@ -0,0 +1,516 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::borrow::Cow;
+use stoolap::api::ParamVec;
+use stoolap::core::Value;
+
+use crate::value::{py_to_value, BindParams};
+
+/// Quote `s` as a string literal. The engine's only escape inside a literal
+/// is a doubled quote; backslashes are taken as is.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Quote `name` as an identifier, doubling embedded double quotes.
+pub fn ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Render a value as a SQL literal the engine reads back as the same value.
+pub fn literal(value: &Value) -> String {
+    match value {
+        Value::Null(_) => "NULL".to_string(),
+        Value::Boolean(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Integer(i) => i.to_string(),
+        // Debug formatting keeps the decimal point on whole numbers (1.0)
+        Value::Float(f) if f.is_finite() => format!("{f:?}"),
+        Value::Float(f) => quote(&f.to_string()),
+        Value::Text(s) => quote(s.as_str()),
+        Value::Timestamp(ts) => quote(&ts.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()),
+        Value::Extension(_) => {
+            if let Some(floats) = value.as_vector_f32() {
+                let items: Vec<String> = floats.iter().map(|f| format!("{f:?}")).collect();
+                quote(&format!("[{}]", items.join(", ")))
+            } else if let Some(s) = value.as_json() {
+                quote(s)
+            } else {
+                quote(&value.to_string())
+            }
+        }
+    }
+}
+
+/// Quote `name` as an identifier, for table or column names that come from
+/// configuration or user input.
+///
+/// The name is always double-quoted, so it is matched exactly and may be a
+/// reserved word. Embedded double quotes are doubled.
+#[pyfunction]
+pub fn quote_ident(name: &str) -> PyResult<String> {
+    if name.is_empty() {
+        return Err(PyValueError::new_err("identifier must not be empty"));
+    }
+    if name.contains('\0') {
+        return Err(PyValueError::new_err("identifier must not contain NUL"));
+    }
+    Ok(ident(name))
+}
+
+/// Render a Python value as a SQL literal, using the same conversions as
+/// query parameters. None becomes NULL.
+#[pyfunction]
+pub fn quote_literal(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    Ok(literal(&py_to_value(value)?))
+}
+
+/// Append the literal of `value`, separated from a preceding minus sign so
+/// that `x-$1` with a negative value does not become a `--` comment.
+fn push_literal(out: &mut String, value: &Value) {
+    let literal = literal(value);
+    if out.ends_with('-') && literal.starts_with('-') {
+        out.push(' ');
+    }
+    out.push_str(&literal);
+}
+
+/// Copy `sql`, letting `f` rewrite the text outside string literals
+/// (including dollar-quoted `$tag$...$tag$` bodies), quoted identifiers and
+/// comments. `f` is given the remaining characters and the
+/// output so far, and returns how many characters it consumed; 0 copies the
+/// next character unchanged. `::` casts are copied without calling `f`.
+fn rewrite<F>(sql: &str, mut f: F) -> Result<String, String>
+where
+    F: FnMut(&[char], &mut String) -> Result<usize, String>,
+{
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied().unwrap_or('\0');
+
+        if c == '\'' || c == '"' {
+            out.push(c);
+            i += 1;
+            while i < chars.len() {
+                out.push(chars[i]);
+                if chars[i] == c {
+                    if chars.get(i + 1) == Some(&c) {
+                        out.push(c);
+                        i += 2;
+                        continue;
+                    }
+                    i += 1;
+                    break;
+                }
+                i += 1;
+            }
+            continue;
+        }
+
+        if c == '-' && next == '-' {
+            while i < chars.len() && chars[i] != '\n' {
+                out.push(chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == '*' {
+            let start = i;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            out.extend(&chars[start..i]);
+            continue;
+        }
+
+        if c == '$' && (next == '$' || next.is_alphabetic() || next == '_') {
+            let tag_len = 1 + run_len(&chars[i + 1..], |c| c.is_alphanumeric() || c == '_');
+            if chars.get(i + tag_len) == Some(&'$') {
+                let tag = &chars[i..=i + tag_len];
+                let start = i;
+                i += tag.len();
+                while i < chars.len() && !chars[i..].starts_with(tag) {
+                    i += 1;
+                }
+                i = (i + tag.len()).min(chars.len());
+                out.extend(&chars[start..i]);
+                continue;
+            }
+        }
+
+        if c == ':' && next == ':' {
+            out.push_str("::");
+            i += 2;
+            continue;
+        }
+
+        match f(&chars[i..], &mut out)? {
+            0 => {
+                out.push(c);
+                i += 1;
+            }
+            consumed => i += consumed,
+        }
+    }
+
+    Ok(out)
+}
+
+/// Length of the run of characters at the start of `chars` matching `pred`.
+fn run_len(chars: &[char], pred: impl Fn(char) -> bool) -> usize {
+    chars.iter().take_while(|c| pred(**c)).count()
+}
+
+/// Whether `sql` contains a `$1` or `:name` placeholder outside string
+/// literals, quoted identifiers and comments.
+pub fn has_placeholders(sql: &str) -> bool {
+    let mut found = false;
+    let _ = rewrite(sql, |rest, _| {
+        let next = rest.get(1).copied().unwrap_or('\0');
+        found |= (rest[0] == '$' && next.is_ascii_digit())
+            || (rest[0] == ':' && (next.is_alphabetic() || next == '_'));
+        Ok(0)
+    });
+    found
+}
+
+/// Whether `sql` produces a result set: a query, or a write with a
+/// RETURNING clause.
+pub fn returns_rows(sql: &str) -> bool {
+    let words = words(sql);
+    match words.first().map(String::as_str) {
+        Some("SELECT" | "WITH" | "SHOW" | "EXPLAIN" | "VALUES" | "DESCRIBE") => true,
+        _ => words.iter().any(|word| word == "RETURNING"),
+    }
+}
+
+/// Whether `sql` only reads: a query, or the `EXPLAIN` of one. Any other
+/// statement, including a write with RETURNING, may change the database.
+pub fn is_read_only(sql: &str) -> bool {
+    let words = words(sql);
+    let mut words = words
+        .iter()
+        .map(String::as_str)
+        .skip_while(|word| matches!(*word, "EXPLAIN" | "ANALYZE"));
+    matches!(words.next(), Some("SELECT" | "WITH" | "SHOW" | "VALUES" | "DESCRIBE"))
+        && !words.any(|word| matches!(word, "RETURNING" | "INSERT" | "UPDATE" | "DELETE"))
+}
+
+/// The upper-cased words of `sql` outside string literals, quoted
+/// identifiers and comments.
+fn words(sql: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let _ = rewrite(sql, |rest, out| {
+        let starts_word = rest[0].is_alphabetic() || rest[0] == '_';
+        let in_word = out
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        if !starts_word || in_word {
+            return Ok(0);
+        }
+        let len = run_len(rest, |c| c.is_alphanumeric() || c == '_');
+        let word: String = rest[..len].iter().collect();
+        out.push_str(&word);
+        words.push(word.to_ascii_uppercase());
+        Ok(len)
+    });
+    words
+}
+
+/// Rewrite the columns of `sql` qualified with `table`, as `table.col` or
+/// `"table".col`, to be qualified with `alias` instead, for a condition
+/// moved onto a derived table. String literals, quoted identifiers and
+/// comments are left alone.
+pub fn requalify(sql: &str, table: &str, alias: &str) -> Result<String, String> {
+    let quoted = ident(table);
+    rewrite(sql, |rest, out| {
+        if rest[0] != '.' {
+            return Ok(0);
+        }
+        let len = if out.ends_with(&quoted) {
+            quoted.len()
+        } else if out.len() >= table.len()
+            && out.is_char_boundary(out.len() - table.len())
+            && out[out.len() - table.len()..].eq_ignore_ascii_case(table)
+        {
+            table.len()
+        } else {
+            return Ok(0);
+        };
+        let head = &out[..out.len() - len];
+        if head.ends_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '"')) {
+            return Ok(0);
+        }
+        out.truncate(head.len());
+        out.push_str(alias);
+        Ok(0)
+    })
+}
+
+/// Substitute the placeholders in `sql` (`$1` positional, `:name` named)
+/// with the literals of `bind`. Placeholders inside string literals, quoted
+/// identifiers and comments are left alone, as are `::` casts.
+pub fn mogrify(sql: &str, bind: &BindParams) -> Result<String, String> {
+    rewrite(sql, |rest, out| {
+        let next = rest.get(1).copied().unwrap_or('\0');
+        if rest[0] == '$' && next.is_ascii_digit() {
+            let len = run_len(&rest[1..], |c| c.is_ascii_digit());
+            let number: String = rest[1..=len].iter().collect();
+            let BindParams::Positional(values) = bind else {
+                return Err(format!("${number} needs positional parameters, got named parameters"));
+            };
+            let value = number
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|n| values.get(n))
+                .ok_or_else(|| format!("no value for parameter ${number}"))?;
+            push_literal(out, value);
+            return Ok(len + 1);
+        }
+        if rest[0] == ':' && (next.is_alphabetic() || next == '_') {
+            let len = run_len(&rest[1..], |c| c.is_alphanumeric() || c == '_');
+            let name: String = rest[1..=len].iter().collect();
+            let BindParams::Named(named) = bind else {
+                return Err(format!(":{name} needs named parameters, got positional parameters"));
+            };
+            let value = named
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("no value for parameter :{name}"))?;
+            push_literal(out, value);
+            return Ok(len + 1);
+        }
+        Ok(0)
+    })
+}
+
+/// Rewrite the `:name` placeholders of `sql` as `$1`, `$2`, ... for the
+/// engine calls that only take positional parameters, returning the
+/// rewritten SQL and the values of `named` in placeholder order. A name
+/// used twice gets the same number.
+pub fn positional(sql: &str, named: &[(String, Value)]) -> Result<(String, ParamVec), String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut values = ParamVec::new();
+    let sql = rewrite(sql, |rest, out| {
+        let next = rest.get(1).copied().unwrap_or('\0');
+        if rest[0] != ':' || !(next.is_alphabetic() || next == '_') {
+            return Ok(0);
+        }
+        let len = run_len(&rest[1..], |c| c.is_alphanumeric() || c == '_');
+        let name: String = rest[1..=len].iter().collect();
+        let number = match names.iter().position(|n| *n == name) {
+            Some(i) => i + 1,
+            None => {
+                let value = named
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value)
+                    .ok_or_else(|| format!("no value for parameter :{name}"))?;
+                values.push(value.clone());
+                names.push(name);
+                names.len()
+            }
+        };
+        out.push_str(&format!("${number}"));
+        Ok(len + 1)
+    })?;
+    Ok((sql, values))
+}
+
+/// Rewrite qmark placeholders (`?`) as `$1`, `$2`, ... in order, leaving
+/// string literals, quoted identifiers and comments alone. Numbering starts
+/// again after each `;`, as every statement of a script binds its own
+/// parameters. SQL without a `?` is returned as is.
+pub fn translate_qmark(sql: &str) -> Cow<'_, str> {
+    if !sql.contains('?') {
+        return Cow::Borrowed(sql);
+    }
+    let mut count = 0;
+    rewrite(sql, |rest, out| {
+        if rest[0] == ';' {
+            count = 0;
+        }
+        if rest[0] != '?' {
+            return Ok(0);
+        }
+        count += 1;
+        out.push_str(&format!("${count}"));
+        Ok(1)
+    })
+    .map_or(Cow::Borrowed(sql), Cow::Owned)
+}
+
+/// Rewrite pyformat placeholders as engine placeholders: `%s` becomes `$1`,
+/// `$2`, ... in order, `%(name)s` becomes `:name`, and `%%` becomes `%`.
+/// String literals, quoted identifiers and comments are left alone, so a
+/// `%` inside them needs no doubling. Any other `%` is kept as the modulo
+/// operator. As with qmark, numbering starts again after each `;`.
+pub fn translate_pyformat(sql: &str) -> Result<Cow<'_, str>, String> {
+    if !sql.contains('%') {
+        return Ok(Cow::Borrowed(sql));
+    }
+    let mut count = 0;
+    rewrite(sql, |rest, out| {
+        if rest[0] == ';' {
+            count = 0;
+        }
+        if rest[0] != '%' {
+            return Ok(0);
+        }
+        match rest.get(1) {
+            Some('s') => {
+                count += 1;
+                out.push_str(&format!("${count}"));
+                Ok(2)
+            }
+            Some('%') => {
+                out.push('%');
+                Ok(2)
+            }
+            Some('(') => {
+                let len = run_len(&rest[2..], |c| c != ')');
+                if rest.get(2 + len + 1) != Some(&'s') {
+                    return Err("unterminated %(name)s placeholder".to_string());
+                }
+                let name: String = rest[2..2 + len].iter().collect();
+                out.push(':');
+                out.push_str(&name);
+                Ok(len + 4)
+            }
+            _ => Ok(0),
+        }
+    })
+    .map(Cow::Owned)
+}
+
+/// Replace each `current_setting('name')` call with the literal `lookup`
+/// gives for `name`, leaving string literals, quoted identifiers and
+/// comments alone. An unknown name is an error, unless the call is
+/// `current_setting('name', true)`, which becomes NULL. SQL without such a
+/// call is returned as is.
+pub fn substitute_settings<'a>(
+    sql: Cow<'a, str>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Cow<'a, str>, String> {
+    const CALL: &str = "current_setting";
+    let called = sql
+        .as_bytes()
+        .windows(CALL.len())
+        .any(|w| w.eq_ignore_ascii_case(CALL.as_bytes()));
+    if !called {
+        return Ok(sql);
+    }
+    rewrite(&sql, |rest, out| {
+        if rest.len() < CALL.len() || out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+            return Ok(0);
+        }
+        let word: String = rest[..CALL.len()].iter().collect();
+        if !word.eq_ignore_ascii_case(CALL) {
+            return Ok(0);
+        }
+        let Some((name, missing_ok, len)) = setting_call(&rest[CALL.len()..]) else {
+            return Ok(0);
+        };
+        match lookup(&name) {
+            Some(literal) => out.push_str(&literal),
+            None if missing_ok => out.push_str("NULL"),
+            None => return Err(format!("unknown session variable '{name}'")),
+        }
+        Ok(CALL.len() + len)
+    })
+    .map(Cow::Owned)
+}
+
+/// Parse the `('name')` or `('name', missing_ok)` arguments following
+/// `current_setting`. Returns the name, `missing_ok` and the number of
+/// characters read, or None when the arguments are not of that form.
+fn setting_call(chars: &[char]) -> Option<(String, bool, usize)> {
+    let skip = |i: usize| i + run_len(&chars[i..], char::is_whitespace);
+    let mut i = skip(0);
+    if chars.get(i) != Some(&'(') {
+        return None;
+    }
+    i = skip(i + 1);
+    if chars.get(i) != Some(&'\'') {
+        return None;
+    }
+    i += 1;
+    let mut name = String::new();
+    loop {
+        match *chars.get(i)? {
+            '\'' if chars.get(i + 1) == Some(&'\'') => {
+                name.push('\'');
+                i += 2;
+            }
+            '\'' => break,
+            c => {
+                name.push(c);
+                i += 1;
+            }
+        }
+    }
+    i = skip(i + 1);
+    let mut missing_ok = false;
+    if chars.get(i) == Some(&',') {
+        i = skip(i + 1);
+        let len = run_len(&chars[i..], char::is_alphabetic);
+        let word: String = chars[i..i + len].iter().collect();
+        missing_ok = match word.to_ascii_lowercase().as_str() {
+            "true" => true,
+            "false" => false,
+            _ => return None,
+        };
+        i = skip(i + len);
+    }
+    (chars.get(i) == Some(&')')).then_some((name, missing_ok, i + 1))
+}
+
+/// Split a script into its statements at the semicolons outside string
+/// literals, quoted identifiers, comments and dollar-quoted bodies. Each
+/// statement is returned as written, trimmed; pieces holding only
+/// whitespace and comments are dropped.
+pub fn split_statements(sql: &str) -> Vec<String> {
+    // Byte ranges of the statements that contain code
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut has_code = false;
+    // The copy is identical to `sql`, so its length is the offset in `sql`
+    let _ = rewrite(sql, |rest, out| {
+        if rest[0] == ';' {
+            if has_code {
+                ranges.push(start..out.len());
+            }
+            start = out.len() + 1;
+            has_code = false;
+        } else if !rest[0].is_whitespace() {
+            has_code = true;
+        }
+        Ok(0)
+    });
+    if has_code {
+        ranges.push(start..sql.len());
+    }
+    ranges.into_iter().map(|range| sql[range].trim().to_string()).collect()
+}
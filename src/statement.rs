@@ -16,29 +16,36 @@ use pyo3::prelude::*;
 use pyo3::types::PyList;
 use std::sync::Arc;
 
-use stoolap::api::Database as ApiDatabase;
 use stoolap::CachedPlanRef;
 
-use crate::database::{first_row_to_dict, rows_to_dicts, rows_to_raw, to_named_params};
+use crate::connection::Connection;
+use crate::database::{
+    first_row_to_dict, rows_to_columnar, rows_to_dicts, rows_to_raw, ConvertOptions,
+};
+use crate::deadline;
 use crate::error::to_py;
-use crate::value::{parse_params, BindParams};
+use crate::value::BindParams;
 
 /// A prepared SQL statement.
 ///
 /// Parses SQL once and reuses the cached execution plan on every call.
 #[pyclass]
 pub struct PreparedStatement {
-    db: Arc<ApiDatabase>,
+    conn: Arc<Connection>,
     sql_text: String,
+    /// `sql_text` in engine placeholders, as the plan was prepared from.
+    translated: String,
     plan: CachedPlanRef,
 }
 
 impl PreparedStatement {
-    pub fn new(db: Arc<ApiDatabase>, sql: &str) -> PyResult<Self> {
-        let plan = db.cached_plan(sql).map_err(to_py)?;
+    pub fn new(conn: Arc<Connection>, sql: &str) -> PyResult<Self> {
+        let translated = conn.translate(sql)?.into_owned();
+        let plan = conn.db()?.cached_plan(&translated).map_err(to_py)?;
         Ok(Self {
-            db,
+            conn,
             sql_text: sql.to_string(),
+            translated,
             plan,
         })
     }
@@ -50,6 +57,10 @@ impl PreparedStatement {
     pub(crate) fn sql_text(&self) -> &str {
         &self.sql_text
     }
+
+    pub(crate) fn translated(&self) -> &str {
+        &self.translated
+    }
 }
 
 #[pymethods]
@@ -57,56 +68,108 @@ impl PreparedStatement {
     /// Execute the prepared statement (DML). Returns rows affected.
     #[pyo3(signature = (params=None))]
     fn execute(&self, py: Python<'_>, params: Option<&Bound<'_, PyAny>>) -> PyResult<i64> {
-        let bind = parse_params(params)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = self.plan.clone();
-        py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.execute_plan(&plan, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.execute_named_plan(&plan, to_named_params(&named)).map_err(to_py)
-            }
+        let db = self.conn.db()?;
+        self.conn.run_autocommit(py, &self.sql_text, info, 1, |py| {
+            self.conn.allow_threads(py, || {
+                deadline::execute_plan(&db, &plan, &self.translated, bind).map_err(to_py)
+            })
         })
     }
 
     /// Query rows using the prepared statement. Returns list of dicts.
-    #[pyo3(signature = (params=None))]
-    fn query(&self, py: Python<'_>, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+    #[pyo3(signature = (params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, limit=None))]
+    fn query(
+        &self,
+        py: Python<'_>,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .limit(limit);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = self.plan.clone();
-        let rows = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query_plan(&plan, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named_plan(&plan, to_named_params(&named)).map_err(to_py)
-            }
-        })?;
-        rows_to_dicts(py, rows)
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &self.sql_text, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                deadline::query_plan(&db, &plan, &self.translated, bind).map_err(to_py)
+            })?;
+            rows_to_dicts(py, rows, options)
+        })
     }
 
     /// Query a single row. Returns dict or None.
     #[pyo3(signature = (params=None))]
     fn query_one(&self, py: Python<'_>, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = self.plan.clone();
-        let rows = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query_plan(&plan, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named_plan(&plan, to_named_params(&named)).map_err(to_py)
-            }
-        })?;
-        first_row_to_dict(py, rows)
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &self.sql_text, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                deadline::query_plan(&db, &plan, &self.translated, bind).map_err(to_py)
+            })?;
+            first_row_to_dict(py, rows, &self.conn.fetch)
+        })
     }
 
     /// Query rows in raw format. Returns { columns: [...], rows: [[...], ...] }.
-    #[pyo3(signature = (params=None))]
-    fn query_raw(&self, py: Python<'_>, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+    #[pyo3(signature = (params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, typed=false))]
+    fn query_raw(
+        &self,
+        py: Python<'_>,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        typed: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .typed(typed);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = self.plan.clone();
-        let rows = py.allow_threads(|| match bind {
-            BindParams::Positional(p) => self.db.query_plan(&plan, p).map_err(to_py),
-            BindParams::Named(named) => {
-                self.db.query_named_plan(&plan, to_named_params(&named)).map_err(to_py)
-            }
-        })?;
-        rows_to_raw(py, rows)
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &self.sql_text, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                deadline::query_plan(&db, &plan, &self.translated, bind).map_err(to_py)
+            })?;
+            rows_to_raw(py, rows, options)
+        })
+    }
+
+    /// Query rows as a dict of column lists: {column: [values...]}.
+    #[pyo3(signature = (params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, numpy=false))]
+    fn query_columnar(
+        &self,
+        py: Python<'_>,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        numpy: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .numpy(py, numpy)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let plan = self.plan.clone();
+        let db = self.conn.db()?;
+        self.conn.run_query(py, &self.sql_text, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                deadline::query_plan(&db, &plan, &self.translated, bind).map_err(to_py)
+            })?;
+            rows_to_columnar(py, rows, options)
+        })
     }
 
     /// Execute with multiple parameter sets.
@@ -117,7 +180,7 @@ impl PreparedStatement {
         // Parse all param sets while holding GIL
         let mut all_params = Vec::with_capacity(params_list.len());
         for item in params_list.iter() {
-            let bind = parse_params(Some(&item))?;
+            let bind = self.conn.binding.params(Some(&item))?;
             match bind {
                 BindParams::Positional(p) => all_params.push(p),
                 BindParams::Named(_) => {
@@ -129,17 +192,22 @@ impl PreparedStatement {
         }
 
         let plan = self.plan.clone();
+        let db = self.conn.db()?;
+        let info = self.conn.hooks.describe_batch(&all_params);
+        let count = all_params.len() as u64;
 
         // Execute without GIL
-        py.allow_threads(|| {
-            let stmt = plan.statement.as_ref();
-            let mut tx = self.db.begin().map_err(to_py)?;
-            let mut total = 0i64;
-            for params in all_params {
-                total += tx.execute_prepared(stmt, params).map_err(to_py)?;
-            }
-            tx.commit().map_err(to_py)?;
-            Ok(total)
+        self.conn.run_autocommit(py, &self.sql_text, info, count, |py| {
+            self.conn.allow_threads(py, || {
+                let stmt = plan.statement.as_ref();
+                let mut tx = db.begin().map_err(to_py)?;
+                let mut total = 0i64;
+                for params in all_params {
+                    total += tx.execute_prepared(stmt, params).map_err(to_py)?;
+                }
+                tx.commit().map_err(to_py)?;
+                Ok(total)
+            })
         })
     }
 
@@ -0,0 +1,266 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+
+use stoolap::api::ParamVec;
+
+use crate::database::{bind_extra, Database};
+use crate::sql;
+use crate::value::BindParams;
+
+/// A handle on one table for simple CRUD with dicts.
+///
+/// Made by `Database.table()`. Each method builds one parameterized
+/// statement and runs it like `Database.exec()` runs a statement. A `where`
+/// is either a dict of column values, matched with `=` (or `IS NULL` for
+/// None), or a SQL condition with its `params`.
+///
+/// Usage:
+///     users = db.table("users")
+///     users.insert({"id": 1, "name": "Alice"})
+///     users.update({"id": 1}, {"name": "Alicia"})
+///     users.find("name LIKE $1", ["A%"])
+#[pyclass]
+pub struct Table {
+    db: Py<Database>,
+    name: String,
+}
+
+impl Table {
+    pub fn new(db: Py<Database>, name: String) -> Self {
+        Self { db, name }
+    }
+}
+
+#[pymethods]
+impl Table {
+    /// Insert one row given as a dict of column values. Returns the number
+    /// of rows inserted.
+    fn insert(&self, py: Python<'_>, row: &Bound<'_, PyDict>) -> PyResult<PyObject> {
+        if row.is_empty() {
+            return Err(PyValueError::new_err("insert() needs at least one column"));
+        }
+        let db = self.db.borrow(py);
+        let mut columns = Vec::with_capacity(row.len());
+        let mut params = ParamVec::new();
+        for (column, value) in row.iter() {
+            columns.push(sql::quote_ident(&column.extract::<String>()?)?);
+            params.push(db.binding().value(&value)?);
+        }
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("${i}")).collect();
+        let stmt = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            sql::quote_ident(&self.name)?,
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        self.run(py, &stmt, BindParams::Positional(params))
+    }
+
+    /// The row whose primary key is `pk`, as a dict, or None. A composite
+    /// key is given as a tuple in key column order.
+    fn get(&self, py: Python<'_>, pk: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let db = self.db.borrow(py);
+        let key = db.primary_key(py, &self.name)?;
+        let (condition, params) = key_condition(&db, &self.name, &key, pk)?;
+        let stmt = format!(
+            "SELECT * FROM {} WHERE {condition} LIMIT 1",
+            sql::quote_ident(&self.name)?
+        );
+        let rows = self.run(py, &stmt, BindParams::Positional(params))?;
+        let rows = rows.bind(py);
+        if rows.len()? == 0 {
+            return Ok(py.None());
+        }
+        Ok(rows.get_item(0)?.unbind())
+    }
+
+    /// Rows matching `where` (all rows when None), as a list of dicts.
+    /// `order_by` is a SQL ordering such as "name DESC".
+    #[pyo3(signature = (r#where=None, params=None, *, order_by=None, limit=None))]
+    fn find(
+        &self,
+        py: Python<'_>,
+        r#where: Option<&Bound<'_, PyAny>>,
+        params: Option<&Bound<'_, PyAny>>,
+        order_by: Option<&str>,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let (condition, bind) = condition(&self.db.borrow(py), r#where, params)?;
+        let mut stmt = format!("SELECT * FROM {}", sql::quote_ident(&self.name)?);
+        if let Some(condition) = condition {
+            stmt.push_str(&format!(" WHERE {condition}"));
+        }
+        if let Some(order_by) = order_by {
+            stmt.push_str(&format!(" ORDER BY {order_by}"));
+        }
+        if let Some(limit) = limit {
+            stmt.push_str(&format!(" LIMIT {limit}"));
+        }
+        self.run(py, &stmt, bind)
+    }
+
+    /// Set the columns in the `values` dict on the rows matching `where`.
+    /// Returns the number of rows updated.
+    ///
+    /// The values are bound after `params`, or as `:set_0`, `:set_1`, ...
+    /// with named parameters.
+    #[pyo3(signature = (r#where, values, params=None))]
+    fn update(
+        &self,
+        py: Python<'_>,
+        r#where: &Bound<'_, PyAny>,
+        values: &Bound<'_, PyDict>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        if values.is_empty() {
+            return Err(PyValueError::new_err("update() needs at least one value"));
+        }
+        let db = self.db.borrow(py);
+        let (condition, mut bind) = condition(&db, Some(r#where), params)?;
+        let condition = required(condition, "update")?;
+        let mut assignments = Vec::with_capacity(values.len());
+        for (i, (column, value)) in values.iter().enumerate() {
+            let value = db.binding().value(&value)?;
+            let placeholder = bind_extra(&mut bind, &format!("set_{i}"), value)?;
+            assignments.push(format!(
+                "{} = {placeholder}",
+                sql::quote_ident(&column.extract::<String>()?)?
+            ));
+        }
+        let stmt = format!(
+            "UPDATE {} SET {} WHERE {condition}",
+            sql::quote_ident(&self.name)?,
+            assignments.join(", ")
+        );
+        self.run(py, &stmt, bind)
+    }
+
+    /// Delete the rows matching `where`. Returns the number of rows deleted.
+    #[pyo3(signature = (r#where, params=None))]
+    fn delete(
+        &self,
+        py: Python<'_>,
+        r#where: &Bound<'_, PyAny>,
+        params: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let (condition, bind) = condition(&self.db.borrow(py), Some(r#where), params)?;
+        let condition = required(condition, "delete")?;
+        let stmt = format!("DELETE FROM {} WHERE {condition}", sql::quote_ident(&self.name)?);
+        self.run(py, &stmt, bind)
+    }
+
+    /// Name of the table.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Table({:?})", self.name)
+    }
+}
+
+impl Table {
+    fn run(&self, py: Python<'_>, stmt: &str, bind: BindParams) -> PyResult<PyObject> {
+        self.db.borrow(py).exec_statement(py, stmt, bind)
+    }
+}
+
+/// The SQL condition of a `where` and the parameters it binds. A dict
+/// gives one comparison per column; a string is used as given.
+pub fn condition(
+    db: &Database,
+    r#where: Option<&Bound<'_, PyAny>>,
+    params: Option<&Bound<'_, PyAny>>,
+) -> PyResult<(Option<String>, BindParams)> {
+    let Some(r#where) = r#where.filter(|w| !w.is_none()) else {
+        return Ok((None, db.binding().params(params)?));
+    };
+    if let Ok(columns) = r#where.downcast::<PyDict>() {
+        if params.is_some_and(|p| !p.is_none()) {
+            return Err(PyTypeError::new_err(
+                "params can only be given with a SQL where condition, not a dict",
+            ));
+        }
+        let mut bind = BindParams::Positional(ParamVec::new());
+        let mut condition = Vec::with_capacity(columns.len());
+        for (i, (column, value)) in columns.iter().enumerate() {
+            let column = sql::quote_ident(&column.extract::<String>()?)?;
+            if value.is_none() {
+                condition.push(format!("{column} IS NULL"));
+            } else {
+                let value = db.binding().value(&value)?;
+                let placeholder = bind_extra(&mut bind, &format!("where_{i}"), value)?;
+                condition.push(format!("{column} = {placeholder}"));
+            }
+        }
+        let condition = (!condition.is_empty()).then(|| condition.join(" AND "));
+        return Ok((condition, bind));
+    }
+    let condition: String = r#where.extract().map_err(|_| {
+        PyTypeError::new_err("where must be a dict of column values or a SQL string")
+    })?;
+    let condition = db.translate(&condition)?;
+    Ok((Some(condition), db.binding().params(params)?))
+}
+
+/// The condition matching primary key `key` of `table` to the value `pk`,
+/// a tuple or list for a composite key, with its parameters.
+pub fn key_condition(
+    db: &Database,
+    table: &str,
+    key: &[String],
+    pk: &Bound<'_, PyAny>,
+) -> PyResult<(String, ParamVec)> {
+    let values: Vec<Bound<'_, PyAny>> = if key.len() == 1 {
+        vec![pk.clone()]
+    } else if let Ok(tuple) = pk.downcast::<PyTuple>() {
+        tuple.iter().collect()
+    } else if let Ok(list) = pk.downcast::<PyList>() {
+        list.iter().collect()
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "table '{table}' has a composite primary key; pass a tuple of {} values",
+            key.len()
+        )));
+    };
+    if values.len() != key.len() {
+        return Err(PyValueError::new_err(format!(
+            "got {} key values for the primary key ({})",
+            values.len(),
+            key.join(", ")
+        )));
+    }
+    let mut params = ParamVec::new();
+    let mut condition = Vec::with_capacity(key.len());
+    for (i, (column, value)) in key.iter().zip(&values).enumerate() {
+        condition.push(format!("{} = ${}", sql::quote_ident(column)?, i + 1));
+        params.push(db.binding().value(value)?);
+    }
+    Ok((condition.join(" AND "), params))
+}
+
+/// The condition of an `update()` or `delete()`, which must not be empty:
+/// touching every row takes an explicit condition such as "TRUE".
+pub fn required(condition: Option<String>, method: &str) -> PyResult<String> {
+    condition.ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "{method}() with an empty where would touch every row; pass \"TRUE\" to mean that"
+        ))
+    })
+}
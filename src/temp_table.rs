@@ -0,0 +1,254 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use stoolap::api::ParamVec;
+use stoolap::core::Value;
+
+use crate::database::Database;
+use crate::sql;
+use crate::value::BindOptions;
+
+/// Numbers the temp tables of this process.
+static NEXT_TEMP_TABLE: AtomicU64 = AtomicU64::new(1);
+
+/// A table of Python data that exists for the duration of a `with` block.
+///
+/// Made by `Database.temp_table()`. Entering the block creates a uniquely
+/// named table, loads the rows and returns the table name; leaving it drops
+/// the table.
+///
+/// Usage:
+///     with db.temp_table([{"id": 1}, {"id": 7}]) as ids:
+///         db.query(f"SELECT u.* FROM users u JOIN {ids} t ON u.id = t.id")
+#[pyclass]
+pub struct TempTable {
+    db: Py<Database>,
+    name: String,
+    columns: Vec<String>,
+    rows: Option<Vec<ParamVec>>,
+}
+
+impl TempTable {
+    /// Read `data`: dicts, sequences (with `columns`) or a DataFrame.
+    pub fn new(
+        db: Py<Database>,
+        data: &Bound<'_, PyAny>,
+        columns: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        let owner = db.clone_ref(data.py());
+        let owner = owner.borrow(data.py());
+        let binding = owner.binding();
+        let (columns, rows) = if data.hasattr("itertuples")? && data.hasattr("columns")? {
+            let columns = match columns {
+                Some(columns) => columns,
+                None => data
+                    .getattr("columns")?
+                    .try_iter()?
+                    .map(|c| Ok(c?.str()?.to_string()))
+                    .collect::<PyResult<_>>()?,
+            };
+            let kwargs = PyDict::new(data.py());
+            kwargs.set_item("index", false)?;
+            kwargs.set_item("name", data.py().None())?;
+            let rows = data
+                .call_method("itertuples", (), Some(&kwargs))?
+                .try_iter()?
+                .map(|row| row_values(binding, &row?))
+                .collect::<PyResult<_>>()?;
+            (columns, rows)
+        } else {
+            let mut columns = columns;
+            let mut rows = Vec::new();
+            for row in data.try_iter()? {
+                let row = row?;
+                if let Ok(row) = row.downcast::<PyDict>() {
+                    let names = columns
+                        .get_or_insert_with(|| row.keys().iter().map(|k| k.to_string()).collect());
+                    let mut params = ParamVec::new();
+                    for name in names.iter() {
+                        params.push(match row.get_item(name)? {
+                            Some(value) => to_value(binding, &value)?,
+                            None => Value::null_unknown(),
+                        });
+                    }
+                    rows.push(params);
+                } else {
+                    rows.push(row_values(binding, &row)?);
+                }
+            }
+            let columns = columns.ok_or_else(|| {
+                PyValueError::new_err("columns are required for rows that are not dicts")
+            })?;
+            (columns, rows)
+        };
+        if columns.is_empty() {
+            return Err(PyValueError::new_err(
+                "a temp table needs at least one column",
+            ));
+        }
+        if let Some(row) = rows.iter().find(|row| row.len() != columns.len()) {
+            return Err(PyValueError::new_err(format!(
+                "got a row of {} values for {} columns",
+                row.len(),
+                columns.len()
+            )));
+        }
+        let name = format!(
+            "temp_{}_{}",
+            std::process::id(),
+            NEXT_TEMP_TABLE.fetch_add(1, Ordering::Relaxed)
+        );
+        Ok(Self {
+            db,
+            name,
+            columns,
+            rows: Some(rows),
+        })
+    }
+}
+
+#[pymethods]
+impl TempTable {
+    /// Create and load the table. Returns its name.
+    fn __enter__(&mut self, py: Python<'_>) -> PyResult<String> {
+        let Some(rows) = self.rows.take() else {
+            return Err(PyValueError::new_err(
+                "a temp table can only be entered once",
+            ));
+        };
+        let names: Vec<String> = self.columns
+            .iter()
+            .map(|c| sql::quote_ident(c))
+            .collect::<PyResult<_>>()?;
+        let definitions: Vec<String> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| format!("{name} {}", column_type(&rows, i)))
+            .collect();
+        let table = sql::quote_ident(&self.name)?;
+        let db = self.db.bind(py);
+        let create = format!("CREATE TABLE {table} ({})", definitions.join(", "));
+        db.call_method1("execute", (create,))?;
+        if !rows.is_empty() {
+            let placeholders: Vec<String> = (1..=names.len()).map(|i| format!("${i}")).collect();
+            let insert = format!(
+                "INSERT INTO {table} ({}) VALUES ({})",
+                names.join(", "),
+                placeholders.join(", ")
+            );
+            if let Err(err) = db.borrow().execute_params(py, &insert, rows) {
+                let _ = self.drop_table(py);
+                return Err(err);
+            }
+        }
+        Ok(self.name.clone())
+    }
+
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> PyResult<bool> {
+        self.drop_table(py)?;
+        Ok(false)
+    }
+
+    /// Name of the table.
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TempTable({:?}, columns={:?})", self.name, self.columns)
+    }
+}
+
+impl TempTable {
+    fn drop_table(&self, py: Python<'_>) -> PyResult<()> {
+        let stmt = format!("DROP TABLE IF EXISTS {}", sql::quote_ident(&self.name)?);
+        self.db.bind(py).call_method1("execute", (stmt,))?;
+        Ok(())
+    }
+}
+
+/// Convert a row given as a sequence of values.
+fn row_values(binding: &BindOptions, row: &Bound<'_, PyAny>) -> PyResult<ParamVec> {
+    if row.downcast::<PyString>().is_ok() {
+        return Err(PyTypeError::new_err(
+            "temp table rows must be dicts or sequences of values, got str",
+        ));
+    }
+    let mut params = ParamVec::new();
+    for value in row.try_iter()? {
+        params.push(to_value(binding, &value?)?);
+    }
+    Ok(params)
+}
+
+/// Like `BindOptions::value`, also unwrapping NumPy scalars such as
+/// `numpy.int64`.
+fn to_value(binding: &BindOptions, value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    match binding.value(value) {
+        Ok(value) => Ok(value),
+        Err(err) if value.hasattr("item")? => {
+            binding.value(&value.call_method0("item")?).map_err(|_| err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// SQL type of column `index`, from its non-NULL values. Integers mixed
+/// with floats make a FLOAT column; columns without values are TEXT.
+fn column_type(rows: &[ParamVec], index: usize) -> String {
+    let mut column_type: Option<String> = None;
+    for row in rows {
+        let Some(value) = row.get(index) else {
+            continue;
+        };
+        let value_type = match value {
+            Value::Null(_) => continue,
+            Value::Boolean(_) => "BOOLEAN".to_string(),
+            Value::Integer(_) => "INTEGER".to_string(),
+            Value::Float(_) => "FLOAT".to_string(),
+            Value::Timestamp(_) => "TIMESTAMP".to_string(),
+            Value::Extension(_) => match value.as_vector_f32() {
+                Some(vector) => format!("VECTOR({})", vector.len()),
+                None if value.as_json().is_some() => "JSON".to_string(),
+                None => "TEXT".to_string(),
+            },
+            _ => "TEXT".to_string(),
+        };
+        column_type = match column_type {
+            None => Some(value_type),
+            Some(current) if current == value_type => Some(current),
+            Some(current) if is_number(&current) && is_number(&value_type) => {
+                Some("FLOAT".to_string())
+            }
+            Some(_) => return "TEXT".to_string(),
+        };
+    }
+    column_type.unwrap_or_else(|| "TEXT".to_string())
+}
+
+fn is_number(column_type: &str) -> bool {
+    column_type == "INTEGER" || column_type == "FLOAT"
+}
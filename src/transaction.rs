@@ -14,14 +14,154 @@
 
 use pyo3::prelude::*;
 use pyo3::types::PyList;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use stoolap::api::Transaction as ApiTransaction;
+use stoolap::api::{Database as ApiDatabase, ParamVec, Rows};
+use stoolap::CachedPlanRef;
 
-use crate::database::{first_row_to_dict, rows_to_dicts, rows_to_raw, to_named_params};
+use crate::cancel::{self, CancellationToken};
+use crate::connection::Connection;
+use crate::database::{
+    first_row_to_dict, rows_to_columnar, rows_to_dicts, rows_to_raw, ConvertOptions,
+};
+use crate::deadline;
 use crate::error::to_py;
+use crate::hooks::{Change, ParamInfo};
+use crate::sql;
 use crate::statement::PreparedStatement;
-use crate::value::{parse_params, BindParams};
+use crate::value::BindParams;
+use crate::wal;
+
+/// The engine side of a `Transaction`: a handle of its own on the database
+/// with an open `BEGIN`. `stoolap::api::Transaction` only runs SELECT,
+/// INSERT, UPDATE and DELETE, while a handle's SQL transaction also takes
+/// DDL and savepoints, and each cloned handle keeps its transaction to
+/// itself.
+pub struct Session {
+    db: ApiDatabase,
+    id: i64,
+    active: bool,
+    /// Savepoints in the order they were set.
+    savepoints: Vec<String>,
+}
+
+impl Session {
+    /// Begin a transaction on a new handle to `db`, at snapshot isolation
+    /// or at the database's default level.
+    pub fn begin(db: &ApiDatabase, snapshot: bool) -> stoolap::Result<Self> {
+        let db = db.clone();
+        let begin = if snapshot {
+            "BEGIN TRANSACTION ISOLATION LEVEL SNAPSHOT"
+        } else {
+            "BEGIN"
+        };
+        db.execute(begin, ())?;
+        let mut session = Self {
+            db,
+            id: 0,
+            active: true,
+            savepoints: Vec::new(),
+        };
+        session.id = session
+            .db
+            .query_one("SELECT CURRENT_TRANSACTION_ID()", ())?;
+        Ok(session)
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn execute(&mut self, sql: &str, bind: BindParams) -> stoolap::Result<i64> {
+        deadline::execute(&self.db, sql, bind)
+    }
+
+    pub fn execute_plan(
+        &mut self,
+        plan: &CachedPlanRef,
+        sql: &str,
+        bind: BindParams,
+    ) -> stoolap::Result<i64> {
+        deadline::execute_plan(&self.db, plan, sql, bind)
+    }
+
+    pub fn query(&mut self, sql: &str, bind: BindParams) -> stoolap::Result<Rows> {
+        deadline::query(&self.db, sql, bind)
+    }
+
+    pub fn query_plan(
+        &mut self,
+        plan: &CachedPlanRef,
+        sql: &str,
+        bind: BindParams,
+    ) -> stoolap::Result<Rows> {
+        deadline::query_plan(&self.db, plan, sql, bind)
+    }
+
+    /// Set savepoint `name`, replacing an earlier one of the same name.
+    /// Returns the statements run.
+    pub fn savepoint(&mut self, name: &str) -> PyResult<Vec<String>> {
+        let sql = format!("SAVEPOINT {}", sql::quote_ident(name)?);
+        self.db.execute(&sql, ()).map_err(to_py)?;
+        self.savepoints.retain(|n| n != name);
+        self.savepoints.push(name.to_string());
+        Ok(vec![sql])
+    }
+
+    /// Forget savepoint `name` and those set after it. The engine does not
+    /// parse RELEASE SAVEPOINT, so the savepoints are only forgotten here;
+    /// no statement is run.
+    pub fn release_savepoint(&mut self, name: &str) -> PyResult<Vec<String>> {
+        let position = self.find_savepoint(name)?;
+        self.savepoints.truncate(position);
+        Ok(Vec::new())
+    }
+
+    /// Undo the changes made since savepoint `name`, forgetting the
+    /// savepoints set after it. The engine drops the savepoint it rolls back
+    /// to, so it is set again. Returns the statements run.
+    pub fn rollback_to_savepoint(&mut self, name: &str) -> PyResult<Vec<String>> {
+        let position = self.find_savepoint(name)?;
+        let quoted = sql::quote_ident(name)?;
+        let statements = vec![
+            format!("ROLLBACK TO SAVEPOINT {quoted}"),
+            format!("SAVEPOINT {quoted}"),
+        ];
+        for sql in &statements {
+            self.db.execute(sql, ()).map_err(to_py)?;
+        }
+        self.savepoints.truncate(position + 1);
+        Ok(statements)
+    }
+
+    fn find_savepoint(&self, name: &str) -> PyResult<usize> {
+        self.savepoints.iter().rposition(|n| n == name).ok_or_else(|| {
+            crate::error::StoolapError::new_err(format!("savepoint '{name}' does not exist"))
+        })
+    }
+
+    pub fn commit(&mut self) -> stoolap::Result<()> {
+        self.finish("COMMIT")
+    }
+
+    pub fn rollback(&mut self) -> stoolap::Result<()> {
+        self.finish("ROLLBACK")
+    }
+
+    fn finish(&mut self, sql: &str) -> stoolap::Result<()> {
+        self.active = false;
+        self.db.execute(sql, ()).map(|_| ())
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Abandoned without commit/rollback
+        if self.active {
+            let _ = self.db.execute("ROLLBACK", ());
+        }
+    }
+}
 
 /// A Stoolap transaction.
 ///
@@ -31,21 +171,173 @@ use crate::value::{parse_params, BindParams};
 ///     tx.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
 ///     # auto-commits on clean exit, auto-rollbacks on exception
 /// ```
+///
+/// A transaction may be used from any thread. Statements issued
+/// concurrently from several threads run one at a time.
 #[pyclass]
 pub struct Transaction {
-    tx: Mutex<Option<ApiTransaction>>,
+    conn: Arc<Connection>,
+    id: i64,
+    isolation_level: &'static str,
+    tx: Mutex<Option<Session>>,
+    /// Writes reported to `watch()` callbacks once the transaction commits.
+    changes: Mutex<Vec<Change>>,
+    /// Write statements whose cached results are dropped on commit.
+    writes: Mutex<Vec<String>>,
+    /// Statements added to the WAL log on commit, while capture is on.
+    wal: Mutex<Vec<String>>,
 }
 
 impl Transaction {
-    pub fn from_tx(tx: ApiTransaction) -> Self {
+    pub fn new(conn: Arc<Connection>, tx: Session, isolation_level: &'static str) -> Self {
+        conn.stats.transaction_started();
         Self {
+            conn,
+            id: tx.id(),
+            isolation_level,
             tx: Mutex::new(Some(tx)),
+            changes: Mutex::new(Vec::new()),
+            writes: Mutex::new(Vec::new()),
+            wal: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Run write statements through the connection, remembering the changed
+    /// table for watchers and the result cache.
+    fn run_write<F>(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        mut params: ParamInfo,
+        count: u64,
+        f: F,
+    ) -> PyResult<i64>
+    where
+        F: FnOnce(Python<'_>) -> PyResult<i64>,
+    {
+        let values = params.values.take();
+        let logged = match values.as_ref().filter(|_| self.conn.hooks.wal.is_enabled()) {
+            Some(values) => Some(wal::statements(sql, values)?),
+            None => None,
+        };
+        let affected = self.conn.run_write(py, sql, params, count, f)?;
+        if let Some(statements) = logged {
+            let mut guard = self.wal.lock().unwrap_or_else(|e| e.into_inner());
+            guard.extend(statements);
+        }
+        if self.conn.cache.is_enabled() {
+            let mut guard = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+            guard.push(sql.to_string());
+        }
+        if affected > 0 && self.conn.hooks.is_watching() {
+            if let Some(change) = self.conn.change(sql, values.as_deref()) {
+                let mut guard = self.changes.lock().unwrap_or_else(|e| e.into_inner());
+                guard.push(change);
+            }
+        }
+        Ok(affected)
+    }
+
+    /// Execute `sql` once per parameter set. Returns total rows affected.
+    pub(crate) fn execute_params(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        all_params: Vec<ParamVec>,
+    ) -> PyResult<i64> {
+        let info = self.conn.hooks.describe_batch(&all_params);
+        let count = all_params.len() as u64;
+        self.run_write(py, sql, info, count, |py| {
+            self.conn.allow_threads(py, || {
+                self.with_tx(|tx| {
+                    let plan = tx.db.cached_plan(sql).map_err(to_py)?;
+                    let mut total = 0i64;
+                    for params in all_params {
+                        total += tx
+                            .execute_plan(&plan, sql, BindParams::Positional(params))
+                            .map_err(to_py)?;
+                    }
+                    Ok(total)
+                })
+            })
+        })
+    }
+
+    /// Report a commit to the hooks and pending changes to watchers, and
+    /// drop the cached results it may have changed.
+    fn committed(&self, py: Python<'_>) {
+        let writes = {
+            let mut guard = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *guard)
+        };
+        for sql in &writes {
+            self.conn.cache.invalidate(sql);
+        }
+        let statements = {
+            let mut guard = self.wal.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *guard)
+        };
+        self.conn.hooks.wal.append(statements);
+        self.conn.hooks.transaction_finished(py, true);
+        self.conn.committed(py);
+        let changes = {
+            let mut guard = self.changes.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *guard)
+        };
+        if !changes.is_empty() {
+            self.conn.hooks.changed(py, &changes);
         }
     }
 
+    /// Whether the transaction has not yet been committed or rolled back.
+    pub fn is_active(&self) -> bool {
+        self.tx.lock().map(|g| g.is_some()).unwrap_or(false)
+    }
+
+    /// Run a savepoint command. It changes no rows, so it skips the write
+    /// bookkeeping of `execute()`.
+    fn run_savepoint<F>(&self, py: Python<'_>, f: F) -> PyResult<()>
+    where
+        F: FnOnce(&mut Session) -> PyResult<Vec<String>> + Send,
+    {
+        self.conn.check_fork()?;
+        let statements = self.conn.allow_threads(py, || self.with_tx(f))?;
+        // Replayed as is, so that a replica undoes the same statements
+        if self.conn.hooks.wal.is_enabled() {
+            let mut guard = self.wal.lock().unwrap_or_else(|e| e.into_inner());
+            guard.extend(statements);
+        }
+        Ok(())
+    }
+
+    /// Run one statement of a `Database.exec()` script: its rows as dicts if
+    /// it produces any, otherwise the number of rows affected.
+    pub(crate) fn exec_statement(
+        &self,
+        py: Python<'_>,
+        stmt: &str,
+        bind: BindParams,
+    ) -> PyResult<PyObject> {
+        let info = self.conn.hooks.describe(&bind);
+        if sql::returns_rows(stmt) {
+            return self.conn.run_query(py, stmt, info, |py| {
+                let rows = self.conn.allow_threads(py, || {
+                    self.with_tx(|tx| tx.query(stmt, bind).map_err(to_py))
+                })?;
+                rows_to_dicts(py, rows, ConvertOptions::plain(&self.conn.fetch))
+            });
+        }
+        let affected = self.run_write(py, stmt, info, 1, |py| {
+            self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.execute(stmt, bind).map_err(to_py))
+            })
+        })?;
+        Ok(affected.into_pyobject(py)?.into_any().unbind())
+    }
+
     fn with_tx<F, R>(&self, f: F) -> PyResult<R>
     where
-        F: FnOnce(&mut ApiTransaction) -> PyResult<R>,
+        F: FnOnce(&mut Session) -> PyResult<R>,
     {
         let mut guard = self
             .tx
@@ -62,77 +354,127 @@ impl Transaction {
 impl Transaction {
     /// Execute a DDL/DML statement within the transaction.
     ///
-    /// Returns the number of rows affected.
-    #[pyo3(signature = (sql, params=None))]
-    fn execute(&self, py: Python<'_>, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<i64> {
-        let bind = parse_params(params)?;
-        let sql = sql.to_string();
-        py.allow_threads(|| {
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.execute(&sql, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.execute_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
+    /// Returns the number of rows affected. `cancel` works as for
+    /// `Database.execute()`.
+    #[pyo3(signature = (sql, params=None, *, cancel=None))]
+    fn execute(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+    ) -> PyResult<i64> {
+        cancel::check(cancel)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let sql = self.conn.translate(sql)?.into_owned();
+        self.run_write(py, &sql, info, 1, |py| {
+            self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.execute(&sql, bind).map_err(to_py))
             })
         })
     }
 
     /// Query rows within the transaction. Returns a list of dicts.
-    #[pyo3(signature = (sql, params=None))]
-    fn query(&self, py: Python<'_>, sql: &str, params: Option<&Bound<'_, PyAny>>) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let sql = sql.to_string();
-        let rows = py.allow_threads(|| {
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query(&sql, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        rows_to_dicts(py, rows)
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, limit=None, cancel=None))]
+    fn query(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        limit: Option<usize>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
+    ) -> PyResult<PyObject> {
+        cancel::check(cancel)?;
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .limit(limit)
+                .cancel(cancel);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let sql = self.conn.translate(sql)?.into_owned();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query(&sql, bind).map_err(to_py))
+            })?;
+            rows_to_dicts(py, rows, options)
+        })
     }
 
     /// Query a single row. Returns a dict or None.
-    #[pyo3(signature = (sql, params=None))]
+    #[pyo3(signature = (sql, params=None, *, cancel=None))]
     fn query_one(
         &self,
         py: Python<'_>,
         sql: &str,
         params: Option<&Bound<'_, PyAny>>,
+        cancel: Option<&Bound<'_, CancellationToken>>,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let sql = sql.to_string();
-        let rows = py.allow_threads(|| {
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query(&sql, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        first_row_to_dict(py, rows)
+        cancel::check(cancel)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let sql = self.conn.translate(sql)?.into_owned();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query(&sql, bind).map_err(to_py))
+            })?;
+            first_row_to_dict(py, rows, &self.conn.fetch)
+        })
     }
 
     /// Query rows in raw format. Returns { columns: [...], rows: [[...], ...] }.
-    #[pyo3(signature = (sql, params=None))]
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, typed=false))]
     fn query_raw(
         &self,
         py: Python<'_>,
         sql: &str,
         params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        typed: bool,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
-        let sql = sql.to_string();
-        let rows = py.allow_threads(|| {
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query(&sql, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        rows_to_raw(py, rows)
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .typed(typed);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let sql = self.conn.translate(sql)?.into_owned();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query(&sql, bind).map_err(to_py))
+            })?;
+            rows_to_raw(py, rows, options)
+        })
+    }
+
+    /// Query rows as a dict of column lists: {column: [values...]}.
+    #[pyo3(signature = (sql, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, numpy=false))]
+    fn query_columnar(
+        &self,
+        py: Python<'_>,
+        sql: &str,
+        params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        numpy: bool,
+    ) -> PyResult<PyObject> {
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .numpy(py, numpy)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
+        let sql = self.conn.translate(sql)?.into_owned();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query(&sql, bind).map_err(to_py))
+            })?;
+            rows_to_columnar(py, rows, options)
+        })
     }
 
     /// Execute the same SQL with multiple parameter sets.
@@ -145,12 +487,12 @@ impl Transaction {
         sql: &str,
         params_list: &Bound<'_, PyList>,
     ) -> PyResult<i64> {
-        let sql = sql.to_string();
+        let sql = self.conn.translate(sql)?.into_owned();
 
         // Parse all param sets while holding GIL
         let mut all_params = Vec::with_capacity(params_list.len());
         for item in params_list.iter() {
-            let bind = parse_params(Some(&item))?;
+            let bind = self.conn.binding.params(Some(&item))?;
             match bind {
                 BindParams::Positional(p) => all_params.push(p),
                 BindParams::Named(_) => {
@@ -161,25 +503,7 @@ impl Transaction {
             }
         }
 
-        // Execute without GIL
-        py.allow_threads(|| {
-            use stoolap::parser::Parser;
-            let mut parser = Parser::new(&sql);
-            let program = parser.parse_program().map_err(|e| {
-                crate::error::StoolapError::new_err(e.to_string())
-            })?;
-            let stmt = program.statements.first().ok_or_else(|| {
-                crate::error::StoolapError::new_err("No SQL statement found")
-            })?;
-
-            self.with_tx(|tx| {
-                let mut total = 0i64;
-                for params in all_params {
-                    total += tx.execute_prepared(stmt, params).map_err(to_py)?;
-                }
-                Ok(total)
-            })
-        })
+        self.execute_params(py, &sql, all_params)
     }
 
     /// Execute a prepared statement within the transaction. Returns rows affected.
@@ -190,41 +514,44 @@ impl Transaction {
         stmt: &PreparedStatement,
         params: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<i64> {
-        let bind = parse_params(params)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = stmt.plan().clone();
         let sql = stmt.sql_text().to_string();
-        py.allow_threads(|| {
-            let statement = plan.statement.as_ref();
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.execute_prepared(statement, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.execute_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
+        let translated = stmt.translated().to_string();
+        self.run_write(py, &sql, info, 1, |py| {
+            self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.execute_plan(&plan, &translated, bind).map_err(to_py))
             })
         })
     }
 
     /// Query rows using a prepared statement within the transaction. Returns list of dicts.
-    #[pyo3(signature = (stmt, params=None))]
+    #[pyo3(signature = (stmt, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, limit=None))]
     fn query_prepared(
         &self,
         py: Python<'_>,
         stmt: &PreparedStatement,
         params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        limit: Option<usize>,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .limit(limit);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = stmt.plan().clone();
         let sql = stmt.sql_text().to_string();
-        let rows = py.allow_threads(|| {
-            let statement = plan.statement.as_ref();
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query_prepared(statement, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        rows_to_dicts(py, rows)
+        let translated = stmt.translated().to_string();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query_plan(&plan, &translated, bind).map_err(to_py))
+            })?;
+            rows_to_dicts(py, rows, options)
+        })
     }
 
     /// Query a single row using a prepared statement. Returns dict or None.
@@ -235,47 +562,51 @@ impl Transaction {
         stmt: &PreparedStatement,
         params: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = stmt.plan().clone();
         let sql = stmt.sql_text().to_string();
-        let rows = py.allow_threads(|| {
-            let statement = plan.statement.as_ref();
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query_prepared(statement, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        first_row_to_dict(py, rows)
+        let translated = stmt.translated().to_string();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query_plan(&plan, &translated, bind).map_err(to_py))
+            })?;
+            first_row_to_dict(py, rows, &self.conn.fetch)
+        })
     }
 
     /// Query rows using a prepared statement in raw format. Returns { columns: [...], rows: [[...], ...] }.
-    #[pyo3(signature = (stmt, params=None))]
+    #[pyo3(signature = (stmt, params=None, *, intern_strings=false, max_rows=None, max_result_bytes=None, typed=false))]
     fn query_raw_prepared(
         &self,
         py: Python<'_>,
         stmt: &PreparedStatement,
         params: Option<&Bound<'_, PyAny>>,
+        intern_strings: bool,
+        max_rows: Option<usize>,
+        max_result_bytes: Option<usize>,
+        typed: bool,
     ) -> PyResult<PyObject> {
-        let bind = parse_params(params)?;
+        let options =
+            ConvertOptions::new(&self.conn.fetch, intern_strings, max_rows, max_result_bytes)
+                .typed(typed);
+        let bind = self.conn.binding.params(params)?;
+        let info = self.conn.hooks.describe(&bind);
         let plan = stmt.plan().clone();
         let sql = stmt.sql_text().to_string();
-        let rows = py.allow_threads(|| {
-            let statement = plan.statement.as_ref();
-            self.with_tx(|tx| match bind {
-                BindParams::Positional(p) => tx.query_prepared(statement, p).map_err(to_py),
-                BindParams::Named(named) => {
-                    tx.query_named(&sql, to_named_params(&named)).map_err(to_py)
-                }
-            })
-        })?;
-        rows_to_raw(py, rows)
+        let translated = stmt.translated().to_string();
+        self.conn.run_query(py, &sql, info, |py| {
+            let rows = self.conn.allow_threads(py, || {
+                self.with_tx(|tx| tx.query_plan(&plan, &translated, bind).map_err(to_py))
+            })?;
+            rows_to_raw(py, rows, options)
+        })
     }
 
     /// Commit the transaction.
-    fn commit(&self, py: Python<'_>) -> PyResult<()> {
-        py.allow_threads(|| {
+    pub(crate) fn commit(&self, py: Python<'_>) -> PyResult<()> {
+        self.conn.check_fork()?;
+        let result = py.allow_threads(|| -> PyResult<PyResult<()>> {
             let mut guard = self
                 .tx
                 .lock()
@@ -283,13 +614,22 @@ impl Transaction {
             let mut tx = guard.take().ok_or_else(|| {
                 crate::error::StoolapError::new_err("Transaction is no longer active")
             })?;
-            tx.commit().map_err(to_py)
-        })
+            let result = tx.commit().map_err(to_py);
+            self.conn.stats.transaction_finished(result.is_ok());
+            Ok(result)
+        })?;
+        if result.is_ok() {
+            self.committed(py);
+        } else {
+            self.conn.hooks.transaction_finished(py, false);
+        }
+        result
     }
 
     /// Rollback the transaction.
-    fn rollback(&self, py: Python<'_>) -> PyResult<()> {
-        py.allow_threads(|| {
+    pub(crate) fn rollback(&self, py: Python<'_>) -> PyResult<()> {
+        self.conn.check_fork()?;
+        let result = py.allow_threads(|| {
             let mut guard = self
                 .tx
                 .lock()
@@ -297,8 +637,47 @@ impl Transaction {
             let mut tx = guard.take().ok_or_else(|| {
                 crate::error::StoolapError::new_err("Transaction is no longer active")
             })?;
+            self.conn.stats.transaction_finished(false);
             tx.rollback().map_err(to_py)
-        })
+        });
+        if result.is_ok() {
+            self.conn.hooks.transaction_finished(py, false);
+        }
+        result
+    }
+
+    /// Mark a savepoint named `name` within the transaction.
+    fn savepoint(&self, py: Python<'_>, name: &str) -> PyResult<()> {
+        self.run_savepoint(py, |tx| tx.savepoint(name))
+    }
+
+    /// Forget savepoint `name`, keeping the changes made since it.
+    fn release_savepoint(&self, py: Python<'_>, name: &str) -> PyResult<()> {
+        self.run_savepoint(py, |tx| tx.release_savepoint(name))
+    }
+
+    /// Undo the changes made since savepoint `name`. The savepoint remains
+    /// and the transaction stays active.
+    fn rollback_to_savepoint(&self, py: Python<'_>, name: &str) -> PyResult<()> {
+        self.run_savepoint(py, |tx| tx.rollback_to_savepoint(name))
+    }
+
+    /// True until the transaction is committed or rolled back.
+    #[getter]
+    fn active(&self) -> bool {
+        self.is_active()
+    }
+
+    /// Engine transaction id.
+    #[getter]
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Isolation level as an SQL name: "READ COMMITTED" or "SNAPSHOT".
+    #[getter]
+    fn isolation_level(&self) -> &'static str {
+        self.isolation_level
     }
 
     /// Context manager: enter.
@@ -315,20 +694,30 @@ impl Transaction {
         _exc_val: Option<&Bound<'_, PyAny>>,
         _exc_tb: Option<&Bound<'_, PyAny>>,
     ) -> PyResult<bool> {
-        let mut guard = self
-            .tx
-            .lock()
+        self.conn.check_fork()?;
+        // Wait for statements running on other threads without holding the GIL
+        let taken = py
+            .allow_threads(|| self.tx.lock().map(|mut guard| guard.take()).map_err(|_| ()))
             .map_err(|_| crate::error::StoolapError::new_err("Transaction lock poisoned"))?;
 
-        if let Some(mut tx) = guard.take() {
+        if let Some(mut tx) = taken {
             if exc_type.is_some() {
                 // Exception occurred -> rollback
+                self.conn.stats.transaction_finished(false);
                 py.allow_threads(|| {
                     let _ = tx.rollback();
                 });
+                self.conn.hooks.transaction_finished(py, false);
             } else {
                 // Clean exit -> commit
-                py.allow_threads(|| tx.commit().map_err(to_py))?;
+                let result = py.allow_threads(|| tx.commit().map_err(to_py));
+                self.conn.stats.transaction_finished(result.is_ok());
+                if result.is_ok() {
+                    self.committed(py);
+                } else {
+                    self.conn.hooks.transaction_finished(py, false);
+                }
+                result?;
             }
         }
 
@@ -337,11 +726,27 @@ impl Transaction {
     }
 
     fn __repr__(&self) -> String {
-        let active = self.tx.lock().map(|g| g.is_some()).unwrap_or(false);
-        if active {
+        if self.is_active() {
             "Transaction(active)".to_string()
         } else {
             "Transaction(closed)".to_string()
         }
     }
 }
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        // Abandoned without commit/rollback: the engine rolls it back on drop
+        if let Ok(guard) = self.tx.get_mut() {
+            if self.conn.inherited() {
+                // Belongs to the parent process's engine; leave it alone
+                std::mem::forget(guard.take());
+            } else if guard.is_some() {
+                self.conn.stats.transaction_finished(false);
+                if self.conn.hooks.is_active() {
+                    Python::with_gil(|py| self.conn.hooks.transaction_finished(py, false));
+                }
+            }
+        }
+    }
+}
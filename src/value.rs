@@ -12,55 +12,77 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use pyo3::exceptions::PyTypeError;
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::sync::GILOnceCell;
 use pyo3::types::{
-    PyBool, PyDateAccess, PyDateTime, PyDict, PyFloat, PyInt, PyList, PyString, PyTimeAccess,
-    PyTuple, timezone_utc,
+    PyBool, PyDate, PyDateAccess, PyDateTime, PyDict, PyFloat, PyInt, PyList, PyString,
+    PyTimeAccess, PyTuple, PyTzInfo, timezone_utc,
 };
 use chrono::{Datelike, Timelike};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use stoolap::api::ParamVec;
 use stoolap::core::Value;
 
-/// A vector of f32 values for similarity search.
+use crate::converter;
+use crate::dlpack;
+use crate::error::StoolapError;
+use crate::json::{Json, JsonPath};
+use crate::vector::{is_ndarray, ndarray_to_vector, PyVector};
+use crate::warning;
+
+/// How NaN and infinite floats are bound and fetched.
+const NAN_POLICIES: [&str; 3] = ["keep", "null", "raise"];
+
+/// Index into `NAN_POLICIES` of the policy for bound parameters.
+static BIND_NAN: AtomicU8 = AtomicU8::new(0);
+/// Index into `NAN_POLICIES` of the policy for fetched values.
+static FETCH_NAN: AtomicU8 = AtomicU8::new(0);
+
+/// Set how NaN and infinite FLOAT values are handled: "keep" (the default)
+/// passes them through, "null" replaces them with NULL (bound) or None
+/// (fetched), and "raise" fails with `ValueError` when one is bound or
+/// `StoolapError` when a query returns one. `bind` and `fetch` are set
+/// independently; a policy left as None is unchanged. Returns the previous
+/// `(bind, fetch)` policies, so they can be restored.
 ///
-/// Wraps a list of floats so that Stoolap stores them as a native VECTOR
-/// rather than a JSON array.
-///
-/// Usage:
-///     from stoolap import Vector
-///     v = Vector([0.1, 0.2, 0.3])
-///     db.execute("INSERT INTO t (embedding) VALUES ($1)", [v])
-#[pyclass(name = "Vector")]
-#[derive(Clone)]
-pub struct PyVector {
-    pub data: Vec<f32>,
-}
-
-#[pymethods]
-impl PyVector {
-    #[new]
-    fn new(data: Vec<f32>) -> Self {
-        PyVector { data }
-    }
-
-    fn __repr__(&self) -> String {
-        format!("Vector({:?})", self.data)
-    }
-
-    fn __len__(&self) -> usize {
-        self.data.len()
+/// The policies apply to the whole process, not to one database: to every
+/// `Database` and thread. They cover scalar values; vector elements and
+/// NumPy or typed columnar results keep NaN as is.
+#[pyfunction]
+#[pyo3(signature = (*, bind=None, fetch=None))]
+pub fn set_nan_policy(
+    bind: Option<&str>,
+    fetch: Option<&str>,
+) -> PyResult<(&'static str, &'static str)> {
+    let index = |policy: &str| {
+        NAN_POLICIES.iter().position(|p| *p == policy).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unknown NaN policy '{policy}' (expected 'keep', 'null' or 'raise')"
+            ))
+        })
+    };
+    let bind = bind.map(index).transpose()?;
+    let fetch = fetch.map(index).transpose()?;
+    let previous = (
+        NAN_POLICIES[BIND_NAN.load(Ordering::Relaxed) as usize],
+        NAN_POLICIES[FETCH_NAN.load(Ordering::Relaxed) as usize],
+    );
+    if let Some(bind) = bind {
+        BIND_NAN.store(bind as u8, Ordering::Relaxed);
     }
-
-    /// Return the vector as a Python list of floats.
-    fn to_list(&self) -> Vec<f32> {
-        self.data.clone()
+    if let Some(fetch) = fetch {
+        FETCH_NAN.store(fetch as u8, Ordering::Relaxed);
     }
+    Ok(previous)
 }
 
 /// Cached `json.dumps` callable — avoids module lookup per JSON parameter.
+/// On free-threaded builds two threads may both run the import on first
+/// use; only one result is kept, which is harmless.
 static JSON_DUMPS: GILOnceCell<PyObject> = GILOnceCell::new();
 
 fn get_json_dumps<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
@@ -71,13 +93,112 @@ fn get_json_dumps<'py>(py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
 }
 
 /// Parsed bind parameters from Python.
+#[derive(Clone)]
 pub enum BindParams {
     Positional(ParamVec),
     Named(Vec<(String, Value)>),
 }
 
-/// Convert a single Python object to a Stoolap Value.
+/// How a connection converts bound Python values.
+#[derive(Default)]
+pub struct BindOptions {
+    /// Refuse implicit conversions: dicts and lists need `Json`, arrays
+    /// and tensors need `Vector`, and datetimes need a timezone.
+    strict: AtomicBool,
+    /// Refuse naive datetimes instead of taking them as UTC.
+    reject_naive: AtomicBool,
+}
+
+/// The conversions of values bound outside a connection.
+static DEFAULT_BINDING: BindOptions = BindOptions {
+    strict: AtomicBool::new(false),
+    reject_naive: AtomicBool::new(false),
+};
+
+impl BindOptions {
+    pub fn is_strict(&self) -> bool {
+        self.strict.load(Ordering::Relaxed)
+    }
+
+    pub fn set_strict(&self, strict: bool) {
+        self.strict.store(strict, Ordering::Relaxed);
+    }
+
+    /// Whether naive datetimes are refused, explicitly or by strict typing.
+    pub fn rejects_naive(&self) -> bool {
+        self.is_strict() || self.reject_naive.load(Ordering::Relaxed)
+    }
+
+    pub fn set_reject_naive(&self, reject: bool) {
+        self.reject_naive.store(reject, Ordering::Relaxed);
+    }
+
+    /// Convert a single Python object to a Stoolap Value.
+    pub fn value(&self, obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+        convert(obj, self)
+    }
+
+    /// Parse Python params into BindParams.
+    /// Accepts: list (positional), tuple (positional), dict (named), or None.
+    pub fn params(&self, params: Option<&Bound<'_, PyAny>>) -> PyResult<BindParams> {
+        let params = match params {
+            None => return Ok(BindParams::Positional(ParamVec::new())),
+            Some(p) => p,
+        };
+
+        if params.is_none() {
+            return Ok(BindParams::Positional(ParamVec::new()));
+        }
+
+        // Try list first (most common)
+        if let Ok(list) = params.downcast::<PyList>() {
+            let mut values = ParamVec::new();
+            for item in list.iter() {
+                values.push(self.value(&item)?);
+            }
+            return Ok(BindParams::Positional(values));
+        }
+
+        // Tuple
+        if let Ok(tuple) = params.downcast::<PyTuple>() {
+            let mut values = ParamVec::new();
+            for item in tuple.iter() {
+                values.push(self.value(&item)?);
+            }
+            return Ok(BindParams::Positional(values));
+        }
+
+        // Dict -> named params
+        if let Ok(dict) = params.downcast::<PyDict>() {
+            let mut named = Vec::with_capacity(dict.len());
+            for (key, val) in dict.iter() {
+                let key_str: String = key.extract()?;
+                let clean = key_str
+                    .trim_start_matches(':')
+                    .trim_start_matches('@')
+                    .trim_start_matches('$')
+                    .to_string();
+                named.push((clean, self.value(&val)?));
+            }
+            return Ok(BindParams::Named(named));
+        }
+
+        Err(PyTypeError::new_err(
+            "Parameters must be a list, tuple, or dict",
+        ))
+    }
+}
+
+/// Convert a single Python object to a Stoolap Value, with the default
+/// conversions.
 pub fn py_to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    DEFAULT_BINDING.value(obj)
+}
+
+/// Convert a single Python object to a Stoolap Value. Under strict typing,
+/// conversions that need an explicit wrapper type or timezone fail.
+fn convert(obj: &Bound<'_, PyAny>, options: &BindOptions) -> PyResult<Value> {
+    let strict = options.is_strict();
     // Check None first
     if obj.is_none() {
         return Ok(Value::null_unknown());
@@ -95,6 +216,17 @@ pub fn py_to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
 
     if let Ok(f) = obj.downcast::<PyFloat>() {
         let val: f64 = f.extract()?;
+        if !val.is_finite() {
+            match NAN_POLICIES[BIND_NAN.load(Ordering::Relaxed) as usize] {
+                "null" => return Ok(Value::null_unknown()),
+                "raise" => {
+                    return Err(PyValueError::new_err(format!(
+                        "cannot bind {val}: the NaN policy is 'raise'"
+                    )))
+                }
+                _ => {}
+            }
+        }
         return Ok(Value::Float(val));
     }
 
@@ -105,17 +237,88 @@ pub fn py_to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
 
     // Check for datetime.datetime (fast downcast via C API)
     if let Ok(dt) = obj.downcast::<PyDateTime>() {
-        return py_datetime_to_value(dt);
+        let value = py_datetime_to_value(dt, options.rejects_naive())?;
+        // Subclasses such as pandas.Timestamp carry nanoseconds
+        if !dt.is_exact_instance_of::<PyDateTime>() {
+            if let (Value::Timestamp(ts), Ok(nanos)) = (&value, dt.getattr("nanosecond")) {
+                let nanos: i64 = nanos.extract()?;
+                return Ok(Value::Timestamp(*ts + chrono::Duration::nanoseconds(nanos)));
+            }
+        }
+        return Ok(value);
+    }
+
+    // datetime.date -> midnight UTC, as the engine stores DATE values
+    if let Ok(date) = obj.downcast::<PyDate>() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+        let midnight = NaiveDate::from_ymd_opt(
+            date.get_year(),
+            date.get_month() as u32,
+            date.get_day() as u32,
+        )
+        .and_then(|d| d.and_hms_opt(0, 0, 0));
+        return match midnight {
+            Some(midnight) => Ok(Value::Timestamp(Utc.from_utc_datetime(&midnight))),
+            None => Err(PyValueError::new_err(format!(
+                "date {} is out of range",
+                date.str()?
+            ))),
+        };
+    }
+
+    // numpy.datetime64 -> Timestamp, to the nanosecond
+    if is_datetime64(obj) {
+        let nanos: i64 = obj
+            .call_method1("astype", ("datetime64[ns]",))?
+            .call_method1("astype", ("int64",))?
+            .extract()?;
+        // NaT
+        if nanos == i64::MIN {
+            return Ok(Value::null_unknown());
+        }
+        return Ok(Value::Timestamp(chrono::DateTime::from_timestamp_nanos(nanos)));
     }
 
-    // Vector -> native VECTOR value
+    // JsonPath -> its path text
+    if let Ok(path) = obj.downcast::<JsonPath>() {
+        return Ok(Value::text(&path.get().path));
+    }
+
+    // Vector or 1-D NumPy float array -> native VECTOR value
     if let Ok(v) = obj.downcast::<PyVector>() {
         let v: PyRef<'_, PyVector> = v.try_borrow()?;
-        return Ok(Value::vector(v.data.clone()));
+        return Ok(Value::vector(v.bind_values()));
+    }
+    if let Ok(json) = obj.downcast::<Json>() {
+        let dumps = get_json_dumps(obj.py())?;
+        let json_str: String = dumps.call1((&json.get().value,))?.extract()?;
+        return Ok(Value::json(&json_str));
+    }
+    let implicit = |wrapper: &str| -> PyResult<Value> {
+        Err(PyTypeError::new_err(format!(
+            "strict_types does not convert {} implicitly; wrap it in {wrapper}()",
+            obj.get_type().name()?
+        )))
+    };
+    if is_ndarray(obj) {
+        if strict {
+            return implicit("Vector");
+        }
+        return Ok(Value::vector(ndarray_to_vector(obj)?));
+    }
+    // Tensors from PyTorch, JAX and others, through DLPack
+    if dlpack::is_dlpack(obj) {
+        if strict {
+            return implicit("Vector");
+        }
+        return Ok(Value::vector(dlpack::import(obj)?.to_f32()));
     }
 
     // dict/list -> JSON string
     if obj.downcast::<PyDict>().is_ok() || obj.downcast::<PyList>().is_ok() {
+        if strict {
+            return implicit("Json");
+        }
         let dumps = get_json_dumps(obj.py())?;
         let json_str: String = dumps.call1((obj,))?.extract()?;
         return Ok(Value::json(&json_str));
@@ -127,10 +330,17 @@ pub fn py_to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
     )))
 }
 
+/// Whether `obj` is a `numpy.datetime64` scalar.
+fn is_datetime64(obj: &Bound<'_, PyAny>) -> bool {
+    let ty = obj.get_type();
+    ty.name().is_ok_and(|name| name.to_str().is_ok_and(|name| name == "datetime64"))
+        && ty.module().is_ok_and(|module| module.to_str().is_ok_and(|m| m == "numpy"))
+}
+
 /// Convert a Python datetime to a Stoolap Timestamp value.
 ///
 /// Extracts components directly via PyO3's C API (no Python method calls).
-fn py_datetime_to_value(dt: &Bound<'_, PyDateTime>) -> PyResult<Value> {
+fn py_datetime_to_value(dt: &Bound<'_, PyDateTime>, reject_naive: bool) -> PyResult<Value> {
     use chrono::{NaiveDate, TimeZone, Utc};
     use pyo3::types::PyTzInfoAccess;
 
@@ -145,7 +355,17 @@ fn py_datetime_to_value(dt: &Bound<'_, PyDateTime>) -> PyResult<Value> {
     let tzinfo = dt.get_tzinfo();
 
     if tzinfo.is_none() {
+        if reject_naive {
+            return Err(PyTypeError::new_err(format!(
+                "naive datetime {} has no timezone; this connection only accepts aware datetimes",
+                dt.str()?
+            )));
+        }
         // Naive datetime -> treat as UTC
+        warning::lossy(
+            dt.py(),
+            format!("naive datetime {} has no timezone and is taken as UTC", dt.str()?),
+        )?;
         if let Some(naive) = NaiveDate::from_ymd_opt(year, month, day)
             .and_then(|d| d.and_hms_micro_opt(hour, minute, second, microsecond))
         {
@@ -182,35 +402,178 @@ fn py_datetime_to_value(dt: &Bound<'_, PyDateTime>) -> PyResult<Value> {
     }
 }
 
-/// Convert a Stoolap Value to a Python object.
-pub fn value_to_py(py: Python<'_>, val: &Value) -> PyObject {
-    match val {
+/// How a connection returns result values.
+///
+/// TIMESTAMP values are aware datetimes in UTC (the default) or in `zone`,
+/// and with `naive`, without their tzinfo. With `nanoseconds`, they are
+/// integer nanoseconds since the Unix epoch instead, which keeps the
+/// precision a datetime truncates to microseconds. With `dates`, values at
+/// midnight UTC, which is how DATE columns are stored, are `datetime.date`
+/// objects. `detect_types` selects the registered converters that apply;
+/// see `converter`.
+#[derive(Default)]
+pub struct FetchOptions {
+    zone: Option<PyObject>,
+    naive: bool,
+    nanoseconds: bool,
+    dates: bool,
+    detect_types: u8,
+}
+
+impl FetchOptions {
+    /// The conversion for `tz`, a zone name such as "Europe/Istanbul" or a
+    /// `tzinfo`; None keeps UTC.
+    pub fn new(
+        tz: Option<&Bound<'_, PyAny>>,
+        naive: bool,
+        nanoseconds: bool,
+        dates: bool,
+        detect_types: u8,
+    ) -> PyResult<Self> {
+        if detect_types & !(converter::PARSE_DECLTYPES | converter::PARSE_COLNAMES) != 0 {
+            return Err(PyValueError::new_err(
+                "detect_types must combine PARSE_DECLTYPES and PARSE_COLNAMES",
+            ));
+        }
+        let zone = match tz.filter(|tz| !tz.is_none()) {
+            None => None,
+            Some(tz) if tz.downcast::<PyTzInfo>().is_ok() => Some(tz.clone().unbind()),
+            Some(tz) => {
+                let name: String = tz.extract().map_err(|_| {
+                    PyTypeError::new_err("tz must be a timezone name or a tzinfo")
+                })?;
+                let zoneinfo = tz.py().import("zoneinfo")?.getattr("ZoneInfo")?;
+                let zone = zoneinfo
+                    .call1((&name,))
+                    .map_err(|_| PyValueError::new_err(format!("unknown timezone '{name}'")))?;
+                Some(zone.unbind())
+            }
+        };
+        Ok(Self {
+            zone,
+            naive,
+            nanoseconds,
+            dates,
+            detect_types,
+        })
+    }
+
+    /// The zone name or tzinfo given as `tz`, or None for UTC.
+    pub fn tz(&self, py: Python<'_>) -> PyResult<PyObject> {
+        match &self.zone {
+            Some(zone) => match zone.bind(py).getattr("key") {
+                Ok(key) => Ok(key.unbind()),
+                Err(_) => Ok(zone.clone_ref(py)),
+            },
+            None => Ok(py.None()),
+        }
+    }
+
+    /// Whether TIMESTAMPs are returned as integer nanoseconds.
+    pub fn in_nanoseconds(&self) -> bool {
+        self.nanoseconds
+    }
+
+    /// Whether TIMESTAMPs are returned without tzinfo.
+    pub fn is_naive(&self) -> bool {
+        self.naive
+    }
+
+    /// Whether TIMESTAMPs at midnight UTC are returned as dates.
+    pub fn midnight_as_date(&self) -> bool {
+        self.dates
+    }
+
+    pub fn detect_types(&self) -> u8 {
+        self.detect_types
+    }
+
+    /// The converter for values of engine type `typename`, under
+    /// PARSE_DECLTYPES.
+    fn declared(&self, py: Python<'_>, typename: &str) -> Option<PyObject> {
+        if self.detect_types & converter::PARSE_DECLTYPES == 0 {
+            return None;
+        }
+        converter::lookup(py, typename)
+    }
+
+    fn timestamp(&self, py: Python<'_>, ts: &chrono::DateTime<chrono::Utc>) -> PyResult<PyObject> {
+        if self.nanoseconds {
+            // i128 so that dates outside 1677-2262 do not overflow
+            let nanos =
+                ts.timestamp() as i128 * 1_000_000_000 + ts.timestamp_subsec_nanos() as i128;
+            return Ok(nanos.into_pyobject(py)?.into_any().unbind());
+        }
+        if self.dates && ts.num_seconds_from_midnight() == 0 && ts.nanosecond() == 0 {
+            let date = PyDate::new(py, ts.year(), ts.month() as u8, ts.day() as u8)?;
+            return Ok(date.into_any().unbind());
+        }
+        let utc_tz = timezone_utc(py);
+        // Without a zone, a naive result is built directly in UTC
+        let tzinfo = (self.zone.is_some() || !self.naive).then_some(&utc_tz);
+        let dt = match PyDateTime::new(
+            py,
+            ts.year(),
+            ts.month() as u8,
+            ts.day() as u8,
+            ts.hour() as u8,
+            ts.minute() as u8,
+            ts.second() as u8,
+            ts.timestamp_subsec_micros(),
+            tzinfo,
+        ) {
+            Ok(dt) => dt.into_any(),
+            Err(_) => {
+                // Fallback to ISO string
+                let iso = ts.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string();
+                return Ok(iso.into_pyobject(py)?.into_any().unbind());
+            }
+        };
+        let Some(zone) = &self.zone else {
+            return Ok(dt.unbind());
+        };
+        let dt = dt.call_method1("astimezone", (zone,))?;
+        if !self.naive {
+            return Ok(dt.unbind());
+        }
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("tzinfo", py.None())?;
+        Ok(dt.call_method("replace", (), Some(&kwargs))?.unbind())
+    }
+}
+
+/// Convert a Stoolap Value to a Python object as `fetch` says, including
+/// the converter of its engine type under PARSE_DECLTYPES. Fails for a NaN
+/// or infinite float under the "raise" fetch policy, or when the converter
+/// raises.
+pub fn value_to_py(py: Python<'_>, val: &Value, fetch: &FetchOptions) -> PyResult<PyObject> {
+    let obj = plain_value_to_py(py, val, fetch)?;
+    match converter::type_name(val).and_then(|typename| fetch.declared(py, typename)) {
+        Some(converter) => converter.call1(py, (obj,)),
+        None => Ok(obj),
+    }
+}
+
+/// Convert a Stoolap Value to a Python object without converters.
+pub fn plain_value_to_py(py: Python<'_>, val: &Value, fetch: &FetchOptions) -> PyResult<PyObject> {
+    Ok(match val {
         Value::Null(_) => py.None(),
+        Value::Float(f) if !f.is_finite() => {
+            match NAN_POLICIES[FETCH_NAN.load(Ordering::Relaxed) as usize] {
+                "null" => py.None(),
+                "raise" => {
+                    return Err(StoolapError::new_err(format!(
+                        "query returned {f}: the NaN policy is 'raise'"
+                    )))
+                }
+                _ => f.into_pyobject(py)?.into_any().unbind(),
+            }
+        }
         Value::Boolean(b) => b.into_pyobject(py).unwrap().to_owned().into_any().unbind(),
         Value::Integer(i) => i.into_pyobject(py).unwrap().to_owned().into_any().unbind(),
         Value::Float(f) => f.into_pyobject(py).unwrap().to_owned().into_any().unbind(),
         Value::Text(s) => s.as_str().into_pyobject(py).unwrap().to_owned().into_any().unbind(),
-        Value::Timestamp(ts) => {
-            let utc_tz = timezone_utc(py);
-            match PyDateTime::new(
-                py,
-                ts.year(),
-                ts.month() as u8,
-                ts.day() as u8,
-                ts.hour() as u8,
-                ts.minute() as u8,
-                ts.second() as u8,
-                ts.timestamp_subsec_micros(),
-                Some(&utc_tz),
-            ) {
-                Ok(dt) => dt.into_any().unbind(),
-                Err(_) => {
-                    // Fallback to ISO string
-                    let iso = ts.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string();
-                    iso.into_pyobject(py).unwrap().to_owned().into_any().unbind()
-                }
-            }
-        }
+        Value::Timestamp(ts) => fetch.timestamp(py, ts)?,
         Value::Extension(_) => {
             if let Some(floats) = val.as_vector_f32() {
                 let list = PyList::new(py, &floats).unwrap();
@@ -221,55 +584,87 @@ pub fn value_to_py(py: Python<'_>, val: &Value) -> PyObject {
                 format!("{}", val).into_pyobject(py).unwrap().to_owned().into_any().unbind()
             }
         }
-    }
+    })
 }
 
-/// Parse Python params into BindParams.
-/// Accepts: list (positional), tuple (positional), dict (named), or None.
-pub fn parse_params(params: Option<&Bound<'_, PyAny>>) -> PyResult<BindParams> {
-    let params = match params {
-        None => return Ok(BindParams::Positional(ParamVec::new())),
-        Some(p) => p,
-    };
+/// Distinct strings kept by a `StringInterner`. Beyond this, further new
+/// values are converted normally so a column of unique text cannot grow the
+/// cache without bound.
+const MAX_INTERNED: usize = 65_536;
+
+/// Converts values for one result set, optionally mapping repeated TEXT
+/// values (enums, categories, country codes) to a single Python string.
+/// Under PARSE_COLNAMES it also holds the converter of each column.
+pub struct StringInterner<'a> {
+    strings: Option<HashMap<String, Py<PyString>>>,
+    fetch: &'a FetchOptions,
+    converters: Vec<Option<PyObject>>,
+}
 
-    if params.is_none() {
-        return Ok(BindParams::Positional(ParamVec::new()));
+impl<'a> StringInterner<'a> {
+    pub fn new(enabled: bool, fetch: &'a FetchOptions) -> Self {
+        // Interned strings would skip a converter for TEXT
+        let enabled = enabled
+            && !(fetch.detect_types & converter::PARSE_DECLTYPES != 0
+                && converter::is_registered("TEXT"));
+        Self {
+            strings: enabled.then(HashMap::new),
+            fetch,
+            converters: Vec::new(),
+        }
     }
 
-    // Try list first (most common)
-    if let Ok(list) = params.downcast::<PyList>() {
-        let mut values = ParamVec::new();
-        for item in list.iter() {
-            values.push(py_to_value(&item)?);
+    /// Column names of the result. Under PARSE_COLNAMES, a column named
+    /// `name [TYPE]` is named `name` and its values converted with the
+    /// converter of `TYPE`, when one is registered.
+    pub fn columns<'c>(&mut self, py: Python<'_>, columns: &'c [String]) -> Cow<'c, [String]> {
+        if self.fetch.detect_types & converter::PARSE_COLNAMES == 0 {
+            return Cow::Borrowed(columns);
+        }
+        let mut names = Vec::with_capacity(columns.len());
+        self.converters.clear();
+        for column in columns {
+            match converter::split_column_name(column) {
+                Some((name, typename)) => {
+                    names.push(name.to_string());
+                    self.converters.push(converter::lookup(py, typename));
+                }
+                None => {
+                    names.push(column.clone());
+                    self.converters.push(None);
+                }
+            }
         }
-        return Ok(BindParams::Positional(values));
+        Cow::Owned(names)
+    }
+
+    /// Whether values of engine type `typename` in column `column` have a
+    /// converter.
+    pub fn converts(&self, column: usize, typename: &str) -> bool {
+        self.converters.get(column).is_some_and(Option::is_some)
+            || (self.fetch.detect_types & converter::PARSE_DECLTYPES != 0
+                && converter::is_registered(typename))
     }
 
-    // Tuple
-    if let Ok(tuple) = params.downcast::<PyTuple>() {
-        let mut values = ParamVec::new();
-        for item in tuple.iter() {
-            values.push(py_to_value(&item)?);
+    /// Convert the value of column `column`.
+    pub fn value_to_py(&mut self, py: Python<'_>, column: usize, val: &Value) -> PyResult<PyObject> {
+        if let Some(converter) = self.converters.get(column).and_then(Option::as_ref) {
+            if matches!(val, Value::Null(_)) {
+                return Ok(py.None());
+            }
+            return converter.call1(py, (plain_value_to_py(py, val, self.fetch)?,));
         }
-        return Ok(BindParams::Positional(values));
-    }
-
-    // Dict -> named params
-    if let Ok(dict) = params.downcast::<PyDict>() {
-        let mut named = Vec::with_capacity(dict.len());
-        for (key, val) in dict.iter() {
-            let key_str: String = key.extract()?;
-            let clean = key_str
-                .trim_start_matches(':')
-                .trim_start_matches('@')
-                .trim_start_matches('$')
-                .to_string();
-            named.push((clean, py_to_value(&val)?));
+        let (Some(strings), Value::Text(s)) = (&mut self.strings, val) else {
+            return value_to_py(py, val, self.fetch);
+        };
+        if let Some(string) = strings.get(s.as_str()) {
+            return Ok(string.clone_ref(py).into_any());
         }
-        return Ok(BindParams::Named(named));
+        let string = PyString::new(py, s.as_str()).unbind();
+        if strings.len() < MAX_INTERNED {
+            strings.insert(s.to_string(), string.clone_ref(py));
+        }
+        Ok(string.into_any())
     }
-
-    Err(PyTypeError::new_err(
-        "Parameters must be a list, tuple, or dict",
-    ))
 }
+
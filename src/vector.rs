@@ -0,0 +1,732 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::{PyBufferError, PyIndexError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyIterator, PyList, PySlice};
+use std::sync::OnceLock;
+
+use stoolap::api::{Database as ApiDatabase, ParamVec};
+use stoolap::core::Value;
+use stoolap::storage::index::HnswIndex;
+use stoolap::storage::traits::Index;
+
+use crate::dlpack;
+use crate::error::{self, to_py};
+use crate::warning;
+
+/// Element storage of a `Vector`.
+#[derive(Clone)]
+pub enum VectorData {
+    F32(Vec<f32>),
+    /// IEEE 754 half-precision values, as their bit patterns.
+    F16(Vec<u16>),
+    /// Quantized values: each element is `code * scale + offset`.
+    I8 {
+        codes: Vec<i8>,
+        scale: f32,
+        offset: f32,
+    },
+}
+
+impl VectorData {
+    pub fn dims(&self) -> usize {
+        match self {
+            VectorData::F32(values) => values.len(),
+            VectorData::F16(values) => values.len(),
+            VectorData::I8 { codes, .. } => codes.len(),
+        }
+    }
+
+    pub fn dtype(&self) -> &'static str {
+        match self {
+            VectorData::F32(_) => "f32",
+            VectorData::F16(_) => "f16",
+            VectorData::I8 { .. } => "i8",
+        }
+    }
+
+    /// Element `i` as f32. `i` must be in range.
+    fn get(&self, i: usize) -> f32 {
+        match self {
+            VectorData::F32(values) => values[i],
+            VectorData::F16(values) => f16_to_f32(values[i]),
+            VectorData::I8 {
+                codes,
+                scale,
+                offset,
+            } => codes[i] as f32 * scale + offset,
+        }
+    }
+
+    /// The elements at `indices`, keeping the element type.
+    fn select(&self, indices: impl Iterator<Item = usize>) -> VectorData {
+        match self {
+            VectorData::F32(values) => VectorData::F32(indices.map(|i| values[i]).collect()),
+            VectorData::F16(values) => VectorData::F16(indices.map(|i| values[i]).collect()),
+            VectorData::I8 {
+                codes,
+                scale,
+                offset,
+            } => VectorData::I8 {
+                codes: indices.map(|i| codes[i]).collect(),
+                scale: *scale,
+                offset: *offset,
+            },
+        }
+    }
+
+    /// The values as f32, the element type of the engine's VECTOR.
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self {
+            VectorData::F32(values) => values.clone(),
+            VectorData::F16(values) => values.iter().map(|h| f16_to_f32(*h)).collect(),
+            VectorData::I8 {
+                codes,
+                scale,
+                offset,
+            } => codes.iter().map(|code| *code as f32 * scale + offset).collect(),
+        }
+    }
+}
+
+/// A vector of float values for similarity search.
+///
+/// Wraps a list of floats (or a 1-D NumPy float array or DLPack tensor) so
+/// that Stoolap stores them as a native VECTOR rather than a JSON array.
+///
+/// `dtype="f16"` keeps the values in half precision, halving the memory a
+/// large batch of embeddings takes before it is inserted. The engine stores
+/// VECTOR elements as f32, so the values are widened when bound; they keep
+/// the precision of f16. NumPy float16 arrays give f16 vectors by default.
+///
+/// `dtype="i8"` takes int8 codes from a quantized index together with
+/// their `scale` and `offset`; each element is `code * scale + offset`.
+/// The codes are dequantized in Rust when the vector is bound. NumPy int8
+/// arrays give i8 vectors by default.
+///
+/// `normalize=True` scales the values to unit L2 norm when the vector is
+/// bound, so cosine distances and inner products agree however the
+/// embeddings were produced. The stored elements are left as given.
+///
+/// Usage:
+///     from stoolap import Vector
+///     v = Vector([0.1, 0.2, 0.3])
+///     db.execute("INSERT INTO t (embedding) VALUES ($1)", [v])
+#[pyclass(name = "Vector")]
+#[derive(Clone)]
+pub struct PyVector {
+    pub data: VectorData,
+    pub normalize: bool,
+}
+
+impl PyVector {
+    /// A vector holding a copy of a DLPack tensor. float16 tensors give f16
+    /// vectors unless `dtype` says otherwise.
+    fn from_dlpack(data: &Bound<'_, PyAny>, dtype: Option<&str>, normalize: bool) -> PyResult<Self> {
+        let data = match (dlpack::import(data)?, dtype) {
+            (data, None) => data,
+            (data, Some(dtype)) if dtype == data.dtype() => data,
+            (data, Some("f32")) => VectorData::F32(data.to_f32()),
+            (data, Some("f16")) => {
+                VectorData::F16(data.to_f32().into_iter().map(f32_to_f16).collect())
+            }
+            (_, Some(dtype)) => {
+                return Err(PyValueError::new_err(format!(
+                    "a DLPack tensor gives an 'f32' or 'f16' vector, not '{dtype}'"
+                )))
+            }
+        };
+        Ok(PyVector { data, normalize })
+    }
+
+    /// The values bound for the vector: f32, normalized if requested.
+    pub fn bind_values(&self) -> Vec<f32> {
+        let mut values = self.data.to_f32();
+        if self.normalize {
+            normalize(&mut values);
+        }
+        values
+    }
+}
+
+#[pymethods]
+impl PyVector {
+    #[new]
+    #[pyo3(signature = (data, dtype=None, *, scale=1.0, offset=0.0, normalize=false))]
+    fn new(
+        data: &Bound<'_, PyAny>,
+        dtype: Option<&str>,
+        scale: f32,
+        offset: f32,
+        normalize: bool,
+    ) -> PyResult<Self> {
+        if !is_ndarray(data) && dlpack::is_dlpack(data) {
+            return Self::from_dlpack(data, dtype, normalize);
+        }
+        let dtype = match dtype {
+            Some(dtype) => dtype,
+            None if is_ndarray(data) => match numpy_dtype(data)?.as_str() {
+                "float16" => "f16",
+                "int8" => "i8",
+                _ => "f32",
+            },
+            None => "f32",
+        };
+        check_dtype(dtype, scale, offset)?;
+        let data = match dtype {
+            "f16" => VectorData::F16(extract_f16(data)?),
+            "i8" => VectorData::I8 {
+                codes: extract_i8(data)?,
+                scale,
+                offset,
+            },
+            _ => VectorData::F32(extract_f32(data)?),
+        };
+        Ok(PyVector { data, normalize })
+    }
+
+    /// Export the vector as a DLPack capsule for `torch.from_dlpack()`,
+    /// `jax.numpy.from_dlpack()` or `numpy.from_dlpack()`. f32 and f16
+    /// vectors share their elements with the importer; i8 vectors are
+    /// exported dequantized to float32, which takes a copy.
+    #[pyo3(signature = (*, stream=None, max_version=None, dl_device=None, copy=None))]
+    fn __dlpack__(
+        slf: &Bound<'_, Self>,
+        stream: Option<&Bound<'_, PyAny>>,
+        max_version: Option<(u32, u32)>,
+        dl_device: Option<(i32, i32)>,
+        copy: Option<bool>,
+    ) -> PyResult<PyObject> {
+        // CPU tensors need no stream, and only unversioned capsules are made
+        let _ = (stream, max_version);
+        if dl_device.is_some_and(|device| device != (dlpack::CPU, 0)) {
+            return Err(PyBufferError::new_err("vectors can only be exported to the CPU"));
+        }
+        if copy == Some(false) && slf.borrow().data.dtype() == "i8" {
+            return Err(PyBufferError::new_err(
+                "i8 vectors are exported dequantized, which needs a copy",
+            ));
+        }
+        dlpack::export(slf.py(), slf)
+    }
+
+    /// The DLPack device of the vector: always the CPU.
+    fn __dlpack_device__(&self) -> (i32, i32) {
+        (dlpack::CPU, 0)
+    }
+
+    /// Read a vector from its packed little-endian form, as produced by
+    /// `to_bytes()`: 4 bytes per f32 element, 2 per f16 and 1 per i8.
+    /// The scale and offset of an i8 vector are not part of the bytes.
+    #[staticmethod]
+    #[pyo3(signature = (data, dtype="f32", *, scale=1.0, offset=0.0, normalize=false))]
+    fn from_bytes(
+        data: &[u8],
+        dtype: &str,
+        scale: f32,
+        offset: f32,
+        normalize: bool,
+    ) -> PyResult<Self> {
+        check_dtype(dtype, scale, offset)?;
+        let width = match dtype {
+            "f32" => 4,
+            "f16" => 2,
+            _ => 1,
+        };
+        if !data.len().is_multiple_of(width) {
+            return Err(PyValueError::new_err(format!(
+                "{} bytes is not a whole number of {dtype} elements",
+                data.len()
+            )));
+        }
+        let data = match dtype {
+            "f16" => VectorData::F16(
+                data.chunks_exact(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                    .collect(),
+            ),
+            "i8" => VectorData::I8 {
+                codes: data.iter().map(|b| *b as i8).collect(),
+                scale,
+                offset,
+            },
+            _ => VectorData::F32(
+                data.chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            ),
+        };
+        Ok(PyVector { data, normalize })
+    }
+
+    /// The packed little-endian form of the elements, in the vector's dtype.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        let bytes: Vec<u8> = match &self.data {
+            VectorData::F32(values) => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            VectorData::F16(values) => values.iter().flat_map(|v| v.to_le_bytes()).collect(),
+            VectorData::I8 { codes, .. } => codes.iter().map(|c| *c as u8).collect(),
+        };
+        PyBytes::new(py, &bytes)
+    }
+
+    fn __repr__(&self) -> String {
+        let repr = match &self.data {
+            VectorData::F32(values) => format!("Vector({values:?})"),
+            VectorData::F16(_) => format!("Vector({:?}, dtype='f16')", self.data.to_f32()),
+            VectorData::I8 {
+                codes,
+                scale,
+                offset,
+            } => format!("Vector({codes:?}, dtype='i8', scale={scale:?}, offset={offset:?})"),
+        };
+        if self.normalize {
+            // Add the flag inside the closing parenthesis
+            return format!("{}, normalize=True)", &repr[..repr.len() - 1]);
+        }
+        repr
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.dims()
+    }
+
+    /// An element as a float, or a slice as a Vector of the same dtype.
+    fn __getitem__(&self, py: Python<'_>, index: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let len = self.data.dims();
+        if let Ok(slice) = index.downcast::<PySlice>() {
+            let range = slice.indices(len as isize)?;
+            let indices = (0..range.slicelength as isize)
+                .map(|k| (range.start + k * range.step) as usize);
+            let data = self.data.select(indices);
+            let normalize = self.normalize;
+            return Ok(Py::new(py, PyVector { data, normalize })?.into_any());
+        }
+        let i: isize = index.extract()?;
+        let i = if i < 0 { i + len as isize } else { i };
+        if i < 0 || i >= len as isize {
+            return Err(PyIndexError::new_err("vector index out of range"));
+        }
+        Ok(self.data.get(i as usize).into_pyobject(py)?.into_any().unbind())
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.data.to_f32())?.into_any().try_iter()
+    }
+
+    fn __contains__(&self, value: &Bound<'_, PyAny>) -> bool {
+        value
+            .extract::<f32>()
+            .is_ok_and(|value| (0..self.data.dims()).any(|i| self.data.get(i) == value))
+    }
+
+    /// Vectors are equal when their values are, whatever their dtype.
+    /// Comparing with anything else, including a list, is not supported.
+    fn __eq__(&self, py: Python<'_>, other: &Bound<'_, PyAny>) -> PyObject {
+        match other.downcast::<PyVector>() {
+            Ok(other) => {
+                let equal = self.data.to_f32() == other.borrow().data.to_f32();
+                PyBool::new(py, equal).to_owned().into_any().unbind()
+            }
+            Err(_) => py.NotImplemented(),
+        }
+    }
+
+    /// Whether the values are scaled to unit length when bound.
+    #[getter]
+    fn normalize(&self) -> bool {
+        self.normalize
+    }
+
+    /// Element type: 'f32', 'f16' or 'i8'.
+    #[getter]
+    fn dtype(&self) -> &'static str {
+        self.data.dtype()
+    }
+
+    /// Quantization scale of an i8 vector; 1.0 for float vectors.
+    #[getter]
+    fn scale(&self) -> f32 {
+        match &self.data {
+            VectorData::I8 { scale, .. } => *scale,
+            _ => 1.0,
+        }
+    }
+
+    /// Quantization offset of an i8 vector; 0.0 for float vectors.
+    #[getter]
+    fn offset(&self) -> f32 {
+        match &self.data {
+            VectorData::I8 { offset, .. } => *offset,
+            _ => 0.0,
+        }
+    }
+
+    /// The int8 codes of an i8 vector, or None for float vectors.
+    fn codes(&self) -> Option<Vec<i8>> {
+        match &self.data {
+            VectorData::I8 { codes, .. } => Some(codes.clone()),
+            _ => None,
+        }
+    }
+
+    /// Return the vector as a Python list of floats. i8 vectors are
+    /// dequantized.
+    fn to_list(&self) -> Vec<f32> {
+        self.data.to_f32()
+    }
+}
+
+/// Check a dtype name and the quantization parameters that go with it.
+fn check_dtype(dtype: &str, scale: f32, offset: f32) -> PyResult<()> {
+    if !matches!(dtype, "f32" | "f16" | "i8") {
+        return Err(PyValueError::new_err(format!(
+            "dtype must be 'f32', 'f16' or 'i8', got '{dtype}'"
+        )));
+    }
+    if dtype != "i8" && (scale != 1.0 || offset != 0.0) {
+        return Err(PyValueError::new_err("scale and offset apply to i8 vectors only"));
+    }
+    if !scale.is_finite() || !offset.is_finite() {
+        return Err(PyValueError::new_err("scale and offset must be finite"));
+    }
+    Ok(())
+}
+
+/// Whether `obj` is a NumPy array, checked by type name so that NumPy is
+/// never imported here.
+pub fn is_ndarray(obj: &Bound<'_, PyAny>) -> bool {
+    let ty = obj.get_type();
+    let named = |name: PyResult<Bound<'_, pyo3::types::PyString>>, expected: &str| {
+        name.is_ok_and(|name| name.to_str().is_ok_and(|name| name == expected))
+    };
+    named(ty.module(), "numpy") && named(ty.name(), "ndarray")
+}
+
+fn numpy_dtype(array: &Bound<'_, PyAny>) -> PyResult<String> {
+    array.getattr("dtype")?.getattr("name")?.extract()
+}
+
+fn check_1d(array: &Bound<'_, PyAny>) -> PyResult<()> {
+    let ndim: usize = array.getattr("ndim")?.extract()?;
+    if ndim != 1 {
+        return Err(PyValueError::new_err(format!(
+            "a vector must be a 1-D array, got {ndim} dimensions"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that `array` is a 1-D float array and convert it to `dtype`.
+fn numpy_floats<'py>(array: &Bound<'py, PyAny>, dtype: &str) -> PyResult<Bound<'py, PyAny>> {
+    check_1d(array)?;
+    let kind: String = array.getattr("dtype")?.getattr("kind")?.extract()?;
+    if kind != "f" {
+        return Err(PyTypeError::new_err(format!(
+            "a vector array must hold floats, got {}",
+            numpy_dtype(array)?
+        )));
+    }
+    array.call_method1("astype", (dtype,))
+}
+
+fn extract_f32(data: &Bound<'_, PyAny>) -> PyResult<Vec<f32>> {
+    let py = data.py();
+    if !is_ndarray(data) {
+        let values: Vec<f64> = data.extract()?;
+        let narrowed: Vec<f32> = values.iter().map(|v| *v as f32).collect();
+        let overflow = values
+            .iter()
+            .zip(&narrowed)
+            .find(|(v, n)| v.is_finite() && n.is_infinite());
+        if let Some((value, _)) = overflow {
+            warning::lossy(py, overflow_message(&value.to_string()))?;
+        }
+        return Ok(narrowed);
+    }
+    let array = numpy_floats(data, "float32")?;
+    if numpy_dtype(data)? != "float32" {
+        let numpy = py.import("numpy")?;
+        let infinities = |a: &Bound<'_, PyAny>| -> PyResult<usize> {
+            let inf = numpy.call_method1("isinf", (a,))?;
+            numpy.call_method1("count_nonzero", (inf,))?.extract()
+        };
+        if infinities(&array)? > infinities(data)? {
+            warning::lossy(py, overflow_message("an array element"))?;
+        }
+    }
+    PyBuffer::<f32>::get(&array)?.to_vec(py)
+}
+
+fn overflow_message(value: &str) -> String {
+    format!("vector element {value} is out of float32 range and is stored as infinity")
+}
+
+fn extract_f16(data: &Bound<'_, PyAny>) -> PyResult<Vec<u16>> {
+    if !is_ndarray(data) {
+        let values: Vec<f32> = data.extract()?;
+        return Ok(values.into_iter().map(f32_to_f16).collect());
+    }
+    // Reinterpret the float16 elements as their bit patterns
+    let bits = numpy_floats(data, "float16")?.call_method1("view", ("uint16",))?;
+    PyBuffer::<u16>::get(&bits)?.to_vec(data.py())
+}
+
+fn extract_i8(data: &Bound<'_, PyAny>) -> PyResult<Vec<i8>> {
+    if !is_ndarray(data) {
+        return data.extract();
+    }
+    check_1d(data)?;
+    // Other integer types could silently wrap, so only int8 is taken
+    let dtype = numpy_dtype(data)?;
+    if dtype != "int8" {
+        return Err(PyTypeError::new_err(format!(
+            "an i8 vector array must be int8, got {dtype}"
+        )));
+    }
+    PyBuffer::<i8>::get(data)?.to_vec(data.py())
+}
+
+/// The values of a NumPy array parameter, read as a `Vector` of the
+/// array's precision.
+pub fn ndarray_to_vector(array: &Bound<'_, PyAny>) -> PyResult<Vec<f32>> {
+    Ok(PyVector::new(array, None, 1.0, 0.0, false)?.data.to_f32())
+}
+
+/// The values of a query vector: a `Vector`, a 1-D NumPy float array or a
+/// sequence of floats.
+pub fn vector_values(obj: &Bound<'_, PyAny>) -> PyResult<Vec<f32>> {
+    if let Ok(v) = obj.downcast::<PyVector>() {
+        return Ok(v.borrow().bind_values());
+    }
+    if is_ndarray(obj) {
+        return ndarray_to_vector(obj);
+    }
+    if dlpack::is_dlpack(obj) {
+        return Ok(dlpack::import(obj)?.to_f32());
+    }
+    match extract_f32(obj) {
+        Err(err) if err.is_instance_of::<PyTypeError>(obj.py()) => Err(PyTypeError::new_err(
+            "a query vector must be a Vector, a NumPy array or a list of floats",
+        )),
+        values => values,
+    }
+}
+
+/// The elements of a 2-D float32 matrix, row-major, with its row count and
+/// width. NumPy arrays of other float types are converted first; anything
+/// else must export float32 elements through the buffer protocol.
+pub fn matrix_values(matrix: &Bound<'_, PyAny>) -> PyResult<(Vec<f32>, usize, usize)> {
+    let py = matrix.py();
+    let matrix = if is_ndarray(matrix) && numpy_dtype(matrix)? != "float32" {
+        let kind: String = matrix.getattr("dtype")?.getattr("kind")?.extract()?;
+        if kind != "f" {
+            return Err(PyTypeError::new_err(format!(
+                "a vector matrix must hold floats, got {}",
+                numpy_dtype(matrix)?
+            )));
+        }
+        matrix.call_method1("astype", ("float32",))?
+    } else {
+        matrix.clone()
+    };
+    let buffer = PyBuffer::<f32>::get(&matrix).map_err(|_| {
+        PyTypeError::new_err("matrix must be a 2-D float32 array or buffer")
+    })?;
+    let (rows, dims) = match buffer.shape() {
+        &[rows, dims] => (rows, dims),
+        shape => {
+            return Err(PyValueError::new_err(format!(
+                "matrix must have 2 dimensions, got {}",
+                shape.len()
+            )))
+        }
+    };
+    if dims == 0 {
+        return Err(PyValueError::new_err("matrix rows must not be empty"));
+    }
+    let values = buffer.to_vec(py)?;
+    buffer.release(py);
+    Ok((values, rows, dims))
+}
+
+/// Cosine distance between two vectors (1 - cosine similarity), computed
+/// by the engine's `VEC_DISTANCE_COSINE`.
+#[pyfunction]
+pub fn cosine_distance(py: Python<'_>, a: &Bound<'_, PyAny>, b: &Bound<'_, PyAny>) -> PyResult<f64> {
+    evaluate(py, "VEC_DISTANCE_COSINE", a, b)
+}
+
+/// Euclidean distance between two vectors, computed by the engine's
+/// `VEC_DISTANCE_L2`.
+#[pyfunction]
+pub fn l2_distance(py: Python<'_>, a: &Bound<'_, PyAny>, b: &Bound<'_, PyAny>) -> PyResult<f64> {
+    evaluate(py, "VEC_DISTANCE_L2", a, b)
+}
+
+/// Inner product of two vectors, the negation of the engine's
+/// `VEC_DISTANCE_IP`.
+#[pyfunction]
+pub fn dot_product(py: Python<'_>, a: &Bound<'_, PyAny>, b: &Bound<'_, PyAny>) -> PyResult<f64> {
+    Ok(-evaluate(py, "VEC_DISTANCE_IP", a, b)?)
+}
+
+/// Evaluate a distance function of the engine on two vectors, so that
+/// client-side re-ranking uses exactly the database's arithmetic.
+fn evaluate(
+    py: Python<'_>,
+    function: &str,
+    a: &Bound<'_, PyAny>,
+    b: &Bound<'_, PyAny>,
+) -> PyResult<f64> {
+    let (a, b) = (vector_values(a)?, vector_values(b)?);
+    if a.len() != b.len() {
+        return Err(error::dimension_error(py, a.len(), b.len(), 1));
+    }
+    let db = engine()?;
+    let sql = format!("SELECT {function}($1, $2)");
+    let mut params = ParamVec::new();
+    params.push(Value::vector(a));
+    params.push(Value::vector(b));
+    let value = py.allow_threads(|| -> stoolap::Result<Option<f64>> {
+        let plan = db.cached_plan(&sql)?;
+        let row = db.query_plan(&plan, params)?.next().transpose()?;
+        Ok(row.and_then(|row| match row.get_value(0) {
+            Some(Value::Float(f)) => Some(*f),
+            // Undefined distances, such as the cosine of a zero vector
+            Some(Value::Null(_)) => Some(f64::NAN),
+            _ => None,
+        }))
+    });
+    value
+        .map_err(to_py)?
+        .ok_or_else(|| error::StoolapError::new_err(format!("{function} returned no value")))
+}
+
+/// A private in-memory engine for evaluating distance functions.
+fn engine() -> PyResult<&'static ApiDatabase> {
+    static ENGINE: OnceLock<ApiDatabase> = OnceLock::new();
+    if let Some(db) = ENGINE.get() {
+        return Ok(db);
+    }
+    let db = ApiDatabase::open("memory://").map_err(to_py)?;
+    Ok(ENGINE.get_or_init(|| db))
+}
+
+/// Scale `values` to unit L2 norm. A zero vector has no direction and is
+/// left as it is.
+pub fn normalize(values: &mut [f32]) {
+    let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        values.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// The SQL distance function for a metric name.
+pub fn distance_function(metric: &str) -> PyResult<&'static str> {
+    match metric.to_ascii_lowercase().as_str() {
+        "cosine" => Ok("VEC_DISTANCE_COSINE"),
+        "l2" => Ok("VEC_DISTANCE_L2"),
+        "ip" => Ok("VEC_DISTANCE_IP"),
+        _ => Err(PyValueError::new_err(format!(
+            "metric must be 'cosine', 'l2' or 'ip', got '{metric}'"
+        ))),
+    }
+}
+
+/// Settings an HNSW index was built with.
+pub struct HnswSettings {
+    /// "l2", "cosine" or "ip", as `distance_function()` takes them.
+    pub metric: &'static str,
+    pub m: u16,
+    pub ef_construction: u16,
+    pub ef_search: Option<usize>,
+}
+
+/// The settings of `index`, None unless it is an HNSW index. The trait
+/// does not expose `ef_search`, so it is read from the HNSW index itself.
+pub fn hnsw_settings(index: &dyn Index) -> Option<HnswSettings> {
+    let metric = match index.hnsw_distance_metric()? {
+        0 => "l2",
+        1 => "cosine",
+        _ => "ip",
+    };
+    let ef_search = index
+        .as_any()
+        .downcast_ref::<HnswIndex>()
+        .map(|hnsw| hnsw.params().2);
+    Some(HnswSettings {
+        metric,
+        m: index.hnsw_m()?,
+        ef_construction: index.hnsw_ef_construction()?,
+        ef_search,
+    })
+}
+
+/// Round an f32 to the nearest half-precision value (ties to even).
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // Infinity, or NaN kept quiet
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exponent <= 0 {
+        // Subnormal, or too small for f16
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let half = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = rest > halfway || (rest == halfway && half & 1 == 1);
+        return sign | (half + round_up as u32) as u16;
+    }
+    let half = ((exponent as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    let round_up = rest > 0x1000 || (rest == 0x1000 && half & 1 == 1);
+    // A carry out of the mantissa correctly bumps the exponent
+    sign | (half + round_up as u32) as u16
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let negative = half & 0x8000 != 0;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let sign = if negative { 0x8000_0000 } else { 0 };
+    match exponent {
+        0 => {
+            // Zero or subnormal: mantissa * 2^-24, exact in f32
+            let value = mantissa as f32 * 2f32.powi(-24);
+            if negative {
+                -value
+            } else {
+                value
+            }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)),
+    }
+}
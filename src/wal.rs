@@ -0,0 +1,175 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Capture of committed writes for shipping to a replica.
+//!
+//! The engine does not expose its write-ahead log, so the binding keeps its
+//! own: every committed autocommit statement or transaction becomes one
+//! record holding its write statements, with the bound values rendered as
+//! SQL literals. Records are numbered by a log sequence number (LSN) and
+//! replayed in order with `Database.apply_wal()`.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::connection::Connection;
+use crate::error::StoolapError;
+use crate::sql;
+use crate::value::BindParams;
+
+#[derive(Default)]
+pub struct WalLog {
+    enabled: AtomicBool,
+    state: Mutex<Option<WalState>>,
+}
+
+struct WalState {
+    max_records: usize,
+    /// Retained records, oldest first, with consecutive LSNs.
+    records: VecDeque<(u64, Arc<Vec<String>>)>,
+    next_lsn: u64,
+}
+
+impl WalLog {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Start capturing, keeping the last `max_records` records, or stop and
+    /// drop the records with None. Numbering restarts at LSN 1.
+    pub fn configure(&self, max_records: Option<usize>) {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *guard = max_records.map(|max_records| WalState {
+            max_records,
+            records: VecDeque::new(),
+            next_lsn: 1,
+        });
+        self.enabled.store(guard.is_some(), Ordering::Relaxed);
+    }
+
+    /// Record the statements of a committed write, if any.
+    pub fn append(&self, statements: Vec<String>) {
+        if statements.is_empty() {
+            return;
+        }
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = guard.as_mut() else {
+            return;
+        };
+        state
+            .records
+            .push_back((state.next_lsn, Arc::new(statements)));
+        state.next_lsn += 1;
+        while state.records.len() > state.max_records {
+            state.records.pop_front();
+        }
+    }
+
+    /// The record at `lsn`, or None once the reader has caught up. Fails
+    /// when the record was already dropped to respect `max_records`.
+    pub fn read(&self, lsn: u64) -> PyResult<Option<(u64, Arc<Vec<String>>)>> {
+        let guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(state) = guard.as_ref() else {
+            return Err(StoolapError::new_err("WAL capture is not enabled"));
+        };
+        let oldest = state.next_lsn - state.records.len() as u64;
+        if lsn < oldest {
+            return Err(StoolapError::new_err(format!(
+                "WAL records before LSN {oldest} are no longer retained"
+            )));
+        }
+        Ok(state.records.get((lsn - oldest) as usize).cloned())
+    }
+}
+
+/// The statements a successful write adds to the log: `sql` with each set
+/// of bound values rendered in. Rendered before the write runs, so a write
+/// the log could not replay fails instead of going unrecorded.
+pub fn statements(sql: &str, values: &[BindParams]) -> PyResult<Vec<String>> {
+    values
+        .iter()
+        .map(|bind| {
+            sql::mogrify(sql, bind)
+                .map_err(|e| StoolapError::new_err(format!("cannot capture write for WAL: {e}")))
+        })
+        .collect()
+}
+
+/// Encode a record as UTF-8 JSON: `{"lsn": n, "statements": [...]}`.
+pub fn encode<'py>(
+    py: Python<'py>,
+    lsn: u64,
+    statements: &[String],
+) -> PyResult<Bound<'py, PyBytes>> {
+    let record = PyDict::new(py);
+    record.set_item("lsn", lsn)?;
+    record.set_item("statements", statements)?;
+    let text: String = py
+        .import("json")?
+        .call_method1("dumps", (record,))?
+        .extract()?;
+    Ok(PyBytes::new(py, text.as_bytes()))
+}
+
+/// Decode a record made by `encode()`, given as bytes or str.
+pub fn decode(record: &Bound<'_, PyAny>) -> PyResult<(u64, Vec<String>)> {
+    let record = record
+        .py()
+        .import("json")?
+        .call_method1("loads", (record,))?;
+    let lsn = record.get_item("lsn")?.extract()?;
+    let statements = record.get_item("statements")?.extract()?;
+    Ok((lsn, statements))
+}
+
+/// Iterator over the captured WAL records of a database, from a given LSN.
+///
+/// Yields each record as bytes, ready to send to a replica. It stops once
+/// it has caught up with the log; iterating it again later continues with
+/// the records committed since.
+#[pyclass]
+pub struct WalReader {
+    conn: Arc<Connection>,
+    lsn: u64,
+}
+
+impl WalReader {
+    pub fn new(conn: Arc<Connection>, lsn: u64) -> Self {
+        Self { conn, lsn }
+    }
+}
+
+#[pymethods]
+impl WalReader {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let Some((lsn, statements)) = self.conn.hooks.wal.read(self.lsn)? else {
+            return Ok(None);
+        };
+        self.lsn = lsn + 1;
+        encode(py, lsn, &statements).map(Some)
+    }
+
+    /// LSN of the next record the reader returns.
+    #[getter]
+    fn lsn(&self) -> u64 {
+        self.lsn
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright 2025 Stoolap Contributors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Warnings for lossy conversions of bound values.
+
+use pyo3::exceptions::{PyUserWarning, PyValueError};
+use pyo3::prelude::*;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use stoolap::api::Database as ApiDatabase;
+use stoolap::core::DataType;
+use stoolap::parser::ast::{Expression, Statement};
+
+use crate::error::StoolapError;
+use crate::hooks::FloatParam;
+
+// Warned when a bound value is converted with a loss: a naive datetime
+// taken as UTC, a float too large for a float32 vector element, or a float
+// written to an INTEGER column that is not a whole number in its range.
+pyo3::create_exception!(stoolap, StoolapWarning, PyUserWarning);
+
+const POLICIES: [&str; 3] = ["warn", "error", "ignore"];
+
+/// Index into `POLICIES` of the process-wide policy.
+static POLICY: AtomicU8 = AtomicU8::new(0);
+
+/// Report a lossy conversion according to the warning policy: as a
+/// `StoolapWarning`, as a `StoolapError` that fails the statement, or not
+/// at all.
+pub fn lossy(py: Python<'_>, message: String) -> PyResult<()> {
+    match POLICIES[POLICY.load(Ordering::Relaxed) as usize] {
+        "warn" => {
+            let message = CString::new(message).unwrap_or_default();
+            PyErr::warn(py, &py.get_type::<StoolapWarning>(), &message, 1)
+        }
+        "error" => Err(StoolapError::new_err(message)),
+        _ => Ok(()),
+    }
+}
+
+/// Report the floats in `floats` that an INSERT or UPDATE of a table in
+/// `db` writes to INTEGER columns. The engine converts them after binding,
+/// truncating toward zero and clamping to the INTEGER range, so `lossy()`
+/// would not see them otherwise. Only parameters written directly to a
+/// column are checked, not ones inside expressions; statements on unknown
+/// tables are left for the engine to reject.
+pub fn coercions(
+    py: Python<'_>,
+    db: &ApiDatabase,
+    sql: &str,
+    floats: &[FloatParam],
+) -> PyResult<()> {
+    if floats.is_empty() || POLICIES[POLICY.load(Ordering::Relaxed) as usize] == "ignore" {
+        return Ok(());
+    }
+    let Ok(mut statements) = stoolap::parser::parse_sql(sql) else {
+        return Ok(());
+    };
+    if statements.len() != 1 {
+        return Ok(());
+    }
+    // Each row of expressions with the columns they are written to; an
+    // empty column list stands for the table's columns in order
+    let (table, rows): (_, Vec<(Vec<String>, Vec<Expression>)>) = match statements.remove(0) {
+        Statement::Insert(insert) => {
+            let columns: Vec<String> = insert.columns.iter().map(|c| c.value.to_string()).collect();
+            let mut rows: Vec<_> = insert
+                .values
+                .into_iter()
+                .map(|row| (columns.clone(), row))
+                .collect();
+            let updated = insert
+                .update_columns
+                .iter()
+                .map(|c| c.value.to_string())
+                .collect();
+            rows.push((updated, insert.update_expressions));
+            (insert.table_name.value.to_string(), rows)
+        }
+        Statement::Update(update) => {
+            let (columns, values) = update
+                .updates
+                .into_iter()
+                .map(|(column, value)| (column.to_string(), value))
+                .unzip();
+            (update.table_name.value.to_string(), vec![(columns, values)])
+        }
+        _ => return Ok(()),
+    };
+    let Ok(store) = db.engine().get_version_store(&table) else {
+        return Ok(());
+    };
+    let schema = store.schema();
+    for (names, row) in &rows {
+        for (position, expr) in row.iter().enumerate() {
+            let Expression::Parameter(param) = expr else {
+                continue;
+            };
+            let column = match names.get(position) {
+                Some(name) => schema
+                    .columns
+                    .iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(name)),
+                None if names.is_empty() => schema.columns.get(position),
+                None => None,
+            };
+            let Some(column) = column.filter(|c| c.data_type == DataType::Integer) else {
+                continue;
+            };
+            let name = param.name.trim_start_matches([':', '@', '$']);
+            let float = floats.iter().find(|f| match &f.name {
+                Some(bound) => bound == name,
+                None => param.index >= 1 && f.index == param.index - 1,
+            });
+            if let Some(float) = float {
+                lossy(
+                    py,
+                    format!(
+                        "float {:?} bound to INTEGER column '{}' of '{table}' is stored as {}",
+                        float.value, column.name, float.value as i64
+                    ),
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Set how lossy conversions of bound values are reported: "warn" (the
+/// default) emits a `StoolapWarning`, "error" raises `StoolapError` instead
+/// of running the statement, and "ignore" stays silent. Returns the
+/// previous policy, so it can be restored.
+///
+/// The policy applies to the whole process, not to one database: to every
+/// `Database` and thread, and to values converted outside a connection,
+/// such as `Vector()`. Python's warning filters still apply under "warn",
+/// so `warnings.simplefilter("error", StoolapWarning)` also turns the
+/// warnings into exceptions.
+#[pyfunction]
+pub fn set_warning_policy(policy: &str) -> PyResult<&'static str> {
+    let index = POLICIES.iter().position(|p| *p == policy).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "unknown warning policy '{policy}' (expected 'warn', 'error' or 'ignore')"
+        ))
+    })?;
+    let previous = POLICY.swap(index as u8, Ordering::Relaxed);
+    Ok(POLICIES[previous as usize])
+}
@@ -130,4 +130,17 @@ async def test_async_execute_batch():
 
     rows = await db.query("SELECT * FROM users ORDER BY id")
     assert len(rows) == 3
+    stats = await db.stats()
+    assert stats["rows_written"] == 3
+    await db.close()
+
+
+@pytest.mark.asyncio
+async def test_async_query_chunks():
+    db = await AsyncDatabase.open(":memory:")
+    await db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    await db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(5)])
+
+    chunks = [c async for c in db.query_chunks("SELECT id FROM t ORDER BY id", chunk_size=2)]
+    assert [[r["id"] for r in c] for c in chunks] == [[0, 1], [2, 3], [4]]
     await db.close()
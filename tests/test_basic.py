@@ -14,7 +14,7 @@
 
 """Basic CRUD operations tests."""
 
-from stoolap import Database, StoolapError
+from stoolap import Column, Database, StoolapError, quote_ident, quote_literal
 import pytest
 
 
@@ -131,6 +131,77 @@ def test_exec_multiple_statements():
     db.close()
 
 
+def test_exec_results():
+    db = Database.open(":memory:")
+    results = db.exec("""
+        CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT);
+        INSERT INTO t VALUES (1, 'a;b'), (2, 'c');
+        -- a comment; with a semicolon
+        SELECT id, val FROM t ORDER BY id;
+        UPDATE t SET val = 'z' WHERE id > 1;
+        WITH x AS (SELECT COUNT(*) AS n FROM t) SELECT n FROM x;
+    """)
+    assert results == [
+        0,
+        2,
+        [{"id": 1, "val": "a;b"}, {"id": 2, "val": "c"}],
+        1,
+        [{"n": 2}],
+    ]
+    db.close()
+
+
+def test_exec_with_params():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+
+    results = db.exec(
+        "INSERT INTO t VALUES ($1, $2); SELECT COUNT(*) AS n FROM t; SELECT val FROM t WHERE id = $1",
+        [1, "a"],
+    )
+    assert results == [1, [{"n": 1}], [{"val": "a"}]]
+
+    results = db.exec(
+        "INSERT INTO t VALUES (:id, 'b'); UPDATE t SET val = $1 WHERE id = $2; SELECT 1 AS one",
+        params_list=[{"id": 2}, ["c", 1], None],
+    )
+    assert results == [1, 1, [{"one": 1}]]
+    assert db.query("SELECT val FROM t ORDER BY id") == [{"val": "c"}, {"val": "b"}]
+
+    with pytest.raises(ValueError):
+        db.exec("SELECT 1; SELECT 2", params_list=[None])
+    with pytest.raises(TypeError):
+        db.exec("SELECT $1", [1], params_list=[[1]])
+    db.close()
+
+
+def test_exec_splitting():
+    db = Database.open(":memory:")
+    results = db.exec("""
+        CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT);
+        INSERT INTO t VALUES (1, 'it''s; fine'), (2, '/* not; a comment */');
+        /* a block comment; with a semicolon */
+        SELECT val FROM t ORDER BY id;
+    """)
+    assert results[1:] == [2, [{"val": "it's; fine"}, {"val": "/* not; a comment */"}]]
+
+    # Each statement numbers its own qmark placeholders
+    db.exec("INSERT INTO t VALUES (?, 'x'); DELETE FROM t WHERE id = ?", params_list=[[3], [1]])
+    assert [r["id"] for r in db.query("SELECT id FROM t ORDER BY id")] == [2, 3]
+    db.close()
+
+
+def test_exec_transaction():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+
+    assert db.exec("INSERT INTO t VALUES (1); INSERT INTO t VALUES (2)", transaction=True) == [1, 1]
+    with pytest.raises(StoolapError):
+        db.exec("INSERT INTO t VALUES (3); INSERT INTO t VALUES (1)", transaction=True)
+    assert [r["id"] for r in db.query("SELECT id FROM t ORDER BY id")] == [1, 2]
+    db.close()
+
+
 def test_type_mapping():
     db = Database.open(":memory:")
     db.exec("CREATE TABLE types (i INTEGER, f FLOAT, t TEXT, b BOOLEAN)")
@@ -171,6 +242,39 @@ def test_error_handling():
     db.close()
 
 
+def test_error_details():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    db.execute("INSERT INTO users VALUES ($1, $2)", [1, "a"])
+
+    with pytest.raises(StoolapError) as info:
+        db.query("SELECT * FROM nonexistent")
+    assert info.value.sqlstate is not None
+    assert info.value.code is None or isinstance(info.value.code, str)
+    assert info.value.category == "missing_table"
+    assert info.value.table in (None, "nonexistent")
+
+    with pytest.raises(StoolapError) as info:
+        db.execute("INSERT INTO users VALUES ($1, $2)", [1, "b"])
+    assert info.value.sqlstate.startswith("23")
+    assert info.value.category == "constraint"
+
+    sql = "SELECT id,\n  name FROM users ORDER name"
+    with pytest.raises(StoolapError) as info:
+        db.query(sql)
+    err = info.value
+    assert err.sqlstate == "42601"
+    assert err.category == "syntax"
+    if err.line is not None and err.offset is not None:
+        line_start = sum(len(line) + 1 for line in sql.split("\n")[: err.line - 1])
+        assert err.offset == line_start + err.column - 1
+
+    # Errors raised by the binding itself carry the attributes as None
+    assert StoolapError("x").line is None
+    assert StoolapError("x").category is None
+    db.close()
+
+
 def test_execute_batch():
     db = Database.open(":memory:")
     db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
@@ -186,3 +290,407 @@ def test_execute_batch():
     assert rows[0]["name"] == "Alice"
     assert rows[2]["name"] == "Charlie"
     db.close()
+
+
+def test_intern_strings():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, country TEXT)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, "NL" if i % 2 else "DE"] for i in range(10)])
+
+    rows = db.query("SELECT country FROM t ORDER BY id", intern_strings=True)
+    assert [r["country"] for r in rows] == ["DE", "NL"] * 5
+    assert rows[0]["country"] is rows[2]["country"]
+
+    raw = db.query_raw("SELECT country FROM t ORDER BY id", intern_strings=True)
+    assert raw["rows"][1][0] is raw["rows"][3][0]
+    db.close()
+
+
+def test_query_chunks():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(7)])
+
+    chunks = db.query_chunks("SELECT id FROM t WHERE id >= $1 ORDER BY id", [1], chunk_size=3)
+    assert chunks.columns == ["id"]
+    assert [[r["id"] for r in c] for c in chunks] == [[1, 2, 3], [4, 5, 6]]
+    assert next(chunks, None) is None
+
+    chunks = db.query_chunks("SELECT id FROM t", chunk_size=2)
+    assert len(next(chunks)) == 2
+    chunks.close()
+    assert list(chunks) == []
+
+    with pytest.raises(ValueError):
+        db.query_chunks("SELECT id FROM t", chunk_size=0)
+    db.close()
+
+
+def test_query_arrow_batches():
+    pa = pytest.importorskip("pyarrow")
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT, score FLOAT)")
+    db.execute_batch(
+        "INSERT INTO t VALUES ($1, $2, $3)", [[i, f"n{i}", i / 2] for i in range(5)]
+    )
+
+    reader = db.query_arrow_batches("SELECT * FROM t ORDER BY id", batch_rows=2)
+    assert isinstance(reader, pa.RecordBatchReader)
+    assert reader.schema.names == ["id", "name", "score"]
+    batches = list(reader)
+    assert [b.num_rows for b in batches] == [2, 2, 1]
+    table = pa.Table.from_batches(batches)
+    assert table.column("name").to_pylist() == [f"n{i}" for i in range(5)]
+
+    empty = db.query_arrow_batches("SELECT id FROM t WHERE id < 0")
+    assert empty.read_all().num_rows == 0
+    db.close()
+
+
+def test_mogrify():
+    db = Database.open(":memory:")
+    assert (
+        db.mogrify("SELECT * FROM t WHERE name = $1 AND id > $2", ["O'Brien", 10])
+        == "SELECT * FROM t WHERE name = 'O''Brien' AND id > 10"
+    )
+    assert db.mogrify("SELECT $1, $2, $3, $4", [None, True, 1.0, -2]) == "SELECT NULL, TRUE, 1.0, -2"
+    assert db.mogrify("SELECT 1-$1", [-1]) == "SELECT 1- -1"
+    assert db.mogrify("SELECT :a, ':a', a::TEXT", {"a": "x"}) == "SELECT 'x', ':a', a::TEXT"
+
+    # The rendered literal reads back as the original value
+    sql = db.mogrify("SELECT $1 AS v", ["it's \\ ok"])
+    assert db.query_one(sql)["v"] == "it's \\ ok"
+
+    with pytest.raises(ValueError):
+        db.mogrify("SELECT $2", [1])
+    with pytest.raises(ValueError):
+        db.mogrify("SELECT :missing", {"a": 1})
+    db.close()
+
+
+def test_explain():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val INTEGER)")
+
+    text = db.explain("SELECT * FROM t WHERE val > $1", [1])
+    assert isinstance(text, str) and text
+
+    plan = db.explain("SELECT * FROM t WHERE val > $1", [1], format="dict")
+    assert set(plan) == {
+        "node", "estimated_rows", "estimated_cost", "actual_rows", "actual_time_ms",
+        "details", "children",
+    }
+    assert plan["actual_rows"] is None
+    assert text.splitlines()[0].strip().removeprefix("->").strip().startswith(plan["node"])
+
+    tree = db.explain("SELECT * FROM t WHERE val > $1", [1], format="tree")
+    assert tree.splitlines()[0] == text.splitlines()[0].strip().removeprefix("->").strip()
+    assert len(tree.splitlines()) == len([line for line in text.splitlines() if line.strip()])
+
+    with pytest.raises(ValueError):
+        db.explain("SELECT * FROM t", format="json")
+    db.close()
+
+
+def test_explain_analyze():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val INTEGER)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, i % 10] for i in range(100)])
+
+    def nodes(node):
+        yield node
+        for child in node["children"]:
+            yield from nodes(child)
+
+    plan = db.explain_analyze("SELECT * FROM t WHERE val = $1", [3], format="dict")
+    for node in nodes(plan):
+        assert node["actual_rows"] is None or node["actual_rows"] >= 0
+        assert node["actual_time_ms"] is None or node["actual_time_ms"] >= 0
+    assert isinstance(db.explain_analyze("SELECT COUNT(*) FROM t"), str)
+    db.close()
+
+
+def test_suggest_indexes():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val INTEGER)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, i % 10] for i in range(100)])
+
+    suggestions = db.suggest_indexes("SELECT * FROM t WHERE val = 3")
+    for suggestion in suggestions:
+        assert suggestion["table"] == "t"
+        assert suggestion["columns"] == ["val"]
+        assert suggestion["queries"] == 1
+        db.exec(suggestion["ddl"])
+    # Columns that an index leads with are not suggested again
+    assert db.suggest_indexes([("SELECT * FROM t WHERE val = $1", [3])]) == []
+    assert db.suggest_indexes(["SELECT * FROM t WHERE id = 1"]) == []
+
+    with pytest.raises(TypeError):
+        db.suggest_indexes([1])
+    db.close()
+
+
+def test_quote_helpers():
+    assert quote_ident("users") == '"users"'
+    assert quote_ident('we"ird') == '"we""ird"'
+    assert quote_literal("it's") == "'it''s'"
+    assert quote_literal(None) == "NULL"
+    assert quote_literal(3) == "3"
+    with pytest.raises(ValueError):
+        quote_ident("")
+    with pytest.raises(TypeError):
+        quote_literal(object())
+
+    db = Database.open(":memory:")
+    table = quote_ident("order")
+    db.exec(f"CREATE TABLE {table} (id INTEGER PRIMARY KEY, note TEXT)")
+    note = quote_literal('a "b" c')
+    db.execute(f"INSERT INTO {table} VALUES (1, {note})")
+    assert db.query_one(f"SELECT note FROM {table}")["note"] == 'a "b" c'
+    db.close()
+
+
+def test_temp_table():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    db.execute_batch("INSERT INTO users VALUES ($1, $2)", [[1, "a"], [2, "b"], [7, "c"]])
+
+    table = db.temp_table([{"id": 1, "weight": 1}, {"id": 7, "weight": 2.5}])
+    with table as name:
+        assert name == table.name
+        assert name in db.tables()
+        rows = db.query(
+            f"SELECT u.name, t.weight FROM users u JOIN {name} t ON u.id = t.id ORDER BY u.id"
+        )
+        assert rows == [{"name": "a", "weight": 1.0}, {"name": "c", "weight": 2.5}]
+    assert name not in db.tables()
+
+    with db.temp_table([(2, None), (3, "x")], columns=["id", "tag"]) as name:
+        assert db.query_one(f"SELECT COUNT(*) AS n FROM {name}")["n"] == 2
+
+    # The table is dropped even when the block raises
+    with pytest.raises(RuntimeError):
+        with db.temp_table([], columns=["id"]) as name:
+            raise RuntimeError
+    assert name not in db.tables()
+
+    with pytest.raises(ValueError):
+        db.temp_table([(1, 2)])
+    with pytest.raises(ValueError):
+        db.temp_table([(1, 2)], columns=["a"])
+    db.close()
+
+
+def test_temp_table_dataframe():
+    pd = pytest.importorskip("pandas")
+    db = Database.open(":memory:")
+    df = pd.DataFrame({"id": [1, 2, 3], "score": [0.5, 1.5, 2.5]})
+    with db.temp_table(df) as name:
+        assert db.query_one(f"SELECT SUM(score) AS s FROM {name}")["s"] == 4.5
+        assert db.query_one(f"SELECT MAX(id) AS m FROM {name}")["m"] == 3
+    db.close()
+
+
+def test_table_handle():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT)")
+    users = db.table("users")
+    assert users.name == "users"
+
+    assert users.insert({"id": 1, "name": "Alice", "email": "a@x"}) == 1
+    users.insert({"id": 2, "name": "Bob", "email": None})
+    users.insert({"id": 3, "name": "Ann"})
+
+    assert users.get(1) == {"id": 1, "name": "Alice", "email": "a@x"}
+    assert users.get(99) is None
+    assert [r["id"] for r in users.find({"email": None}, order_by="id")] == [2, 3]
+    assert [r["id"] for r in users.find("name LIKE $1", ["A%"], order_by="id DESC")] == [3, 1]
+    assert len(users.find(limit=2)) == 2
+
+    assert users.update({"id": 1}, {"name": "Alicia"}) == 1
+    assert users.get(1)["name"] == "Alicia"
+    assert users.update("id > $1", {"email": "?"}, [1]) == 2
+    assert users.delete({"name": "Bob"}) == 1
+    assert users.delete("id = :id", {"id": 3}) == 1
+    assert [r["id"] for r in users.find()] == [1]
+
+    with pytest.raises(ValueError):
+        users.delete({})
+    with pytest.raises(TypeError):
+        users.find({"id": 1}, [1])
+    db.close()
+
+
+def test_delete_where():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT)")
+    db.execute_batch(
+        "INSERT INTO events VALUES ($1, $2)",
+        [[i, "old" if i % 3 else "new"] for i in range(100)],
+    )
+    commits = []
+    db.set_commit_hook(lambda: commits.append(1))
+
+    assert db.delete_where("events", "kind = $1", ["old"], batch_size=10) == 66
+    assert len(commits) == 7
+    assert db.query_one("SELECT COUNT(*) AS n FROM events")["n"] == 34
+    assert db.delete_where("events", {"kind": "new"}) == 34
+    assert db.delete_where("events", "TRUE", batch_size=5) == 0
+
+    with pytest.raises(ValueError):
+        db.delete_where("events", {})
+    with pytest.raises(ValueError):
+        db.delete_where("events", "TRUE", batch_size=0)
+    db.close()
+
+
+def test_create_table():
+    db = Database.open(":memory:")
+    db.create_table("order items", {"id": "INTEGER PRIMARY KEY", "order": "TEXT NOT NULL"})
+    db.execute('INSERT INTO "order items" VALUES ($1, $2)', [1, "a"])
+    db.create_table("order items", {"id": "INTEGER"})
+    with pytest.raises(StoolapError):
+        db.create_table("order items", {"id": "INTEGER"}, if_not_exists=False)
+
+    db.create_table("users", [
+        Column("id", "INTEGER", primary_key=True),
+        Column("email", "TEXT", not_null=True, unique=True),
+        Column("role", "TEXT", default="member"),
+    ])
+    db.execute("INSERT INTO users (id, email) VALUES ($1, $2)", [1, "a@x"])
+    assert db.query_one("SELECT * FROM users") == {"id": 1, "email": "a@x", "role": "member"}
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO users (id, email) VALUES ($1, $2)", [2, "a@x"])
+    assert repr(Column("id", "INTEGER", primary_key=True)) == "Column(\"id\", \"INTEGER\", primary_key=True)"
+
+    db.create_table("memberships", [
+        Column("user_id", "INTEGER", primary_key=True),
+        Column("group_id", "INTEGER", primary_key=True),
+        Column("role", "TEXT", default="member"),
+    ])
+    db.execute("INSERT INTO memberships (user_id, group_id) VALUES (1, 1)")
+    db.execute("INSERT INTO memberships (user_id, group_id) VALUES (1, 2)")
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO memberships (user_id, group_id) VALUES (1, 1)")
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO memberships (user_id, group_id) VALUES (NULL, 3)")
+    assert db.query_one("SELECT COUNT(*) AS n FROM memberships") == {"n": 2}
+
+    with pytest.raises(ValueError):
+        db.create_table("empty", {})
+    with pytest.raises(TypeError):
+        db.create_table("bad", ["id INTEGER"])
+    db.close()
+
+
+def test_truncate():
+    db = Database.open(":memory:")
+    db.exec("""
+        CREATE TABLE staging (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT);
+        CREATE INDEX idx_staging_name ON staging (name);
+    """)
+    db.execute_batch("INSERT INTO staging (name) VALUES ($1)", [["a"], ["b"], ["c"]])
+    db.truncate("staging")
+    assert db.query("SELECT * FROM staging") == []
+
+    # Without restart_identity, ids carry on after the truncated rows
+    db.execute("INSERT INTO staging (name) VALUES ($1)", ["d"])
+    assert db.query("SELECT * FROM staging") == [{"id": 4, "name": "d"}]
+    db.truncate("staging", restart_identity=True)
+    db.execute("INSERT INTO staging (name) VALUES ($1)", ["e"])
+    assert db.query("SELECT * FROM staging") == [{"id": 1, "name": "e"}]
+    assert any("idx_staging_name" in map(str, row.values()) for row in db.indexes("staging"))
+    assert db.stats()["active_transactions"] == 0
+    with pytest.raises(StoolapError):
+        db.truncate("missing")
+    with pytest.raises(StoolapError):
+        db.truncate("missing", restart_identity=True)
+    assert db.stats()["active_transactions"] == 0
+    db.close()
+
+
+def test_truncate_keeps_unique_indexes():
+    db = Database.open("memory://truncate_unique")
+    db.exec("""
+        CREATE TABLE accounts (id INTEGER PRIMARY KEY AUTO_INCREMENT, email TEXT UNIQUE, login TEXT);
+        CREATE UNIQUE INDEX idx_accounts_login ON accounts (login);
+    """)
+    indexes = db.indexes("accounts")
+    db.execute("INSERT INTO accounts (email, login) VALUES ('a@x', 'a')")
+    db.truncate("accounts", restart_identity=True)
+    assert db.indexes("accounts") == indexes
+    db.execute("INSERT INTO accounts (email, login) VALUES ('a@x', 'a')")
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO accounts (email, login) VALUES ('a@x', 'b')")
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO accounts (email, login) VALUES ('b@x', 'a')")
+    db.close()
+
+
+def test_truncate_failure_keeps_table():
+    db = Database.open("memory://truncate_failure")
+    db.exec("""
+        CREATE TABLE parents (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT UNIQUE);
+        CREATE TABLE children (id INTEGER PRIMARY KEY, parent_id INTEGER REFERENCES parents(id));
+        INSERT INTO parents (name) VALUES ('a'), ('b');
+        INSERT INTO children VALUES (1, 1);
+    """)
+    indexes = db.indexes("parents")
+    for restart_identity in (False, True):
+        with pytest.raises(StoolapError):
+            db.truncate("parents", restart_identity=restart_identity)
+        assert db.query("SELECT id, name FROM parents ORDER BY id") == [
+            {"id": 1, "name": "a"},
+            {"id": 2, "name": "b"},
+        ]
+        assert db.indexes("parents") == indexes
+    db.execute("INSERT INTO parents (name) VALUES ('c')")
+    assert db.query_one("SELECT id FROM parents WHERE name = 'c'") == {"id": 3}
+    db.close()
+
+
+def test_map_class():
+    from dataclasses import dataclass
+
+    @dataclass(frozen=True)
+    class Tag:
+        id: int
+        label: str
+
+    class User:
+        def __init__(self, id, name):
+            self.id = id
+            self.name = name
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)")
+    db.exec("CREATE TABLE tags (id INTEGER PRIMARY KEY, label TEXT)")
+    db.map(User, "users")
+    db.map(Tag, "tags", pk="id")
+
+    alice = User(None, "Alice")
+    db.save(alice)
+    assert alice.id is not None
+    db.save(User(None, "Bob"))
+
+    loaded = db.get(User, alice.id)
+    assert isinstance(loaded, User) and loaded.name == "Alice"
+    loaded.name = "Alicia"
+    db.save(loaded)
+    assert db.get(User, alice.id).name == "Alicia"
+    assert db.get(User, 999) is None
+    assert [u.name for u in db.select(User, order_by="id")] == ["Alicia", "Bob"]
+    assert [u.name for u in db.select(User, "name LIKE $1", ["B%"])] == ["Bob"]
+
+    db.save(Tag(5, "red"))
+    assert db.get(Tag, 5) == Tag(5, "red")
+    assert db.select(Tag, {"label": "red"}) == [Tag(5, "red")]
+
+    class Unmapped:
+        pass
+
+    with pytest.raises(TypeError):
+        db.get(Unmapped, 1)
+    with pytest.raises(ValueError):
+        db.map(Tag, "tags", pk="id", columns=["label"])
+    db.close()
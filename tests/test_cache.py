@@ -0,0 +1,81 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Result cache tests."""
+
+import pytest
+from stoolap import Database
+
+
+@pytest.fixture
+def db():
+    d = Database.open(":memory:")
+    d.exec("CREATE TABLE a (id INTEGER PRIMARY KEY, val TEXT)")
+    d.exec("CREATE TABLE b (id INTEGER PRIMARY KEY, val TEXT)")
+    d.execute("INSERT INTO a VALUES ($1, $2)", [1, "x"])
+    d.execute("INSERT INTO b VALUES ($1, $2)", [1, "y"])
+    d.enable_result_cache(1 << 20)
+    yield d
+    d.close()
+
+
+def test_hits_and_misses(db):
+    assert db.query("SELECT * FROM a") == [{"id": 1, "val": "x"}]
+    assert db.query("SELECT * FROM a") == [{"id": 1, "val": "x"}]
+    assert db.query("SELECT * FROM a WHERE id = $1", [2]) == []
+    stats = db.stats()
+    assert (stats["cache_hits"], stats["cache_misses"]) == (1, 2)
+    assert stats["queries"] == 3
+
+
+def test_results_are_copies(db):
+    db.query("SELECT * FROM a")[0]["val"] = "changed"
+    assert db.query("SELECT * FROM a")[0]["val"] == "x"
+
+
+def test_write_invalidates_named_table_only(db):
+    db.query("SELECT * FROM a")
+    db.query_raw("SELECT * FROM b")
+    db.execute("UPDATE a SET val = $1 WHERE id = $2", ["z", 1])
+    assert db.query("SELECT * FROM a") == [{"id": 1, "val": "z"}]
+    assert db.query_raw("SELECT * FROM b")["rows"] == [[1, "y"]]
+    assert db.stats()["cache_hits"] == 1
+
+
+def test_transaction_invalidates_on_commit(db):
+    db.query("SELECT * FROM a")
+    with db.begin() as tx:
+        tx.execute("INSERT INTO a VALUES ($1, $2)", [2, "w"])
+        assert len(db.query("SELECT * FROM a")) == 1
+    assert len(db.query("SELECT * FROM a")) == 2
+
+
+def test_ddl_clears_cache(db):
+    db.query("SELECT * FROM b")
+    db.exec("CREATE TABLE c (id INTEGER PRIMARY KEY)")
+    db.query("SELECT * FROM b")
+    assert db.stats()["cache_hits"] == 0
+
+
+def test_budget_and_disable(db):
+    db.enable_result_cache(1)
+    db.query("SELECT * FROM a")
+    db.query("SELECT * FROM a")
+    assert db.stats()["cache_hits"] == 0
+
+    db.enable_result_cache(1 << 20)
+    db.query("SELECT * FROM a")
+    db.disable_result_cache()
+    db.query("SELECT * FROM a")
+    assert db.stats()["cache_hits"] == 0
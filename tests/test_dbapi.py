@@ -0,0 +1,216 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""DB-API 2.0 interface tests."""
+
+import pytest
+import stoolap
+from stoolap import Database, dbapi
+
+
+def test_module_attributes():
+    assert stoolap.apilevel == "2.0"
+    assert stoolap.threadsafety == 2
+    assert stoolap.paramstyle == "numeric_dollar"
+    assert stoolap.connect is dbapi.connect
+    assert issubclass(dbapi.OperationalError, dbapi.Error)
+    assert issubclass(dbapi.InterfaceError, dbapi.Error)
+    assert stoolap.__version__.count(".") == 2
+
+
+def test_cursor_roundtrip():
+    conn = stoolap.connect(":memory:")
+    cur = conn.cursor()
+    cur.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+    cur.executemany("INSERT INTO t VALUES ($1, $2)", [[1, "a"], [2, "b"], [3, "c"]])
+    assert cur.rowcount == 3
+    assert cur.description is None
+
+    cur.execute("SELECT id, name FROM t WHERE id > $1 ORDER BY id", [1])
+    assert [d[0] for d in cur.description] == ["id", "name"]
+    assert cur.fetchone() == (2, "b")
+    assert cur.fetchmany(5) == [(3, "c")]
+    assert cur.fetchone() is None
+
+    cur.execute("UPDATE t SET name = $1", ["z"])
+    assert cur.rowcount == 3
+    with pytest.raises(dbapi.Error):
+        cur.fetchall()
+    conn.close()
+
+
+def test_commit_and_rollback():
+    conn = stoolap.connect(":memory:", autocommit=True)
+    conn.cursor().execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    conn.autocommit = False
+
+    cur = conn.cursor()
+    cur.execute("INSERT INTO t VALUES (1)")
+    assert conn.in_transaction
+    conn.rollback()
+    assert list(cur.execute("SELECT id FROM t")) == []
+
+    with conn:
+        cur.execute("INSERT INTO t VALUES (2)")
+    assert not conn.in_transaction
+    assert conn.database.query("SELECT id FROM t") == [{"id": 2}]
+    conn.close()
+
+    with pytest.raises(dbapi.InterfaceError):
+        conn.cursor()
+
+
+def test_version_and_isolation():
+    db = Database.open(":memory:")
+    assert db.server_version == stoolap.__version__
+    assert db.server_version_info == tuple(int(p) for p in stoolap.__version__.split("."))
+    assert db.get_isolation_level() == "READ COMMITTED"
+    with db.begin() as tx:
+        assert tx.isolation_level == "READ COMMITTED"
+    with db.begin("snapshot") as tx:
+        assert tx.isolation_level == "SNAPSHOT"
+    db.close()
+
+
+def test_integrity_error():
+    assert issubclass(stoolap.IntegrityError, stoolap.StoolapError)
+    assert issubclass(dbapi.IntegrityError, stoolap.IntegrityError)
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    with pytest.raises(stoolap.IntegrityError):
+        db.execute("INSERT INTO t VALUES ($1, $2)", [1, "b"])
+    with pytest.raises(stoolap.IntegrityError):
+        db.execute("INSERT INTO t VALUES ($1, $2)", [2, None])
+    with pytest.raises(stoolap.StoolapError) as excinfo:
+        db.execute("INSERT INTO missing VALUES (1)")
+    assert not isinstance(excinfo.value, stoolap.IntegrityError)
+    db.close()
+
+
+def test_error_classes():
+    for name in ["DataError", "OperationalError", "IntegrityError", "InternalError",
+                 "ProgrammingError", "NotSupportedError"]:
+        cls = getattr(dbapi, name)
+        assert issubclass(cls, dbapi.DatabaseError)
+        assert getattr(dbapi.Connection, name) is cls
+    assert not issubclass(dbapi.InterfaceError, dbapi.DatabaseError)
+
+    conn = stoolap.connect(":memory:", autocommit=True)
+    cur = conn.cursor()
+    cur.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    cur.execute("INSERT INTO t VALUES (1)")
+    with pytest.raises(dbapi.IntegrityError) as excinfo:
+        cur.execute("INSERT INTO t VALUES (1)")
+    assert isinstance(excinfo.value, stoolap.IntegrityError)
+    assert excinfo.value.sqlstate.startswith("23")
+    with pytest.raises(dbapi.ProgrammingError) as excinfo:
+        cur.execute("SELECT * FROM missing")
+    assert not isinstance(excinfo.value, dbapi.IntegrityError)
+    assert excinfo.value.category == "missing_table"
+    with pytest.raises(dbapi.ProgrammingError):
+        cur.execute("SELEC 1")
+    with pytest.raises(dbapi.DataError):
+        cur.execute("INSERT INTO t VALUES ('x')")
+    conn.close()
+
+
+def test_conflict_is_operational_error():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)")
+    db.execute("INSERT INTO t VALUES (1, 0)")
+    conn = dbapi.Connection(db)
+    other = db.begin()
+    other.execute("UPDATE t SET v = 1 WHERE id = 1")
+    cur = conn.cursor()
+    with pytest.raises(dbapi.OperationalError) as excinfo:
+        cur.execute("UPDATE t SET v = 2 WHERE id = 1")
+    assert isinstance(excinfo.value, stoolap.ConflictError)
+    assert excinfo.value.sqlstate == "40001"
+    other.rollback()
+    conn.close()
+
+
+def test_returning_inside_literal():
+    conn = stoolap.connect(":memory:")
+    cur = conn.cursor()
+    cur.execute("CREATE TABLE t (id INTEGER PRIMARY KEY, note TEXT)")
+    cur.execute("INSERT INTO t VALUES (1, 'a RETURNING b')")
+    assert cur.rowcount == 1
+    assert cur.description is None
+    cur.execute("UPDATE t SET note = 'it''s RETURNING' WHERE id = 1")
+    assert cur.rowcount == 1
+    cur.execute("DELETE FROM t WHERE id = 1 RETURNING id")
+    assert cur.fetchall() == [(1,)]
+    conn.close()
+
+
+def test_savepoints():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES (1)")
+        tx.savepoint("sp")
+        tx.execute("INSERT INTO t VALUES (2)")
+        tx.rollback_to_savepoint("sp")
+        tx.execute("INSERT INTO t VALUES (3)")
+        tx.rollback_to_savepoint("sp")  # the savepoint remains
+        tx.release_savepoint("sp")
+        with pytest.raises(stoolap.StoolapError):
+            tx.rollback_to_savepoint("sp")
+        assert tx.active
+    assert db.query("SELECT id FROM t") == [{"id": 1}]
+    db.close()
+
+
+def test_cursor_savepoints_and_ddl():
+    conn = stoolap.connect(":memory:")
+    cur = conn.cursor()
+    cur.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    cur.execute("INSERT INTO t VALUES (1)")
+    cur.execute("SAVEPOINT sp")
+    cur.execute("INSERT INTO t VALUES (2)")
+    cur.execute("ROLLBACK TO SAVEPOINT sp")
+    cur.execute("RELEASE SAVEPOINT sp")
+    conn.commit()
+    cur.execute("SELECT id FROM t")
+    assert cur.fetchall() == [(1,)]
+
+    cur.execute("CREATE TABLE u (id INTEGER PRIMARY KEY)")
+    conn.rollback()
+    assert conn.database.tables() == ["t"]
+    conn.close()
+
+
+def test_introspection():
+    db = Database.open(":memory:")
+    db.exec("""
+        CREATE TABLE b (id INTEGER PRIMARY KEY, email TEXT);
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE UNIQUE INDEX idx_email ON b(email);
+    """)
+    assert db.tables() == ["a", "b"]
+    assert len(db.describe("b")) == 2
+    assert any("idx_email" in map(str, row.values()) for row in db.indexes("b"))
+    db.close()
+
+
+def test_last_insert_id():
+    conn = stoolap.connect(":memory:")
+    cur = conn.cursor()
+    cur.execute("CREATE TABLE t (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT)")
+    cur.execute("INSERT INTO t (name) VALUES ($1)", ["a"])
+    cur.execute("INSERT INTO t (name) VALUES ($1)", ["b"])
+    assert conn.last_insert_id("t") == 2
+    conn.close()
@@ -15,7 +15,17 @@
 """Edge cases and error handling tests."""
 
 import pytest
-from stoolap import Database, AsyncDatabase, StoolapError
+from stoolap import (
+    AsyncDatabase,
+    CancellationToken,
+    ConflictError,
+    Database,
+    LimitedRows,
+    QueryCancelled,
+    QueryTimeout,
+    ResultTooLarge,
+    StoolapError,
+)
 
 
 # --- Error handling ---
@@ -44,6 +54,18 @@ def test_duplicate_primary_key():
     db.close()
 
 
+def test_conflict_error_hierarchy():
+    assert issubclass(ConflictError, StoolapError)
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    # Constraint violations are not conflicts and must not be retried
+    with pytest.raises(StoolapError) as excinfo:
+        db.execute("INSERT INTO t VALUES ($1)", [1])
+    assert not isinstance(excinfo.value, ConflictError)
+    db.close()
+
+
 def test_constraint_not_null():
     db = Database.open(":memory:")
     db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
@@ -261,6 +283,22 @@ def test_many_columns():
     db.close()
 
 
+def test_many_rows():
+    # Spans several conversion chunks
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, str(i)] for i in range(10_000)])
+
+    rows = db.query("SELECT id, val FROM t ORDER BY id")
+    assert len(rows) == 10_000
+    assert rows[4096] == {"id": 4096, "val": "4096"}
+    assert rows[-1] == {"id": 9999, "val": "9999"}
+
+    raw = db.query_raw("SELECT id FROM t ORDER BY id")
+    assert [r[0] for r in raw["rows"]] == list(range(10_000))
+    db.close()
+
+
 # --- Async edge cases ---
 
 
@@ -287,3 +325,177 @@ async def test_async_tx_rollback_on_exception():
     rows = await db.query("SELECT * FROM t")
     assert len(rows) == 0
     await db.close()
+
+
+# --- Result limits ---
+
+
+def test_max_rows():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(5000)])
+
+    assert len(db.query("SELECT * FROM t", max_rows=5000)) == 5000
+    with pytest.raises(ResultTooLarge):
+        db.query("SELECT * FROM t", max_rows=4999)
+    with pytest.raises(ResultTooLarge):
+        db.query_raw("SELECT * FROM t", max_rows=10)
+
+    stmt = db.prepare("SELECT * FROM t WHERE id < $1")
+    assert len(stmt.query([10], max_rows=10)) == 10
+    with pytest.raises(ResultTooLarge):
+        stmt.query([11], max_rows=10)
+
+    with db.begin() as tx:
+        with pytest.raises(ResultTooLarge):
+            tx.query("SELECT * FROM t", max_rows=1)
+    db.close()
+
+
+def test_max_result_bytes():
+    assert issubclass(ResultTooLarge, StoolapError)
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "x" * 10_000])
+
+    with pytest.raises(ResultTooLarge):
+        db.query("SELECT val FROM t", max_result_bytes=1000)
+    assert db.query("SELECT val FROM t", max_result_bytes=100_000)[0]["val"] == "x" * 10_000
+    db.close()
+
+
+def test_query_limit():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(5000)])
+
+    rows = db.query("SELECT * FROM t ORDER BY id", limit=10)
+    assert isinstance(rows, list) and isinstance(rows, LimitedRows)
+    assert [r["id"] for r in rows] == list(range(10))
+    assert rows.truncated
+    rows = db.query("SELECT * FROM t", limit=5000)
+    assert len(rows) == 5000
+    assert not rows.truncated
+    rows = db.query("SELECT * FROM t WHERE id < 0", limit=3)
+    assert rows == [] and not rows.truncated
+    # The limit is applied before max_rows is checked
+    assert len(db.query("SELECT * FROM t", limit=10, max_rows=10)) == 10
+    # Without a limit, the result is a plain list
+    assert type(db.query("SELECT * FROM t WHERE id < 3")) is list
+
+    stmt = db.prepare("SELECT * FROM t WHERE id < $1")
+    rows = stmt.query([3], limit=3)
+    assert rows == [{"id": 0}, {"id": 1}, {"id": 2}] and not rows.truncated
+    with db.begin() as tx:
+        assert tx.query("SELECT * FROM t", limit=1).truncated
+    with db.snapshot() as snap:
+        assert len(snap.query("SELECT * FROM t", limit=2)) == 2
+        it = snap.query_iter("SELECT * FROM t", limit=4)
+        assert len(it) == 4
+        assert it.truncated
+        assert not snap.query_iter("SELECT * FROM t").truncated
+    db.close()
+
+
+def test_cancellation_token():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    token = CancellationToken()
+    assert not token.cancelled
+    db.execute("INSERT INTO t VALUES ($1)", [1], cancel=token)
+    assert db.query("SELECT * FROM t", cancel=token) == [{"id": 1}]
+
+    token.cancel()
+    assert token.cancelled
+    with pytest.raises(QueryCancelled):
+        db.query("SELECT * FROM t", cancel=token)
+    with pytest.raises(QueryCancelled):
+        db.query_one("SELECT * FROM t", cancel=token)
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO t VALUES ($1)", [2], cancel=token)
+    with pytest.raises(QueryCancelled):
+        db.exec("INSERT INTO t VALUES (3); INSERT INTO t VALUES (4)",
+                transaction=True, cancel=token)
+    with db.begin() as tx:
+        with pytest.raises(QueryCancelled):
+            tx.query("SELECT * FROM t", cancel=token)
+    assert db.query("SELECT id FROM t") == [{"id": 1}]
+    db.close()
+
+
+def test_cancellation_token_does_not_interrupt_running_write():
+    import threading
+
+    db = Database.open("memory://cancel_running")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, i % 7] for i in range(500)])
+    token = CancellationToken()
+    threading.Timer(0.05, token.cancel).start()
+    # The engine runs the whole update, cancelled token or not
+    updated = db.execute(
+        "UPDATE t SET v = 1 + (SELECT COUNT(*) FROM t b WHERE b.v = t.v AND b.id > t.id)",
+        cancel=token,
+    )
+    assert token.cancelled
+    assert updated == 500
+    assert db.query_one("SELECT COUNT(*) AS n FROM t WHERE v >= 1") == {"n": 500}
+    with pytest.raises(QueryCancelled):
+        db.query("SELECT * FROM t", cancel=token)
+    db.close()
+
+
+def test_time_limit():
+    import time
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    stmt = db.prepare("INSERT INTO t VALUES ($1)")
+    with db.time_limit(10.0) as limit:
+        stmt.execute([1])
+        assert db.query("SELECT * FROM t") == [{"id": 1}]
+        assert 0.0 < limit.remaining <= 10.0
+        with db.time_limit(60.0) as inner:
+            assert inner.remaining <= 10.0
+
+    with db.time_limit(0.01):
+        time.sleep(0.05)
+        with pytest.raises(QueryTimeout):
+            db.query("SELECT * FROM t")
+        with pytest.raises(QueryCancelled):
+            stmt.execute([2])
+        with db.begin() as tx:
+            with pytest.raises(QueryTimeout):
+                tx.execute("INSERT INTO t VALUES ($1)", [3])
+    assert db.query("SELECT id FROM t") == [{"id": 1}]
+
+    with pytest.raises(ValueError):
+        db.time_limit(0)
+    db.close()
+
+
+def test_time_limit_stops_running_query():
+    import time
+
+    db = Database.open("memory://time_limit")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[i, i % 7] for i in range(1500)])
+    # Runs for seconds: a subquery over the table for each of its rows
+    slow = """
+        SELECT COUNT(*) AS n FROM t a
+        WHERE (SELECT COUNT(*) FROM t b WHERE b.v = a.v AND b.id > a.id) >= :min
+    """
+    for params in ({"min": 0}, None):
+        sql = slow if params else slow.replace(":min", "0")
+        start = time.monotonic()
+        with pytest.raises(QueryTimeout):
+            with db.time_limit(0.2):
+                db.query(sql, params)
+        assert time.monotonic() - start < 1.0
+    stmt = db.prepare(slow.replace(":min", "$1"))
+    start = time.monotonic()
+    with pytest.raises(QueryTimeout):
+        with db.time_limit(0.2):
+            with db.begin() as tx:
+                tx.query_prepared(stmt, [0])
+    assert time.monotonic() - start < 1.0
+    db.close()
@@ -0,0 +1,90 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Maintenance API tests (analyze, optimize, integrity_check, table_stats)."""
+
+import pytest
+from stoolap import Database, StoolapError
+
+
+@pytest.fixture
+def db():
+    d = Database.open(":memory:")
+    d.exec("""
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+        CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER);
+        CREATE INDEX idx_orders_user ON orders(user_id);
+    """)
+    d.execute_batch(
+        "INSERT INTO orders VALUES ($1, $2)",
+        [[i, i % 10] for i in range(100)],
+    )
+    yield d
+    d.close()
+
+
+def test_analyze_all_tables(db):
+    db.analyze()
+    rows = db.query("SELECT COUNT(*) AS cnt FROM orders WHERE user_id = $1", [3])
+    assert rows[0]["cnt"] == 10
+
+
+def test_analyze_single_table(db):
+    db.analyze("orders")
+
+
+def test_analyze_missing_table(db):
+    # The engine's ANALYZE only logs a warning for a missing table
+    with pytest.raises(StoolapError) as excinfo:
+        db.analyze("nonexistent")
+    assert excinfo.value.category == "missing_table"
+    assert excinfo.value.code == "TableNotFound"
+    with pytest.raises(StoolapError, match="orders_typo"):
+        db.analyze("orders_typo")
+    db.analyze("ORDERS")
+
+
+def test_optimize(db):
+    db.execute("DELETE FROM orders WHERE id < $1", [50])
+    db.optimize()
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM orders")["cnt"] == 50
+
+
+def test_integrity_check_healthy(db):
+    assert db.integrity_check() == []
+
+
+def test_integrity_check_empty_database():
+    d = Database.open(":memory:")
+    assert d.integrity_check() == []
+    d.close()
+
+
+def test_table_stats(db):
+    stats = db.table_stats("orders")
+    assert stats["table"] == "orders"
+    assert stats["row_count"] == 100
+    assert stats["size_bytes"] is None
+    assert set(stats["columns"]) == {"id", "user_id"}
+    for column in stats["columns"].values():
+        assert set(column) == {"min", "max", "ndv", "null_count"}
+
+    db.analyze("orders")
+    ndv = db.table_stats("orders")["columns"]["user_id"]["ndv"]
+    assert ndv is None or int(ndv) == 10
+
+
+def test_table_stats_missing_table(db):
+    with pytest.raises(StoolapError):
+        db.table_stats("nonexistent")
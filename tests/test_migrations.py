@@ -0,0 +1,176 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Schema migration and schema diff tests."""
+
+import pytest
+from stoolap import Database, Migrations, StoolapError, schema_diff
+
+
+def write(directory, name, sql):
+    (directory / name).write_text(sql)
+
+
+def test_apply_and_rollback(tmp_path):
+    write(tmp_path, "0001_create_users.sql", "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    write(tmp_path, "0001_create_users.down.sql", "DROP TABLE users")
+    write(
+        tmp_path,
+        "0002_seed.sql",
+        "INSERT INTO users VALUES (1, 'a'); INSERT INTO users VALUES (2, 'b');",
+    )
+    write(tmp_path, "0002_seed.down.sql", "DELETE FROM users")
+    write(tmp_path, "notes.txt", "ignored")
+
+    db = Database.open(":memory:")
+    migrations = Migrations(db, tmp_path)
+    assert migrations.current_version is None
+    assert migrations.pending() == [(1, "create_users"), (2, "seed")]
+
+    assert migrations.apply() == [1, 2]
+    assert migrations.apply() == []
+    assert migrations.current_version == 2
+    assert db.query_one("SELECT COUNT(*) AS n FROM users")["n"] == 2
+    assert [m["name"] for m in migrations.applied()] == ["create_users", "seed"]
+
+    assert migrations.rollback(1) == [2]
+    assert db.query_one("SELECT COUNT(*) AS n FROM users")["n"] == 0
+    assert migrations.pending() == [(2, "seed")]
+    assert migrations.rollback(0) == [1]
+    assert "users" not in db.tables()
+    db.close()
+
+
+def test_failed_migration_rolls_back(tmp_path):
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    migrations = Migrations(db, table="schema_versions")
+    migrations.add(1, "INSERT INTO t VALUES (1)")
+    migrations.add(2, "INSERT INTO t VALUES (2); INSERT INTO t VALUES (1)")
+    migrations.add(3, "INSERT INTO t VALUES (3)")
+
+    with pytest.raises(StoolapError):
+        migrations.apply()
+    assert migrations.current_version == 1
+    assert [r["id"] for r in db.query("SELECT id FROM t ORDER BY id")] == [1]
+    assert "schema_versions" in db.tables()
+
+    with pytest.raises(StoolapError):
+        migrations.rollback(0)  # migration 1 has no down step
+    db.close()
+
+
+def test_callable_migrations():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER)")
+    migrations = Migrations(db)
+
+    def up(tx):
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [1, 10])
+
+    def down(tx):
+        tx.execute("DELETE FROM t WHERE id = $1", [1])
+
+    migrations.add(5, up, down, name="seed")
+    migrations.add(7, "UPDATE t SET n = n + 1")
+    with pytest.raises(ValueError):
+        migrations.add(5, "SELECT 1")
+    with pytest.raises(TypeError):
+        migrations.add(9, 42)
+
+    assert migrations.apply(target=5) == [5]
+    assert migrations.pending() == [(7, "migration_7")]
+    assert migrations.apply() == [7]
+    assert db.query_one("SELECT n FROM t")["n"] == 11
+
+    with pytest.raises(StoolapError):
+        migrations.rollback(5)  # 7 has no down step
+    db.close()
+
+
+def test_schema_diff():
+    # Every ":memory:" handle shares one engine, so the two need names
+    reference = Database.open("memory://reference")
+    deployed = Database.open("memory://deployed")
+    reference.exec(
+        """
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT);
+        CREATE INDEX idx_users_name ON users (name);
+        CREATE TABLE audit_log (id INTEGER PRIMARY KEY, entry TEXT);
+        """
+    )
+    deployed.exec(
+        """
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name INTEGER, legacy TEXT);
+        CREATE TABLE scratch (id INTEGER PRIMARY KEY);
+        """
+    )
+
+    diff = schema_diff(reference, deployed)
+    assert not diff["equal"]
+    assert "ddl" not in diff
+    assert diff["missing_tables"] == ["audit_log"]
+    assert diff["extra_tables"] == ["scratch"]
+    changes = {(c["column"], c["change"]) for c in diff["columns"]}
+    assert changes == {("email", "missing"), ("name", "changed"), ("legacy", "extra")}
+    assert [(i["index"], i["change"]) for i in diff["indexes"]] == [("idx_users_name", "missing")]
+
+    # Applying the generated DDL reconciles the schemas
+    diff = reference.diff_schema(deployed, ddl=True)
+    for stmt in diff["ddl"]:
+        deployed.execute(stmt)
+    assert schema_diff(reference, deployed)["equal"]
+    assert reference.diff_schema(reference)["equal"]
+    reference.close()
+    deployed.close()
+
+
+def test_schema_diff_ddl_creates_missing_tables():
+    reference = Database.open("memory://reference_tables")
+    deployed = Database.open("memory://deployed_tables")
+    reference.exec(
+        """
+        CREATE TABLE teams (id INTEGER PRIMARY KEY AUTO_INCREMENT, name TEXT UNIQUE NOT NULL);
+        CREATE TABLE members (
+            id INTEGER PRIMARY KEY,
+            team_id INTEGER REFERENCES teams(id),
+            login TEXT,
+            email TEXT UNIQUE,
+            score FLOAT DEFAULT 0.0 CHECK (score >= 0),
+            embedding VECTOR(3)
+        );
+        CREATE UNIQUE INDEX idx_members_login ON members (login);
+        CREATE INDEX idx_members_team_score ON members (team_id, score);
+        CREATE INDEX idx_members_embedding ON members (embedding)
+            USING HNSW WITH (metric = 'cosine', m = 12, ef_search = 50);
+        """
+    )
+
+    diff = schema_diff(reference, deployed, ddl=True)
+    assert diff["missing_tables"] == ["members", "teams"]
+    # Referenced tables are created first
+    assert diff["ddl"][0].startswith('CREATE TABLE "teams"')
+    for stmt in diff["ddl"]:
+        deployed.execute(stmt)
+    assert schema_diff(reference, deployed)["equal"]
+    (column,) = deployed.vector_columns("members")
+    assert (column["dimensions"], column["metric"]) == (3, "cosine")
+    info = deployed.vector_index_info("members", "embedding")
+    assert info == reference.vector_index_info("members", "embedding")
+    assert (info["m"], info["ef_search"]) == (12, 50)
+    deployed.execute("INSERT INTO teams (name) VALUES ('a')")
+    with pytest.raises(StoolapError):
+        deployed.execute("INSERT INTO teams (name) VALUES ('a')")
+    reference.close()
+    deployed.close()
@@ -0,0 +1,406 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Monitoring tests (stats, slow query handler, tracing, listeners, audit)."""
+
+import logging
+
+import pytest
+from stoolap import Database, StoolapError
+
+
+@pytest.fixture
+def db():
+    d = Database.open(":memory:")
+    d.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+    yield d
+    d.close()
+
+
+def test_stats_initial_keys(db):
+    stats = db.stats()
+    for key in (
+        "queries",
+        "statements",
+        "rows_read",
+        "rows_written",
+        "transactions",
+        "commits",
+        "rollbacks",
+        "active_transactions",
+        "wal_bytes",
+        "query_cache_hits",
+        "query_cache_misses",
+        "query_cache_hit_ratio",
+    ):
+        assert key in stats
+    assert stats["wal_bytes"] == 0
+
+
+def test_stats_query_cache_hit_ratio(db):
+    before = db.stats()
+    for _ in range(4):
+        db.query("SELECT * FROM t WHERE val > 'a'")
+    after = db.stats()
+    hits = after["query_cache_hits"] - before["query_cache_hits"]
+    misses = after["query_cache_misses"] - before["query_cache_misses"]
+    assert hits + misses == 4
+    assert hits >= 1
+    lookups = after["query_cache_hits"] + after["query_cache_misses"]
+    assert after["query_cache_hit_ratio"] == after["query_cache_hits"] / lookups
+
+
+def test_stats_counts_reads_and_writes(db):
+    before = db.stats()
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[2, "b"], [3, "c"]])
+    db.query("SELECT * FROM t")
+    db.query_one("SELECT * FROM t WHERE id = $1", [1])
+
+    after = db.stats()
+    assert after["statements"] - before["statements"] == 3
+    assert after["rows_written"] - before["rows_written"] == 3
+    assert after["queries"] - before["queries"] == 2
+    assert after["rows_read"] - before["rows_read"] == 4
+
+
+def test_ping(db, tmp_path):
+    before = db.stats()
+    assert db.ping() >= 0.0
+    assert db.stats()["queries"] == before["queries"]
+
+    d = Database.open(f"file://{tmp_path / 'ping.db'}")
+    assert d.ping() >= 0.0
+    d.close()
+    with pytest.raises(StoolapError):
+        d.ping()
+
+
+def test_stats_transactions(db):
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+        assert db.stats()["active_transactions"] == 1
+
+    tx = db.begin()
+    tx.rollback()
+
+    stats = db.stats()
+    assert stats["transactions"] == 2
+    assert stats["commits"] == 1
+    assert stats["rollbacks"] == 1
+    assert stats["active_transactions"] == 0
+
+
+def test_stats_prepared_statement(db):
+    stmt = db.prepare("INSERT INTO t VALUES ($1, $2)")
+    stmt.execute([1, "a"])
+    stmt.execute_batch([[2, "b"], [3, "c"]])
+    assert db.stats()["rows_written"] == 3
+
+
+def test_slow_query_handler_zero_threshold(db):
+    calls = []
+    db.set_slow_query_handler(0, lambda sql, ms, rows: calls.append((sql, ms, rows)))
+
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.query("SELECT * FROM t")
+
+    assert [c[0] for c in calls] == [
+        "INSERT INTO t VALUES ($1, $2)",
+        "SELECT * FROM t",
+    ]
+    assert all(c[1] >= 0 for c in calls)
+    assert calls[0][2] == 1
+    assert calls[1][2] == 1
+
+
+def test_slow_query_handler_threshold_not_reached(db):
+    calls = []
+    db.set_slow_query_handler(60_000, lambda *args: calls.append(args))
+    db.query("SELECT * FROM t")
+    assert calls == []
+
+
+def test_slow_query_handler_removed(db):
+    calls = []
+    db.set_slow_query_handler(0, lambda *args: calls.append(args))
+    db.set_slow_query_handler(0, None)
+    db.query("SELECT * FROM t")
+    assert calls == []
+
+
+def test_slow_query_handler_transaction_and_prepared(db):
+    calls = []
+    db.set_slow_query_handler(0, lambda sql, ms, rows: calls.append(sql))
+
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    stmt = db.prepare("SELECT * FROM t WHERE id = $1")
+    stmt.query_one([1])
+
+    assert calls == [
+        "INSERT INTO t VALUES ($1, $2)",
+        "SELECT * FROM t WHERE id = $1",
+    ]
+
+
+def test_slow_query_handler_failed_statement(db):
+    calls = []
+    db.set_slow_query_handler(0, lambda sql, ms, rows: calls.append(rows))
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO missing VALUES ($1)", [1])
+    assert calls == [None]
+
+
+def test_slow_query_handler_exception_does_not_fail_query(db):
+    def boom(*args):
+        raise ValueError("handler failed")
+
+    db.set_slow_query_handler(0, boom)
+    assert db.query("SELECT * FROM t") == []
+
+
+def test_slow_query_handler_validation(db):
+    with pytest.raises(TypeError):
+        db.set_slow_query_handler(10, "not callable")
+    with pytest.raises(ValueError):
+        db.set_slow_query_handler(-1, lambda *args: None)
+
+
+def test_trace_callable(db):
+    calls = []
+    db.set_trace(lambda sql, ms, params: calls.append((sql, params)))
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.query("SELECT * FROM t")
+    assert calls == [
+        ("INSERT INTO t VALUES ($1, $2)", None),
+        ("SELECT * FROM t", None),
+    ]
+
+
+def test_trace_include_params(db):
+    calls = []
+    db.set_trace(lambda sql, ms, params: calls.append(params), include_params=True)
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a" * 100])
+    db.query_one("SELECT * FROM t WHERE id = :id", {"id": 1})
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[2, "b"], [3, None]])
+
+    assert calls[0].startswith("[1, 'aaaa")
+    assert "(100 chars)" in calls[0]
+    assert calls[1] == "{id: 1}"
+    assert calls[2] == "<2 parameter sets>"
+
+
+def test_trace_logger(db, caplog):
+    logger = logging.getLogger("stoolap.test.trace")
+    db.set_trace(logger, include_params=True)
+    with caplog.at_level(logging.DEBUG, logger="stoolap.test.trace"):
+        db.query("SELECT * FROM t WHERE id = $1", [7])
+    assert len(caplog.records) == 1
+    message = caplog.records[0].getMessage()
+    assert message.startswith("SELECT * FROM t WHERE id = $1 (")
+    assert message.endswith("params=[7]")
+
+
+def test_trace_disabled(db):
+    calls = []
+    db.set_trace(lambda *args: calls.append(args))
+    db.set_trace(None)
+    db.query("SELECT * FROM t")
+    assert calls == []
+
+
+def test_trace_invalid_target(db):
+    with pytest.raises(TypeError):
+        db.set_trace(42)
+
+
+def test_listener_start_and_end(db):
+    events = []
+
+    def on_start(event):
+        events.append(("start", dict(event)))
+        return "ctx"
+
+    def on_end(event, context):
+        events.append(("end", dict(event), context))
+
+    db.add_listener(on_start, on_end)
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+
+    assert events[0] == (
+        "start",
+        {"sql": "INSERT INTO t VALUES ($1, $2)", "param_count": 2, "batch_size": None},
+    )
+    kind, event, context = events[1]
+    assert kind == "end"
+    assert context == "ctx"
+    assert event["rows"] == 1
+    assert event["error"] is None
+    assert event["duration_ms"] >= 0
+
+
+def test_listener_error(db):
+    ended = []
+    db.add_listener(on_end=lambda event, ctx: ended.append(event))
+    with pytest.raises(StoolapError):
+        db.query("SELECT * FROM missing")
+    assert ended[0]["rows"] is None
+    assert isinstance(ended[0]["error"], StoolapError)
+
+
+def test_listener_batch(db):
+    started = []
+    db.add_listener(on_start=lambda event: started.append(event))
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[1, "a"], [2, "b"], [3, "c"]])
+    assert started[0]["param_count"] == 2
+    assert started[0]["batch_size"] == 3
+
+
+def test_remove_listener(db):
+    calls = []
+    listener_id = db.add_listener(on_start=lambda event: calls.append(event))
+    assert db.remove_listener(listener_id) is True
+    assert db.remove_listener(listener_id) is False
+    db.query("SELECT * FROM t")
+    assert calls == []
+
+
+def test_listener_validation(db):
+    with pytest.raises(ValueError):
+        db.add_listener()
+    with pytest.raises(TypeError):
+        db.add_listener(on_start=1)
+
+
+def test_audit_hook_all_entry_points(db):
+    seen = []
+    db.set_audit_hook(lambda event: seen.append(event["sql"]))
+
+    db.exec("INSERT INTO t VALUES (1, 'a'); INSERT INTO t VALUES (2, 'b')")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2)", [[3, "c"], [4, "d"]])
+    with db.begin() as tx:
+        tx.query("SELECT * FROM t")
+    db.prepare("SELECT * FROM t WHERE id = $1").query_one([1])
+    db.analyze("t")
+
+    assert seen == [
+        "INSERT INTO t VALUES (1, 'a')",
+        "INSERT INTO t VALUES (2, 'b')",
+        "INSERT INTO t VALUES ($1, $2)",
+        "SELECT * FROM t",
+        "SELECT * FROM t WHERE id = $1",
+        'ANALYZE "t"',
+    ]
+
+
+def test_audit_hook_blocks_statement(db):
+    def deny_deletes(event):
+        if event["sql"].startswith("DELETE"):
+            raise PermissionError("deletes are not allowed")
+
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.set_audit_hook(deny_deletes)
+    with pytest.raises(PermissionError):
+        db.execute("DELETE FROM t WHERE id = $1", [1])
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 1
+
+
+def test_audit_hook_removed(db):
+    seen = []
+    db.set_audit_hook(lambda event: seen.append(event))
+    db.set_audit_hook(None)
+    db.query("SELECT * FROM t")
+    assert seen == []
+
+
+def test_commit_and_rollback_hooks(db):
+    events = []
+    db.set_commit_hook(lambda: events.append("commit"))
+    db.set_rollback_hook(lambda: events.append("rollback"))
+
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [2, "b"])
+    tx = db.begin()
+    tx.execute("INSERT INTO t VALUES ($1, $2)", [3, "c"])
+    tx.rollback()
+    with pytest.raises(ValueError):
+        with db.begin() as tx:
+            raise ValueError("boom")
+    with pytest.raises(StoolapError):
+        db.execute("INSERT INTO t VALUES ($1, $2)", [1, "duplicate"])
+    db.query("SELECT * FROM t")
+
+    assert events == ["commit", "commit", "rollback", "rollback", "rollback"]
+
+
+def test_commit_hook_removed(db):
+    events = []
+    db.set_commit_hook(lambda: events.append("commit"))
+    db.set_commit_hook(None)
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    assert events == []
+
+
+def test_watch_autocommit_and_transactions(db):
+    events = []
+    watch_id = db.watch("t", lambda op, table, key: events.append((op, table, key)))
+
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.execute("UPDATE t SET val = $1 WHERE id = $2", ["b", 1])
+    db.execute("DELETE FROM t WHERE id = $1", [99])  # no rows changed
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [2, "c"])
+        assert events == [("insert", "t", 1), ("update", "t", 1)]
+    tx = db.begin()
+    tx.execute("DELETE FROM t WHERE id = $1", [2])
+    tx.rollback()
+
+    assert events == [
+        ("insert", "t", 1),
+        ("update", "t", 1),
+        ("insert", "t", 2),
+    ]
+    assert db.unwatch(watch_id)
+    assert not db.unwatch(watch_id)
+    db.execute("DELETE FROM t")
+    assert len(events) == 3
+
+
+def test_watch_key(db):
+    events = []
+    db.watch("t", lambda op, table, key: events.append((op, key)))
+
+    db.execute("INSERT INTO t (val, id) VALUES ('a', 3)")
+    db.execute("UPDATE t SET val = :val WHERE val = 'a' AND id = :id", {"val": "b", "id": 3})
+    db.execute("INSERT INTO t VALUES ($1, $2), ($3, $4)", [4, "c", 5, "d"])
+    db.execute("UPDATE t SET val = 'e' WHERE id > 3")
+    db.execute("DELETE FROM t WHERE 3 = id")
+
+    assert events == [
+        ("insert", 3),
+        ("update", 3),
+        ("insert", None),
+        ("update", None),
+        ("delete", 3),
+    ]
+
+
+def test_watch_other_table_ignored(db):
+    db.exec("CREATE TABLE other (id INTEGER PRIMARY KEY)")
+    events = []
+    db.watch("t", lambda *args: events.append(args))
+    db.execute("INSERT INTO other VALUES ($1)", [1])
+    assert events == []
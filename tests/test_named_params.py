@@ -15,7 +15,7 @@
 """Named parameter tests across all APIs."""
 
 import pytest
-from stoolap import Database, AsyncDatabase
+from stoolap import Database, AsyncDatabase, StoolapError
 
 
 @pytest.fixture
@@ -247,3 +247,76 @@ def test_named_params_with_prefix_colon():
     row = db.query_one("SELECT val FROM t WHERE id = :id", {":id": 1})
     assert row["val"] == "hello"
     db.close()
+
+
+# --- qmark placeholders ---
+
+
+def test_qmark_placeholders(db):
+    db.execute("INSERT INTO users VALUES (?, ?, ?)", [3, "Carol", "carol@example.com"])
+    assert db.query_one("SELECT name FROM users WHERE id = ?", [3])["name"] == "Carol"
+    # '?' inside literals, quoted identifiers and comments is left alone
+    row = db.query_one("SELECT '?' AS q, name FROM users WHERE id = ? -- ?", (3,))
+    assert row == {"q": "?", "name": "Carol"}
+    # The engine rejects block comments inside a statement, so check those
+    # through mogrify(), which numbers placeholders the same way
+    assert db.mogrify('SELECT ?, "?" /* ? */ -- ?', [1]) == 'SELECT 1, "?" /* ? */ -- ?'
+    assert db.mogrify("SELECT ?, ?", [1, "a"]) == "SELECT 1, 'a'"
+
+    stmt = db.prepare("SELECT name FROM users WHERE id = ?")
+    assert stmt.query_one([1])["name"] == "Alice"
+
+    with db.begin() as tx:
+        tx.execute_batch("INSERT INTO users VALUES (?, ?, ?)", [[4, "Dan", None], [5, "Eve", None]])
+        assert len(tx.query("SELECT id FROM users WHERE id > ?", [3])) == 2
+    with db.snapshot() as snap:
+        assert snap.query_one("SELECT COUNT(*) AS n FROM users WHERE id >= ?", [4])["n"] == 2
+
+
+# --- pyformat placeholders ---
+
+
+def test_pyformat_placeholders():
+    db = Database.open(":memory:", paramstyle="pyformat")
+    assert db.paramstyle == "pyformat"
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+    db.execute("INSERT INTO t VALUES (%s, %s)", [1, "100%"])
+    db.execute("INSERT INTO t VALUES (%(id)s, %(name)s)", {"id": 2, "name": "b"})
+
+    assert db.query_one("SELECT name FROM t WHERE name LIKE '100%'")["name"] == "100%"
+    assert db.query_one("SELECT id %% 2 AS r FROM t WHERE id = %s", [1])["r"] == 1
+    with db.begin() as tx:
+        assert tx.query_one("SELECT name FROM t WHERE id = %(id)s", {"id": 2})["name"] == "b"
+
+    db.paramstyle = "numeric_dollar"
+    assert db.query_one("SELECT name FROM t WHERE id = $1", [2])["name"] == "b"
+    with pytest.raises(ValueError):
+        db.paramstyle = "format"
+    db.close()
+
+
+# --- session variables ---
+
+
+def test_session_variables():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, tenant INTEGER, name TEXT)")
+    db.execute_batch("INSERT INTO t VALUES ($1, $2, $3)", [[1, 1, "a"], [2, 2, "b"], [3, 2, "c"]])
+
+    db.set_var("Tenant", 2)
+    assert db.get_var("tenant") == 2
+    sql = "SELECT id FROM t WHERE tenant = current_setting('tenant') ORDER BY id"
+    assert [r["id"] for r in db.query(sql)] == [2, 3]
+    with db.begin() as tx:
+        assert len(tx.query(sql)) == 2
+    db.set_var("name", "it's")
+    assert db.query_one("SELECT current_setting('name') AS v")["v"] == "it's"
+    # Text that only mentions the function is left alone
+    assert db.query_one("SELECT 'current_setting(''x'')' AS v")["v"] == "current_setting('x')"
+
+    db.set_var("tenant", None)
+    assert db.get_var("tenant") is None
+    with pytest.raises(StoolapError, match="tenant"):
+        db.query(sql)
+    assert db.query_one("SELECT current_setting('tenant', true) AS v")["v"] is None
+    db.close()
@@ -12,14 +12,14 @@
 # See the License for the specific language governing permissions and
 # limitations under the License.
 
-"""Persistence tests (file-based database, crash recovery)."""
+"""Persistence tests (file-based database, crash recovery, reopening)."""
 
 import os
 import shutil
 import tempfile
 
 import pytest
-from stoolap import Database
+from stoolap import Database, StoolapError
 
 
 @pytest.fixture
@@ -220,6 +220,88 @@ def test_file_persistence_named_params(db_dir):
     db2.close()
 
 
+def test_journal_mode(db_dir):
+    """durability= and set_journal_mode() control how commits are synced."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(path, durability="full")
+    assert db.journal_mode == "full"
+    assert "sync_mode=full" in db.dsn
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.set_journal_mode("none")
+    assert db.journal_mode == "none"
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    db.set_journal_mode("normal")
+    with pytest.raises(ValueError):
+        db.set_journal_mode("wal")
+    db.close()
+
+    db = Database.open(f"file://{path}?sync_mode=none")
+    assert db.journal_mode == "none"
+    assert db.query_one("SELECT COUNT(*) AS n FROM t")["n"] == 1
+    db.close()
+    with pytest.raises(ValueError):
+        Database.open(f"file://{path}?sync_mode=none", durability="full")
+
+    mem = Database.open(":memory:")
+    assert mem.journal_mode == "normal"
+    mem.set_journal_mode("full")
+    assert mem.journal_mode == "full"
+    mem.close()
+
+
+def test_synchronous(db_dir):
+    """synchronous can be lowered for a bulk load and restored."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(path)
+    assert db.synchronous == "normal"
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.synchronous = "off"
+    assert db.synchronous == "off"
+    assert db.journal_mode == "none"
+    db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(100)])
+    db.synchronous = "full"
+    assert db.journal_mode == "full"
+    with pytest.raises(ValueError):
+        db.synchronous = "none"
+    db.close()
+
+    db = Database.open(path)
+    assert db.query_one("SELECT COUNT(*) AS n FROM t")["n"] == 100
+    db.close()
+
+
+def test_synchronous_change_reopens_engine(db_dir):
+    """The engine takes its sync mode only when opened, so a change reopens it."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    stmt = db.prepare("SELECT COUNT(*) AS n FROM t")
+
+    snap = db.snapshot()
+    db.synchronous = "normal"  # unchanged: no reopen
+    with pytest.raises(StoolapError, match="snapshot"):
+        db.synchronous = "off"
+    assert db.synchronous == "normal"
+    snap.close()
+
+    tx = db.begin()
+    with pytest.raises(StoolapError, match="transaction"):
+        db.set_journal_mode("full")
+    assert db.journal_mode == "normal"
+    tx.rollback()
+
+    db.synchronous = "off"
+    assert stmt.query_one()["n"] == 1
+    db.close()
+
+    mem = Database.open(":memory:")
+    with mem.snapshot():
+        mem.synchronous = "off"
+    assert mem.synchronous == "off"
+    mem.close()
+
+
 def test_relative_path_persistence(db_dir):
     """Relative paths work for file-based databases."""
     # We can't easily test true relative paths in a fixture,
@@ -235,3 +317,481 @@ def test_relative_path_persistence(db_dir):
     rows = db2.query("SELECT * FROM t")
     assert len(rows) == 1
     db2.close()
+
+
+def test_checkpoint_and_flush(db_dir):
+    """checkpoint() and flush() persist data on demand."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "durable"])
+    db.checkpoint()
+    db.execute("INSERT INTO t VALUES ($1, $2)", [2, "flushed"])
+    db.flush()
+    db.close()
+
+    db2 = Database.open(path)
+    rows = db2.query("SELECT val FROM t ORDER BY id")
+    assert [r["val"] for r in rows] == ["durable", "flushed"]
+    db2.close()
+
+
+def test_checkpoint_memory_noop():
+    """checkpoint() on an in-memory database is a no-op."""
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.checkpoint()
+    db.flush()
+    db.close()
+
+
+def test_checkpoint_interval(db_dir):
+    """set_checkpoint_interval() and the WAL size trigger."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(f"file://{path}?checkpoint_interval=30")
+    status = db.checkpoint_status()
+    assert status["interval_seconds"] == 30
+    assert status["wal_bytes_threshold"] is None
+    assert status["checkpoints"] == 0
+    assert status["seconds_since_checkpoint"] is None
+
+    db.set_checkpoint_interval(120, wal_bytes=1)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "x" * 1000])
+    with db.begin() as tx:
+        tx.execute("INSERT INTO t VALUES ($1, $2)", [2, "y" * 1000])
+    status = db.checkpoint_status()
+    assert status["interval_seconds"] == 120
+    assert status["wal_bytes_threshold"] == 1
+    assert status["checkpoints"] >= 1
+    assert status["seconds_since_checkpoint"] >= 0
+
+    db.set_checkpoint_interval(wal_bytes=0)
+    assert db.checkpoint_status()["wal_bytes_threshold"] is None
+    assert db.checkpoint_status()["interval_seconds"] == 120
+    with pytest.raises(ValueError):
+        db.set_checkpoint_interval(0)
+    db.close()
+
+
+def test_settings(db_dir):
+    """get_setting() and set_setting() by name, backed by the dedicated methods."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(f"file://{path}")
+    db.set_setting("journal_mode", "full")
+    assert db.journal_mode == "full"
+    assert db.get_setting("synchronous") == "full"
+    db.set_setting("checkpoint_interval", 90)
+    db.set_setting("checkpoint_wal_bytes", 1024)
+    assert db.get_setting("checkpoint_interval") == 90
+    assert db.get_setting("checkpoint_wal_bytes") == 1024
+    db.set_setting("checkpoint_wal_bytes", None)
+    assert db.get_setting("checkpoint_wal_bytes") is None
+    db.set_setting("compact_threshold", 8)
+    assert db.get_setting("compact_threshold") == 8
+    assert db.get_setting("auto_compaction") is True
+    db.set_setting("strict_types", True)
+    assert db.strict_types
+    db.set_setting("paramstyle", "pyformat")
+    assert db.get_setting("paramstyle") == "pyformat"
+
+    with pytest.raises(ValueError, match="unknown setting"):
+        db.get_setting("sort_memory")
+    with pytest.raises(ValueError):
+        db.set_setting("journal_mode", "sometimes")
+    db.close()
+
+
+def test_dsn_options(db_dir):
+    """Options in the DSN are validated, and the binding's own applied."""
+    path = os.path.join(db_dir, "testdb")
+    with pytest.raises(ValueError, match="sync_mod"):
+        Database.open(f"file://{path}?sync_mod=full")
+    with pytest.raises(ValueError, match="checkpoint_interval"):
+        Database.open(f"file://{path}?checkpoint_interval=soon")
+    with pytest.raises(ValueError, match="read_only"):
+        Database.open(f"file://{path}?read_only=yes")
+
+    db = Database.open(f"file://{path}?sync_mode=full")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    db.close()
+
+    db = Database.open(f"file://{path}?read_only=true&cache_mb=1")
+    assert db.dsn == f"file://{path}?read_only=true&cache_mb=1"
+    assert db.query("SELECT * FROM t") == [{"id": 1}]
+    assert db.query("SELECT * FROM t") == [{"id": 1}]
+    assert db.stats()["cache_hits"] == 1
+    with pytest.raises(StoolapError, match="read-only"):
+        db.execute("INSERT INTO t VALUES ($1)", [2])
+    with pytest.raises(StoolapError, match="read-only"):
+        db.query("DELETE FROM t RETURNING id")
+    db.close()
+
+
+def test_open_from_env(db_dir, monkeypatch):
+    """open_from_env() assembles the DSN and options from the environment."""
+    path = os.path.join(db_dir, "testdb")
+    with pytest.raises(ValueError, match="APP_DSN"):
+        Database.open_from_env(prefix="APP_")
+
+    monkeypatch.setenv("APP_DSN", path)
+    monkeypatch.setenv("APP_SYNC_MODE", "full")
+    monkeypatch.setenv("APP_STRICT_TYPES", "1")
+    db = Database.open_from_env(prefix="APP_")
+    assert db.dsn == f"file://{path}?sync_mode=full"
+    assert db.journal_mode == "full"
+    assert db.strict_types is True
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.close()
+
+    monkeypatch.setenv("APP_READ_ONLY", "true")
+    db = Database.open_from_env(prefix="APP_")
+    with pytest.raises(StoolapError, match="read-only"):
+        db.execute("INSERT INTO t VALUES ($1)", [1])
+    db.close()
+
+    monkeypatch.setenv("APP_STRICT_TYPE", "1")
+    with pytest.raises(ValueError, match="APP_STRICT_TYPE"):
+        Database.open_from_env(prefix="APP_")
+
+
+def test_vacuum_after_delete(db_dir):
+    """vacuum() reclaims space and returns the bytes freed."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, payload TEXT)")
+    db.execute_batch(
+        "INSERT INTO t VALUES ($1, $2)",
+        [[i, "x" * 200] for i in range(500)],
+    )
+    db.checkpoint()
+    db.execute("DELETE FROM t WHERE id >= $1", [10])
+
+    reclaimed = db.vacuum()
+    assert isinstance(reclaimed, int)
+    assert reclaimed >= 0
+    assert db.compact("t") >= 0
+
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 10
+    db.close()
+
+
+def test_vacuum_memory():
+    """vacuum() on an in-memory database reports nothing reclaimed."""
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    db.execute("DELETE FROM t WHERE id = $1", [1])
+    assert db.vacuum() == 0
+    assert db.vacuum("t") == 0
+    db.close()
+
+
+def test_disk_usage(db_dir):
+    """disk_usage() reports the files of the database and the space left."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(path)
+    db.exec("CREATE TABLE a (id INTEGER PRIMARY KEY, payload TEXT)")
+    db.exec("CREATE TABLE b (id INTEGER PRIMARY KEY)")
+    db.execute_batch(
+        "INSERT INTO a VALUES ($1, $2)",
+        [[i, "x" * 200] for i in range(500)],
+    )
+    db.checkpoint()
+
+    usage = db.disk_usage()
+    assert usage["total_bytes"] > 0
+    assert 0 <= usage["wal_bytes"] <= usage["total_bytes"]
+    assert usage["free_bytes"] > 0
+    assert set(usage["tables"]) == {"a", "b"}
+    assert sum(size or 0 for size in usage["tables"].values()) <= usage["total_bytes"]
+    db.close()
+
+
+def test_disk_usage_memory():
+    """disk_usage() on an in-memory database reports no files."""
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    assert db.disk_usage() == {
+        "total_bytes": 0, "wal_bytes": 0, "free_bytes": None, "tables": {"t": 0},
+    }
+    db.close()
+
+
+def test_auto_compaction(db_dir):
+    """set_auto_compaction() is reported by compaction_status()."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(f"file://{path}?compact_threshold=6")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    status = db.compaction_status()
+    assert status["enabled"] is True
+    assert status["threshold"] == 6
+    assert set(status["volumes"]) == {"t"}
+
+    db.set_auto_compaction(False)
+    db.execute_batch("INSERT INTO t VALUES ($1)", [[i] for i in range(100)])
+    db.checkpoint()
+    status = db.compaction_status()
+    assert status["enabled"] is False
+    assert status["threshold"] == 6
+    assert all(table in status["volumes"] for table in status["pending"])
+
+    db.set_auto_compaction(True, threshold=2)
+    assert db.compaction_status()["threshold"] == 2
+    with pytest.raises(ValueError):
+        db.set_auto_compaction(True, threshold=0)
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 100
+    db.close()
+
+
+def test_integrity_check_after_reopen(db_dir):
+    """integrity_check() reports no problems for a cleanly reopened file."""
+    path = os.path.join(db_dir, "testdb")
+
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, val TEXT, kind INTEGER)")
+    db.exec("CREATE UNIQUE INDEX idx_t_val ON t(val)")
+    db.exec("CREATE INDEX idx_t_kind_val ON t(kind, val)")
+    db.execute_batch(
+        "INSERT INTO t VALUES ($1, $2, $3)",
+        [[i, f"v{i}", i % 3] for i in range(100)],
+    )
+    db.execute("DELETE FROM t WHERE id < $1", [20])
+    db.checkpoint()
+    db.execute("UPDATE t SET kind = $1 WHERE id < $2", [7, 40])
+    db.close()
+
+    db2 = Database.open(path)
+    assert db2.integrity_check() == []
+    db2.close()
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="requires os.fork")
+def test_inherited_file_database_rejected_after_fork(db_dir):
+    """A forked child cannot use the parent's file database handle."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    mem = Database.open(":memory:")
+    mem.exec("CREATE TABLE m (id INTEGER PRIMARY KEY)")
+
+    pid = os.fork()
+    if pid == 0:
+        code = 0
+        try:
+            db.query("SELECT * FROM t")
+            code = 1
+        except StoolapError as e:
+            if "fork" not in str(e):
+                code = 2
+        try:
+            mem.execute("INSERT INTO m VALUES ($1)", [1])
+        except Exception:
+            code = 3
+        os._exit(code)
+
+    _, status = os.waitpid(pid, 0)
+    assert os.waitstatus_to_exitcode(status) == 0
+
+    # The parent's handle is unaffected
+    db.execute("INSERT INTO t VALUES ($1)", [2])
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 2
+    assert mem.query("SELECT * FROM m") == []
+    db.close()
+
+
+def test_pickle_reopens_database(db_dir):
+    """A pickled file database unpickles into a fresh handle on the same DSN."""
+    import pickle
+
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(f"file://{path}?sync_mode=full")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    data = pickle.dumps(db)
+    db.close()
+
+    db2 = pickle.loads(data)
+    assert db2.dsn == f"file://{path}?sync_mode=full"
+    assert db2.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 1
+    db2.close()
+
+
+def test_pickle_keeps_open_options(db_dir):
+    """Every option given to open() survives a pickle round trip."""
+    import datetime
+    import pickle
+    import stoolap
+
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(path)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, at TIMESTAMP)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "2024-01-02 00:00:00"])
+    db.close()
+
+    options = [
+        dict(paramstyle="pyformat", strict_types=True, tz="Europe/Istanbul",
+             naive_timestamps=True, detect_types=stoolap.PARSE_DECLTYPES, threads=2),
+        dict(reject_naive=True, nanosecond_timestamps=True),
+        dict(midnight_as_date=True),
+    ]
+    for kwargs in options:
+        db = Database.open(path, **kwargs)
+        db.synchronous = "full"
+        expected = db.query_one("SELECT at FROM t")["at"]
+        db2 = pickle.loads(pickle.dumps(db))
+        assert db2.query_one("SELECT at FROM t")["at"] == expected
+        assert db2.paramstyle == kwargs.get("paramstyle", "numeric_dollar")
+        assert db2.strict_types == kwargs.get("strict_types", False)
+        assert db2.reject_naive == kwargs.get("reject_naive", db2.strict_types)
+        assert db2.tz == kwargs.get("tz")
+        assert db2.detect_types == kwargs.get("detect_types", 0)
+        assert db2.threads == kwargs.get("threads", os.cpu_count())
+        assert db2.journal_mode == "full"
+        db2.close()
+        db.close()
+
+    # The options change how the same value comes back
+    assert isinstance(expected, datetime.date) and not isinstance(expected, datetime.datetime)
+
+
+def test_pickle_memory_database_rejected():
+    import pickle
+
+    db = Database.open(":memory:")
+    with pytest.raises(TypeError):
+        pickle.dumps(db)
+    db.close()
+
+
+def test_reopen(db_dir):
+    """Reopening keeps data, prepared statements and settings."""
+    path = os.path.join(db_dir, "testdb")
+    db = Database.open(f"file://{path}")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    db.execute("INSERT INTO t VALUES ($1)", [1])
+    stmt = db.prepare("SELECT COUNT(*) AS cnt FROM t")
+    db.set_checkpoint_interval(120)
+
+    db.reopen()
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 1
+    assert stmt.query_one()["cnt"] == 1
+    assert db.checkpoint_status()["interval_seconds"] == 120
+
+    tx = db.begin()
+    with pytest.raises(StoolapError):
+        db.reopen()
+    tx.rollback()
+
+    snap = db.snapshot()
+    with pytest.raises(StoolapError, match="snapshot"):
+        db.reopen()
+    assert snap.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 1
+    snap.close()
+    with db.snapshot():
+        pass
+    db.reopen()
+    db.close()
+
+    mem = Database.open(":memory:")
+    with pytest.raises(StoolapError):
+        mem.reopen()
+    mem.close()
+
+
+def test_sync_from(db_dir):
+    """sync_from copies new and changed rows from a device database."""
+    central = Database.open(os.path.join(db_dir, "central"))
+    device = Database.open(":memory:")
+    for db in (central, device):
+        db.exec("CREATE TABLE readings (id INTEGER PRIMARY KEY, value FLOAT, note TEXT)")
+    central.execute_batch(
+        "INSERT INTO readings VALUES ($1, $2, $3)",
+        [[1, 1.0, "old"], [2, 2.0, "same"], [9, 9.0, "central only"]],
+    )
+    device.execute_batch(
+        "INSERT INTO readings VALUES ($1, $2, $3)",
+        [[1, 1.5, "new"], [2, 2.0, "same"], [3, 3.0, None]],
+    )
+
+    result = central.sync_from(device, mode="insert")
+    assert result == {"readings": {"inserted": 1, "updated": 0, "deleted": 0}}
+    assert central.query_one("SELECT note FROM readings WHERE id = 1")["note"] == "old"
+
+    result = central.sync_from(device, ["readings"])
+    assert result == {"readings": {"inserted": 0, "updated": 1, "deleted": 0}}
+    assert central.query_one("SELECT value, note FROM readings WHERE id = 1") == {
+        "value": 1.5,
+        "note": "new",
+    }
+
+    result = central.sync_from(device, mode="mirror")
+    assert result == {"readings": {"inserted": 0, "updated": 0, "deleted": 1}}
+    assert central.query("SELECT * FROM readings ORDER BY id") == device.query(
+        "SELECT * FROM readings ORDER BY id"
+    )
+
+    with pytest.raises(ValueError):
+        central.sync_from(device, mode="replace")
+    device.exec("CREATE TABLE log (entry TEXT)")
+    central.exec("CREATE TABLE log (entry TEXT)")
+    with pytest.raises(StoolapError):
+        central.sync_from(device, ["log"])
+    central.close()
+    device.close()
+
+
+def test_wal_shipping():
+    """Writes captured on a primary replay on a replica."""
+    primary = Database.open("memory://primary")
+    replica = Database.open("memory://replica")
+    with pytest.raises(StoolapError):
+        primary.wal_reader()
+
+    primary.enable_wal_capture()
+    primary.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    primary.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+    primary.execute_batch("INSERT INTO users VALUES ($1, $2)", [[2, "Bob"], [3, "O'Hara"]])
+    with primary.begin() as tx:
+        tx.execute("UPDATE users SET name = $1 WHERE id = $2", ["Alicia", 1])
+        tx.savepoint("sp")
+        tx.execute("DELETE FROM users WHERE id = $1", [2])
+        tx.rollback_to_savepoint("sp")
+    tx = primary.begin()
+    tx.execute("DELETE FROM users")
+    tx.rollback()
+    primary.query("SELECT * FROM users")
+
+    reader = primary.wal_reader()
+    records = list(reader)
+    assert len(records) == 4
+    assert all(isinstance(r, bytes) for r in records)
+    assert reader.lsn == 5
+    assert replica.apply_wal(records) == 4
+    assert replica.query("SELECT * FROM users ORDER BY id") == primary.query(
+        "SELECT * FROM users ORDER BY id"
+    )
+
+    # The reader picks up later commits
+    primary.execute("DELETE FROM users WHERE id = $1", [3])
+    assert replica.apply_wal(reader) == 5
+    assert replica.query_one("SELECT COUNT(*) AS n FROM users")["n"] == 2
+    assert replica.apply_wal([]) is None
+
+    # Dropped records can no longer be read
+    primary.enable_wal_capture(max_records=1)
+    primary.execute("INSERT INTO users VALUES ($1, $2)", [4, "Dan"])
+    primary.execute("INSERT INTO users VALUES ($1, $2)", [5, "Eve"])
+    with pytest.raises(StoolapError):
+        primary.wal_reader(from_lsn=1)
+    assert len(list(primary.wal_reader(from_lsn=2))) == 1
+    primary.disable_wal_capture()
+    primary.close()
+    replica.close()
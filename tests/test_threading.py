@@ -0,0 +1,157 @@
+# Copyright 2025 Stoolap Contributors
+#
+# Licensed under the Apache License, Version 2.0 (the "License");
+# you may not use this file except in compliance with the License.
+# You may obtain a copy of the License at
+#
+#     http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS,
+# WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+# See the License for the specific language governing permissions and
+# limitations under the License.
+
+"""Multi-threaded use, including free-threaded CPython builds."""
+
+import os
+import sys
+import sysconfig
+import time
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+import stoolap
+from stoolap import Database
+
+
+@pytest.fixture
+def db():
+    d = Database.open(":memory:")
+    d.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, kind INTEGER)")
+    d.execute_batch(
+        "INSERT INTO t VALUES ($1, $2)", [[i, i % 4] for i in range(1000)]
+    )
+    yield d
+    d.close()
+
+
+@pytest.mark.skipif(
+    not sysconfig.get_config_var("Py_GIL_DISABLED"),
+    reason="requires a free-threaded CPython build",
+)
+def test_import_keeps_gil_disabled():
+    assert stoolap.Database is not None
+    assert not sys._is_gil_enabled()
+
+
+def test_parallel_queries(db):
+    def count(kind):
+        return db.query_one("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [kind])["n"]
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        counts = list(pool.map(count, [i % 4 for i in range(64)]))
+    assert counts == [250] * 64
+
+
+def test_parallel_writes(db):
+    def insert(start):
+        for i in range(start, start + 100):
+            db.execute("INSERT INTO t VALUES ($1, $2)", [i, 9])
+
+    with ThreadPoolExecutor(max_workers=4) as pool:
+        list(pool.map(insert, [1000, 1100, 1200, 1300]))
+    assert db.query_one("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [9])["n"] == 400
+
+
+def test_query_many(db):
+    results = db.query_many(
+        [("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [k]) for k in range(4)]
+        + ["SELECT id FROM t WHERE id < 3 ORDER BY id"],
+        max_parallel=2,
+    )
+    assert [r[0]["n"] for r in results[:4]] == [250] * 4
+    assert results[4] == [{"id": 0}, {"id": 1}, {"id": 2}]
+    assert db.stats()["queries"] == 5
+
+
+def test_query_many_errors(db):
+    assert db.query_many([]) == []
+    with pytest.raises(stoolap.StoolapError):
+        db.query_many([("SELECT * FROM t", None), ("SELECT * FROM missing", None)])
+    with pytest.raises(ValueError):
+        db.query_many(["SELECT 1"], max_parallel=0)
+    with pytest.raises(TypeError):
+        db.query_many([42])
+
+
+def test_thread_pool(db):
+    assert db.threads >= 1
+    db.set_threads(2)
+    assert db.threads == 2
+    assert db.query_one("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [1])["n"] == 250
+    with db.begin() as tx:
+        assert tx.query_one("SELECT SUM(kind) AS s FROM t")["s"] == 1500
+    db.set_threads(None)
+    with pytest.raises(ValueError):
+        db.set_threads(0)
+
+    capped = Database.open(":memory:", threads=1)
+    assert capped.threads == 1
+    assert capped.get_setting("threads") == 1
+    capped.close()
+
+
+def test_query_hints(db):
+    sql = "SELECT kind, COUNT(*) AS n FROM t GROUP BY kind ORDER BY kind"
+    expected = [{"kind": k, "n": 250} for k in range(4)]
+    assert db.query(sql, hints={"parallel": 3}) == expected
+    assert db.query_one("SELECT COUNT(*) AS n FROM t", hints={"parallel": 1}) == {"n": 1000}
+    with pytest.raises(ValueError, match="prefer_index"):
+        db.query(sql, hints={"prefer_index": "idx_kind"})
+    with pytest.raises(ValueError, match="parallel"):
+        db.query(sql, hints={"parallel": 0})
+
+
+def _pool_threads():
+    names = []
+    for task in os.listdir("/proc/self/task"):
+        try:
+            with open(f"/proc/self/task/{task}/comm") as f:
+                names.append(f.read().strip())
+        except OSError:
+            pass
+    return sum(name.startswith("stoolap-") for name in names)
+
+
+@pytest.mark.skipif(not os.path.isdir("/proc/self/task"), reason="needs /proc")
+def test_query_hint_pools_are_bounded(db):
+    sql = "SELECT COUNT(*) AS n FROM t"
+    cpus = os.cpu_count()
+    before = _pool_threads()
+    assert db.query_one(sql, hints={"parallel": 100_000}) == {"n": 1000}
+    assert _pool_threads() - before <= cpus
+
+    # Pools of many sizes are not all kept: beyond four idle ones, the
+    # least recently used are shut down
+    for threads in range(1, 9):
+        db.query_one(sql, hints={"parallel": threads})
+    bound = before + sum(range(1, min(cpus, 8) + 1)[-5:]) + cpus
+    deadline = time.monotonic() + 5
+    while _pool_threads() > bound and time.monotonic() < deadline:
+        time.sleep(0.05)
+    assert _pool_threads() <= bound
+
+
+def test_parallel_execute_batch(db):
+    rows = [[i, 7] for i in range(1000, 1997)]
+    assert db.execute_batch("INSERT INTO t VALUES ($1, $2)", rows, parallel=4) == 997
+    assert db.query_one("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [7])["n"] == 997
+
+    # A duplicate key in one share rolls back every share
+    rows = [[i, 8] for i in range(2000, 2100)] + [[0, 8]]
+    with pytest.raises(stoolap.StoolapError):
+        db.execute_batch("INSERT INTO t VALUES ($1, $2)", rows, parallel=3)
+    assert db.query_one("SELECT COUNT(*) AS n FROM t WHERE kind = $1", [8])["n"] == 0
+    with pytest.raises(ValueError):
+        db.execute_batch("INSERT INTO t VALUES ($1, $2)", rows, parallel=0)
@@ -14,7 +14,7 @@
 
 """Transaction tests."""
 
-from stoolap import Database, StoolapError
+from stoolap import ConflictError, Database, StoolapError
 import pytest
 
 
@@ -126,3 +126,250 @@ def test_tx_positional_params():
     assert rows[0]["name"] == "Alice"
     assert rows[1]["name"] == "Bob"
     db.close()
+
+
+def test_query_as_of_snapshot():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    db.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+
+    snapshot = db.current_snapshot_id()
+    assert isinstance(snapshot, int)
+    db.execute("UPDATE users SET name = $1 WHERE id = $2", ["Alicia", 1])
+    db.execute("INSERT INTO users VALUES ($1, $2)", [2, "Bob"])
+
+    row = db.query_one("SELECT name FROM users WHERE id = $1", [1], as_of=snapshot)
+    assert row["name"] == "Alice"
+    assert len(db.query("SELECT * FROM users", as_of=snapshot)) == 1
+    assert len(db.query("SELECT * FROM users")) == 2
+    db.close()
+
+
+def test_query_as_of_from_items():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    db.exec("CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER)")
+    db.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+    db.execute("INSERT INTO orders VALUES ($1, $2)", [1, 1])
+
+    snapshot = db.current_snapshot_id()
+    db.execute("UPDATE users SET name = $1 WHERE id = $2", ["Alicia", 1])
+    db.execute("INSERT INTO users VALUES ($1, $2)", [2, "Bob"])
+    db.execute("INSERT INTO orders VALUES ($1, $2)", [2, 2])
+
+    # Projections and primary key lookups
+    assert db.query("SELECT name FROM users", as_of=snapshot) == [{"name": "Alice"}]
+    assert db.query_one("SELECT name FROM users WHERE id = 1", as_of=snapshot)["name"] == "Alice"
+
+    # Aliases, with and without AS
+    assert db.query("SELECT u.name FROM users u", as_of=snapshot) == [{"name": "Alice"}]
+    rows = db.query("SELECT u.name FROM users AS u WHERE u.name LIKE 'A%'", as_of=snapshot)
+    assert rows == [{"name": "Alice"}]
+    rows = db.query(
+        "SELECT u.name, o.id FROM users u JOIN orders AS o ON o.user_id = u.id",
+        as_of=snapshot,
+    )
+    assert rows == [{"name": "Alice", "id": 1}]
+
+    # Subqueries in the FROM list read the snapshot too
+    rows = db.query("SELECT s.name FROM (SELECT * FROM users) s", as_of=snapshot)
+    assert rows == [{"name": "Alice"}]
+    rows = db.query(
+        "SELECT s.name, o.id FROM (SELECT * FROM users) AS s, orders o", as_of=snapshot
+    )
+    assert rows == [{"name": "Alice", "id": 1}]
+    rows = db.query("SELECT (SELECT COUNT(*) FROM orders) AS c FROM users", as_of=snapshot)
+    assert rows == [{"c": 1}]
+
+    # Table functions are left alone
+    rows = db.query("SELECT * FROM generate_series(1, 2)", as_of=snapshot)
+    assert [r["value"] for r in rows] == [1, 2]
+    rows = db.query(
+        "SELECT u.name FROM generate_series(1, 2) n JOIN users u ON u.id = n.value",
+        as_of=snapshot,
+    )
+    assert rows == [{"name": "Alice"}]
+    db.close()
+
+
+def test_query_as_of_invalid():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    with pytest.raises(TypeError):
+        db.query("SELECT * FROM users", as_of=1.5)
+    db.close()
+
+
+def test_snapshot_sees_stable_view():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    db.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+
+    with db.snapshot() as snap:
+        db.execute("INSERT INTO users VALUES ($1, $2)", [2, "Bob"])
+        assert len(snap.query("SELECT * FROM users")) == 1
+        assert snap.query_one("SELECT name FROM users WHERE id = $1", [1])["name"] == "Alice"
+        assert snap.query_raw("SELECT id FROM users")["rows"] == [[1]]
+
+        rows = snap.query_iter("SELECT * FROM users ORDER BY id")
+        assert rows.columns == ["id", "name"]
+        assert len(rows) == 1
+        assert list(rows) == [{"id": 1, "name": "Alice"}]
+
+    assert len(db.query("SELECT * FROM users")) == 2
+    with pytest.raises(StoolapError):
+        snap.query("SELECT * FROM users")
+    db.close()
+
+
+def test_transaction_helper_commits():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+
+    def add(tx):
+        tx.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+        return "ok"
+
+    assert db.transaction(add) == "ok"
+    assert len(db.query("SELECT * FROM users")) == 1
+    db.close()
+
+
+def test_transaction_helper_rolls_back_on_error():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    calls = []
+
+    def fail(tx):
+        calls.append(1)
+        tx.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+        raise ValueError("boom")
+
+    with pytest.raises(ValueError):
+        db.transaction(fail)
+    assert calls == [1]
+    assert db.query("SELECT * FROM users") == []
+    db.close()
+
+
+def test_transaction_helper_retries_conflicts():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+    attempts = []
+
+    def flaky(tx):
+        attempts.append(1)
+        if len(attempts) < 3:
+            raise ConflictError("write conflict")
+        tx.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+
+    db.transaction(flaky, retries=3, backoff=0)
+    assert len(attempts) == 3
+    assert len(db.query("SELECT * FROM users")) == 1
+
+    def always_conflict(tx):
+        attempts.append(1)
+        raise ConflictError("write conflict")
+
+    attempts.clear()
+    with pytest.raises(ConflictError):
+        db.transaction(always_conflict, retries=1, backoff=0)
+    assert len(attempts) == 2
+    db.close()
+
+
+def test_transaction_state_properties():
+    db = Database.open(":memory:")
+    assert not db.in_transaction
+
+    tx = db.begin()
+    assert tx.active
+    assert isinstance(tx.id, int)
+    assert db.in_transaction
+    tx.commit()
+    assert not tx.active
+    assert not db.in_transaction
+
+    with db.begin() as tx2:
+        assert tx2.id != tx.id
+        assert db.in_transaction
+    assert not tx2.active
+    assert not db.in_transaction
+    db.close()
+
+
+def test_begin_modes():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")
+
+    with db.begin(mode="snapshot") as tx:
+        db.execute("INSERT INTO users VALUES ($1, $2)", [1, "Alice"])
+        assert tx.query("SELECT * FROM users") == []
+    with db.begin(mode="deferred") as tx:
+        assert len(tx.query("SELECT * FROM users")) == 1
+
+    with pytest.raises(StoolapError):
+        db.begin(mode="immediate")
+    with pytest.raises(StoolapError):
+        db.begin(mode="exclusive")
+    with pytest.raises(ValueError):
+        db.begin(mode="bogus")
+    assert not db.in_transaction
+    db.close()
+
+
+def test_transaction_used_from_other_threads():
+    import threading
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    tx = db.begin()
+    errors = []
+
+    def insert(start):
+        try:
+            for i in range(start, start + 50):
+                tx.execute("INSERT INTO t VALUES ($1)", [i])
+        except Exception as e:  # pragma: no cover - reported below
+            errors.append(e)
+
+    threads = [threading.Thread(target=insert, args=(n * 50,)) for n in range(4)]
+    for t in threads:
+        t.start()
+    for t in threads:
+        t.join()
+    assert errors == []
+
+    done = threading.Thread(target=tx.commit)
+    done.start()
+    done.join()
+    assert not tx.active
+    assert db.query_one("SELECT COUNT(*) AS cnt FROM t")["cnt"] == 200
+    with pytest.raises(StoolapError):
+        tx.execute("INSERT INTO t VALUES ($1)", [1000])
+    db.close()
+
+
+def test_rollback_undoes_create_table():
+    db = Database.open(":memory:")
+    tx = db.begin()
+    tx.execute("CREATE TABLE t (id INTEGER PRIMARY KEY)")
+    tx.execute("INSERT INTO t VALUES (1)")
+    tx.rollback()
+    assert db.tables() == []
+    db.close()
+
+
+def test_rollback_of_drop_table_loses_rows():
+    # The engine drops the rows at once; a rollback only restores the columns
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT)")
+    db.exec("CREATE INDEX t_v ON t (v)")
+    db.execute("INSERT INTO t VALUES (1, 'a')")
+    tx = db.begin()
+    tx.execute("DROP TABLE t")
+    tx.rollback()
+    assert db.tables() == ["t"]
+    assert db.query("SELECT * FROM t") == []
+    assert [c["Field"] for c in db.describe("t")] == ["id", "v"]
+    db.close()
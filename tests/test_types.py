@@ -15,9 +15,26 @@
 """Type mapping and conversion tests."""
 
 import json
-from datetime import datetime, timezone, timedelta
-
-from stoolap import Database
+import math
+import pickle
+from array import array
+from datetime import date, datetime, timezone, timedelta
+
+import pytest
+
+from stoolap import (
+    PARSE_COLNAMES,
+    PARSE_DECLTYPES,
+    Database,
+    Json,
+    JsonPath,
+    StoolapError,
+    StoolapWarning,
+    Vector,
+    register_converter,
+    set_nan_policy,
+    set_warning_policy,
+)
 
 
 def test_integer_types():
@@ -165,6 +182,44 @@ def test_json_list():
     db.close()
 
 
+def test_json_path():
+    assert JsonPath("user", "tags", 0).path == "$.user.tags[0]"
+    assert JsonPath("$.user.name") == JsonPath("user", "name")
+    assert JsonPath("first name").path == '$."first name"'
+    assert JsonPath("user").child("name").path == "$.user.name"
+    assert str(JsonPath("a")) == "$.a"
+    with pytest.raises(ValueError):
+        JsonPath("tags", -1)
+    with pytest.raises(TypeError):
+        JsonPath("tags", 1.5)
+
+
+def test_json_get():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t_json_get (id INTEGER PRIMARY KEY, data JSON)")
+    db.execute_batch(
+        "INSERT INTO t_json_get VALUES ($1, $2)",
+        [
+            [1, {"user": {"name": "Alice", "tags": ["a", "b"]}}],
+            [2, {"user": {"name": "Bob", "tags": []}}],
+        ],
+    )
+
+    assert sorted(db.json_get("t_json_get", "data", "$.user.name")) == ["Alice", "Bob"]
+    assert db.json_get("t_json_get", "data", "user", where="id = $1", params=[2]) != [None]
+    assert db.json_get(
+        "t_json_get", "data", JsonPath("user", "tags", 0), where="id = :id", params={"id": 1}
+    ) == ["a"]
+    assert db.json_get("t_json_get", "data", "$.missing", where="id = 1") == [None]
+
+    rows = db.query(
+        "SELECT id FROM t_json_get WHERE JSON_EXTRACT(data, $1) = $2",
+        [JsonPath("user", "name"), "Bob"],
+    )
+    assert rows == [{"id": 2}]
+    db.close()
+
+
 def test_mixed_types_in_query():
     """Query returning multiple column types."""
     db = Database.open(":memory:")
@@ -205,3 +260,364 @@ def test_bool_not_confused_with_int():
     assert type(rows[0]["flag"]) is bool
     assert type(rows[1]["flag"]) is bool
     db.close()
+
+
+def test_query_columnar():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER, name TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, "a"])
+    db.execute("INSERT INTO t VALUES ($1, $2)", [2, None])
+
+    cols = db.query_columnar("SELECT id, name FROM t ORDER BY id")
+    assert cols == {"id": [1, 2], "name": ["a", None]}
+    assert db.query_columnar("SELECT id, name FROM t WHERE id > 5") == {"id": [], "name": []}
+
+    with db.begin() as tx:
+        assert tx.query_columnar("SELECT id FROM t WHERE id = $1", [2]) == {"id": [2]}
+    db.close()
+
+
+def test_query_columnar_numpy():
+    np = pytest.importorskip("numpy")
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER, price FLOAT, active BOOLEAN, qty INTEGER, name TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2, $3, $4, $5)", [1, 9.5, True, 3, "pen"])
+    db.execute("INSERT INTO t VALUES ($1, $2, $3, $4, $5)", [2, 12.0, False, None, "ink"])
+
+    cols = db.query_columnar("SELECT * FROM t ORDER BY id", numpy=True)
+    assert cols["id"].dtype == np.int64
+    assert cols["id"].tolist() == [1, 2]
+    assert cols["price"].dtype == np.float64
+    assert cols["price"].tolist() == [9.5, 12.0]
+    assert cols["active"].dtype == np.bool_
+    assert cols["active"].tolist() == [True, False]
+    assert isinstance(cols["qty"], np.ma.MaskedArray)
+    assert cols["qty"].mask.tolist() == [False, True]
+    assert cols["qty"][0] == 3
+    assert cols["name"].dtype == object
+    assert cols["name"].tolist() == ["pen", "ink"]
+    db.close()
+
+
+def test_query_raw_typed():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER, price FLOAT, active BOOLEAN, name TEXT, note TEXT)")
+    db.execute("INSERT INTO t VALUES ($1, $2, $3, $4, $5)", [1, 9.5, True, "pen", None])
+    db.execute("INSERT INTO t VALUES ($1, $2, $3, $4, $5)", [2, 12.0, False, "ink", "x"])
+
+    raw = db.query_raw("SELECT id, price, active, name, note FROM t ORDER BY id", typed=True)
+    assert raw["columns"] == ["id", "price", "active", "name", "note"]
+    ids, prices, active, names, notes = raw["data"]
+    assert ids == array("q", [1, 2])
+    assert prices == array("d", [9.5, 12.0])
+    assert active == array("B", [1, 0])
+    assert names == ["pen", "ink"]
+    # NULLs keep the column a list
+    assert notes == [None, "x"]
+
+    empty = db.query_raw("SELECT id FROM t WHERE id > 10", typed=True)
+    assert empty == {"columns": ["id"], "data": [[]]}
+    db.close()
+
+
+def test_results_pickle():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER, price FLOAT, name TEXT, at TIMESTAMP)")
+    db.execute(
+        "INSERT INTO t VALUES ($1, $2, $3, $4)",
+        [1, 9.5, "pen", datetime(2024, 1, 15, 10, 30, tzinfo=timezone.utc)],
+    )
+    db.execute("INSERT INTO t VALUES ($1, $2, $3, $4)", [2, None, "ink", None])
+
+    sql = "SELECT * FROM t ORDER BY id"
+    for result in (
+        db.query(sql),
+        db.query_raw(sql),
+        db.query_raw(sql, typed=True),
+        db.query_columnar(sql),
+    ):
+        assert pickle.loads(pickle.dumps(result)) == result
+    db.close()
+
+
+def test_lossy_conversion_warnings():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (ts TIMESTAMP)")
+    with pytest.warns(StoolapWarning, match="taken as UTC"):
+        db.execute("INSERT INTO t VALUES ($1)", [datetime(2024, 1, 1)])
+    with pytest.warns(StoolapWarning, match="float32"):
+        Vector([1.0, 1e39])
+
+    previous = set_warning_policy("error")
+    try:
+        assert previous == "warn"
+        with pytest.raises(StoolapError):
+            db.execute("INSERT INTO t VALUES ($1)", [datetime(2024, 1, 1)])
+        # Timezone-aware datetimes lose nothing
+        db.execute("INSERT INTO t VALUES ($1)", [datetime(2024, 1, 1, tzinfo=timezone.utc)])
+        set_warning_policy("ignore")
+        db.execute("INSERT INTO t VALUES ($1)", [datetime(2024, 1, 2)])
+    finally:
+        set_warning_policy(previous)
+    assert db.query_one("SELECT COUNT(*) AS n FROM t")["n"] == 3
+    with pytest.raises(ValueError):
+        set_warning_policy("loud")
+    db.close()
+
+
+def test_warning_policy_is_process_wide():
+    from concurrent.futures import ThreadPoolExecutor
+
+    first = Database.open("memory://policy_first")
+    second = Database.open("memory://policy_second")
+    for db in (first, second):
+        db.exec("CREATE TABLE t (ts TIMESTAMP)")
+    previous = set_warning_policy("error")
+    try:
+        # Set once, it covers other databases, other threads and values
+        # converted outside any database
+        with pytest.raises(StoolapError):
+            second.execute("INSERT INTO t VALUES ($1)", [datetime(2024, 1, 1)])
+        with ThreadPoolExecutor(max_workers=1) as pool:
+            sql = "INSERT INTO t VALUES ($1)"
+            future = pool.submit(first.execute, sql, [datetime(2024, 1, 1)])
+            with pytest.raises(StoolapError):
+                future.result()
+        with pytest.raises(StoolapError):
+            Vector([1e39])
+    finally:
+        assert set_warning_policy(previous) == "error"
+    first.close()
+    second.close()
+
+
+def test_float_to_integer_column_warnings():
+    import warnings
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER, x FLOAT)")
+    with pytest.warns(StoolapWarning, match="stored as 2"):
+        db.execute("INSERT INTO t VALUES ($1, $2, $3)", [1, 2.7, 0.5])
+    with pytest.warns(StoolapWarning, match="column 'n'"):
+        db.execute("UPDATE t SET n = :n WHERE id = 1", {"n": -4.5})
+    with pytest.warns(StoolapWarning, match="1e30"):
+        db.execute_batch("INSERT INTO t (n, id) VALUES ($1, $2)", [[3.0, 2], [1e30, 3]])
+    with db.begin() as tx:
+        with pytest.warns(StoolapWarning, match="stored as 9"):
+            tx.execute("INSERT INTO t (id, n) VALUES ($1, $2)", [4, 9.9])
+    assert db.query("SELECT n FROM t ORDER BY id") == [
+        {"n": -4}, {"n": 3}, {"n": 9223372036854775807}, {"n": 9},
+    ]
+
+    # Whole numbers, FLOAT columns and expressions are not reported
+    with warnings.catch_warnings():
+        warnings.simplefilter("error", StoolapWarning)
+        db.execute("INSERT INTO t VALUES ($1, $2, $3)", [5, 6.0, 0.25])
+        db.execute("UPDATE t SET x = $1 WHERE id = 5", [1.5])
+        db.execute("UPDATE t SET n = n + 1 WHERE x < $1", [2.5])
+
+    previous = set_warning_policy("error")
+    try:
+        with pytest.raises(StoolapError, match="INTEGER column"):
+            db.execute("INSERT INTO t VALUES ($1, $2, $3)", [6, 0.5, 0.5])
+    finally:
+        set_warning_policy(previous)
+    assert db.query_one("SELECT COUNT(*) AS c FROM t WHERE id = 6")["c"] == 0
+    db.close()
+
+
+def test_nan_policy():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, x FLOAT)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, float("nan")])
+    assert math.isnan(db.query_one("SELECT x FROM t WHERE id = 1")["x"])
+
+    previous = set_nan_policy(bind="null", fetch="raise")
+    try:
+        assert previous == ("keep", "keep")
+        db.execute("INSERT INTO t VALUES ($1, $2)", [2, float("inf")])
+        assert db.query_one("SELECT x FROM t WHERE id = 2")["x"] is None
+        with pytest.raises(StoolapError):
+            db.query("SELECT x FROM t WHERE id = 1")
+
+        set_nan_policy(bind="raise", fetch="null")
+        with pytest.raises(ValueError):
+            db.execute("INSERT INTO t VALUES ($1, $2)", [3, float("-inf")])
+        assert db.query_one("SELECT x FROM t WHERE id = 1")["x"] is None
+    finally:
+        set_nan_policy(bind=previous[0], fetch=previous[1])
+    with pytest.raises(ValueError):
+        set_nan_policy(bind="drop")
+    db.close()
+
+
+def test_nan_policy_is_process_wide():
+    from concurrent.futures import ThreadPoolExecutor
+
+    first = Database.open("memory://nan_first")
+    second = Database.open("memory://nan_second")
+    for db in (first, second):
+        db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, x FLOAT)")
+    previous = set_nan_policy(bind="null")
+    try:
+        second.execute("INSERT INTO t VALUES ($1, $2)", [1, float("nan")])
+        with ThreadPoolExecutor(max_workers=1) as pool:
+            sql = "INSERT INTO t VALUES ($1, $2)"
+            pool.submit(first.execute, sql, [1, float("inf")]).result()
+        for db in (first, second):
+            assert db.query_one("SELECT x FROM t WHERE id = 1")["x"] is None
+    finally:
+        assert set_nan_policy(bind=previous[0]) == ("null", previous[1])
+    first.close()
+    second.close()
+
+
+def test_strict_types():
+    db = Database.open(":memory:", strict_types=True)
+    assert db.strict_types
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, data JSON, ts TIMESTAMP, v VECTOR(2))")
+    for value in ({"a": 1}, [1, 2], datetime(2024, 1, 1)):
+        with pytest.raises(TypeError):
+            db.execute("INSERT INTO t (id, data) VALUES ($1, $2)", [1, value])
+
+    aware = datetime(2024, 1, 1, tzinfo=timezone.utc)
+    db.execute(
+        "INSERT INTO t VALUES ($1, $2, $3, $4)",
+        [1, Json({"a": [1, 2]}), aware, Vector([0.5, 1.5])],
+    )
+    row = db.query_one("SELECT data, ts FROM t WHERE id = 1")
+    assert json.loads(row["data"]) == {"a": [1, 2]}
+    assert row["ts"].year == 2024
+    assert repr(Json([1])) == "Json([1])"
+    db.close()
+
+    db = Database.open(":memory:")
+    assert not db.strict_types
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, data JSON)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, Json([1, 2])])
+    db.execute("INSERT INTO t VALUES ($1, $2)", [2, [1, 2]])
+    rows = db.query("SELECT data FROM t ORDER BY id")
+    assert rows[0]["data"] == rows[1]["data"]
+    db.close()
+
+
+def test_timestamp_timezone():
+    stored = datetime(2024, 6, 15, 12, 30, tzinfo=timezone.utc)
+
+    def fetch(**options):
+        db = Database.open(":memory:", **options)
+        db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, ts TIMESTAMP)")
+        db.execute("INSERT INTO t VALUES ($1, $2)", [1, stored])
+        ts = db.query_one("SELECT ts FROM t")["ts"]
+        assert db.query("SELECT ts FROM t")[0]["ts"] == ts
+        db.close()
+        return ts
+
+    assert fetch().tzinfo is not None
+    assert fetch() == stored
+
+    naive = fetch(naive_timestamps=True)
+    assert naive.tzinfo is None
+    assert naive == datetime(2024, 6, 15, 12, 30)
+
+    local = fetch(tz="Europe/Istanbul")
+    assert local == stored
+    assert local.hour == 15
+    assert fetch(tz=timezone(timedelta(hours=-4))).hour == 8
+    assert fetch(tz="Europe/Istanbul", naive_timestamps=True) == datetime(2024, 6, 15, 15, 30)
+
+    db = Database.open(":memory:", tz="Europe/Istanbul")
+    assert db.tz == "Europe/Istanbul"
+    db.close()
+    assert Database.open(":memory:").tz is None
+    with pytest.raises(ValueError):
+        Database.open(":memory:", tz="Nowhere/Special")
+
+
+def test_reject_naive_datetimes():
+    db = Database.open(":memory:", reject_naive=True)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, ts TIMESTAMP)")
+    with pytest.raises(TypeError, match="naive datetime"):
+        db.execute("INSERT INTO t VALUES ($1, $2)", [1, datetime(2024, 1, 1)])
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, datetime(2024, 1, 1, tzinfo=timezone.utc)])
+    assert db.query_one("SELECT COUNT(*) AS n FROM t")["n"] == 1
+    db.close()
+
+
+def test_nanosecond_timestamps():
+    np = pytest.importorskip("numpy")
+    db = Database.open(":memory:", nanosecond_timestamps=True)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, ts TIMESTAMP)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, np.datetime64("2024-06-15T12:30:00.123456789")])
+    db.execute("INSERT INTO t VALUES ($1, $2)", [2, datetime(2024, 6, 15, 12, 30, tzinfo=timezone.utc)])
+
+    nanos = 1718454600 * 10**9
+    rows = db.query("SELECT ts FROM t ORDER BY id")
+    assert [row["ts"] for row in rows] == [nanos + 123456789, nanos]
+
+    cols = db.query_columnar("SELECT ts FROM t ORDER BY id", numpy=True)
+    assert cols["ts"].dtype == np.dtype("datetime64[ns]")
+    assert cols["ts"][0] == np.datetime64("2024-06-15T12:30:00.123456789")
+
+    raw = db.query_raw("SELECT ts FROM t ORDER BY id", typed=True)
+    assert list(raw["data"][0]) == [nanos + 123456789, nanos]
+    db.close()
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, ts TIMESTAMP)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, np.datetime64("NaT")])
+    assert db.query_one("SELECT ts FROM t")["ts"] is None
+    db.close()
+
+
+def test_midnight_as_date():
+    db = Database.open(":memory:", midnight_as_date=True)
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, d DATE, ts TIMESTAMP)")
+    noon = datetime(2024, 12, 25, 12, 0, tzinfo=timezone.utc)
+    db.execute("INSERT INTO t VALUES ($1, $2, $3)", [1, date(2024, 12, 25), noon])
+    row = db.query_one("SELECT d, ts FROM t")
+    assert type(row["d"]) is date
+    assert row["d"] == date(2024, 12, 25)
+    assert row["ts"] == noon
+    assert db.query_one("SELECT id FROM t WHERE d = $1", [date(2024, 12, 25)])["id"] == 1
+    db.close()
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, d DATE)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, date(2024, 12, 25)])
+    assert db.query_one("SELECT d FROM t")["d"] == datetime(2024, 12, 25, tzinfo=timezone.utc)
+    db.close()
+
+
+def test_detect_types_converters():
+    import uuid
+
+    register_converter("uuid", uuid.UUID)
+    register_converter("JSON", json.loads)
+    db = Database.open(":memory:", detect_types=PARSE_DECLTYPES | PARSE_COLNAMES)
+    assert db.detect_types == PARSE_DECLTYPES | PARSE_COLNAMES
+    db.exec("CREATE TABLE t (n INTEGER PRIMARY KEY, id TEXT, data JSON)")
+    key = uuid.uuid4()
+    db.execute("INSERT INTO t VALUES ($1, $2, $3)", [1, str(key), {"a": 1}])
+    db.execute("INSERT INTO t VALUES ($1, $2, $3)", [2, str(uuid.uuid4()), None])
+
+    row = db.query_one('SELECT id AS "id [UUID]", data FROM t WHERE id = $1', [str(key)])
+    assert row == {"id": key, "data": {"a": 1}}
+    raw = db.query_raw('SELECT id AS "id [UUID]", data FROM t')
+    assert raw["columns"] == ["id", "data"]
+    assert all(isinstance(r[0], uuid.UUID) for r in raw["rows"])
+    assert None in [r[1] for r in raw["rows"]]
+    db.close()
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE t (id INTEGER PRIMARY KEY, data JSON)")
+    db.execute("INSERT INTO t VALUES ($1, $2)", [1, {"a": 1}])
+    row = db.query_one('SELECT id AS "id [UUID]", data FROM t')
+    assert isinstance(row["data"], str)
+    assert "id [UUID]" in row
+    db.close()
+
+    with pytest.raises(TypeError):
+        register_converter("POINT", 42)
+    with pytest.raises(ValueError):
+        Database.open(":memory:", detect_types=8)
@@ -15,8 +15,19 @@
 """Vector type and similarity search tests."""
 
 import math
+import struct
 
-from stoolap import Database, Vector
+import pytest
+
+from stoolap import (
+    Database,
+    StoolapError,
+    Vector,
+    VectorDimensionError,
+    cosine_distance,
+    dot_product,
+    l2_distance,
+)
 
 
 def test_vector_class():
@@ -27,6 +38,107 @@ def test_vector_class():
     assert "Vector" in repr(v)
 
 
+def test_vector_sequence():
+    v = Vector([1.0, 2.0, 3.0, 4.0])
+    assert v[0] == 1.0
+    assert v[-1] == 4.0
+    assert list(v) == [1.0, 2.0, 3.0, 4.0]
+    assert 2.0 in v
+    assert 5.0 not in v
+    assert "a" not in v
+    assert v[1:3] == Vector([2.0, 3.0])
+    assert v[::-2].to_list() == [4.0, 2.0]
+    assert v == Vector([1.0, 2.0, 3.0, 4.0])
+    assert v != Vector([1.0, 2.0])
+    assert v != [1.0, 2.0, 3.0, 4.0]
+    with pytest.raises(IndexError):
+        v[4]
+
+    q = Vector([2, 4], dtype="i8", scale=0.5)
+    assert q[1] == 2.0
+    assert q[:1].dtype == "i8"
+    assert q[:1].codes() == [2]
+    assert q == Vector([1.0, 2.0])
+
+
+def test_vector_bytes():
+    v = Vector([1.0, -2.5, 0.125])
+    blob = v.to_bytes()
+    assert blob == struct.pack("<3f", 1.0, -2.5, 0.125)
+    assert Vector.from_bytes(blob) == v
+
+    half = Vector([0.5, 2.0], dtype="f16")
+    assert half.to_bytes() == struct.pack("<2e", 0.5, 2.0)
+    assert Vector.from_bytes(half.to_bytes(), "f16").to_list() == [0.5, 2.0]
+
+    q = Vector.from_bytes(bytes([1, 255]), "i8", scale=2.0)
+    assert q.codes() == [1, -1]
+    assert q.to_bytes() == bytes([1, 255])
+
+    with pytest.raises(ValueError):
+        Vector.from_bytes(b"abc", "f32")
+
+
+def test_vector_f16():
+    v = Vector([0.5, -0.25, 0.1], dtype="f16")
+    assert v.dtype == "f16"
+    assert len(v) == 3
+    values = v.to_list()
+    assert values[:2] == [0.5, -0.25]
+    # 0.1 rounds to the nearest half-precision value
+    assert values[2] == 0.0999755859375
+    assert "f16" in repr(v)
+
+    with pytest.raises(ValueError):
+        Vector([1.0], dtype="f64")
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE e (id INTEGER PRIMARY KEY, embedding VECTOR(3))")
+    db.execute("INSERT INTO e VALUES ($1, $2)", [1, v])
+    emb = db.query_one("SELECT embedding FROM e WHERE id = 1")["embedding"]
+    assert emb == pytest.approx(values)
+    db.close()
+
+
+def test_vector_numpy():
+    np = pytest.importorskip("numpy")
+    half = np.array([0.5, 0.25, 1.5], dtype=np.float16)
+    assert Vector(half).dtype == "f16"
+    assert Vector(half).to_list() == [0.5, 0.25, 1.5]
+    assert Vector(half.astype(np.float32)).dtype == "f32"
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE e (id INTEGER PRIMARY KEY, embedding VECTOR(3))")
+    db.execute("INSERT INTO e VALUES ($1, $2)", [1, half])
+    assert db.query_one("SELECT embedding FROM e")["embedding"] == [0.5, 0.25, 1.5]
+
+    with pytest.raises(ValueError):
+        Vector(np.zeros((2, 2), dtype=np.float16))
+    with pytest.raises(TypeError):
+        Vector(np.arange(3))
+    db.close()
+
+
+def test_vector_i8():
+    v = Vector([12, -127, 64], dtype="i8", scale=0.5, offset=1.0)
+    assert v.dtype == "i8"
+    assert (v.scale, v.offset) == (0.5, 1.0)
+    assert v.codes() == [12, -127, 64]
+    assert v.to_list() == [7.0, -62.5, 33.0]
+    assert Vector([1.0]).codes() is None
+
+    with pytest.raises(OverflowError):
+        Vector([200], dtype="i8")
+    with pytest.raises(ValueError):
+        Vector([1.0], scale=2.0)
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE e (id INTEGER PRIMARY KEY, embedding VECTOR(3))")
+    db.execute("INSERT INTO e VALUES ($1, $2)", [1, v])
+    assert db.query_one("SELECT embedding FROM e")["embedding"] == [7.0, -62.5, 33.0]
+    db.close()
+
+
 def test_vector_insert_and_query():
     """Insert vectors via Vector() param and read back as list[float]."""
     db = Database.open(":memory:")
@@ -292,3 +404,275 @@ async def test_async_vector():
     assert isinstance(rows[0]["v"], list)
     assert abs(rows[0]["v"][0] - 0.1) < 1e-6
     await db.close()
+
+
+def test_vector_search():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, kind TEXT, embedding VECTOR(2))")
+    db.execute_batch(
+        "INSERT INTO docs VALUES ($1, $2, $3)",
+        [
+            [1, "a", Vector([1.0, 0.0])],
+            [2, "b", Vector([0.0, 1.0])],
+            [3, "a", Vector([0.9, 0.1])],
+        ],
+    )
+
+    hits = db.vector_search("docs", "embedding", [1.0, 0.0], k=2, metric="l2")
+    assert [h["id"] for h in hits] == [1, 3]
+    assert hits[0]["distance"] == pytest.approx(0.0)
+    assert hits[0]["kind"] == "a"
+
+    hits = db.vector_search("docs", "embedding", Vector([0.0, 1.0]), k=3, where="kind = $1", params=["a"])
+    assert [h["id"] for h in hits] == [3, 1]
+
+    hits = db.vector_search(
+        "docs", "embedding", [0.0, 1.0], k=1, where="kind = :kind", params={"kind": "b"}
+    )
+    assert [h["id"] for h in hits] == [2]
+
+    with pytest.raises(ValueError):
+        db.vector_search("docs", "embedding", [1.0, 0.0], k=0)
+    with pytest.raises(ValueError):
+        db.vector_search("docs", "embedding", [1.0, 0.0], k=1, metric="hamming")
+    with pytest.raises(ValueError):
+        db.vector_search("docs", "", [1.0, 0.0], k=1)
+    with pytest.raises(ValueError):
+        db.vector_search("docs\0", "embedding", [1.0, 0.0], k=1)
+    db.close()
+
+
+def test_vector_search_strategy():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, kind TEXT, embedding VECTOR(2))")
+    db.execute_batch(
+        "INSERT INTO docs VALUES ($1, $2, $3)",
+        [[i, "a" if i < 20 else "b", Vector([1.0, i / 100])] for i in range(25)],
+    )
+    query = [1.0, 0.0]
+
+    hits = db.vector_search("docs", "embedding", query, k=3, metric="l2", where="kind = 'b'", strategy="pre_filter")
+    assert [h["id"] for h in hits] == [20, 21, 22]
+
+    # The 10 nearest candidates are all kind 'a'
+    hits = db.vector_search(
+        "docs", "embedding", query, k=3, metric="l2", where="kind = 'b'",
+        strategy="post_filter", candidates=10,
+    )
+    assert hits == []
+
+    hits = db.vector_search(
+        "docs", "embedding", query, k=3, metric="l2", where="kind = $1", params=["b"],
+        strategy="auto", candidates=10,
+    )
+    assert [h["id"] for h in hits] == [20, 21, 22]
+
+    hits = db.vector_search(
+        "docs", "embedding", query, k=2, metric="l2", where="kind = :kind", params={"kind": "a"},
+        strategy="post_filter",
+    )
+    assert [h["id"] for h in hits] == [0, 1]
+
+    # Columns qualified with the table name also work on the candidates
+    for where in ("docs.kind = 'a' AND docs.id > 0", 'DOCS.kind = \'a\' AND "docs".id > 0'):
+        for strategy in ("pre_filter", "post_filter"):
+            hits = db.vector_search(
+                "docs", "embedding", query, k=2, metric="l2", where=where, strategy=strategy
+            )
+            assert [h["id"] for h in hits] == [1, 2]
+
+    with pytest.raises(ValueError):
+        db.vector_search("docs", "embedding", query, k=1, where="kind = 'a'", strategy="exact")
+    with pytest.raises(ValueError):
+        db.vector_search("docs", "embedding", query, k=5, where="kind = 'a'", candidates=2)
+    db.close()
+
+
+def test_create_vector_index():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(2))")
+    assert db.vector_index_info("docs", "embedding") is None
+
+    name = db.create_vector_index("docs", "embedding", metric="l2", m=8, ef_construction=64)
+    assert name == "idx_docs_embedding"
+    info = db.vector_index_info("docs", "embedding")
+    assert info["name"] == "idx_docs_embedding"
+    assert info["index_type"] == "hnsw"
+    assert info["column"] == "embedding"
+    assert (info["metric"], info["m"], info["ef_construction"]) == ("l2", 8, 64)
+    assert isinstance(info["ef_search"], int)
+    assert db.create_vector_index("docs", "embedding", if_not_exists=True) == name
+
+    # Settings left out are the ones the engine chose
+    db.exec("CREATE TABLE pics (id INTEGER PRIMARY KEY, clip VECTOR(4))")
+    db.create_vector_index("pics", "clip", metric="ip", ef_search=40)
+    info = db.vector_index_info("pics", "clip")
+    assert (info["metric"], info["ef_search"]) == ("ip", 40)
+    assert info["m"] >= 2 and info["ef_construction"] >= info["m"]
+
+    with pytest.raises(ValueError):
+        db.create_vector_index("docs", "embedding", index_type="ivf")
+    with pytest.raises(ValueError):
+        db.create_vector_index("docs", "embedding", metric="hamming")
+    with pytest.raises(ValueError):
+        db.create_vector_index("docs", "embedding", m=1)
+    with pytest.raises(ValueError):
+        db.create_vector_index("docs", "embedding", m=16, ef_construction=8)
+    db.close()
+
+
+def test_insert_vectors():
+    np = pytest.importorskip("numpy")
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(3))")
+    matrix = np.arange(30, dtype=np.float32).reshape(10, 3)
+
+    assert db.insert_vectors("docs", "id", "embedding", range(10), matrix, batch_size=4) == 10
+    rows = db.query("SELECT id, embedding FROM docs ORDER BY id")
+    assert len(rows) == 10
+    assert rows[3]["embedding"] == [9.0, 10.0, 11.0]
+
+    # float64 arrays are converted
+    db.insert_vectors("docs", "id", "embedding", [10], np.ones((1, 3)))
+    assert db.query_one("SELECT embedding FROM docs WHERE id = 10")["embedding"] == [1.0, 1.0, 1.0]
+
+    with pytest.raises(ValueError):
+        db.insert_vectors("docs", "id", "embedding", [20, 21], matrix[:3])
+    with pytest.raises(ValueError):
+        db.insert_vectors("docs", "id", "embedding", [20], np.ones(3, dtype=np.float32))
+    with pytest.raises(TypeError):
+        db.insert_vectors("docs", "id", "embedding", [20], np.ones((1, 3), dtype=np.int32))
+    db.close()
+
+
+def test_vector_normalize():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(2))")
+    v = Vector([3.0, 4.0], normalize=True)
+    assert v.normalize
+    assert v.to_list() == [3.0, 4.0]
+    assert repr(v) == "Vector([3.0, 4.0], normalize=True)"
+    assert v[:1].normalize
+
+    db.execute("INSERT INTO docs VALUES ($1, $2)", [1, v])
+    db.execute("INSERT INTO docs VALUES ($1, $2)", [2, Vector([0.0, 0.0], normalize=True)])
+    rows = db.query("SELECT embedding FROM docs ORDER BY id")
+    assert rows[0]["embedding"] == pytest.approx([0.6, 0.8])
+    assert rows[1]["embedding"] == [0.0, 0.0]
+    db.close()
+
+
+def test_insert_vectors_normalize():
+    np = pytest.importorskip("numpy")
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(2))")
+    matrix = np.array([[3.0, 4.0], [0.0, 2.0]], dtype=np.float32)
+    db.insert_vectors("docs", "id", "embedding", [1, 2], matrix, normalize=True)
+    rows = db.query("SELECT embedding FROM docs ORDER BY id")
+    assert rows[0]["embedding"] == pytest.approx([0.6, 0.8])
+    assert rows[1]["embedding"] == [0.0, 1.0]
+    db.close()
+
+
+def test_vector_dimension_error():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, embedding VECTOR(3))")
+    assert issubclass(VectorDimensionError, StoolapError)
+
+    with pytest.raises(VectorDimensionError) as exc:
+        db.execute("INSERT INTO docs VALUES ($1, $2, $3)", [1, "a", Vector([0.1, 0.2])])
+    assert (exc.value.expected, exc.value.actual) == (3, 2)
+    assert exc.value.param_index == 2
+    assert exc.value.param_name is None
+
+    with pytest.raises(VectorDimensionError) as exc:
+        db.execute(
+            "INSERT INTO docs VALUES (:id, :title, :emb)",
+            {"id": 1, "title": "a", "emb": Vector([0.1, 0.2, 0.3, 0.4])},
+        )
+    assert (exc.value.expected, exc.value.actual) == (3, 4)
+    assert exc.value.param_name == "emb"
+    db.close()
+
+
+def test_vector_dlpack():
+    np = pytest.importorskip("numpy")
+    if not hasattr(np, "from_dlpack"):
+        pytest.skip("numpy.from_dlpack requires NumPy 1.22")
+    v = Vector([0.5, 1.5, 2.5])
+    assert v.__dlpack_device__() == (1, 0)
+    array = np.from_dlpack(v)
+    assert array.dtype == np.float32
+    assert array.tolist() == [0.5, 1.5, 2.5]
+    del v
+    assert array.tolist() == [0.5, 1.5, 2.5]
+
+    assert np.from_dlpack(Vector([0.5, 1.5], dtype="f16")).dtype == np.float16
+    assert np.from_dlpack(Vector([2, 4], dtype="i8", scale=0.5)).tolist() == [1.0, 2.0]
+
+    # Vectors and capsules are read back through DLPack
+    assert Vector(Vector([0.5, 1.5])) == Vector([0.5, 1.5])
+    assert Vector(Vector([0.5], dtype="f16")).dtype == "f16"
+    assert Vector(np.arange(3, dtype=np.float32).__dlpack__()).to_list() == [0.0, 1.0, 2.0]
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(2))")
+    db.execute("INSERT INTO docs VALUES ($1, $2)", [1, np.array([0.5, 1.5]).__dlpack__()])
+    assert db.query_one("SELECT embedding FROM docs")["embedding"] == [0.5, 1.5]
+    db.close()
+
+
+def test_vector_torch():
+    torch = pytest.importorskip("torch")
+    t = torch.from_dlpack(Vector([0.5, 1.5]))
+    assert t.tolist() == [0.5, 1.5]
+
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, embedding VECTOR(2))")
+    db.execute("INSERT INTO docs VALUES ($1, $2)", [1, torch.tensor([0.5, 1.5])])
+    assert db.query_one("SELECT embedding FROM docs")["embedding"] == [0.5, 1.5]
+    hits = db.vector_search("docs", "embedding", torch.tensor([0.5, 1.5]), k=1)
+    assert hits[0]["id"] == 1
+    db.close()
+
+
+def test_distance_functions():
+    assert l2_distance([0.0, 0.0], [3.0, 4.0]) == pytest.approx(5.0)
+    assert cosine_distance(Vector([1.0, 0.0]), [0.0, 2.0]) == pytest.approx(1.0)
+    assert cosine_distance([1.0, 1.0], [2.0, 2.0]) == pytest.approx(0.0, abs=1e-6)
+    assert dot_product([1.0, 2.0], Vector([3.0, 4.0])) == pytest.approx(11.0)
+
+    # Same values as the database computes
+    db = Database.open(":memory:")
+    row = db.query_one(
+        "SELECT VEC_DISTANCE_COSINE($1, $2) AS d", [Vector([0.3, 0.7]), Vector([0.9, 0.2])]
+    )
+    assert cosine_distance([0.3, 0.7], [0.9, 0.2]) == row["d"]
+    db.close()
+
+    with pytest.raises(VectorDimensionError) as exc:
+        l2_distance([1.0, 2.0], [1.0, 2.0, 3.0])
+    assert (exc.value.expected, exc.value.actual, exc.value.param_index) == (2, 3, 1)
+
+
+def test_vector_columns():
+    db = Database.open(":memory:")
+    db.exec("CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, embedding VECTOR(3))")
+    db.exec("CREATE TABLE images (id INTEGER PRIMARY KEY, thumb VECTOR(8), clip VECTOR(4))")
+    db.create_vector_index("images", "clip", metric="cosine")
+
+    (col,) = db.vector_columns("docs")
+    assert col["table"] == "docs"
+    assert col["column"] == "embedding"
+    assert col["dimensions"] == 3
+    assert col["indexed"] is False
+    assert col["index"] is None
+    assert col["metric"] is None
+
+    by_column = {c["column"]: c for c in db.vector_columns()}
+    assert set(by_column) == {"embedding", "thumb", "clip"}
+    assert by_column["thumb"]["dimensions"] == 8
+    assert by_column["clip"]["indexed"] is True
+    assert by_column["clip"]["index"] == "idx_images_clip"
+    assert by_column["clip"]["metric"] == "cosine"
+    db.close()